@@ -0,0 +1,172 @@
+//! End-to-end walkthrough of the router: registration, login, refresh and
+//! role-guarded admin routes.
+//!
+//! This runs against a real Postgres database, not SQLite or an in-memory
+//! store. `DBClient` is a concrete Postgres client with no alternative
+//! backend, so the demo needs `DATABASE_URL` pointing at an empty or
+//! migrated database and the usual environment from `.env.example`.
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Method, Request, StatusCode, header},
+};
+use axum_auth_backend::{
+    AppState,
+    config::Config,
+    db::{DBClient, UserExt},
+    routes::create_router,
+    utils::password,
+};
+use serde_json::{Value, json};
+use sqlx::postgres::PgPoolOptions;
+use tower::ServiceExt;
+
+type DemoResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[tokio::main]
+async fn main() -> DemoResult<()> {
+    dotenv::dotenv().ok();
+    let config = Config::init();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&config.database_url)
+        .await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+
+    let db_client = DBClient::new(pool);
+    let app = create_router(Arc::new(AppState::new(config, db_client.clone())));
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let user_email = format!("demo-user-{}@example.com", &suffix[..8]);
+    let admin_email = format!("demo-admin-{}@example.com", &suffix[..8]);
+
+    println!("1. Registering {}", user_email);
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/auth/register",
+        None,
+        Some(json!({
+            "name": "Demo User",
+            "email": user_email,
+            "password": "password123",
+            "password_confirm": "password123"
+        })),
+    )
+    .await?;
+    expect(status, StatusCode::CREATED)?;
+
+    println!("2. Logging in as the regular user");
+    let (user_token, refresh_token) = login(&app, &user_email).await?;
+
+    println!("3. Refreshing the session");
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/auth/refresh",
+        None,
+        Some(json!({ "refresh_token": refresh_token })),
+    )
+    .await?;
+    expect(status, StatusCode::OK)?;
+    let user_token = body["token"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or(user_token);
+
+    println!("4. Regular user is denied access to admin routes");
+    let (status, _) = call(
+        &app,
+        Method::GET,
+        "/api/admin/metrics",
+        Some(&user_token),
+        None,
+    )
+    .await?;
+    expect(status, StatusCode::FORBIDDEN)?;
+
+    println!("5. Seeding admin {}", admin_email);
+    let hashed = password::hash("password123").map_err(|e| e.to_string())?;
+    db_client
+        .create_admin("Demo Admin", &admin_email, &hashed)
+        .await?;
+
+    println!("6. Admin can reach admin routes");
+    let (admin_token, _) = login(&app, &admin_email).await?;
+    let (status, body) = call(
+        &app,
+        Method::GET,
+        "/api/admin/metrics",
+        Some(&admin_token),
+        None,
+    )
+    .await?;
+    expect(status, StatusCode::OK)?;
+    println!("   metrics: {}", body);
+
+    println!("7. Requests without a token are rejected");
+    let (status, _) = call(&app, Method::GET, "/api/admin/metrics", None, None).await?;
+    expect(status, StatusCode::UNAUTHORIZED)?;
+
+    println!("Demo completed successfully");
+    Ok(())
+}
+
+async fn login(app: &Router, email: &str) -> DemoResult<(String, String)> {
+    let (status, body) = call(
+        app,
+        Method::POST,
+        "/api/auth/login",
+        None,
+        Some(json!({ "email": email, "password": "password123" })),
+    )
+    .await?;
+    expect(status, StatusCode::OK)?;
+
+    let token = body["token"]
+        .as_str()
+        .ok_or("login response did not contain a token")?;
+    let refresh_token = body["refreshToken"]
+        .as_str()
+        .ok_or("login response did not contain a refresh token")?;
+
+    Ok((token.to_string(), refresh_token.to_string()))
+}
+
+async fn call(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> DemoResult<(StatusCode, Value)> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?,
+        None => request.body(Body::empty())?,
+    };
+
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+    Ok((status, body))
+}
+
+fn expect(actual: StatusCode, expected: StatusCode) -> DemoResult<()> {
+    if actual != expected {
+        return Err(format!("expected {}, got {}", expected, actual).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS users_organization_id_idx;
+
+ALTER TABLE users DROP COLUMN IF EXISTS organization_id;
+
+DROP TABLE IF EXISTS organizations;
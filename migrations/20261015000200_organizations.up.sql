@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE organizations (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    name VARCHAR(100) NOT NULL,
+    branding_display_name VARCHAR(100),
+    branding_logo_url VARCHAR(500),
+    branding_accent_color VARCHAR(7),
+    branding_reply_to VARCHAR(100),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+ALTER TABLE users ADD COLUMN organization_id UUID REFERENCES organizations (id) ON DELETE SET NULL;
+
+CREATE INDEX users_organization_id_idx ON users (organization_id);
@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE invitations (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    email VARCHAR(100) NOT NULL,
+    token VARCHAR(255) UNIQUE NOT NULL,
+    organization_id UUID REFERENCES organizations (id) ON DELETE CASCADE,
+    invited_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    accepted_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX invitations_email_idx ON invitations (email);
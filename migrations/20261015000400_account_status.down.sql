@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS users_account_status_idx;
+
+ALTER TABLE users DROP COLUMN IF EXISTS account_status;
+
+DROP TYPE IF EXISTS account_status;
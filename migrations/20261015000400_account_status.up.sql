@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE TYPE account_status AS ENUM ('active', 'pending_approval', 'rejected');
+
+ALTER TABLE users ADD COLUMN account_status account_status DEFAULT 'active' NOT NULL;
+
+CREATE INDEX users_account_status_idx ON users (account_status);
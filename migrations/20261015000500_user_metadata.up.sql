@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE user_metadata (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    key VARCHAR(64) NOT NULL,
+    value TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (user_id, key)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE users DROP COLUMN IF EXISTS registration_state;
+
+DROP TYPE IF EXISTS registration_state;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+CREATE TYPE registration_state AS ENUM ('pending_profile', 'complete');
+
+ALTER TABLE users ADD COLUMN registration_state registration_state DEFAULT 'complete' NOT NULL;
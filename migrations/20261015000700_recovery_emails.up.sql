@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE recovery_emails (
+    user_id UUID NOT NULL PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    email VARCHAR(100) NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE,
+    verification_token VARCHAR(255) UNIQUE,
+    token_expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX recovery_emails_email_idx ON recovery_emails (email);
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE security_questions (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    position SMALLINT NOT NULL,
+    question VARCHAR(255) NOT NULL,
+    answer_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (user_id, position)
+);
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE delegations (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    owner_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    delegate_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    revoked_by UUID REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX delegations_active_pair_idx ON delegations (owner_id, delegate_id) WHERE revoked_at IS NULL;
+CREATE INDEX delegations_delegate_idx ON delegations (delegate_id);
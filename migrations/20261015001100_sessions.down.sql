@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE organizations DROP COLUMN IF EXISTS session_max_lifetime;
+ALTER TABLE organizations DROP COLUMN IF EXISTS session_idle_timeout;
+
+DROP TABLE IF EXISTS sessions;
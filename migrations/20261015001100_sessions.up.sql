@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE sessions (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    idle_timeout INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX sessions_user_idx ON sessions (user_id);
+
+ALTER TABLE organizations ADD COLUMN session_idle_timeout INTEGER;
+ALTER TABLE organizations ADD COLUMN session_max_lifetime INTEGER;
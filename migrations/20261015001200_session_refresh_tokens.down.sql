@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE sessions DROP COLUMN IF EXISTS client_fingerprint;
+ALTER TABLE sessions DROP COLUMN IF EXISTS refresh_token_hash;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE sessions ADD COLUMN refresh_token_hash VARCHAR(64) UNIQUE;
+ALTER TABLE sessions ADD COLUMN client_fingerprint VARCHAR(64);
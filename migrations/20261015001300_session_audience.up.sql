@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE sessions ADD COLUMN audience VARCHAR(50);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE organizations
+    DROP COLUMN IF EXISTS geo_blocked_countries,
+    DROP COLUMN IF EXISTS geo_mfa_countries,
+    DROP COLUMN IF EXISTS geo_blocked_asns;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE organizations
+    ADD COLUMN geo_blocked_countries TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN geo_mfa_countries TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN geo_blocked_asns BIGINT[] NOT NULL DEFAULT '{}';
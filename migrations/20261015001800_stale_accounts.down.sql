@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS users_last_login_at_idx;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS last_login_at,
+    DROP COLUMN IF EXISTS dormant_notified_at,
+    DROP COLUMN IF EXISTS deactivated_at;
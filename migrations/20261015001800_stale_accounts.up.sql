@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN last_login_at TIMESTAMPTZ,
+    ADD COLUMN dormant_notified_at TIMESTAMPTZ,
+    ADD COLUMN deactivated_at TIMESTAMPTZ;
+
+CREATE INDEX users_last_login_at_idx ON users (last_login_at);
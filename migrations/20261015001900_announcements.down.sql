@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS announcements;
+
+DROP TYPE IF EXISTS announcement_severity;
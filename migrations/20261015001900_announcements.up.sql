@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TYPE announcement_severity AS ENUM ('info', 'warning', 'critical');
+
+CREATE TABLE announcements (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    message TEXT NOT NULL,
+    severity announcement_severity NOT NULL DEFAULT 'info',
+    starts_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX announcements_active_idx ON announcements (starts_at, ends_at);
@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE usage_counters (
+    subject VARCHAR(100) NOT NULL,
+    period VARCHAR(10) NOT NULL,
+    period_start DATE NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subject, period, period_start)
+);
+
+CREATE INDEX usage_counters_period_start_idx ON usage_counters (period_start);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN plan VARCHAR(50);
+
+ALTER TABLE organizations ADD COLUMN plan VARCHAR(50);
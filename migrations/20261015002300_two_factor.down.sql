@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS backup_codes;
+
+ALTER TABLE users DROP COLUMN IF EXISTS totp_enabled_at;
+ALTER TABLE users DROP COLUMN IF EXISTS totp_secret;
@@ -0,0 +1,27 @@
+-- Add up migration script here
+CREATE TABLE user_changes (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL,
+    operation VARCHAR(10) NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE FUNCTION record_user_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO user_changes (user_id, operation) VALUES (OLD.id, 'deleted');
+        RETURN OLD;
+    END IF;
+
+    INSERT INTO user_changes (user_id, operation)
+    VALUES (NEW.id, CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE 'updated' END);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_record_change
+AFTER INSERT OR UPDATE OR DELETE ON users
+FOR EACH ROW EXECUTE FUNCTION record_user_change();
+
+INSERT INTO user_changes (user_id, operation, changed_at)
+SELECT id, 'created', created_at FROM users ORDER BY created_at;
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE api_keys DROP COLUMN IF EXISTS signing_secret;
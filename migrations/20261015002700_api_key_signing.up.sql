@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE api_keys ADD COLUMN signing_secret VARCHAR(64);
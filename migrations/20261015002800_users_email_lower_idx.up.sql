@@ -0,0 +1,2 @@
+-- Add up migration script here
+CREATE INDEX users_email_lower_idx ON users (LOWER(email));
@@ -0,0 +1,22 @@
+-- Add up migration script here
+CREATE TYPE outbox_status AS ENUM ('pending', 'sent', 'dead');
+
+CREATE TABLE email_outbox (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    recipient VARCHAR(255) NOT NULL,
+    cc TEXT[] NOT NULL DEFAULT '{}',
+    reply_to VARCHAR(255),
+    from_name VARCHAR(255),
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT,
+    status outbox_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX email_outbox_due_idx ON email_outbox (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX email_outbox_status_idx ON email_outbox (status, created_at);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE users ALTER COLUMN password TYPE VARCHAR(100);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS role_grants;
+DROP TYPE IF EXISTS role_grant_status;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TYPE role_grant_status AS ENUM ('pending', 'approved', 'rejected');
+
+CREATE TABLE role_grants (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role user_role NOT NULL,
+    status role_grant_status NOT NULL DEFAULT 'pending',
+    requested_by UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    decided_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    decided_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX role_grants_pending_idx ON role_grants (user_id, role) WHERE status = 'pending';
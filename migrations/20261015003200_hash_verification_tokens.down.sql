@@ -0,0 +1,12 @@
+-- Add down migration script here
+UPDATE recovery_emails SET verification_token_hash = NULL, token_expires_at = NULL;
+
+ALTER TABLE recovery_emails ALTER COLUMN verification_token_hash TYPE VARCHAR(255);
+
+ALTER TABLE recovery_emails RENAME COLUMN verification_token_hash TO verification_token;
+
+UPDATE users SET verification_token_hash = NULL, token_expires_at = NULL;
+
+ALTER TABLE users ALTER COLUMN verification_token_hash TYPE VARCHAR(255);
+
+ALTER TABLE users RENAME COLUMN verification_token_hash TO verification_token;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+ALTER TABLE users RENAME COLUMN verification_token TO verification_token_hash;
+
+UPDATE users
+SET verification_token_hash = encode(sha256(convert_to(verification_token_hash, 'UTF8')), 'hex')
+WHERE verification_token_hash IS NOT NULL;
+
+ALTER TABLE users ALTER COLUMN verification_token_hash TYPE VARCHAR(64);
+
+ALTER TABLE recovery_emails RENAME COLUMN verification_token TO verification_token_hash;
+
+UPDATE recovery_emails
+SET verification_token_hash = encode(sha256(convert_to(verification_token_hash, 'UTF8')), 'hex')
+WHERE verification_token_hash IS NOT NULL;
+
+ALTER TABLE recovery_emails ALTER COLUMN verification_token_hash TYPE VARCHAR(64);
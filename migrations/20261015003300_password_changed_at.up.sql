@@ -0,0 +1,9 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN password_changed_at TIMESTAMPTZ;
+
+ALTER TABLE users DISABLE TRIGGER users_record_change;
+UPDATE users SET password_changed_at = created_at;
+ALTER TABLE users ENABLE TRIGGER users_record_change;
+
+ALTER TABLE users ALTER COLUMN password_changed_at SET DEFAULT NOW();
+ALTER TABLE users ALTER COLUMN password_changed_at SET NOT NULL;
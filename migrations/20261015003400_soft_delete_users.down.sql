@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS users_deleted_at_idx;
+
+ALTER TABLE users DROP COLUMN deleted_at;
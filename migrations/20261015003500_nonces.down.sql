@@ -0,0 +1,10 @@
+-- Add down migration script here
+CREATE TABLE webauthn_ceremonies (
+    id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    state TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+DROP TABLE nonces;
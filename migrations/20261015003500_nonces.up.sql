@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE nonces (
+    purpose VARCHAR(32) NOT NULL,
+    nonce VARCHAR(255) NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    payload TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (purpose, nonce)
+);
+
+CREATE INDEX nonces_expires_at_idx ON nonces (expires_at);
+
+DROP TABLE webauthn_ceremonies;
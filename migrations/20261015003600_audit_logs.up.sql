@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE audit_logs (
+    id BIGSERIAL PRIMARY KEY,
+    event VARCHAR(64) NOT NULL,
+    actor_id UUID,
+    target_id UUID,
+    ip VARCHAR(64),
+    user_agent TEXT,
+    detail JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_logs_event_idx ON audit_logs (event, created_at);
+CREATE INDEX audit_logs_actor_id_idx ON audit_logs (actor_id, created_at);
+CREATE INDEX audit_logs_target_id_idx ON audit_logs (target_id, created_at);
+CREATE INDEX audit_logs_created_at_idx ON audit_logs (created_at);
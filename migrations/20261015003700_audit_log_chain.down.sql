@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE audit_logs
+    DROP COLUMN hash,
+    DROP COLUMN prev_hash;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE audit_logs
+    ADD COLUMN prev_hash VARCHAR(64),
+    ADD COLUMN hash VARCHAR(64);
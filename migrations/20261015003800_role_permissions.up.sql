@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE role_permissions (
+    role user_role NOT NULL,
+    permission VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role, permission)
+);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('user', 'profile:read'),
+    ('user', 'profile:write'),
+    ('admin', 'profile:read'),
+    ('admin', 'profile:write'),
+    ('admin', 'users:read'),
+    ('admin', 'users:write'),
+    ('admin', 'roles:write');
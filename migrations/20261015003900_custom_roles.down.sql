@@ -0,0 +1,23 @@
+-- Add down migration script here
+CREATE TYPE user_role AS ENUM ('user', 'admin');
+
+UPDATE users SET role = 'user' WHERE role NOT IN ('user', 'admin');
+UPDATE api_keys SET role = NULL WHERE role NOT IN ('user', 'admin');
+DELETE FROM role_grants WHERE role NOT IN ('user', 'admin');
+DELETE FROM role_permissions WHERE role NOT IN ('user', 'admin');
+
+ALTER TABLE role_permissions DROP CONSTRAINT IF EXISTS role_permissions_role_fkey;
+ALTER TABLE role_permissions ALTER COLUMN role TYPE user_role USING role::user_role;
+
+ALTER TABLE role_grants DROP CONSTRAINT IF EXISTS role_grants_role_fkey;
+ALTER TABLE role_grants ALTER COLUMN role TYPE user_role USING role::user_role;
+
+ALTER TABLE api_keys DROP CONSTRAINT IF EXISTS api_keys_role_fkey;
+ALTER TABLE api_keys ALTER COLUMN role TYPE user_role USING role::user_role;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_fkey;
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE user_role USING role::user_role;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'user';
+
+DROP TABLE IF EXISTS roles;
@@ -0,0 +1,34 @@
+-- Add up migration script here
+CREATE TABLE roles (
+    name VARCHAR(50) PRIMARY KEY,
+    description TEXT,
+    parent VARCHAR(50) REFERENCES roles(name) ON UPDATE CASCADE ON DELETE RESTRICT,
+    built_in BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (parent IS NULL OR parent <> name)
+);
+
+INSERT INTO roles (name, description, parent, built_in) VALUES
+    ('user', 'Default role for registered users', NULL, true),
+    ('admin', 'Full administrative access', 'user', true);
+
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE VARCHAR(50) USING role::text;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'user';
+ALTER TABLE users ADD CONSTRAINT users_role_fkey
+    FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
+
+ALTER TABLE api_keys ALTER COLUMN role TYPE VARCHAR(50) USING role::text;
+ALTER TABLE api_keys ADD CONSTRAINT api_keys_role_fkey
+    FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
+
+ALTER TABLE role_grants ALTER COLUMN role TYPE VARCHAR(50) USING role::text;
+ALTER TABLE role_grants ADD CONSTRAINT role_grants_role_fkey
+    FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE ON DELETE CASCADE;
+
+ALTER TABLE role_permissions ALTER COLUMN role TYPE VARCHAR(50) USING role::text;
+ALTER TABLE role_permissions ADD CONSTRAINT role_permissions_role_fkey
+    FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE ON DELETE CASCADE;
+
+DROP TYPE user_role;
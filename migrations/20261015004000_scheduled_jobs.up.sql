@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE scheduled_jobs (
+    name VARCHAR(64) PRIMARY KEY,
+    locked_by VARCHAR(128),
+    locked_until TIMESTAMPTZ,
+    last_started_at TIMESTAMPTZ,
+    last_finished_at TIMESTAMPTZ,
+    last_run_by VARCHAR(128),
+    last_duration_ms BIGINT,
+    last_error TEXT,
+    run_count BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS sessions_revoked_notify ON sessions;
+DROP TRIGGER IF EXISTS revoked_tokens_notify ON revoked_tokens;
+DROP FUNCTION IF EXISTS notify_session_revoked();
+DROP FUNCTION IF EXISTS notify_token_revoked();
@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE OR REPLACE FUNCTION notify_token_revoked() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('auth_revocations', 'token:' || NEW.jti);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION notify_session_revoked() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('auth_revocations', 'session:' || NEW.id::TEXT);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER revoked_tokens_notify
+    AFTER INSERT ON revoked_tokens
+    FOR EACH ROW EXECUTE FUNCTION notify_token_revoked();
+
+CREATE TRIGGER sessions_revoked_notify
+    AFTER UPDATE OF revoked_at ON sessions
+    FOR EACH ROW
+    WHEN (OLD.revoked_at IS NULL AND NEW.revoked_at IS NOT NULL)
+    EXECUTE FUNCTION notify_session_revoked();
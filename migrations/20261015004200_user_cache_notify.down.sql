@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS users_changed_notify ON users;
+DROP FUNCTION IF EXISTS notify_user_changed();
@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE OR REPLACE FUNCTION notify_user_changed() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM pg_notify('auth_revocations', 'user:' || OLD.id::TEXT);
+        RETURN OLD;
+    END IF;
+
+    PERFORM pg_notify('auth_revocations', 'user:' || NEW.id::TEXT);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_changed_notify
+    AFTER UPDATE OR DELETE ON users
+    FOR EACH ROW EXECUTE FUNCTION notify_user_changed();
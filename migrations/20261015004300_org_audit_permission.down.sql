@@ -0,0 +1,3 @@
+-- Add down migration script here
+DELETE FROM role_permissions WHERE permission = 'audit:org:read';
+DELETE FROM roles WHERE name = 'org_admin' AND NOT EXISTS (SELECT 1 FROM users WHERE role = 'org_admin');
@@ -0,0 +1,9 @@
+-- Add up migration script here
+INSERT INTO roles (name, description, parent, built_in) VALUES
+    ('org_admin', 'Views audit events for members of their organization', 'user', false)
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'audit:org:read'),
+    ('org_admin', 'audit:org:read')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS users_email_lower_idx;
+CREATE INDEX users_email_lower_idx ON users (LOWER(email));
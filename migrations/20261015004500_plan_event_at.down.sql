@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE organizations DROP COLUMN IF EXISTS plan_event_at;
+ALTER TABLE users DROP COLUMN IF EXISTS plan_event_at;
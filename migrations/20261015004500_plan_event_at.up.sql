@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN plan_event_at TIMESTAMPTZ;
+ALTER TABLE organizations ADD COLUMN plan_event_at TIMESTAMPTZ;
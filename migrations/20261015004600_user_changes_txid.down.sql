@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS user_changes_txid_id_idx;
+ALTER TABLE user_changes DROP COLUMN IF EXISTS txid;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE user_changes
+    ADD COLUMN txid BIGINT NOT NULL DEFAULT pg_current_xact_id()::text::bigint;
+
+CREATE INDEX user_changes_txid_id_idx ON user_changes (txid, id);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE users DROP COLUMN IF EXISTS reset_token_expires_at;
+ALTER TABLE users DROP COLUMN IF EXISTS reset_token_hash;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN reset_token_hash VARCHAR(64) UNIQUE;
+ALTER TABLE users ADD COLUMN reset_token_expires_at TIMESTAMPTZ;
+
+UPDATE users
+SET verification_token_hash = NULL, token_expires_at = NULL
+WHERE verified = true;
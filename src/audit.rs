@@ -0,0 +1,384 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::{AuditLogExt, DBClient},
+    middleware::ClientContext,
+    models::AuditLog,
+};
+
+const VERIFY_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEvent {
+    Login,
+    LoginFailed,
+    PasswordChanged,
+    RoleChanged,
+    RoleDefined,
+    RoleDeleted,
+    RoleGrantRequested,
+    RoleGrantApproved,
+    RoleGrantRejected,
+    RecoveryEmailChanged,
+    RiskDecision,
+    JwtKeyRotated,
+    MagicLinkUsed,
+    BackupCodeUsed,
+    MfaLockedOut,
+    ReauthenticationFailed,
+    SecurityAnswersRejected,
+    LoginLockedOut,
+    AccountReactivated,
+    OAuthLinked,
+    ApiKeyCreated,
+    ApiKeyRevoked,
+    ApiKeyLeaked,
+    IpBlocked,
+    QuotaExceeded,
+}
+
+impl AuditEvent {
+    pub fn to_str(&self) -> &str {
+        match self {
+            AuditEvent::Login => "login",
+            AuditEvent::LoginFailed => "login_failed",
+            AuditEvent::PasswordChanged => "password_changed",
+            AuditEvent::RoleChanged => "role_changed",
+            AuditEvent::RoleDefined => "role_defined",
+            AuditEvent::RoleDeleted => "role_deleted",
+            AuditEvent::RoleGrantRequested => "role_grant_requested",
+            AuditEvent::RoleGrantApproved => "role_grant_approved",
+            AuditEvent::RoleGrantRejected => "role_grant_rejected",
+            AuditEvent::RecoveryEmailChanged => "recovery_email_changed",
+            AuditEvent::RiskDecision => "risk_decision",
+            AuditEvent::JwtKeyRotated => "jwt_key_rotated",
+            AuditEvent::MagicLinkUsed => "magic_link_used",
+            AuditEvent::BackupCodeUsed => "backup_code_used",
+            AuditEvent::MfaLockedOut => "mfa_locked_out",
+            AuditEvent::ReauthenticationFailed => "reauthentication_failed",
+            AuditEvent::SecurityAnswersRejected => "security_answers_rejected",
+            AuditEvent::LoginLockedOut => "login_locked_out",
+            AuditEvent::AccountReactivated => "account_reactivated",
+            AuditEvent::OAuthLinked => "oauth_linked",
+            AuditEvent::ApiKeyCreated => "api_key_created",
+            AuditEvent::ApiKeyRevoked => "api_key_revoked",
+            AuditEvent::ApiKeyLeaked => "api_key_leaked",
+            AuditEvent::IpBlocked => "ip_blocked",
+            AuditEvent::QuotaExceeded => "quota_exceeded",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub event: AuditEvent,
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub detail: serde_json::Value,
+}
+
+impl AuditEntry {
+    pub fn new(event: AuditEvent) -> Self {
+        AuditEntry {
+            event,
+            actor_id: None,
+            target_id: None,
+            ip: None,
+            user_agent: None,
+            detail: serde_json::json!({}),
+        }
+    }
+
+    pub fn actor(mut self, actor_id: Uuid) -> Self {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    pub fn target(mut self, target_id: Uuid) -> Self {
+        self.target_id = Some(target_id);
+        self
+    }
+
+    pub fn client(mut self, client: &ClientContext) -> Self {
+        self.ip = client.ip.clone();
+        self.user_agent = client.user_agent.clone();
+        self
+    }
+
+    pub fn ip(mut self, ip: Option<IpAddr>) -> Self {
+        self.ip = ip.map(|ip| ip.to_string());
+        self
+    }
+
+    pub fn detail(mut self, detail: serde_json::Value) -> Self {
+        self.detail = detail;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub event: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct AuditChain {
+    key: Vec<u8>,
+}
+
+impl std::fmt::Debug for AuditChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditChain").finish_non_exhaustive()
+    }
+}
+
+impl AuditChain {
+    pub fn new(secret: &str) -> Self {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+            .expect("HMAC can take a key of any size");
+        mac.update(b"audit-log");
+
+        AuditChain {
+            key: mac.finalize().into_bytes().to_vec(),
+        }
+    }
+
+    pub fn sign(&self, log: &AuditLog) -> String {
+        let message = serde_json::json!([
+            log.prev_hash,
+            log.id,
+            log.event,
+            log.actor_id,
+            log.target_id,
+            log.ip,
+            log.user_agent,
+            log.detail,
+            log.created_at.timestamp_micros(),
+        ]);
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.key)
+            .expect("HMAC can take a key of any size");
+        mac.update(message.to_string().as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditVerification {
+    pub checked: i64,
+    pub unchained: i64,
+    pub first_invalid_id: Option<i64>,
+    pub reason: Option<&'static str>,
+}
+
+impl AuditVerification {
+    pub fn is_valid(&self) -> bool {
+        self.first_invalid_id.is_none()
+    }
+}
+
+pub async fn verify(
+    db_client: &DBClient,
+    chain: &AuditChain,
+) -> Result<AuditVerification, sqlx::Error> {
+    let mut result = AuditVerification::default();
+    let mut prev_hash: Option<String> = None;
+    let mut after_id = 0;
+
+    loop {
+        let logs = db_client
+            .get_audit_log_chain(after_id, VERIFY_BATCH_SIZE)
+            .await?;
+        let Some(last) = logs.last() else {
+            return Ok(result);
+        };
+        after_id = last.id;
+
+        for log in logs {
+            if log.hash.is_none() && prev_hash.is_none() {
+                result.unchained += 1;
+                continue;
+            }
+
+            if let Some(reason) = link_error(chain, prev_hash.as_deref(), &log) {
+                result.first_invalid_id = Some(log.id);
+                result.reason = Some(reason);
+                return Ok(result);
+            }
+
+            result.checked += 1;
+            prev_hash = log.hash;
+        }
+    }
+}
+
+fn link_error(chain: &AuditChain, prev_hash: Option<&str>, log: &AuditLog) -> Option<&'static str> {
+    match &log.hash {
+        None => Some("missing hash"),
+        Some(_) if log.prev_hash.as_deref() != prev_hash => Some("previous hash does not match"),
+        Some(hash) if *hash != chain.sign(log) => Some("hash does not match contents"),
+        Some(_) => None,
+    }
+}
+
+pub fn record(app_state: &AppState, entry: AuditEntry) {
+    tracing::info!(
+        target: "audit",
+        event = entry.event.to_str(),
+        actor_id = ?entry.actor_id,
+        target_id = ?entry.target_id,
+        ip = ?entry.ip,
+        detail = %entry.detail,
+    );
+
+    let db_client = app_state.db_client.clone();
+    let chain = app_state.audit_chain.clone();
+    tokio::spawn(async move {
+        if let Err(e) = db_client.insert_audit_log(&entry, &chain).await {
+            tracing::warn!("Failed to store audit log {}: {}", entry.event.to_str(), e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn log(id: i64, prev_hash: Option<String>) -> AuditLog {
+        AuditLog {
+            id,
+            event: AuditEvent::Login.to_str().to_string(),
+            actor_id: Some(Uuid::nil()),
+            target_id: Some(Uuid::nil()),
+            ip: Some("203.0.113.9".to_string()),
+            user_agent: Some("curl/8.0".to_string()),
+            detail: serde_json::json!({ "method": "password" }),
+            created_at: Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap(),
+            prev_hash,
+            hash: None,
+        }
+    }
+
+    fn chained(chain: &AuditChain, count: i64) -> Vec<AuditLog> {
+        let mut logs: Vec<AuditLog> = Vec::new();
+        for id in 1..=count {
+            let prev_hash = logs.last().and_then(|log| log.hash.clone());
+            let mut entry = log(id, prev_hash);
+            entry.hash = Some(chain.sign(&entry));
+            logs.push(entry);
+        }
+        logs
+    }
+
+    fn first_error(chain: &AuditChain, logs: &[AuditLog]) -> Option<(i64, &'static str)> {
+        let mut prev_hash: Option<&str> = None;
+        for log in logs {
+            if let Some(reason) = link_error(chain, prev_hash, log) {
+                return Some((log.id, reason));
+            }
+            prev_hash = log.hash.as_deref();
+        }
+        None
+    }
+
+    #[test]
+    fn sign_is_deterministic_and_keyed() {
+        let entry = log(1, None);
+
+        assert_eq!(
+            AuditChain::new("secret").sign(&entry),
+            AuditChain::new("secret").sign(&entry)
+        );
+        assert_ne!(
+            AuditChain::new("secret").sign(&entry),
+            AuditChain::new("other").sign(&entry)
+        );
+    }
+
+    #[test]
+    fn sign_covers_the_previous_hash() {
+        let chain = AuditChain::new("secret");
+
+        assert_ne!(
+            chain.sign(&log(2, Some("a".to_string()))),
+            chain.sign(&log(2, Some("b".to_string())))
+        );
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let chain = AuditChain::new("secret");
+        assert_eq!(first_error(&chain, &chained(&chain, 5)), None);
+    }
+
+    #[test]
+    fn edited_entries_are_detected() {
+        let chain = AuditChain::new("secret");
+        let mut logs = chained(&chain, 5);
+        logs[2].detail = serde_json::json!({ "method": "passkey" });
+
+        assert_eq!(
+            first_error(&chain, &logs),
+            Some((3, "hash does not match contents"))
+        );
+    }
+
+    #[test]
+    fn deleted_entries_are_detected() {
+        let chain = AuditChain::new("secret");
+        let mut logs = chained(&chain, 5);
+        logs.remove(2);
+
+        assert_eq!(
+            first_error(&chain, &logs),
+            Some((4, "previous hash does not match"))
+        );
+    }
+
+    #[test]
+    fn resigned_entries_break_the_next_link() {
+        let chain = AuditChain::new("secret");
+        let mut logs = chained(&chain, 5);
+        logs[1].ip = None;
+        logs[1].hash = Some(chain.sign(&logs[1]));
+
+        assert_eq!(
+            first_error(&chain, &logs),
+            Some((3, "previous hash does not match"))
+        );
+    }
+
+    #[test]
+    fn entries_signed_with_another_key_are_detected() {
+        let chain = AuditChain::new("secret");
+        let forged = chained(&AuditChain::new("other"), 3);
+
+        assert_eq!(
+            first_error(&chain, &forged),
+            Some((1, "hash does not match contents"))
+        );
+    }
+
+    #[test]
+    fn missing_hashes_after_the_chain_starts_are_detected() {
+        let chain = AuditChain::new("secret");
+        let mut logs = chained(&chain, 3);
+        logs[1].hash = None;
+
+        assert_eq!(first_error(&chain, &logs), Some((2, "missing hash")));
+    }
+}
@@ -0,0 +1,212 @@
+use std::io::{self, BufRead, Write};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum_auth_backend::{
+    audit, bootstrap,
+    config::Config,
+    db::{DBClient, UserExt},
+    doctor,
+    utils::password::Passwords,
+};
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Parser)]
+#[command(
+    name = "axum-auth-cli",
+    about = "Operational tasks for the axum-auth backend"
+)]
+struct Cli {
+    #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+    database_url: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    CreateAdmin {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        email: String,
+        #[arg(long, help = "Read from stdin when omitted")]
+        password: Option<String>,
+    },
+    ResetPassword {
+        #[arg(long)]
+        email: String,
+        #[arg(long, help = "Read from stdin when omitted")]
+        password: Option<String>,
+    },
+    Lock {
+        #[arg(long)]
+        email: String,
+    },
+    Unlock {
+        #[arg(long)]
+        email: String,
+    },
+    SeedAdmin,
+    RotateJwtSecret,
+    RunMigrations,
+    PurgeExpiredTokens,
+    Doctor,
+    VerifyAuditLog,
+}
+
+type CliResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> CliResult {
+    match cli.command {
+        Command::RotateJwtSecret => rotate_jwt_secret(),
+        command => {
+            let database_url = cli
+                .database_url
+                .ok_or("DATABASE_URL must be set or passed with --database-url")?;
+            let pool = PgPoolOptions::new()
+                .max_connections(2)
+                .connect(&database_url)
+                .await?;
+
+            run_db_command(command, DBClient::new(pool)).await
+        }
+    }
+}
+
+async fn run_db_command(command: Command, db_client: DBClient) -> CliResult {
+    match command {
+        Command::CreateAdmin {
+            name,
+            email,
+            password,
+        } => {
+            let password = read_password(password)?;
+            let hashed = Passwords::from_config(&Config::init())
+                .hash(password)
+                .map_err(|e| e.to_string())?;
+            let user = db_client.create_admin(&name, &email, &hashed).await?;
+            println!("Created admin {} ({})", user.email, user.id);
+        }
+        Command::ResetPassword { email, password } => {
+            let user = find_user(&db_client, &email).await?;
+            let password = read_password(password)?;
+            let hashed = Passwords::from_config(&Config::init())
+                .hash(password)
+                .map_err(|e| e.to_string())?;
+            db_client.update_user_password(user.id, hashed).await?;
+            println!("Password reset for {}", user.email);
+        }
+        Command::SeedAdmin => {
+            let config = Config::init();
+            match bootstrap::seed_admin(&db_client, &config).await? {
+                Some(user) => println!("Created admin {} ({})", user.email, user.id),
+                None => println!("Nothing to do, an admin already exists or ADMIN_EMAIL is unset"),
+            }
+        }
+        Command::Lock { email } => {
+            let user = find_user(&db_client, &email).await?;
+            db_client.set_user_locked(user.id, true).await?;
+            println!("Locked {}", user.email);
+        }
+        Command::Unlock { email } => {
+            let user = find_user(&db_client, &email).await?;
+            db_client.set_user_locked(user.id, false).await?;
+            println!("Unlocked {}", user.email);
+        }
+        Command::RunMigrations => {
+            sqlx::migrate!("./migrations").run(db_client.pool()).await?;
+            println!("Migrations are up to date");
+        }
+        Command::PurgeExpiredTokens => {
+            let purged = db_client.purge_expired_tokens().await?;
+            println!("Purged {} expired tokens", purged);
+        }
+        Command::Doctor => {
+            let report = doctor::run(&db_client).await?;
+            for check in &report.checks {
+                println!(
+                    "[{}] {}: {}",
+                    check.status.to_str(),
+                    check.name,
+                    check.detail
+                );
+            }
+            if !report.healthy {
+                return Err("Doctor found problems".into());
+            }
+        }
+        Command::VerifyAuditLog => {
+            let config = Config::init();
+            let chain = audit::AuditChain::new(&config.audit_log_key);
+            let verification = audit::verify(&db_client, &chain).await?;
+            println!(
+                "Checked {} chained entries ({} written before chaining)",
+                verification.checked, verification.unchained
+            );
+            if let (Some(id), Some(reason)) = (verification.first_invalid_id, verification.reason) {
+                return Err(
+                    format!("Audit log entry {} failed verification: {}", id, reason).into(),
+                );
+            }
+        }
+        Command::RotateJwtSecret => rotate_jwt_secret()?,
+    }
+
+    Ok(())
+}
+
+async fn find_user(
+    db_client: &DBClient,
+    email: &str,
+) -> Result<axum_auth_backend::models::User, Box<dyn std::error::Error + Send + Sync>> {
+    db_client
+        .get_user(None, None, Some(email), None)
+        .await?
+        .ok_or_else(|| format!("No user found with email {}", email).into())
+}
+
+fn read_password(
+    password: Option<String>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(password) = password {
+        return Ok(password);
+    }
+
+    print!("Password: ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+
+    if password.is_empty() {
+        return Err("Password cannot be empty".into());
+    }
+    Ok(password)
+}
+
+fn rotate_jwt_secret() -> CliResult {
+    let mut secret = [0u8; 64];
+    OsRng.fill_bytes(&mut secret);
+
+    println!("JWT_SECRET={}", hex::encode(secret));
+    eprintln!(
+        "Deploy the new secret to every instance. Tokens signed with the old secret stop verifying once it is replaced."
+    );
+    eprintln!(
+        "If AUDIT_LOG_KEY is unset, set it to the old secret first so existing audit log entries keep verifying."
+    );
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::{
+    config::Config,
+    db::{DBClient, UserExt},
+    models::User,
+    utils::password::Passwords,
+};
+
+pub async fn seed_admin(
+    db_client: &DBClient,
+    config: &Config,
+) -> Result<Option<User>, Box<dyn std::error::Error + Send + Sync>> {
+    let (Some(email), Some(admin_password)) = (&config.admin_email, &config.admin_password) else {
+        return Ok(None);
+    };
+
+    if db_client.admin_exists().await? {
+        return Ok(None);
+    }
+
+    if db_client
+        .get_user(None, None, Some(email), None)
+        .await?
+        .is_some()
+    {
+        return Err(format!(
+            "ADMIN_EMAIL {} belongs to an existing non-admin user, promote it instead",
+            email
+        )
+        .into());
+    }
+
+    let hashed = Passwords::from_config(config)
+        .hash(admin_password)
+        .map_err(|e| e.to_string())?;
+    let user = db_client
+        .create_admin(&config.admin_name, email, &hashed)
+        .await?;
+
+    Ok(Some(user))
+}
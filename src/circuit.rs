@@ -0,0 +1,187 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{config::Config, metrics::Metrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn to_str(&self) -> &str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { trial_started: Instant },
+}
+
+#[derive(Debug)]
+pub enum CircuitError<E> {
+    Open(&'static str),
+    Timeout(&'static str),
+    Failed(E),
+}
+
+impl<E: fmt::Display> fmt::Display for CircuitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Open(name) => write!(f, "{} is unavailable (circuit open)", name),
+            CircuitError::Timeout(name) => write!(f, "{} timed out", name),
+            CircuitError::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for CircuitError<E> {}
+
+impl<E> CircuitError<E> {
+    pub fn flatten(self, wrap: impl FnOnce(String) -> E) -> E {
+        match self {
+            CircuitError::Open(name) => wrap(format!("{} is unavailable (circuit open)", name)),
+            CircuitError::Timeout(name) => wrap(format!("{} timed out", name)),
+            CircuitError::Failed(err) => err,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    timeout: Duration,
+    state: Mutex<State>,
+    successes: Arc<AtomicU64>,
+    failures: Arc<AtomicU64>,
+    timeouts: Arc<AtomicU64>,
+    rejected: Arc<AtomicU64>,
+    opened: Arc<AtomicU64>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, config: &Config, metrics: &Metrics) -> Self {
+        CircuitBreaker {
+            name,
+            failure_threshold: config.circuit_failure_threshold.max(1),
+            open_for: Duration::from_secs(config.circuit_open_secs),
+            timeout: Duration::from_millis(config.circuit_call_timeout_ms),
+            state: Mutex::new(State::Closed { failures: 0 }),
+            successes: metrics.counter(&format!("circuit.{}.success", name)),
+            failures: metrics.counter(&format!("circuit.{}.failure", name)),
+            timeouts: metrics.counter(&format!("circuit.{}.timeout", name)),
+            rejected: metrics.counter(&format!("circuit.{}.rejected", name)),
+            opened: metrics.counter(&format!("circuit.{}.opened", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    pub async fn call<T, E>(
+        &self,
+        operation: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, CircuitError<E>> {
+        if !self.acquire() {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(CircuitError::Open(self.name));
+        }
+
+        match tokio::time::timeout(self.timeout, operation).await {
+            Ok(Ok(value)) => {
+                self.successes.fetch_add(1, Ordering::Relaxed);
+                self.record(true);
+                Ok(value)
+            }
+            Ok(Err(err)) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                self.record(false);
+                Err(CircuitError::Failed(err))
+            }
+            Err(_) => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                self.record(false);
+                Err(CircuitError::Timeout(self.name))
+            }
+        }
+    }
+
+    fn acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } if now >= until => {
+                *state = State::HalfOpen { trial_started: now };
+                true
+            }
+            State::HalfOpen { trial_started }
+                if now.duration_since(trial_started) >= self.timeout =>
+            {
+                *state = State::HalfOpen { trial_started: now };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record(&self, ok: bool) {
+        let mut state = self.state.lock().unwrap();
+        if ok {
+            if !matches!(*state, State::Closed { .. }) {
+                tracing::info!(dependency = self.name, "circuit closed");
+            }
+            *state = State::Closed { failures: 0 };
+            return;
+        }
+
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            State::Open { .. } | State::HalfOpen { .. } => self.failure_threshold,
+        };
+
+        if failures >= self.failure_threshold {
+            if !matches!(*state, State::Open { .. }) {
+                self.opened.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    dependency = self.name,
+                    open_secs = self.open_for.as_secs(),
+                    "circuit opened"
+                );
+            }
+            *state = State::Open {
+                until: Instant::now() + self.open_for,
+            };
+        } else {
+            *state = State::Closed { failures };
+        }
+    }
+}
@@ -1,29 +1,1591 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+use axum_extra::extract::cookie::{Key, SameSite};
+use ipnet::IpNet;
+use jsonwebtoken::Algorithm;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenSource {
+    Header,
+    Cookie,
+    Query,
+}
+
+impl TokenSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "header" => Some(TokenSource::Header),
+            "cookie" => Some(TokenSource::Cookie),
+            "query" => Some(TokenSource::Query),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenDelivery {
+    Body,
+    Cookie,
+    Both,
+}
+
+impl TokenDelivery {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "body" => Some(TokenDelivery::Body),
+            "cookie" => Some(TokenDelivery::Cookie),
+            "both" => Some(TokenDelivery::Both),
+            _ => None,
+        }
+    }
+
+    pub fn in_body(&self) -> bool {
+        *self != TokenDelivery::Cookie
+    }
+
+    pub fn in_cookie(&self) -> bool {
+        *self != TokenDelivery::Body
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionBackend {
+    Database,
+    Cookie,
+}
+
+impl SessionBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "database" => Some(SessionBackend::Database),
+            "cookie" => Some(SessionBackend::Cookie),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RouteGroup {
+    Registration,
+    Admin,
+    SocialLogin,
+}
+
+impl RouteGroup {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "registration" => Some(RouteGroup::Registration),
+            "admin" => Some(RouteGroup::Admin),
+            "social_login" => Some(RouteGroup::SocialLogin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasswordAlgorithm {
+    Argon2,
+    Bcrypt,
+    Scrypt,
+}
+
+impl PasswordAlgorithm {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "argon2" => Some(PasswordAlgorithm::Argon2),
+            "bcrypt" => Some(PasswordAlgorithm::Bcrypt),
+            "scrypt" => Some(PasswordAlgorithm::Scrypt),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpTls {
+    StartTls,
+    Tls,
+    None,
+}
+
+impl SmtpTls {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "starttls" => Some(SmtpTls::StartTls),
+            "tls" => Some(SmtpTls::Tls),
+            "none" => Some(SmtpTls::None),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitBackend {
+    Memory,
+    Redis,
+}
+
+impl RateLimitBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "memory" => Some(RateLimitBackend::Memory),
+            "redis" => Some(RateLimitBackend::Redis),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedisMode {
+    Single,
+    Sentinel,
+    Cluster,
+}
+
+impl RedisMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "single" => Some(RedisMode::Single),
+            "sentinel" => Some(RedisMode::Sentinel),
+            "cluster" => Some(RedisMode::Cluster),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            RedisMode::Single => "single",
+            RedisMode::Sentinel => "sentinel",
+            RedisMode::Cluster => "cluster",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrationField {
+    pub name: String,
+    pub required: bool,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub options: Vec<String>,
+}
+
+impl RegistrationField {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split(':');
+        let name = parts.next()?.trim().to_lowercase();
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+
+        let mut field = RegistrationField {
+            name,
+            required: false,
+            min_length: None,
+            max_length: None,
+            options: Vec::new(),
+        };
+
+        for rule in parts {
+            match rule.trim().split_once('=') {
+                None if rule.trim() == "required" => field.required = true,
+                Some(("min", length)) => field.min_length = Some(length.parse().ok()?),
+                Some(("max", length)) => field.max_length = Some(length.parse().ok()?),
+                Some(("oneof", options)) => {
+                    field.options = options.split('|').map(|o| o.trim().to_string()).collect()
+                }
+                _ => return None,
+            }
+        }
+
+        Some(field)
+    }
+
+    pub fn validate(&self, value: Option<&str>) -> Result<(), String> {
+        let value = match value.map(str::trim) {
+            Some(value) if !value.is_empty() => value,
+            _ if self.required => return Err(format!("{} is required", self.name)),
+            _ => return Ok(()),
+        };
+
+        let length = value.chars().count();
+        if let Some(min) = self.min_length
+            && length < min
+        {
+            return Err(format!(
+                "{} must be at least {} characters long",
+                self.name, min
+            ));
+        }
+        if let Some(max) = self.max_length
+            && length > max
+        {
+            return Err(format!(
+                "{} must be at most {} characters long",
+                self.name, max
+            ));
+        }
+        if !self.options.is_empty() && !self.options.iter().any(|option| option == value) {
+            return Err(format!(
+                "{} must be one of: {}",
+                self.name,
+                self.options.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientType {
+    pub name: String,
+    pub token_lifetime: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcSettings {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: String,
+    pub email_claim: String,
+    pub name_claim: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl Environment {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Environment::Development => "development",
+            Environment::Production => "production",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
+    pub environment: Environment,
+    pub startup_strict: bool,
+    pub startup_query_plan_check: bool,
     pub database_url: String,
     pub jwt_secret: String,
+    pub jwt_algorithm: Algorithm,
+    pub jwt_private_key: Option<String>,
+    pub jwt_public_key: Option<String>,
+    pub jwt_legacy_secret: Option<String>,
+    pub jwt_legacy_algorithm: Algorithm,
+    pub jwt_previous_secret: Option<String>,
+    pub jwt_previous_public_key: Option<String>,
+    pub jwt_key_grace_secs: u64,
+    pub jwt_key_files: bool,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
     pub jwt_maxage: i64,
+    pub password_hasher: PasswordAlgorithm,
+    pub password_rehash: bool,
+    pub password_peppers: HashMap<String, String>,
+    pub password_pepper_id: Option<String>,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    pub bcrypt_cost: u32,
+    pub scrypt_log_n: u8,
+    pub scrypt_r: u32,
+    pub scrypt_p: u32,
     pub port: u16,
+    pub public_ids: bool,
+    pub public_id_secret: Option<String>,
+    pub audit_log_key: String,
+    pub query_strict: bool,
+    pub query_clamp_limit: bool,
+    pub token_sources: Vec<TokenSource>,
+    pub token_delivery: TokenDelivery,
+    pub token_cookie_same_site: SameSite,
+    pub disabled_route_groups: Vec<RouteGroup>,
+    pub maintenance_mode: bool,
+    pub maintenance_retry_after: u64,
+    pub maintenance_allowlist: Vec<String>,
+    pub read_only_mode: bool,
+    pub admin_ip_allowlist: Vec<IpNet>,
+    pub admin_ip_denylist: Vec<IpNet>,
+    pub trust_proxy_headers: bool,
+    pub trusted_proxy_hops: usize,
+    pub security_headers: bool,
+    pub content_security_policy: String,
+    pub idempotency_ttl: u64,
+    pub idempotency_max_entries: usize,
+    pub revocation_cache_ttl: u64,
+    pub user_cache_ttl: u64,
+    pub user_cache_max_entries: usize,
+    pub user_cache_preload: u64,
+    pub user_cache_preload_interval: u64,
+    pub request_signature_tolerance: u64,
+    pub max_concurrent_requests: usize,
+    pub route_concurrency_limits: HashMap<String, usize>,
+    pub handler_timeout_ms: u64,
+    pub route_timeouts_ms: HashMap<String, u64>,
+    pub db_statement_timeout_ms: u64,
+    pub access_log: bool,
+    pub access_log_sample_rates: HashMap<String, f64>,
+    pub profiling_enabled: bool,
+    pub profiling_console_addr: Option<SocketAddr>,
+    pub tarpit_enabled: bool,
+    pub tarpit_account_threshold: usize,
+    pub tarpit_window: u64,
+    pub tarpit_base_delay_ms: u64,
+    pub tarpit_max_delay_ms: u64,
+    pub tarpit_decoy: bool,
+    pub admin_name: String,
+    pub admin_email: Option<String>,
+    pub admin_password: Option<String>,
+    pub smtp_server: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_tls: SmtpTls,
+    pub smtp_username: String,
+    pub smtp_password: String,
+    pub smtp_from: String,
+    pub email_template_dir: Option<String>,
+    pub email_subject_verification: String,
+    pub email_subject_password_reset: String,
+    pub email_subject_welcome: String,
+    pub email_outbox: bool,
+    pub outbox_poll_interval: u64,
+    pub outbox_batch_size: i64,
+    pub outbox_max_attempts: i32,
+    pub outbox_retry_base: u64,
+    pub slack_webhook_url: Option<String>,
+    pub notify_webhook_url: Option<String>,
+    pub availability_check: bool,
+    pub availability_rate_limit: u32,
+    pub nonce_ttl: i64,
+    pub nonce_rate_limit: u32,
+    pub two_factor_rate_limit: u32,
+    pub reauth_rate_limit: u32,
+    pub reset_password_rate_limit: u32,
+    pub activity_export_rate_limit: u32,
+    pub activity_export_sync_limit: i64,
+    pub activity_export_ttl: i64,
+    pub rate_limit_backend: RateLimitBackend,
+    pub redis_url: Option<String>,
+    pub redis_mode: RedisMode,
+    pub redis_sentinel_master: Option<String>,
+    pub redis_connect_timeout_ms: u64,
+    pub redis_response_timeout_ms: u64,
+    pub redis_max_retries: usize,
+    pub login_rate_limit_ip: u32,
+    pub login_rate_limit_email: u32,
+    pub register_rate_limit_ip: u32,
+    pub register_rate_limit_email: u32,
+    pub forgot_password_rate_limit_ip: u32,
+    pub forgot_password_rate_limit_email: u32,
+    pub invite_only: bool,
+    pub waitlist: bool,
+    pub role_grant_approval: bool,
+    pub name_denylist: Vec<String>,
+    pub name_reserved: Vec<String>,
+    pub registration_fields: Vec<RegistrationField>,
+    pub security_questions: bool,
+    pub security_questions_count: usize,
+    pub captcha_secret: Option<String>,
+    pub captcha_verify_url: String,
+    pub captcha_ip_threshold: u32,
+    pub captcha_account_threshold: u32,
+    pub captcha_window: u64,
+    pub risk_captcha_threshold: u8,
+    pub risk_mfa_threshold: u8,
+    pub risk_block_threshold: u8,
+    pub risk_timeout_ms: u64,
+    pub circuit_failure_threshold: u32,
+    pub circuit_open_secs: u64,
+    pub circuit_call_timeout_ms: u64,
+    pub login_lockout_threshold: i32,
+    pub login_lockout_duration: u64,
+    pub cleanup_interval: u64,
+    pub instance_id: String,
+    pub job_lease_ttl: u64,
+    pub stale_account_months: i32,
+    pub stale_account_grace_days: i32,
+    pub password_max_age_days: i64,
+    pub deleted_account_retention_days: i32,
+    pub reset_code_ttl: i64,
+    pub verification_token_ttl: i64,
+    pub reset_token_ttl: i64,
+    pub reset_code_max_attempts: i32,
+    pub security_answer_max_attempts: i32,
+    pub sudo_maxage: i64,
+    pub totp_issuer: String,
+    pub mfa_token_maxage: i64,
+    pub mfa_max_attempts: i32,
+    pub reauth_max_attempts: i32,
+    pub magic_link_maxage: i64,
+    pub backup_code_count: usize,
+    pub session_idle_timeout: Option<i32>,
+    pub session_max_lifetime: i64,
+    pub session_backend: SessionBackend,
+    pub session_cookie_key: Option<Key>,
+    pub refresh_token_binding: bool,
+    pub client_types: Vec<ClientType>,
+    pub route_audiences: HashMap<String, Vec<String>>,
+    pub plans: HashMap<String, Vec<String>>,
+    pub default_plan: String,
+    pub stripe_webhook_secret: Option<String>,
+    pub stripe_price_plans: HashMap<String, String>,
+    pub quota_daily_limits: HashMap<String, i64>,
+    pub quota_monthly_limits: HashMap<String, i64>,
+    pub geo_country_header: String,
+    pub secret_scanning_keys_url: String,
+    pub secret_scanning_keys_ttl: u64,
+    pub oauth_redirect_base: String,
+    pub webauthn_rp_id: String,
+    pub webauthn_rp_origin: String,
+    pub webauthn_rp_name: String,
+    pub webauthn_ceremony_ttl: i64,
+    pub google_client_id: Option<String>,
+    pub google_client_secret: Option<String>,
+    pub github_client_id: Option<String>,
+    pub github_client_secret: Option<String>,
+    pub oidc: Option<OidcSettings>,
+    pub geo_asn_header: String,
+    pub login_blocked_countries: Vec<String>,
+    pub login_mfa_countries: Vec<String>,
+    pub login_blocked_asns: Vec<i64>,
+    pub admin_allowed_countries: Vec<String>,
+    pub admin_allowed_asns: Vec<i64>,
+    pub invitation_maxage: i64,
+    pub app_url: String,
 }
 
 impl Config {
     pub fn init() -> Self {
+        let environment = match std::env::var("APP_ENV").as_deref() {
+            Ok("production") | Ok("prod") => Environment::Production,
+            _ => Environment::Development,
+        };
+        let startup_strict = std::env::var("STARTUP_STRICT")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let startup_query_plan_check = std::env::var("STARTUP_QUERY_PLAN_CHECK")
+            .map(|value| value == "true")
+            .unwrap_or(false);
         let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+        let jwt_secret = read_secret("JWT_SECRET").expect("JWT_SECRET must be set");
+        let jwt_algorithm = parse_jwt_algorithm("JWT_ALGORITHM");
+        let jwt_private_key = read_pem("JWT_PRIVATE_KEY");
+        let jwt_public_key = read_pem("JWT_PUBLIC_KEY");
+        if !is_hmac(jwt_algorithm) && jwt_public_key.is_none() {
+            panic!(
+                "JWT_PUBLIC_KEY or JWT_PUBLIC_KEY_FILE must be set for asymmetric JWT algorithms"
+            );
+        }
+        let jwt_legacy_secret = std::env::var("JWT_LEGACY_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let jwt_legacy_algorithm = parse_hmac_algorithm("JWT_LEGACY_ALGORITHM");
         let jwt_maxage = std::env::var("JWT_MAXAGE")
             .expect("JWT_MAXAGE must be set")
             .parse::<i64>()
             .expect("JWT_MAXAGE must be a number");
+        let jwt_previous_secret = read_secret("JWT_PREVIOUS_SECRET");
+        let jwt_previous_public_key = read_pem("JWT_PREVIOUS_PUBLIC_KEY");
+        let jwt_key_grace_secs = match std::env::var("JWT_KEY_GRACE_SECS") {
+            Ok(value) if !value.is_empty() => value
+                .parse::<u64>()
+                .expect("JWT_KEY_GRACE_SECS must be a number"),
+            _ => jwt_maxage.max(0) as u64 * 60,
+        };
+        let jwt_issuer = std::env::var("JWT_ISSUER")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let jwt_audience = std::env::var("JWT_AUDIENCE")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let jwt_key_files = ["JWT_SECRET", "JWT_PRIVATE_KEY", "JWT_PUBLIC_KEY"]
+            .iter()
+            .any(|key| std::env::var(format!("{}_FILE", key)).is_ok_and(|path| !path.is_empty()));
+        let password_hasher = std::env::var("PASSWORD_HASHER")
+            .map(|value| {
+                PasswordAlgorithm::parse(&value)
+                    .expect("PASSWORD_HASHER must be argon2, bcrypt or scrypt")
+            })
+            .unwrap_or(PasswordAlgorithm::Argon2);
+        let password_rehash = std::env::var("PASSWORD_REHASH")
+            .map(|value| value == "true")
+            .unwrap_or(true);
+        let password_peppers = parse_peppers();
+        let password_pepper_id = std::env::var("PASSWORD_PEPPER_ID")
+            .ok()
+            .filter(|value| !value.is_empty());
+        if let Some(id) = &password_pepper_id
+            && !password_peppers.contains_key(id)
+        {
+            panic!("PASSWORD_PEPPER_ID must name one of the PASSWORD_PEPPERS");
+        }
+        let argon2_memory_kib = std::env::var("ARGON2_MEMORY_KIB")
+            .unwrap_or_else(|_| "19456".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_MEMORY_KIB must be a number");
+        let argon2_iterations = std::env::var("ARGON2_ITERATIONS")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_ITERATIONS must be a number");
+        let argon2_parallelism = std::env::var("ARGON2_PARALLELISM")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<u32>()
+            .expect("ARGON2_PARALLELISM must be a number");
+        let bcrypt_cost = std::env::var("BCRYPT_COST")
+            .unwrap_or_else(|_| "12".to_string())
+            .parse::<u32>()
+            .expect("BCRYPT_COST must be a number");
+        let scrypt_log_n = std::env::var("SCRYPT_LOG_N")
+            .unwrap_or_else(|_| "17".to_string())
+            .parse::<u8>()
+            .expect("SCRYPT_LOG_N must be a number");
+        let scrypt_r = std::env::var("SCRYPT_R")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse::<u32>()
+            .expect("SCRYPT_R must be a number");
+        let scrypt_p = std::env::var("SCRYPT_P")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<u32>()
+            .expect("SCRYPT_P must be a number");
         let port = std::env::var("PORT")
             .expect("PORT must be set")
             .parse::<u16>()
             .expect("PORT must be a number");
+        let public_ids = std::env::var("PUBLIC_IDS")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let public_id_secret = std::env::var("PUBLIC_ID_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        if public_ids && public_id_secret.is_none() {
+            panic!("PUBLIC_ID_SECRET must be set when PUBLIC_IDS is enabled");
+        }
+        let audit_log_key = std::env::var("AUDIT_LOG_KEY")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| jwt_secret.clone());
+        let query_strict = std::env::var("QUERY_STRICT")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let query_clamp_limit = std::env::var("QUERY_CLAMP_LIMIT")
+            .map(|value| value == "true")
+            .unwrap_or(true);
+        let token_sources: Vec<TokenSource> = std::env::var("TOKEN_SOURCES")
+            .unwrap_or_else(|_| "header,cookie".to_string())
+            .split(',')
+            .filter(|source| !source.trim().is_empty())
+            .map(|source| {
+                TokenSource::parse(source)
+                    .expect("TOKEN_SOURCES must only contain header, cookie or query")
+            })
+            .collect();
+        let token_delivery = std::env::var("TOKEN_DELIVERY")
+            .map(|value| {
+                TokenDelivery::parse(&value).expect("TOKEN_DELIVERY must be body, cookie or both")
+            })
+            .unwrap_or(TokenDelivery::Body);
+        if token_delivery.in_cookie() && !token_sources.contains(&TokenSource::Cookie) {
+            panic!("TOKEN_SOURCES must include cookie when TOKEN_DELIVERY is cookie or both");
+        }
+        let token_cookie_same_site = match std::env::var("TOKEN_COOKIE_SAME_SITE")
+            .unwrap_or_else(|_| "lax".to_string())
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "lax" => SameSite::Lax,
+            "strict" => SameSite::Strict,
+            "none" => SameSite::None,
+            _ => panic!("TOKEN_COOKIE_SAME_SITE must be lax, strict or none"),
+        };
+        let disabled_route_groups = std::env::var("DISABLED_ROUTE_GROUPS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|group| !group.trim().is_empty())
+            .map(|group| {
+                RouteGroup::parse(group).expect(
+                    "DISABLED_ROUTE_GROUPS must only contain registration, admin or social_login",
+                )
+            })
+            .collect();
+        let maintenance_mode = std::env::var("MAINTENANCE_MODE")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let maintenance_retry_after = std::env::var("MAINTENANCE_RETRY_AFTER")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse::<u64>()
+            .expect("MAINTENANCE_RETRY_AFTER must be a number");
+        let maintenance_allowlist = std::env::var("MAINTENANCE_ALLOWLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+
+        let read_only_mode = std::env::var("READ_ONLY_MODE")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let admin_ip_allowlist = parse_networks("ADMIN_IP_ALLOWLIST");
+        let admin_ip_denylist = parse_networks("ADMIN_IP_DENYLIST");
+        let trust_proxy_headers = std::env::var("TRUST_PROXY_HEADERS")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let trusted_proxy_hops = match std::env::var("TRUSTED_PROXY_HOPS") {
+            Ok(value) if !value.is_empty() => {
+                value.parse().expect("TRUSTED_PROXY_HOPS must be a number")
+            }
+            _ => 1,
+        };
+        let trusted_proxy_hops = if trust_proxy_headers {
+            trusted_proxy_hops
+        } else {
+            0
+        };
+        let security_headers = std::env::var("SECURITY_HEADERS")
+            .map(|value| value == "true")
+            .unwrap_or(environment == Environment::Production);
+        let content_security_policy = std::env::var("CONTENT_SECURITY_POLICY")
+            .unwrap_or_else(|_| "default-src 'none'; frame-ancestors 'none'".to_string());
+        let idempotency_ttl = std::env::var("IDEMPOTENCY_TTL")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse::<u64>()
+            .expect("IDEMPOTENCY_TTL must be a number");
+        let idempotency_max_entries = std::env::var("IDEMPOTENCY_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<usize>()
+            .expect("IDEMPOTENCY_MAX_ENTRIES must be a number");
+        let revocation_cache_ttl = std::env::var("REVOCATION_CACHE_TTL")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<u64>()
+            .expect("REVOCATION_CACHE_TTL must be a number");
+        let user_cache_ttl = std::env::var("USER_CACHE_TTL")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<u64>()
+            .expect("USER_CACHE_TTL must be a number");
+        let user_cache_max_entries = std::env::var("USER_CACHE_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<usize>()
+            .expect("USER_CACHE_MAX_ENTRIES must be a number");
+        let user_cache_preload = std::env::var("USER_CACHE_PRELOAD")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<u64>()
+            .expect("USER_CACHE_PRELOAD must be a number");
+        let user_cache_preload_interval = std::env::var("USER_CACHE_PRELOAD_INTERVAL")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<u64>()
+            .expect("USER_CACHE_PRELOAD_INTERVAL must be a number");
+        let request_signature_tolerance = std::env::var("REQUEST_SIGNATURE_TOLERANCE")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse::<u64>()
+            .expect("REQUEST_SIGNATURE_TOLERANCE must be a number");
+        let max_concurrent_requests = std::env::var("MAX_CONCURRENT_REQUESTS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<usize>()
+            .expect("MAX_CONCURRENT_REQUESTS must be a number");
+        let route_concurrency_limits = std::env::var("ROUTE_CONCURRENCY_LIMITS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(route, limit)| {
+                let limit = limit
+                    .trim()
+                    .parse::<usize>()
+                    .expect("ROUTE_CONCURRENCY_LIMITS values must be numbers");
+                (route.trim().to_string(), limit)
+            })
+            .collect();
+        let handler_timeout_ms = std::env::var("HANDLER_TIMEOUT_MS")
+            .unwrap_or_else(|_| "30000".to_string())
+            .parse::<u64>()
+            .expect("HANDLER_TIMEOUT_MS must be a number");
+        let route_timeouts_ms: HashMap<String, u64> = std::env::var("ROUTE_TIMEOUTS_MS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(route, timeout)| {
+                let timeout = timeout
+                    .trim()
+                    .parse::<u64>()
+                    .expect("ROUTE_TIMEOUTS_MS values must be numbers");
+                (route.trim().to_string(), timeout)
+            })
+            .collect();
+        let db_statement_timeout_ms = match std::env::var("DB_STATEMENT_TIMEOUT_MS") {
+            Ok(value) if !value.is_empty() => value
+                .parse::<u64>()
+                .expect("DB_STATEMENT_TIMEOUT_MS must be a number"),
+            _ if handler_timeout_ms == 0 => 0,
+            _ => route_timeouts_ms
+                .values()
+                .copied()
+                .fold(handler_timeout_ms, u64::max),
+        };
+        let profiling_enabled = std::env::var("PROFILING_ENABLED")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let profiling_console_addr = std::env::var("PROFILING_CONSOLE_ADDR")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse::<SocketAddr>()
+                    .expect("PROFILING_CONSOLE_ADDR must be a socket address")
+            });
+        let access_log = std::env::var("ACCESS_LOG")
+            .map(|value| value == "true")
+            .unwrap_or(true);
+        let access_log_sample_rates = std::env::var("ACCESS_LOG_SAMPLE_RATES")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(class, rate)| {
+                let rate = rate
+                    .trim()
+                    .parse::<f64>()
+                    .expect("ACCESS_LOG_SAMPLE_RATES values must be numbers");
+                (class.trim().to_lowercase(), rate.clamp(0.0, 1.0))
+            })
+            .collect();
+        let tarpit_enabled = std::env::var("TARPIT_ENABLED")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let tarpit_account_threshold = std::env::var("TARPIT_ACCOUNT_THRESHOLD")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<usize>()
+            .expect("TARPIT_ACCOUNT_THRESHOLD must be a number");
+        let tarpit_window = std::env::var("TARPIT_WINDOW")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse::<u64>()
+            .expect("TARPIT_WINDOW must be a number");
+        let tarpit_base_delay_ms = std::env::var("TARPIT_BASE_DELAY_MS")
+            .unwrap_or_else(|_| "250".to_string())
+            .parse::<u64>()
+            .expect("TARPIT_BASE_DELAY_MS must be a number");
+        let tarpit_max_delay_ms = std::env::var("TARPIT_MAX_DELAY_MS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<u64>()
+            .expect("TARPIT_MAX_DELAY_MS must be a number");
+        let tarpit_decoy = std::env::var("TARPIT_DECOY")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let admin_name =
+            std::env::var("ADMIN_NAME").unwrap_or_else(|_| "Administrator".to_string());
+        let admin_email = std::env::var("ADMIN_EMAIL")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let admin_password = match std::env::var("ADMIN_PASSWORD_FILE") {
+            Ok(path) if !path.is_empty() => Some(
+                std::fs::read_to_string(&path)
+                    .expect("ADMIN_PASSWORD_FILE must be readable")
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string(),
+            ),
+            _ => std::env::var("ADMIN_PASSWORD")
+                .ok()
+                .filter(|value| !value.is_empty()),
+        };
+        let smtp_server = std::env::var("SMTP_SERVER")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let smtp_port = std::env::var("SMTP_PORT")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<u16>().expect("SMTP_PORT must be a number"))
+            .unwrap_or(587);
+        let smtp_tls = std::env::var("SMTP_TLS")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| SmtpTls::parse(&value).expect("SMTP_TLS must be starttls, tls or none"))
+            .unwrap_or(SmtpTls::StartTls);
+        let smtp_username = std::env::var("SMTP_USERNAME").unwrap_or_default();
+        let smtp_password = std::env::var("SMTP_PASSWORD").unwrap_or_default();
+        let smtp_from = std::env::var("SMTP_FROM")
+            .unwrap_or_else(|_| "Axum Auth <no-reply@example.com>".to_string());
+        let email_template_dir = std::env::var("EMAIL_TEMPLATE_DIR")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let email_subject_verification = std::env::var("EMAIL_SUBJECT_VERIFICATION")
+            .unwrap_or_else(|_| "Verify your email".to_string());
+        let email_subject_password_reset = std::env::var("EMAIL_SUBJECT_PASSWORD_RESET")
+            .unwrap_or_else(|_| "Reset your password".to_string());
+        let email_subject_welcome = std::env::var("EMAIL_SUBJECT_WELCOME")
+            .unwrap_or_else(|_| "Welcome to {{ brand_name }}".to_string());
+        let email_outbox = std::env::var("EMAIL_OUTBOX")
+            .map(|value| value == "true")
+            .unwrap_or(true);
+        let outbox_poll_interval = std::env::var("OUTBOX_POLL_INTERVAL")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u64>()
+            .expect("OUTBOX_POLL_INTERVAL must be a number");
+        let outbox_batch_size = std::env::var("OUTBOX_BATCH_SIZE")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse::<i64>()
+            .expect("OUTBOX_BATCH_SIZE must be a number");
+        let outbox_max_attempts = std::env::var("OUTBOX_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse::<i32>()
+            .expect("OUTBOX_MAX_ATTEMPTS must be a number");
+        let outbox_retry_base = std::env::var("OUTBOX_RETRY_BASE")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u64>()
+            .expect("OUTBOX_RETRY_BASE must be a number");
+        let slack_webhook_url = std::env::var("SLACK_WEBHOOK_URL")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let notify_webhook_url = std::env::var("NOTIFY_WEBHOOK_URL")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let availability_check = std::env::var("AVAILABILITY_CHECK")
+            .map(|value| value == "true")
+            .unwrap_or(true);
+        let rate_limit_backend = std::env::var("RATE_LIMIT_STORE")
+            .map(|value| {
+                RateLimitBackend::parse(&value).expect("RATE_LIMIT_STORE must be memory or redis")
+            })
+            .unwrap_or(RateLimitBackend::Memory);
+        let redis_url = std::env::var("REDIS_URL")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let redis_mode = std::env::var("REDIS_MODE")
+            .map(|value| {
+                RedisMode::parse(&value).expect("REDIS_MODE must be single, sentinel or cluster")
+            })
+            .unwrap_or(RedisMode::Single);
+        let redis_sentinel_master = std::env::var("REDIS_SENTINEL_MASTER")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let redis_connect_timeout_ms = std::env::var("REDIS_CONNECT_TIMEOUT_MS")
+            .unwrap_or_else(|_| "2000".to_string())
+            .parse::<u64>()
+            .expect("REDIS_CONNECT_TIMEOUT_MS must be a number");
+        let redis_response_timeout_ms = std::env::var("REDIS_RESPONSE_TIMEOUT_MS")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse::<u64>()
+            .expect("REDIS_RESPONSE_TIMEOUT_MS must be a number");
+        let redis_max_retries = std::env::var("REDIS_MAX_RETRIES")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<usize>()
+            .expect("REDIS_MAX_RETRIES must be a number");
+        let availability_rate_limit = std::env::var("AVAILABILITY_RATE_LIMIT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("AVAILABILITY_RATE_LIMIT must be a number");
+        let nonce_ttl = std::env::var("NONCE_TTL")
+            .unwrap_or_else(|_| "120".to_string())
+            .parse::<i64>()
+            .expect("NONCE_TTL must be a number");
+        let nonce_rate_limit = std::env::var("NONCE_RATE_LIMIT")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u32>()
+            .expect("NONCE_RATE_LIMIT must be a number");
+        let two_factor_rate_limit = std::env::var("TWO_FACTOR_RATE_LIMIT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("TWO_FACTOR_RATE_LIMIT must be a number");
+        let reauth_rate_limit = std::env::var("REAUTH_RATE_LIMIT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("REAUTH_RATE_LIMIT must be a number");
+        let reset_password_rate_limit = std::env::var("RESET_PASSWORD_RATE_LIMIT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("RESET_PASSWORD_RATE_LIMIT must be a number");
+        let activity_export_rate_limit = std::env::var("ACTIVITY_EXPORT_RATE_LIMIT")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()
+            .expect("ACTIVITY_EXPORT_RATE_LIMIT must be a number");
+        let activity_export_sync_limit = std::env::var("ACTIVITY_EXPORT_SYNC_LIMIT")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse::<i64>()
+            .expect("ACTIVITY_EXPORT_SYNC_LIMIT must be a number");
+        let activity_export_ttl = std::env::var("ACTIVITY_EXPORT_TTL")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<i64>()
+            .expect("ACTIVITY_EXPORT_TTL must be a number");
+        let login_rate_limit_ip = std::env::var("LOGIN_RATE_LIMIT_IP")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse::<u32>()
+            .expect("LOGIN_RATE_LIMIT_IP must be a number");
+        let login_rate_limit_email = std::env::var("LOGIN_RATE_LIMIT_EMAIL")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()
+            .expect("LOGIN_RATE_LIMIT_EMAIL must be a number");
+        let register_rate_limit_ip = std::env::var("REGISTER_RATE_LIMIT_IP")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("REGISTER_RATE_LIMIT_IP must be a number");
+        let register_rate_limit_email = std::env::var("REGISTER_RATE_LIMIT_EMAIL")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<u32>()
+            .expect("REGISTER_RATE_LIMIT_EMAIL must be a number");
+        let forgot_password_rate_limit_ip = std::env::var("FORGOT_PASSWORD_RATE_LIMIT_IP")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()
+            .expect("FORGOT_PASSWORD_RATE_LIMIT_IP must be a number");
+        let forgot_password_rate_limit_email = std::env::var("FORGOT_PASSWORD_RATE_LIMIT_EMAIL")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<u32>()
+            .expect("FORGOT_PASSWORD_RATE_LIMIT_EMAIL must be a number");
+        let invite_only = std::env::var("INVITE_ONLY")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let waitlist = std::env::var("WAITLIST")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let role_grant_approval = std::env::var("ROLE_GRANT_APPROVAL")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let invitation_maxage = std::env::var("INVITATION_MAXAGE")
+            .unwrap_or_else(|_| "168".to_string())
+            .parse::<i64>()
+            .expect("INVITATION_MAXAGE must be a number");
+        let app_url =
+            std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let name_denylist = parse_list("NAME_DENYLIST");
+        let name_reserved = parse_list("NAME_RESERVED");
+        let registration_fields = parse_list("REGISTRATION_FIELDS")
+            .iter()
+            .map(|field| {
+                RegistrationField::parse(field)
+                    .unwrap_or_else(|| panic!("Invalid REGISTRATION_FIELDS entry: {}", field))
+            })
+            .collect();
+        let security_questions = std::env::var("SECURITY_QUESTIONS")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let security_questions_count = std::env::var("SECURITY_QUESTIONS_COUNT")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<usize>()
+            .expect("SECURITY_QUESTIONS_COUNT must be a number");
+        let captcha_secret = std::env::var("CAPTCHA_SECRET").ok();
+        let captcha_verify_url = std::env::var("CAPTCHA_VERIFY_URL")
+            .unwrap_or_else(|_| "https://hcaptcha.com/siteverify".to_string());
+        let captcha_ip_threshold = std::env::var("CAPTCHA_IP_THRESHOLD")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u32>()
+            .expect("CAPTCHA_IP_THRESHOLD must be a number");
+        let captcha_account_threshold = std::env::var("CAPTCHA_ACCOUNT_THRESHOLD")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<u32>()
+            .expect("CAPTCHA_ACCOUNT_THRESHOLD must be a number");
+        let captcha_window = std::env::var("CAPTCHA_WINDOW")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse::<u64>()
+            .expect("CAPTCHA_WINDOW must be a number");
+        let risk_captcha_threshold = std::env::var("RISK_CAPTCHA_THRESHOLD")
+            .unwrap_or_else(|_| "50".to_string())
+            .parse::<u8>()
+            .expect("RISK_CAPTCHA_THRESHOLD must be a number");
+        let risk_mfa_threshold = std::env::var("RISK_MFA_THRESHOLD")
+            .unwrap_or_else(|_| "70".to_string())
+            .parse::<u8>()
+            .expect("RISK_MFA_THRESHOLD must be a number");
+        let risk_block_threshold = std::env::var("RISK_BLOCK_THRESHOLD")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse::<u8>()
+            .expect("RISK_BLOCK_THRESHOLD must be a number");
+        let risk_timeout_ms = std::env::var("RISK_TIMEOUT_MS")
+            .unwrap_or_else(|_| "2000".to_string())
+            .parse::<u64>()
+            .expect("RISK_TIMEOUT_MS must be a number");
+        let circuit_failure_threshold = std::env::var("CIRCUIT_FAILURE_THRESHOLD")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()
+            .expect("CIRCUIT_FAILURE_THRESHOLD must be a number");
+        let circuit_open_secs = std::env::var("CIRCUIT_OPEN_SECS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u64>()
+            .expect("CIRCUIT_OPEN_SECS must be a number");
+        let circuit_call_timeout_ms = std::env::var("CIRCUIT_CALL_TIMEOUT_MS")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse::<u64>()
+            .expect("CIRCUIT_CALL_TIMEOUT_MS must be a number");
+        let login_lockout_threshold = std::env::var("LOGIN_LOCKOUT_THRESHOLD")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i32>()
+            .expect("LOGIN_LOCKOUT_THRESHOLD must be a number");
+        let login_lockout_duration = std::env::var("LOGIN_LOCKOUT_DURATION")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse::<u64>()
+            .expect("LOGIN_LOCKOUT_DURATION must be a number");
+        let cleanup_interval = std::env::var("CLEANUP_INTERVAL")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse::<u64>()
+            .expect("CLEANUP_INTERVAL must be a number");
+        let instance_id = std::env::var("INSTANCE_ID")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()[..12].to_string());
+        let job_lease_ttl = std::env::var("JOB_LEASE_TTL")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse::<u64>()
+            .expect("JOB_LEASE_TTL must be a number");
+        let stale_account_months = std::env::var("STALE_ACCOUNT_MONTHS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i32>()
+            .expect("STALE_ACCOUNT_MONTHS must be a number");
+        let stale_account_grace_days = std::env::var("STALE_ACCOUNT_GRACE_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i32>()
+            .expect("STALE_ACCOUNT_GRACE_DAYS must be a number");
+        let password_max_age_days = std::env::var("PASSWORD_MAX_AGE_DAYS")
+            .unwrap_or_else(|_| "365".to_string())
+            .parse::<i64>()
+            .expect("PASSWORD_MAX_AGE_DAYS must be a number");
+        let deleted_account_retention_days = std::env::var("DELETED_ACCOUNT_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i32>()
+            .expect("DELETED_ACCOUNT_RETENTION_DAYS must be a number");
+        let reset_code_ttl = std::env::var("RESET_CODE_TTL")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<i64>()
+            .expect("RESET_CODE_TTL must be a number");
+        let verification_token_ttl = std::env::var("VERIFICATION_TOKEN_TTL")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse::<i64>()
+            .expect("VERIFICATION_TOKEN_TTL must be a number");
+        let reset_token_ttl = std::env::var("RESET_TOKEN_TTL")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i64>()
+            .expect("RESET_TOKEN_TTL must be a number");
+        let reset_code_max_attempts = std::env::var("RESET_CODE_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i32>()
+            .expect("RESET_CODE_MAX_ATTEMPTS must be a number");
+        let security_answer_max_attempts = std::env::var("SECURITY_ANSWER_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<i32>()
+            .expect("SECURITY_ANSWER_MAX_ATTEMPTS must be a number");
+        let sudo_maxage = std::env::var("SUDO_MAXAGE")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i64>()
+            .expect("SUDO_MAXAGE must be a number");
+        let totp_issuer = std::env::var("TOTP_ISSUER").unwrap_or_else(|_| "axum-auth".to_string());
+        let mfa_token_maxage = std::env::var("MFA_TOKEN_MAXAGE")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i64>()
+            .expect("MFA_TOKEN_MAXAGE must be a number");
+        let mfa_max_attempts = std::env::var("MFA_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i32>()
+            .expect("MFA_MAX_ATTEMPTS must be a number");
+        let reauth_max_attempts = std::env::var("REAUTH_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<i32>()
+            .expect("REAUTH_MAX_ATTEMPTS must be a number");
+        let magic_link_maxage = std::env::var("MAGIC_LINK_MAXAGE")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse::<i64>()
+            .expect("MAGIC_LINK_MAXAGE must be a number");
+        let backup_code_count = std::env::var("BACKUP_CODE_COUNT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<usize>()
+            .expect("BACKUP_CODE_COUNT must be a number");
+        let session_idle_timeout = std::env::var("SESSION_IDLE_TIMEOUT")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i32>()
+            .map(|minutes| (minutes > 0).then_some(minutes))
+            .expect("SESSION_IDLE_TIMEOUT must be a number");
+        let session_max_lifetime = std::env::var("SESSION_MAX_LIFETIME")
+            .unwrap_or_else(|_| "1440".to_string())
+            .parse::<i64>()
+            .expect("SESSION_MAX_LIFETIME must be a number");
+        let session_backend = std::env::var("SESSION_BACKEND")
+            .map(|value| {
+                SessionBackend::parse(&value).expect("SESSION_BACKEND must be database or cookie")
+            })
+            .unwrap_or(SessionBackend::Database);
+        let session_cookie_key = std::env::var("SESSION_COOKIE_KEY")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| {
+                hex::decode(value.trim())
+                    .ok()
+                    .and_then(|bytes| Key::try_from(bytes.as_slice()).ok())
+                    .expect("SESSION_COOKIE_KEY must be at least 64 hex-encoded bytes")
+            });
+        if session_backend == SessionBackend::Cookie && session_cookie_key.is_none() {
+            panic!("SESSION_COOKIE_KEY must be set when SESSION_BACKEND is cookie");
+        }
+        let refresh_token_binding = std::env::var("REFRESH_TOKEN_BINDING")
+            .map(|value| value == "true")
+            .unwrap_or(false);
+        let client_types = std::env::var("CLIENT_TYPES")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(name, lifetime)| ClientType {
+                name: name.trim().to_string(),
+                token_lifetime: lifetime
+                    .trim()
+                    .parse::<i64>()
+                    .expect("CLIENT_TYPES values must be numbers"),
+            })
+            .collect();
+        let route_audiences = parse_groups("ROUTE_AUDIENCES");
+        let plans = parse_groups("PLANS");
+        let default_plan = std::env::var("DEFAULT_PLAN").unwrap_or_else(|_| "free".to_string());
+        let stripe_webhook_secret = std::env::var("STRIPE_WEBHOOK_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let stripe_price_plans = std::env::var("STRIPE_PRICE_PLANS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(price, plan)| (price.trim().to_string(), plan.trim().to_string()))
+            .collect();
+        let quota_daily_limits = parse_limits("QUOTA_DAILY_LIMITS");
+        let quota_monthly_limits = parse_limits("QUOTA_MONTHLY_LIMITS");
+        let secret_scanning_keys_url =
+            std::env::var("SECRET_SCANNING_KEYS_URL").unwrap_or_else(|_| {
+                "https://api.github.com/meta/public_keys/secret_scanning".to_string()
+            });
+        let secret_scanning_keys_ttl = std::env::var("SECRET_SCANNING_KEYS_TTL")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<u64>()
+            .expect("SECRET_SCANNING_KEYS_TTL must be a number");
+        let oauth_redirect_base = std::env::var("OAUTH_REDIRECT_BASE")
+            .unwrap_or_else(|_| format!("http://localhost:{}", port));
+        let webauthn_rp_id =
+            std::env::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| "localhost".to_string());
+        let webauthn_rp_origin = std::env::var("WEBAUTHN_RP_ORIGIN")
+            .unwrap_or_else(|_| format!("http://localhost:{}", port));
+        let webauthn_rp_name =
+            std::env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "axum-auth".to_string());
+        let webauthn_ceremony_ttl = std::env::var("WEBAUTHN_CEREMONY_TTL")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse::<i64>()
+            .expect("WEBAUTHN_CEREMONY_TTL must be a number");
+        let google_client_id = std::env::var("GOOGLE_CLIENT_ID")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let google_client_secret = std::env::var("GOOGLE_CLIENT_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let github_client_id = std::env::var("GITHUB_CLIENT_ID")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let github_client_secret = std::env::var("GITHUB_CLIENT_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        let oidc = match (
+            std::env::var("OIDC_ISSUER_URL")
+                .ok()
+                .filter(|value| !value.is_empty()),
+            std::env::var("OIDC_CLIENT_ID")
+                .ok()
+                .filter(|value| !value.is_empty()),
+        ) {
+            (Some(issuer_url), Some(client_id)) => Some(OidcSettings {
+                issuer_url,
+                client_id,
+                client_secret: std::env::var("OIDC_CLIENT_SECRET").unwrap_or_default(),
+                scopes: std::env::var("OIDC_SCOPES")
+                    .unwrap_or_else(|_| "openid email profile".to_string()),
+                email_claim: std::env::var("OIDC_EMAIL_CLAIM")
+                    .unwrap_or_else(|_| "email".to_string()),
+                name_claim: std::env::var("OIDC_NAME_CLAIM").unwrap_or_else(|_| "name".to_string()),
+            }),
+            _ => None,
+        };
+        let geo_country_header = std::env::var("GEO_COUNTRY_HEADER")
+            .unwrap_or_else(|_| "cf-ipcountry".to_string())
+            .to_lowercase();
+        let geo_asn_header = std::env::var("GEO_ASN_HEADER")
+            .unwrap_or_else(|_| "x-client-asn".to_string())
+            .to_lowercase();
+        let login_blocked_countries = parse_countries("LOGIN_BLOCKED_COUNTRIES");
+        let login_mfa_countries = parse_countries("LOGIN_MFA_COUNTRIES");
+        let login_blocked_asns = parse_asns("LOGIN_BLOCKED_ASNS");
+        let admin_allowed_countries = parse_countries("ADMIN_ALLOWED_COUNTRIES");
+        let admin_allowed_asns = parse_asns("ADMIN_ALLOWED_ASNS");
 
         Config {
+            environment,
+            startup_strict,
+            startup_query_plan_check,
             database_url,
             jwt_secret,
+            jwt_algorithm,
+            jwt_private_key,
+            jwt_public_key,
+            jwt_legacy_secret,
+            jwt_legacy_algorithm,
+            jwt_previous_secret,
+            jwt_previous_public_key,
+            jwt_key_grace_secs,
+            jwt_key_files,
+            jwt_issuer,
+            jwt_audience,
             jwt_maxage,
+            password_hasher,
+            password_rehash,
+            password_peppers,
+            password_pepper_id,
+            argon2_memory_kib,
+            argon2_iterations,
+            argon2_parallelism,
+            bcrypt_cost,
+            scrypt_log_n,
+            scrypt_r,
+            scrypt_p,
             port,
+            public_ids,
+            public_id_secret,
+            audit_log_key,
+            query_strict,
+            query_clamp_limit,
+            token_sources,
+            token_delivery,
+            token_cookie_same_site,
+            disabled_route_groups,
+            maintenance_mode,
+            maintenance_retry_after,
+            maintenance_allowlist,
+            read_only_mode,
+            admin_ip_allowlist,
+            admin_ip_denylist,
+            trust_proxy_headers,
+            trusted_proxy_hops,
+            security_headers,
+            content_security_policy,
+            idempotency_ttl,
+            idempotency_max_entries,
+            revocation_cache_ttl,
+            user_cache_ttl,
+            user_cache_max_entries,
+            user_cache_preload,
+            user_cache_preload_interval,
+            request_signature_tolerance,
+            max_concurrent_requests,
+            route_concurrency_limits,
+            handler_timeout_ms,
+            route_timeouts_ms,
+            db_statement_timeout_ms,
+            access_log,
+            access_log_sample_rates,
+            profiling_enabled,
+            profiling_console_addr,
+            tarpit_enabled,
+            tarpit_account_threshold,
+            tarpit_window,
+            tarpit_base_delay_ms,
+            tarpit_max_delay_ms,
+            tarpit_decoy,
+            admin_name,
+            admin_email,
+            admin_password,
+            smtp_server,
+            smtp_port,
+            smtp_tls,
+            smtp_username,
+            smtp_password,
+            smtp_from,
+            email_template_dir,
+            email_subject_verification,
+            email_subject_password_reset,
+            email_subject_welcome,
+            email_outbox,
+            outbox_poll_interval,
+            outbox_batch_size,
+            outbox_max_attempts,
+            outbox_retry_base,
+            slack_webhook_url,
+            notify_webhook_url,
+            availability_check,
+            availability_rate_limit,
+            nonce_ttl,
+            nonce_rate_limit,
+            two_factor_rate_limit,
+            reauth_rate_limit,
+            reset_password_rate_limit,
+            activity_export_rate_limit,
+            activity_export_sync_limit,
+            activity_export_ttl,
+            rate_limit_backend,
+            redis_url,
+            redis_mode,
+            redis_sentinel_master,
+            redis_connect_timeout_ms,
+            redis_response_timeout_ms,
+            redis_max_retries,
+            login_rate_limit_ip,
+            login_rate_limit_email,
+            register_rate_limit_ip,
+            register_rate_limit_email,
+            forgot_password_rate_limit_ip,
+            forgot_password_rate_limit_email,
+            invite_only,
+            waitlist,
+            role_grant_approval,
+            name_denylist,
+            name_reserved,
+            registration_fields,
+            security_questions,
+            security_questions_count,
+            captcha_secret,
+            captcha_verify_url,
+            captcha_ip_threshold,
+            captcha_account_threshold,
+            captcha_window,
+            risk_captcha_threshold,
+            risk_mfa_threshold,
+            risk_block_threshold,
+            risk_timeout_ms,
+            circuit_failure_threshold,
+            circuit_open_secs,
+            circuit_call_timeout_ms,
+            login_lockout_threshold,
+            login_lockout_duration,
+            cleanup_interval,
+            instance_id,
+            job_lease_ttl,
+            stale_account_months,
+            stale_account_grace_days,
+            password_max_age_days,
+            deleted_account_retention_days,
+            reset_code_ttl,
+            verification_token_ttl,
+            reset_token_ttl,
+            reset_code_max_attempts,
+            security_answer_max_attempts,
+            sudo_maxage,
+            totp_issuer,
+            mfa_token_maxage,
+            mfa_max_attempts,
+            reauth_max_attempts,
+            magic_link_maxage,
+            backup_code_count,
+            session_idle_timeout,
+            session_max_lifetime,
+            session_backend,
+            session_cookie_key,
+            refresh_token_binding,
+            client_types,
+            route_audiences,
+            plans,
+            default_plan,
+            stripe_webhook_secret,
+            stripe_price_plans,
+            quota_daily_limits,
+            quota_monthly_limits,
+            geo_country_header,
+            secret_scanning_keys_url,
+            secret_scanning_keys_ttl,
+            oauth_redirect_base,
+            webauthn_rp_id,
+            webauthn_rp_origin,
+            webauthn_rp_name,
+            webauthn_ceremony_ttl,
+            google_client_id,
+            google_client_secret,
+            github_client_id,
+            github_client_secret,
+            oidc,
+            geo_asn_header,
+            login_blocked_countries,
+            login_mfa_countries,
+            login_blocked_asns,
+            admin_allowed_countries,
+            admin_allowed_asns,
+            invitation_maxage,
+            app_url,
         }
     }
 }
+
+fn parse_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn parse_networks(key: &str) -> Vec<IpNet> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<IpNet>()
+                .or_else(|_| value.parse::<std::net::IpAddr>().map(IpNet::from))
+                .unwrap_or_else(|_| panic!("{} contains an invalid CIDR: {}", key, value))
+        })
+        .collect()
+}
+
+fn parse_countries(key: &str) -> Vec<String> {
+    parse_list(key)
+        .into_iter()
+        .map(|country| country.to_uppercase())
+        .collect()
+}
+
+fn parse_asns(key: &str) -> Vec<i64> {
+    parse_list(key)
+        .iter()
+        .map(|value| {
+            value
+                .trim_start_matches("AS")
+                .parse::<i64>()
+                .unwrap_or_else(|_| panic!("{} contains an invalid ASN: {}", key, value))
+        })
+        .collect()
+}
+
+fn parse_limits(key: &str) -> HashMap<String, i64> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(name, limit)| {
+            let limit = limit
+                .trim()
+                .parse::<i64>()
+                .unwrap_or_else(|_| panic!("{} values must be numbers", key));
+            (name.trim().to_string(), limit)
+        })
+        .collect()
+}
+
+fn parse_groups(key: &str) -> HashMap<String, Vec<String>> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(name, members)| {
+            (
+                name.trim().to_string(),
+                members
+                    .split('|')
+                    .map(|member| member.trim().to_string())
+                    .filter(|member| !member.is_empty())
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+pub fn is_hmac(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    )
+}
+
+fn parse_jwt_algorithm(key: &str) -> Algorithm {
+    match std::env::var(key).as_deref() {
+        Ok("") | Err(_) => Algorithm::HS256,
+        Ok(value) => value.trim().parse::<Algorithm>().unwrap_or_else(|_| {
+            panic!(
+                "{} must be one of HS256, HS384, HS512, RS256, RS384, RS512, PS256, PS384, PS512, ES256, ES384 or EdDSA",
+                key
+            )
+        }),
+    }
+}
+
+pub fn read_secret(key: &str) -> Option<String> {
+    read_pem(key)
+        .map(|value| value.trim_end().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn read_pem(key: &str) -> Option<String> {
+    match std::env::var(format!("{}_FILE", key)) {
+        Ok(path) if !path.is_empty() => Some(
+            std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("{}_FILE could not be read: {}", key, e)),
+        ),
+        _ => std::env::var(key)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.replace("\\n", "\n")),
+    }
+}
+
+fn parse_hmac_algorithm(key: &str) -> Algorithm {
+    match std::env::var(key).as_deref() {
+        Ok("") | Err(_) => Algorithm::HS256,
+        Ok(value) => match value.trim().parse::<Algorithm>() {
+            Ok(algorithm @ (Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)) => algorithm,
+            _ => panic!("{} must be HS256, HS384 or HS512", key),
+        },
+    }
+}
+
+fn parse_peppers() -> HashMap<String, String> {
+    let value = match std::env::var("PASSWORD_PEPPERS_FILE") {
+        Ok(path) if !path.is_empty() => std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("PASSWORD_PEPPERS_FILE could not be read: {}", e)),
+        _ => std::env::var("PASSWORD_PEPPERS").unwrap_or_default(),
+    };
+
+    parse_pepper_list(&value)
+}
+
+fn parse_pepper_list(value: &str) -> HashMap<String, String> {
+    value
+        .split([',', '\n'])
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(id, secret)| {
+            let (id, secret) = (id.trim(), secret.trim());
+            if id.is_empty() || id.contains('$') || secret.is_empty() {
+                panic!("PASSWORD_PEPPERS entries must be id=secret and ids must not contain '$'");
+            }
+            (id.to_string(), secret.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comma_and_newline_separated_peppers() {
+        let peppers = parse_pepper_list("v1=alpha, v2 = beta\nv3=gamma=delta\n");
+
+        assert_eq!(peppers.len(), 3);
+        assert_eq!(peppers["v1"], "alpha");
+        assert_eq!(peppers["v2"], "beta");
+        assert_eq!(peppers["v3"], "gamma=delta");
+    }
+
+    #[test]
+    fn ignores_entries_without_a_secret_separator() {
+        let peppers = parse_pepper_list("v1=alpha,,garbage\n");
+
+        assert_eq!(peppers.len(), 1);
+        assert_eq!(peppers["v1"], "alpha");
+    }
+
+    #[test]
+    fn empty_value_has_no_peppers() {
+        assert!(parse_pepper_list("").is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "PASSWORD_PEPPERS entries")]
+    fn rejects_ids_containing_the_hash_delimiter() {
+        parse_pepper_list("v$1=alpha");
+    }
+
+    #[test]
+    #[should_panic(expected = "PASSWORD_PEPPERS entries")]
+    fn rejects_empty_secrets() {
+        parse_pepper_list("v1=");
+    }
+}
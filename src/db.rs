@@ -0,0 +1,3767 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    audit::{AuditChain, AuditEntry, AuditLogFilter},
+    mail::EmailMessage,
+    models::{
+        AccountStatus, Announcement, AnnouncementSeverity, ApiKey, AuditLog, Credential,
+        Delegation, EmailBranding, Invitation, LoginAttempt, Nonce, OAuthAccount, Organization,
+        OutboxEmail, OutboxStatus, RecoveryEmail, ResetCode, Role, RoleGrant, RolePermission,
+        ScheduledJob, SecurityQuestion, Session, User, UserChange, UserRole,
+    },
+    pagination::PageQuery,
+};
+
+const USER_BY_ID_SQL: &str = "SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL";
+const USER_BY_EMAIL_SQL: &str =
+    "SELECT * FROM users WHERE LOWER(email) = LOWER($1) AND deleted_at IS NULL";
+const SESSION_BY_ID_SQL: &str = "SELECT * FROM sessions WHERE id = $1";
+const SESSION_BY_REFRESH_TOKEN_SQL: &str = "SELECT * FROM sessions WHERE refresh_token_hash = $1";
+const TOKEN_REVOKED_SQL: &str = "SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)";
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HotQueryParam {
+    Uuid,
+    Text,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HotQuery {
+    pub name: &'static str,
+    pub sql: &'static str,
+    pub param: HotQueryParam,
+}
+
+pub const HOT_QUERIES: &[HotQuery] = &[
+    HotQuery {
+        name: "user_by_id",
+        sql: USER_BY_ID_SQL,
+        param: HotQueryParam::Uuid,
+    },
+    HotQuery {
+        name: "user_by_email",
+        sql: USER_BY_EMAIL_SQL,
+        param: HotQueryParam::Text,
+    },
+    HotQuery {
+        name: "session_by_id",
+        sql: SESSION_BY_ID_SQL,
+        param: HotQueryParam::Uuid,
+    },
+    HotQuery {
+        name: "session_by_refresh_token",
+        sql: SESSION_BY_REFRESH_TOKEN_SQL,
+        param: HotQueryParam::Text,
+    },
+    HotQuery {
+        name: "token_revoked",
+        sql: TOKEN_REVOKED_SQL,
+        param: HotQueryParam::Text,
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct DBClient {
+    pool: Pool<Postgres>,
+}
+
+impl DBClient {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        DBClient { pool }
+    }
+
+    pub fn pool(&self) -> &Pool<Postgres> {
+        &self.pool
+    }
+}
+
+#[async_trait]
+pub trait UserExt {
+    async fn get_user(
+        &self,
+        user_id: Option<Uuid>,
+        name: Option<&str>,
+        email: Option<&str>,
+        token_hash: Option<&str>,
+    ) -> Result<Option<User>, sqlx::Error>;
+
+    async fn save_user<T: Into<String> + Send>(
+        &self,
+        name: T,
+        email: T,
+        password: T,
+        verification_token_hash: T,
+        token_expires_at: DateTime<Utc>,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn verify_user_token(&self, token_hash: &str) -> Result<(), sqlx::Error>;
+
+    async fn add_verification_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn update_user_password(
+        &self,
+        user_id: Uuid,
+        password: String,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn create_admin(
+        &self,
+        name: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn set_user_locked(&self, user_id: Uuid, locked: bool) -> Result<User, sqlx::Error>;
+
+    async fn purge_expired_tokens(&self) -> Result<u64, sqlx::Error>;
+
+    async fn admin_exists(&self) -> Result<bool, sqlx::Error>;
+
+    async fn get_users_by_status(
+        &self,
+        account_status: AccountStatus,
+        query: &PageQuery<User>,
+    ) -> Result<Vec<User>, sqlx::Error>;
+
+    async fn count_users_by_status(
+        &self,
+        account_status: AccountStatus,
+    ) -> Result<i64, sqlx::Error>;
+
+    async fn set_account_status(
+        &self,
+        user_id: Uuid,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn update_user_name(&self, user_id: Uuid, name: &str) -> Result<User, sqlx::Error>;
+
+    async fn start_registration(
+        &self,
+        email: &str,
+        verification_token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn complete_registration(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        password: &str,
+    ) -> Result<User, sqlx::Error>;
+
+    async fn record_login(&self, user_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn get_recently_active_users(&self, limit: i64) -> Result<Vec<User>, sqlx::Error>;
+
+    async fn flag_dormant_users(&self, months: i32) -> Result<Vec<User>, sqlx::Error>;
+
+    async fn deactivate_dormant_users(&self, grace_days: i32) -> Result<Vec<User>, sqlx::Error>;
+
+    async fn set_user_plan(
+        &self,
+        user_id: Uuid,
+        plan: Option<&str>,
+    ) -> Result<Option<User>, sqlx::Error>;
+
+    async fn bulk_assign_role(
+        &self,
+        user_ids: &[Uuid],
+        role: &UserRole,
+    ) -> Result<Vec<(Uuid, Option<UserRole>)>, sqlx::Error>;
+    async fn rehash_user_password(
+        &self,
+        user_id: Uuid,
+        current: &str,
+        password: &str,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn soft_delete_user(&self, user_id: Uuid) -> Result<Option<User>, sqlx::Error>;
+
+    async fn get_deleted_user_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error>;
+
+    async fn restore_user(&self, user_id: Uuid) -> Result<Option<User>, sqlx::Error>;
+
+    async fn purge_deleted_users(&self, retention_days: i32) -> Result<Vec<Uuid>, sqlx::Error>;
+
+    async fn email_exists(&self, email: &str) -> Result<bool, sqlx::Error>;
+
+    async fn sync_user_plan(
+        &self,
+        user_id: Uuid,
+        plan: Option<&str>,
+        event_at: DateTime<Utc>,
+    ) -> Result<Option<User>, sqlx::Error>;
+
+    async fn add_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn get_user_by_reset_token(&self, token_hash: &str) -> Result<Option<User>, sqlx::Error>;
+
+    async fn clear_reset_token(&self, user_id: Uuid) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+impl UserExt for DBClient {
+    async fn get_user(
+        &self,
+        user_id: Option<Uuid>,
+        name: Option<&str>,
+        email: Option<&str>,
+        token_hash: Option<&str>,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let mut user: Option<User> = None;
+
+        if let Some(user_id) = user_id {
+            user = sqlx::query_as::<_, User>(USER_BY_ID_SQL)
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        } else if let Some(name) = name {
+            user = sqlx::query_as::<_, User>(
+                "SELECT * FROM users WHERE name = $1 AND deleted_at IS NULL",
+            )
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        } else if let Some(email) = email {
+            user = sqlx::query_as::<_, User>(USER_BY_EMAIL_SQL)
+                .bind(email)
+                .fetch_optional(&self.pool)
+                .await?;
+        } else if let Some(token_hash) = token_hash {
+            user = sqlx::query_as::<_, User>(
+                "SELECT * FROM users WHERE verification_token_hash = $1 AND deleted_at IS NULL",
+            )
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        }
+
+        Ok(user)
+    }
+
+    async fn save_user<T: Into<String> + Send>(
+        &self,
+        name: T,
+        email: T,
+        password: T,
+        verification_token_hash: T,
+        token_expires_at: DateTime<Utc>,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (name, email, password, verification_token_hash, token_expires_at, account_status)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(name.into())
+        .bind(normalize_email(&email.into()))
+        .bind(password.into())
+        .bind(verification_token_hash.into())
+        .bind(token_expires_at)
+        .bind(account_status)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn verify_user_token(&self, token_hash: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET verified = true,
+                verification_token_hash = NULL,
+                token_expires_at = NULL,
+                updated_at = NOW()
+            WHERE verification_token_hash = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(token_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn add_verification_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET verification_token_hash = $1, token_expires_at = $2, updated_at = NOW()
+            WHERE id = $3
+            "#,
+        )
+        .bind(token_hash)
+        .bind(token_expires_at)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_user_password(
+        &self,
+        user_id: Uuid,
+        password: String,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET password = $1,
+                password_changed_at = NOW(),
+                verification_token_hash = NULL,
+                token_expires_at = NULL,
+                reset_token_hash = NULL,
+                reset_token_expires_at = NULL,
+                updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(password)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn create_admin(
+        &self,
+        name: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (name, email, password, role, verified)
+            VALUES ($1, $2, $3, 'admin', true)
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(normalize_email(email))
+        .bind(password)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn set_user_locked(&self, user_id: Uuid, locked: bool) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET locked_at = CASE WHEN $1 THEN NOW() ELSE NULL END, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(locked)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn purge_expired_tokens(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET verification_token_hash = NULL, token_expires_at = NULL
+            WHERE token_expires_at IS NOT NULL AND token_expires_at < NOW()
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let reset = sqlx::query(
+            r#"
+            UPDATE users
+            SET reset_token_hash = NULL, reset_token_expires_at = NULL
+            WHERE reset_token_expires_at IS NOT NULL AND reset_token_expires_at < NOW()
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() + reset.rows_affected())
+    }
+
+    async fn admin_exists(&self) -> Result<bool, sqlx::Error> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE role = 'admin' AND deleted_at IS NULL)",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn get_users_by_status(
+        &self,
+        account_status: AccountStatus,
+        query: &PageQuery<User>,
+    ) -> Result<Vec<User>, sqlx::Error> {
+        let sql = format!(
+            "SELECT * FROM users WHERE account_status = $1 AND deleted_at IS NULL {} LIMIT $2 OFFSET $3",
+            query.order_by()
+        );
+
+        let users = sqlx::query_as::<_, User>(&sql)
+            .bind(account_status)
+            .bind(query.limit as i64)
+            .bind(query.offset())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(users)
+    }
+
+    async fn count_users_by_status(
+        &self,
+        account_status: AccountStatus,
+    ) -> Result<i64, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM users WHERE account_status = $1 AND deleted_at IS NULL",
+        )
+        .bind(account_status)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn set_account_status(
+        &self,
+        user_id: Uuid,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET account_status = $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(account_status)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn update_user_name(&self, user_id: Uuid, name: &str) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET name = $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn start_registration(
+        &self,
+        email: &str,
+        verification_token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+        account_status: AccountStatus,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (name, email, password, verification_token_hash, token_expires_at, account_status, registration_state)
+            VALUES ('', $1, '', $2, $3, $4, 'pending_profile')
+            RETURNING *
+            "#,
+        )
+        .bind(normalize_email(email))
+        .bind(verification_token_hash)
+        .bind(token_expires_at)
+        .bind(account_status)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn complete_registration(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        password: &str,
+    ) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET name = $1,
+                password = $2,
+                password_changed_at = NOW(),
+                verified = true,
+                verification_token_hash = NULL,
+                token_expires_at = NULL,
+                registration_state = 'complete',
+                updated_at = NOW()
+            WHERE id = $3 AND registration_state = 'pending_profile'
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(password)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn record_login(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET last_login_at = NOW(), dormant_notified_at = NULL, deactivated_at = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_recently_active_users(&self, limit: i64) -> Result<Vec<User>, sqlx::Error> {
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT * FROM users
+            WHERE deleted_at IS NULL AND last_login_at IS NOT NULL
+            ORDER BY last_login_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn flag_dormant_users(&self, months: i32) -> Result<Vec<User>, sqlx::Error> {
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET dormant_notified_at = NOW()
+            WHERE account_status = 'active'
+                AND deleted_at IS NULL
+                AND deactivated_at IS NULL
+                AND dormant_notified_at IS NULL
+                AND COALESCE(last_login_at, created_at) < NOW() - make_interval(months => $1)
+            RETURNING *
+            "#,
+        )
+        .bind(months)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn deactivate_dormant_users(&self, grace_days: i32) -> Result<Vec<User>, sqlx::Error> {
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET deactivated_at = NOW()
+            WHERE deactivated_at IS NULL
+                AND deleted_at IS NULL
+                AND dormant_notified_at < NOW() - make_interval(days => $1)
+            RETURNING *
+            "#,
+        )
+        .bind(grace_days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn set_user_plan(
+        &self,
+        user_id: Uuid,
+        plan: Option<&str>,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET plan = $1, updated_at = NOW()
+            WHERE id = $2 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(plan)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn bulk_assign_role(
+        &self,
+        user_ids: &[Uuid],
+        role: &UserRole,
+    ) -> Result<Vec<(Uuid, Option<UserRole>)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut previous_roles = Vec::with_capacity(user_ids.len());
+
+        for user_id in user_ids {
+            let previous: Option<UserRole> = sqlx::query_scalar(
+                "SELECT role FROM users WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+            )
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if previous.as_ref().is_some_and(|previous| previous != role) {
+                sqlx::query("UPDATE users SET role = $1, updated_at = NOW() WHERE id = $2")
+                    .bind(role)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            previous_roles.push((*user_id, previous));
+        }
+
+        tx.commit().await?;
+
+        Ok(previous_roles)
+    }
+    async fn rehash_user_password(
+        &self,
+        user_id: Uuid,
+        current: &str,
+        password: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE users SET password = $1 WHERE id = $2 AND password = $3")
+            .bind(password)
+            .bind(user_id)
+            .bind(current)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn soft_delete_user(&self, user_id: Uuid) -> Result<Option<User>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET deleted_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if user.is_some() {
+            sqlx::query(
+                "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "UPDATE api_keys SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn get_deleted_user_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE LOWER(email) = LOWER($1) AND deleted_at IS NOT NULL",
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn restore_user(&self, user_id: Uuid) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET deleted_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn purge_deleted_users(&self, retention_days: i32) -> Result<Vec<Uuid>, sqlx::Error> {
+        let user_ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            DELETE FROM users
+            WHERE deleted_at < NOW() - make_interval(days => $1)
+            RETURNING id
+            "#,
+        )
+        .bind(retention_days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(user_ids)
+    }
+
+    async fn email_exists(&self, email: &str) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))",
+        )
+        .bind(normalize_email(email))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn sync_user_plan(
+        &self,
+        user_id: Uuid,
+        plan: Option<&str>,
+        event_at: DateTime<Utc>,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET plan = $1, plan_event_at = $3, updated_at = NOW()
+            WHERE id = $2 AND deleted_at IS NULL
+                AND (plan_event_at IS NULL OR plan_event_at < $3)
+            RETURNING *
+            "#,
+        )
+        .bind(plan)
+        .bind(user_id)
+        .bind(event_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn add_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET reset_token_hash = $1, reset_token_expires_at = $2, updated_at = NOW()
+            WHERE id = $3
+            "#,
+        )
+        .bind(token_hash)
+        .bind(token_expires_at)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_user_by_reset_token(&self, token_hash: &str) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE reset_token_hash = $1 AND deleted_at IS NULL",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn clear_reset_token(&self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET reset_token_hash = NULL, reset_token_expires_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait OrganizationExt {
+    async fn create_organization(&self, name: &str) -> Result<Organization, sqlx::Error>;
+
+    async fn get_organization(&self, org_id: Uuid) -> Result<Option<Organization>, sqlx::Error>;
+
+    async fn update_organization_branding(
+        &self,
+        org_id: Uuid,
+        branding: &EmailBranding,
+    ) -> Result<Option<Organization>, sqlx::Error>;
+
+    async fn get_email_branding(&self, user: &User) -> Result<Option<EmailBranding>, sqlx::Error>;
+
+    async fn update_organization_session_policy(
+        &self,
+        org_id: Uuid,
+        idle_timeout: Option<i32>,
+        max_lifetime: Option<i32>,
+    ) -> Result<Option<Organization>, sqlx::Error>;
+
+    async fn update_organization_geo_policy(
+        &self,
+        org_id: Uuid,
+        blocked_countries: &[String],
+        mfa_countries: &[String],
+        blocked_asns: &[i64],
+    ) -> Result<Option<Organization>, sqlx::Error>;
+
+    async fn set_organization_plan(
+        &self,
+        org_id: Uuid,
+        plan: Option<&str>,
+    ) -> Result<Option<Organization>, sqlx::Error>;
+
+    async fn sync_organization_plan(
+        &self,
+        org_id: Uuid,
+        plan: Option<&str>,
+        event_at: DateTime<Utc>,
+    ) -> Result<Option<Organization>, sqlx::Error>;
+}
+
+#[async_trait]
+impl OrganizationExt for DBClient {
+    async fn create_organization(&self, name: &str) -> Result<Organization, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            "INSERT INTO organizations (name) VALUES ($1) RETURNING *",
+        )
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn get_organization(&self, org_id: Uuid) -> Result<Option<Organization>, sqlx::Error> {
+        let organization =
+            sqlx::query_as::<_, Organization>("SELECT * FROM organizations WHERE id = $1")
+                .bind(org_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(organization)
+    }
+
+    async fn update_organization_branding(
+        &self,
+        org_id: Uuid,
+        branding: &EmailBranding,
+    ) -> Result<Option<Organization>, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+            UPDATE organizations
+            SET branding_display_name = $1,
+                branding_logo_url = $2,
+                branding_accent_color = $3,
+                branding_reply_to = $4,
+                updated_at = NOW()
+            WHERE id = $5
+            RETURNING *
+            "#,
+        )
+        .bind(&branding.display_name)
+        .bind(&branding.logo_url)
+        .bind(&branding.accent_color)
+        .bind(&branding.reply_to)
+        .bind(org_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn get_email_branding(&self, user: &User) -> Result<Option<EmailBranding>, sqlx::Error> {
+        let Some(org_id) = user.organization_id else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .get_organization(org_id)
+            .await?
+            .map(|organization| organization.email_branding()))
+    }
+
+    async fn update_organization_session_policy(
+        &self,
+        org_id: Uuid,
+        idle_timeout: Option<i32>,
+        max_lifetime: Option<i32>,
+    ) -> Result<Option<Organization>, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+            UPDATE organizations
+            SET session_idle_timeout = $1,
+                session_max_lifetime = $2,
+                updated_at = NOW()
+            WHERE id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(idle_timeout)
+        .bind(max_lifetime)
+        .bind(org_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn update_organization_geo_policy(
+        &self,
+        org_id: Uuid,
+        blocked_countries: &[String],
+        mfa_countries: &[String],
+        blocked_asns: &[i64],
+    ) -> Result<Option<Organization>, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+            UPDATE organizations
+            SET geo_blocked_countries = $1,
+                geo_mfa_countries = $2,
+                geo_blocked_asns = $3,
+                updated_at = NOW()
+            WHERE id = $4
+            RETURNING *
+            "#,
+        )
+        .bind(blocked_countries)
+        .bind(mfa_countries)
+        .bind(blocked_asns)
+        .bind(org_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn set_organization_plan(
+        &self,
+        org_id: Uuid,
+        plan: Option<&str>,
+    ) -> Result<Option<Organization>, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            "UPDATE organizations SET plan = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
+        )
+        .bind(plan)
+        .bind(org_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn sync_organization_plan(
+        &self,
+        org_id: Uuid,
+        plan: Option<&str>,
+        event_at: DateTime<Utc>,
+    ) -> Result<Option<Organization>, sqlx::Error> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+            UPDATE organizations SET plan = $1, plan_event_at = $3, updated_at = NOW()
+            WHERE id = $2 AND (plan_event_at IS NULL OR plan_event_at < $3)
+            RETURNING *
+            "#,
+        )
+        .bind(plan)
+        .bind(org_id)
+        .bind(event_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(organization)
+    }
+}
+
+#[async_trait]
+pub trait InvitationExt {
+    async fn create_invitation(
+        &self,
+        email: &str,
+        token: &str,
+        organization_id: Option<Uuid>,
+        invited_by: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Invitation, sqlx::Error>;
+
+    async fn get_invitation_by_token(&self, token: &str)
+    -> Result<Option<Invitation>, sqlx::Error>;
+
+    async fn accept_invitation(
+        &self,
+        invitation: &Invitation,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+impl InvitationExt for DBClient {
+    async fn create_invitation(
+        &self,
+        email: &str,
+        token: &str,
+        organization_id: Option<Uuid>,
+        invited_by: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Invitation, sqlx::Error> {
+        let invitation = sqlx::query_as::<_, Invitation>(
+            r#"
+            INSERT INTO invitations (email, token, organization_id, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(normalize_email(email))
+        .bind(token)
+        .bind(organization_id)
+        .bind(invited_by)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(invitation)
+    }
+
+    async fn get_invitation_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<Invitation>, sqlx::Error> {
+        let invitation =
+            sqlx::query_as::<_, Invitation>("SELECT * FROM invitations WHERE token = $1")
+                .bind(token)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(invitation)
+    }
+
+    async fn accept_invitation(
+        &self,
+        invitation: &Invitation,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE invitations SET accepted_at = NOW(), accepted_by = $1 WHERE id = $2")
+            .bind(user_id)
+            .bind(invitation.id)
+            .execute(&mut *tx)
+            .await?;
+
+        if let Some(organization_id) = invitation.organization_id {
+            sqlx::query("UPDATE users SET organization_id = $1, updated_at = NOW() WHERE id = $2")
+                .bind(organization_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait UserMetadataExt {
+    async fn set_user_metadata(
+        &self,
+        user_id: Uuid,
+        metadata: &HashMap<String, String>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn get_user_metadata(
+        &self,
+        user_id: Uuid,
+    ) -> Result<HashMap<String, String>, sqlx::Error>;
+}
+
+#[async_trait]
+impl UserMetadataExt for DBClient {
+    async fn set_user_metadata(
+        &self,
+        user_id: Uuid,
+        metadata: &HashMap<String, String>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for (key, value) in metadata {
+            sqlx::query(
+                r#"
+                INSERT INTO user_metadata (user_id, key, value)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, key)
+                DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+                "#,
+            )
+            .bind(user_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_user_metadata(
+        &self,
+        user_id: Uuid,
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT key, value FROM user_metadata
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[async_trait]
+pub trait RecoveryEmailExt {
+    async fn set_recovery_email(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        verification_token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<RecoveryEmail, sqlx::Error>;
+
+    async fn get_recovery_email(&self, user_id: Uuid)
+    -> Result<Option<RecoveryEmail>, sqlx::Error>;
+
+    async fn verify_recovery_email(
+        &self,
+        verification_token_hash: &str,
+    ) -> Result<Option<RecoveryEmail>, sqlx::Error>;
+
+    async fn delete_recovery_email(&self, user_id: Uuid) -> Result<Option<String>, sqlx::Error>;
+
+    async fn get_user_by_recovery_email(&self, email: &str) -> Result<Option<User>, sqlx::Error>;
+}
+
+#[async_trait]
+impl RecoveryEmailExt for DBClient {
+    async fn set_recovery_email(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        verification_token_hash: &str,
+        token_expires_at: DateTime<Utc>,
+    ) -> Result<RecoveryEmail, sqlx::Error> {
+        let recovery_email = sqlx::query_as::<_, RecoveryEmail>(
+            r#"
+            INSERT INTO recovery_emails (user_id, email, verification_token_hash, token_expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id)
+            DO UPDATE SET email = EXCLUDED.email,
+                verified_at = NULL,
+                verification_token_hash = EXCLUDED.verification_token_hash,
+                token_expires_at = EXCLUDED.token_expires_at,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(normalize_email(email))
+        .bind(verification_token_hash)
+        .bind(token_expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(recovery_email)
+    }
+
+    async fn get_recovery_email(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<RecoveryEmail>, sqlx::Error> {
+        let recovery_email =
+            sqlx::query_as::<_, RecoveryEmail>("SELECT * FROM recovery_emails WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(recovery_email)
+    }
+
+    async fn verify_recovery_email(
+        &self,
+        verification_token_hash: &str,
+    ) -> Result<Option<RecoveryEmail>, sqlx::Error> {
+        let recovery_email = sqlx::query_as::<_, RecoveryEmail>(
+            r#"
+            UPDATE recovery_emails
+            SET verified_at = NOW(),
+                verification_token_hash = NULL,
+                token_expires_at = NULL,
+                updated_at = NOW()
+            WHERE verification_token_hash = $1 AND token_expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(verification_token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(recovery_email)
+    }
+
+    async fn delete_recovery_email(&self, user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("DELETE FROM recovery_emails WHERE user_id = $1 RETURNING email")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn get_user_by_recovery_email(&self, email: &str) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT users.* FROM users
+            JOIN recovery_emails ON recovery_emails.user_id = users.id
+            WHERE LOWER(recovery_emails.email) = LOWER($1)
+                AND recovery_emails.verified_at IS NOT NULL
+                AND users.deleted_at IS NULL
+            "#,
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+pub trait SecurityQuestionExt {
+    async fn set_security_questions(
+        &self,
+        user_id: Uuid,
+        questions: &[(String, String)],
+    ) -> Result<Vec<SecurityQuestion>, sqlx::Error>;
+
+    async fn get_security_questions(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<SecurityQuestion>, sqlx::Error>;
+}
+
+#[async_trait]
+impl SecurityQuestionExt for DBClient {
+    async fn set_security_questions(
+        &self,
+        user_id: Uuid,
+        questions: &[(String, String)],
+    ) -> Result<Vec<SecurityQuestion>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM security_questions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut saved = Vec::with_capacity(questions.len());
+        for (position, (question, answer_hash)) in questions.iter().enumerate() {
+            let security_question = sqlx::query_as::<_, SecurityQuestion>(
+                r#"
+                INSERT INTO security_questions (user_id, position, question, answer_hash)
+                VALUES ($1, $2, $3, $4)
+                RETURNING *
+                "#,
+            )
+            .bind(user_id)
+            .bind(position as i16)
+            .bind(question)
+            .bind(answer_hash)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            saved.push(security_question);
+        }
+
+        tx.commit().await?;
+
+        Ok(saved)
+    }
+
+    async fn get_security_questions(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<SecurityQuestion>, sqlx::Error> {
+        let questions = sqlx::query_as::<_, SecurityQuestion>(
+            "SELECT * FROM security_questions WHERE user_id = $1 ORDER BY position",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(questions)
+    }
+}
+
+#[async_trait]
+pub trait DelegationExt {
+    async fn create_delegation(
+        &self,
+        owner_id: Uuid,
+        delegate_id: Uuid,
+        scopes: &[String],
+    ) -> Result<Delegation, sqlx::Error>;
+
+    async fn get_delegation(&self, delegation_id: Uuid) -> Result<Option<Delegation>, sqlx::Error>;
+
+    async fn get_active_delegation(
+        &self,
+        owner_id: Uuid,
+        delegate_id: Uuid,
+    ) -> Result<Option<Delegation>, sqlx::Error>;
+
+    async fn get_delegations_for_user(&self, user_id: Uuid)
+    -> Result<Vec<Delegation>, sqlx::Error>;
+
+    async fn revoke_delegation(
+        &self,
+        delegation_id: Uuid,
+        revoked_by: Uuid,
+    ) -> Result<Delegation, sqlx::Error>;
+}
+
+#[async_trait]
+impl DelegationExt for DBClient {
+    async fn create_delegation(
+        &self,
+        owner_id: Uuid,
+        delegate_id: Uuid,
+        scopes: &[String],
+    ) -> Result<Delegation, sqlx::Error> {
+        let delegation = sqlx::query_as::<_, Delegation>(
+            r#"
+            INSERT INTO delegations (owner_id, delegate_id, scopes)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+        )
+        .bind(owner_id)
+        .bind(delegate_id)
+        .bind(scopes)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(delegation)
+    }
+
+    async fn get_delegation(&self, delegation_id: Uuid) -> Result<Option<Delegation>, sqlx::Error> {
+        let delegation = sqlx::query_as::<_, Delegation>("SELECT * FROM delegations WHERE id = $1")
+            .bind(delegation_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(delegation)
+    }
+
+    async fn get_active_delegation(
+        &self,
+        owner_id: Uuid,
+        delegate_id: Uuid,
+    ) -> Result<Option<Delegation>, sqlx::Error> {
+        let delegation = sqlx::query_as::<_, Delegation>(
+            r#"
+            SELECT * FROM delegations
+            WHERE owner_id = $1 AND delegate_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(owner_id)
+        .bind(delegate_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(delegation)
+    }
+
+    async fn get_delegations_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<Delegation>, sqlx::Error> {
+        let delegations = sqlx::query_as::<_, Delegation>(
+            r#"
+            SELECT * FROM delegations
+            WHERE (owner_id = $1 OR delegate_id = $1) AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(delegations)
+    }
+
+    async fn revoke_delegation(
+        &self,
+        delegation_id: Uuid,
+        revoked_by: Uuid,
+    ) -> Result<Delegation, sqlx::Error> {
+        let delegation = sqlx::query_as::<_, Delegation>(
+            r#"
+            UPDATE delegations
+            SET revoked_at = NOW(), revoked_by = $1
+            WHERE id = $2 AND revoked_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(revoked_by)
+        .bind(delegation_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(delegation)
+    }
+}
+
+#[async_trait]
+pub trait ResetCodeExt {
+    async fn create_reset_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn get_active_reset_code(&self, user_id: Uuid) -> Result<Option<ResetCode>, sqlx::Error>;
+
+    async fn increment_reset_code_attempts(&self, code_id: Uuid) -> Result<i32, sqlx::Error>;
+
+    async fn consume_reset_code(&self, code_id: Uuid) -> Result<bool, sqlx::Error>;
+}
+
+#[async_trait]
+impl ResetCodeExt for DBClient {
+    async fn create_reset_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM password_reset_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_codes (user_id, code_hash, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_active_reset_code(&self, user_id: Uuid) -> Result<Option<ResetCode>, sqlx::Error> {
+        let code = sqlx::query_as::<_, ResetCode>(
+            r#"
+            SELECT * FROM password_reset_codes
+            WHERE user_id = $1 AND used_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(code)
+    }
+
+    async fn increment_reset_code_attempts(&self, code_id: Uuid) -> Result<i32, sqlx::Error> {
+        let attempts = sqlx::query_scalar::<_, i32>(
+            r#"
+            UPDATE password_reset_codes
+            SET attempts = attempts + 1
+            WHERE id = $1
+            RETURNING attempts
+            "#,
+        )
+        .bind(code_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    async fn consume_reset_code(&self, code_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE password_reset_codes SET used_at = NOW() WHERE id = $1 AND used_at IS NULL",
+        )
+        .bind(code_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+#[async_trait]
+pub trait SessionExt {
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        idle_timeout: Option<i32>,
+        expires_at: DateTime<Utc>,
+        refresh_token_hash: &str,
+        client_fingerprint: Option<&str>,
+        audience: Option<&str>,
+    ) -> Result<Session, sqlx::Error>;
+
+    async fn get_session(&self, session_id: Uuid) -> Result<Option<Session>, sqlx::Error>;
+
+    async fn get_session_by_refresh_token(
+        &self,
+        refresh_token_hash: &str,
+    ) -> Result<Option<Session>, sqlx::Error>;
+
+    async fn rotate_refresh_token(
+        &self,
+        session_id: Uuid,
+        current_hash: &str,
+        new_hash: &str,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn touch_session(&self, session_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn get_open_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, sqlx::Error>;
+
+    async fn count_user_sessions(&self, user_id: Uuid) -> Result<i64, sqlx::Error>;
+
+    async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, sqlx::Error>;
+}
+
+#[async_trait]
+impl SessionExt for DBClient {
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        idle_timeout: Option<i32>,
+        expires_at: DateTime<Utc>,
+        refresh_token_hash: &str,
+        client_fingerprint: Option<&str>,
+        audience: Option<&str>,
+    ) -> Result<Session, sqlx::Error> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            INSERT INTO sessions (user_id, idle_timeout, expires_at, refresh_token_hash, client_fingerprint, audience)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(idle_timeout)
+        .bind(expires_at)
+        .bind(refresh_token_hash)
+        .bind(client_fingerprint)
+        .bind(audience)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn get_session(&self, session_id: Uuid) -> Result<Option<Session>, sqlx::Error> {
+        let session = sqlx::query_as::<_, Session>(SESSION_BY_ID_SQL)
+            .bind(session_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn get_session_by_refresh_token(
+        &self,
+        refresh_token_hash: &str,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        let session = sqlx::query_as::<_, Session>(SESSION_BY_REFRESH_TOKEN_SQL)
+            .bind(refresh_token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        session_id: Uuid,
+        current_hash: &str,
+        new_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET refresh_token_hash = $1, last_seen_at = NOW()
+            WHERE id = $2 AND refresh_token_hash = $3
+            "#,
+        )
+        .bind(new_hash)
+        .bind(session_id)
+        .bind(current_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn touch_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET last_seen_at = NOW() WHERE id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_open_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, sqlx::Error> {
+        let sessions = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT * FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn count_user_sessions(&self, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, sqlx::Error> {
+        let sessions = sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE user_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+}
+
+#[async_trait]
+pub trait LoginAttemptExt {
+    async fn record_login_failure(
+        &self,
+        key: &str,
+        window_seconds: u64,
+        lock_threshold: i32,
+        lock_seconds: u64,
+    ) -> Result<LoginAttempt, sqlx::Error>;
+
+    async fn get_login_failures(&self, key: &str, window_seconds: u64) -> Result<i32, sqlx::Error>;
+
+    async fn get_login_lockout(
+        &self,
+        keys: &[String],
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    async fn reset_login_failures(&self, key: &str) -> Result<(), sqlx::Error>;
+
+    async fn delete_stale_login_attempts(&self, window_seconds: u64) -> Result<u64, sqlx::Error>;
+
+    async fn get_login_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, sqlx::Error>;
+
+    async fn get_top_login_failures(
+        &self,
+        prefix: &str,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, sqlx::Error>;
+
+    async fn get_active_lockouts(&self, limit: i64) -> Result<Vec<LoginAttempt>, sqlx::Error>;
+
+    async fn count_active_lockouts(&self) -> Result<i64, sqlx::Error>;
+}
+
+#[async_trait]
+impl LoginAttemptExt for DBClient {
+    async fn record_login_failure(
+        &self,
+        key: &str,
+        window_seconds: u64,
+        lock_threshold: i32,
+        lock_seconds: u64,
+    ) -> Result<LoginAttempt, sqlx::Error> {
+        let attempt = sqlx::query_as::<_, LoginAttempt>(
+            r#"
+            INSERT INTO login_attempts AS a (key, failures, window_started_at, locked_until, updated_at)
+            VALUES (
+                $1,
+                1,
+                NOW(),
+                CASE WHEN $3 > 0 AND $3 <= 1 THEN NOW() + make_interval(secs => $4) END,
+                NOW()
+            )
+            ON CONFLICT (key) DO UPDATE SET
+                failures = CASE
+                    WHEN a.window_started_at < NOW() - make_interval(secs => $2) THEN 1
+                    ELSE a.failures + 1
+                END,
+                window_started_at = CASE
+                    WHEN a.window_started_at < NOW() - make_interval(secs => $2) THEN NOW()
+                    ELSE a.window_started_at
+                END,
+                locked_until = CASE
+                    WHEN $3 > 0
+                        AND a.window_started_at >= NOW() - make_interval(secs => $2)
+                        AND a.failures + 1 >= $3
+                    THEN NOW() + make_interval(secs => $4)
+                    ELSE a.locked_until
+                END,
+                updated_at = NOW()
+            RETURNING key, failures, window_started_at, locked_until, updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(window_seconds as f64)
+        .bind(lock_threshold)
+        .bind(lock_seconds as f64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(attempt)
+    }
+
+    async fn get_login_failures(&self, key: &str, window_seconds: u64) -> Result<i32, sqlx::Error> {
+        let failures = sqlx::query_scalar::<_, i32>(
+            r#"
+            SELECT failures FROM login_attempts
+            WHERE key = $1 AND window_started_at >= NOW() - make_interval(secs => $2)
+            "#,
+        )
+        .bind(key)
+        .bind(window_seconds as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(failures.unwrap_or(0))
+    }
+
+    async fn get_login_lockout(
+        &self,
+        keys: &[String],
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let locked_until = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            r#"
+            SELECT MAX(locked_until) FROM login_attempts
+            WHERE key = ANY($1) AND locked_until > NOW()
+            "#,
+        )
+        .bind(keys)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(locked_until)
+    }
+
+    async fn reset_login_failures(&self, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM login_attempts WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_stale_login_attempts(&self, window_seconds: u64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM login_attempts
+            WHERE window_started_at < NOW() - make_interval(secs => $1)
+                AND (locked_until IS NULL OR locked_until < NOW())
+            "#,
+        )
+        .bind(window_seconds as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_login_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, sqlx::Error> {
+        let attempt =
+            sqlx::query_as::<_, LoginAttempt>("SELECT * FROM login_attempts WHERE key = $1")
+                .bind(key)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(attempt)
+    }
+
+    async fn get_top_login_failures(
+        &self,
+        prefix: &str,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, sqlx::Error> {
+        let attempts = sqlx::query_as::<_, LoginAttempt>(
+            r#"
+            SELECT * FROM login_attempts
+            WHERE starts_with(key, $1) AND updated_at >= $2 AND failures > 0
+            ORDER BY failures DESC, updated_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(prefix)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    async fn get_active_lockouts(&self, limit: i64) -> Result<Vec<LoginAttempt>, sqlx::Error> {
+        let attempts = sqlx::query_as::<_, LoginAttempt>(
+            r#"
+            SELECT * FROM login_attempts
+            WHERE locked_until > NOW()
+            ORDER BY locked_until DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    async fn count_active_lockouts(&self) -> Result<i64, sqlx::Error> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM login_attempts WHERE locked_until > NOW()")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+pub trait ApiKeyExt {
+    async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        role: Option<&UserRole>,
+        signing_secret: Option<&str>,
+    ) -> Result<ApiKey, sqlx::Error>;
+
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, sqlx::Error>;
+
+    async fn revoke_api_key(&self, id: Uuid) -> Result<Option<ApiKey>, sqlx::Error>;
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>, sqlx::Error>;
+
+    async fn revoke_user_api_key(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ApiKey>, sqlx::Error>;
+
+    async fn touch_api_key(&self, id: Uuid) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+impl ApiKeyExt for DBClient {
+    async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        role: Option<&UserRole>,
+        signing_secret: Option<&str>,
+    ) -> Result<ApiKey, sqlx::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, role, signing_secret)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(prefix)
+        .bind(key_hash)
+        .bind(role)
+        .bind(signing_secret)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(api_key)
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE key_hash = $1")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(api_key)
+    }
+
+    async fn revoke_api_key(&self, id: Uuid) -> Result<Option<ApiKey>, sqlx::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND revoked_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(api_key)
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>, sqlx::Error> {
+        let api_keys = sqlx::query_as::<_, ApiKey>(
+            "SELECT * FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(api_keys)
+    }
+
+    async fn revoke_user_api_key(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ApiKey>, sqlx::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(api_key)
+    }
+
+    async fn touch_api_key(&self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait RevokedTokenExt {
+    async fn revoke_token(
+        &self,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, sqlx::Error>;
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<u64, sqlx::Error>;
+
+    async fn consume_token(
+        &self,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error>;
+}
+
+#[async_trait]
+impl RevokedTokenExt for DBClient {
+    async fn revoke_token(
+        &self,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(jti)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, sqlx::Error> {
+        let revoked = sqlx::query_scalar::<_, bool>(TOKEN_REVOKED_SQL)
+            .bind(jti)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(revoked)
+    }
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn consume_token(
+        &self,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+        )
+        .bind(jti)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+pub trait AnnouncementExt {
+    async fn create_announcement(
+        &self,
+        message: &str,
+        severity: AnnouncementSeverity,
+        starts_at: Option<DateTime<Utc>>,
+        ends_at: Option<DateTime<Utc>>,
+        created_by: Uuid,
+    ) -> Result<Announcement, sqlx::Error>;
+
+    async fn update_announcement(
+        &self,
+        id: Uuid,
+        message: &str,
+        severity: AnnouncementSeverity,
+        starts_at: Option<DateTime<Utc>>,
+        ends_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<Announcement>, sqlx::Error>;
+
+    async fn delete_announcement(&self, id: Uuid) -> Result<bool, sqlx::Error>;
+
+    async fn get_announcements(&self) -> Result<Vec<Announcement>, sqlx::Error>;
+
+    async fn get_active_announcements(&self) -> Result<Vec<Announcement>, sqlx::Error>;
+}
+
+#[async_trait]
+impl AnnouncementExt for DBClient {
+    async fn create_announcement(
+        &self,
+        message: &str,
+        severity: AnnouncementSeverity,
+        starts_at: Option<DateTime<Utc>>,
+        ends_at: Option<DateTime<Utc>>,
+        created_by: Uuid,
+    ) -> Result<Announcement, sqlx::Error> {
+        let announcement = sqlx::query_as::<_, Announcement>(
+            r#"
+            INSERT INTO announcements (message, severity, starts_at, ends_at, created_by)
+            VALUES ($1, $2, COALESCE($3, NOW()), $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(message)
+        .bind(severity)
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(announcement)
+    }
+
+    async fn update_announcement(
+        &self,
+        id: Uuid,
+        message: &str,
+        severity: AnnouncementSeverity,
+        starts_at: Option<DateTime<Utc>>,
+        ends_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<Announcement>, sqlx::Error> {
+        let announcement = sqlx::query_as::<_, Announcement>(
+            r#"
+            UPDATE announcements
+            SET message = $1,
+                severity = $2,
+                starts_at = COALESCE($3, starts_at),
+                ends_at = $4,
+                updated_at = NOW()
+            WHERE id = $5
+            RETURNING *
+            "#,
+        )
+        .bind(message)
+        .bind(severity)
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(announcement)
+    }
+
+    async fn delete_announcement(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM announcements WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_announcements(&self) -> Result<Vec<Announcement>, sqlx::Error> {
+        let announcements = sqlx::query_as::<_, Announcement>(
+            "SELECT * FROM announcements ORDER BY starts_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(announcements)
+    }
+
+    async fn get_active_announcements(&self) -> Result<Vec<Announcement>, sqlx::Error> {
+        let announcements = sqlx::query_as::<_, Announcement>(
+            r#"
+            SELECT * FROM announcements
+            WHERE starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW())
+            ORDER BY severity DESC, starts_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(announcements)
+    }
+}
+
+#[async_trait]
+pub trait OAuthAccountExt {
+    async fn get_oauth_account(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> Result<Option<OAuthAccount>, sqlx::Error>;
+
+    async fn link_oauth_account(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        provider_user_id: &str,
+        email: &str,
+    ) -> Result<OAuthAccount, sqlx::Error>;
+
+    async fn create_oauth_user(
+        &self,
+        name: &str,
+        email: &str,
+        password: &str,
+        account_status: AccountStatus,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> Result<User, sqlx::Error>;
+}
+
+#[async_trait]
+impl OAuthAccountExt for DBClient {
+    async fn get_oauth_account(
+        &self,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> Result<Option<OAuthAccount>, sqlx::Error> {
+        let account = sqlx::query_as::<_, OAuthAccount>(
+            "SELECT * FROM oauth_accounts WHERE provider = $1 AND provider_user_id = $2",
+        )
+        .bind(provider)
+        .bind(provider_user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    async fn link_oauth_account(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        provider_user_id: &str,
+        email: &str,
+    ) -> Result<OAuthAccount, sqlx::Error> {
+        let account = sqlx::query_as::<_, OAuthAccount>(
+            r#"
+            INSERT INTO oauth_accounts (user_id, provider, provider_user_id, email)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(provider_user_id)
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    async fn create_oauth_user(
+        &self,
+        name: &str,
+        email: &str,
+        password: &str,
+        account_status: AccountStatus,
+        provider: &str,
+        provider_user_id: &str,
+    ) -> Result<User, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (name, email, password, verified, account_status)
+            VALUES ($1, $2, $3, true, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(normalize_email(email))
+        .bind(password)
+        .bind(account_status)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_accounts (user_id, provider, provider_user_id, email)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(user.id)
+        .bind(provider)
+        .bind(provider_user_id)
+        .bind(email)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+pub trait UsageExt {
+    async fn increment_usage(
+        &self,
+        subject: &str,
+        period: &str,
+        period_start: NaiveDate,
+        limit: Option<i64>,
+    ) -> Result<Option<i64>, sqlx::Error>;
+
+    async fn get_usage(
+        &self,
+        subject: &str,
+        period: &str,
+        period_start: NaiveDate,
+    ) -> Result<i64, sqlx::Error>;
+
+    async fn delete_usage_before(&self, cutoff: NaiveDate) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+impl UsageExt for DBClient {
+    async fn increment_usage(
+        &self,
+        subject: &str,
+        period: &str,
+        period_start: NaiveDate,
+        limit: Option<i64>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO usage_counters AS u (subject, period, period_start, count)
+            SELECT $1, $2, $3, 1
+            WHERE $4::BIGINT IS NULL OR $4 > 0
+            ON CONFLICT (subject, period, period_start) DO UPDATE
+            SET count = u.count + 1, updated_at = NOW()
+            WHERE $4::BIGINT IS NULL OR u.count < $4
+            RETURNING count
+            "#,
+        )
+        .bind(subject)
+        .bind(period)
+        .bind(period_start)
+        .bind(limit)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn get_usage(
+        &self,
+        subject: &str,
+        period: &str,
+        period_start: NaiveDate,
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT count FROM usage_counters
+            WHERE subject = $1 AND period = $2 AND period_start = $3
+            "#,
+        )
+        .bind(subject)
+        .bind(period)
+        .bind(period_start)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn delete_usage_before(&self, cutoff: NaiveDate) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM usage_counters WHERE period_start < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+pub trait TwoFactorExt {
+    async fn set_totp_secret(&self, user_id: Uuid, secret: &str) -> Result<User, sqlx::Error>;
+
+    async fn enable_totp(&self, user_id: Uuid, code_hashes: &[String])
+    -> Result<User, sqlx::Error>;
+
+    async fn replace_backup_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error>;
+
+    async fn consume_backup_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn count_unused_backup_codes(&self, user_id: Uuid) -> Result<i64, sqlx::Error>;
+}
+
+#[async_trait]
+impl TwoFactorExt for DBClient {
+    async fn set_totp_secret(&self, user_id: Uuid, secret: &str) -> Result<User, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET totp_secret = $1, totp_enabled_at = NULL, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(secret)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn enable_totp(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<User, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET totp_enabled_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM backup_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO backup_codes (user_id, code_hash) SELECT $1, UNNEST($2::VARCHAR[])",
+        )
+        .bind(user_id)
+        .bind(code_hashes)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn replace_backup_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM backup_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO backup_codes (user_id, code_hash) SELECT $1, UNNEST($2::VARCHAR[])",
+        )
+        .bind(user_id)
+        .bind(code_hashes)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn consume_backup_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE backup_codes
+            SET used_at = NOW()
+            WHERE id = (
+                SELECT id FROM backup_codes
+                WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+                LIMIT 1
+                FOR UPDATE
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn count_unused_backup_codes(&self, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM backup_codes WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+pub trait UserChangeExt {
+    async fn get_user_changes(
+        &self,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<UserChange>, sqlx::Error>;
+
+    async fn get_users_by_ids(&self, user_ids: &[Uuid]) -> Result<Vec<User>, sqlx::Error>;
+}
+
+#[async_trait]
+impl UserChangeExt for DBClient {
+    async fn get_user_changes(
+        &self,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<UserChange>, sqlx::Error> {
+        let changes = sqlx::query_as::<_, UserChange>(
+            r#"
+            SELECT * FROM user_changes
+            WHERE (txid, id) > (COALESCE((SELECT txid FROM user_changes WHERE id = $1), 0), $1)
+                AND txid < pg_snapshot_xmin(pg_current_snapshot())::text::bigint
+            ORDER BY txid ASC, id ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(changes)
+    }
+
+    async fn get_users_by_ids(&self, user_ids: &[Uuid]) -> Result<Vec<User>, sqlx::Error> {
+        let users = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE id = ANY($1) AND deleted_at IS NULL",
+        )
+        .bind(user_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+}
+
+#[async_trait]
+pub trait CredentialExt {
+    async fn create_credential(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        credential_id: &str,
+        passkey: &str,
+    ) -> Result<Credential, sqlx::Error>;
+
+    async fn get_credentials(&self, user_id: Uuid) -> Result<Vec<Credential>, sqlx::Error>;
+
+    async fn record_credential_use(
+        &self,
+        id: Uuid,
+        passkey: Option<&str>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn delete_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Credential>, sqlx::Error>;
+}
+
+#[async_trait]
+impl CredentialExt for DBClient {
+    async fn create_credential(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        credential_id: &str,
+        passkey: &str,
+    ) -> Result<Credential, sqlx::Error> {
+        let credential = sqlx::query_as::<_, Credential>(
+            r#"
+            INSERT INTO credentials (user_id, name, credential_id, passkey)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(credential_id)
+        .bind(passkey)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(credential)
+    }
+
+    async fn get_credentials(&self, user_id: Uuid) -> Result<Vec<Credential>, sqlx::Error> {
+        let credentials = sqlx::query_as::<_, Credential>(
+            "SELECT * FROM credentials WHERE user_id = $1 ORDER BY created_at ASC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(credentials)
+    }
+
+    async fn record_credential_use(
+        &self,
+        id: Uuid,
+        passkey: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE credentials
+            SET passkey = COALESCE($1, passkey), last_used_at = NOW()
+            WHERE id = $2
+            "#,
+        )
+        .bind(passkey)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Credential>, sqlx::Error> {
+        let credential = sqlx::query_as::<_, Credential>(
+            "DELETE FROM credentials WHERE id = $1 AND user_id = $2 RETURNING *",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(credential)
+    }
+}
+
+#[async_trait]
+pub trait DoctorExt {
+    async fn ping(&self) -> Result<(), sqlx::Error>;
+
+    async fn get_applied_migrations(&self) -> Result<Vec<(i64, bool, Vec<u8>)>, sqlx::Error>;
+
+    async fn get_index_definitions(&self, table: &str) -> Result<Vec<String>, sqlx::Error>;
+
+    async fn get_enum_labels(&self, type_name: &str) -> Result<Vec<String>, sqlx::Error>;
+
+    async fn count_orphaned_rows(&self, table: &str) -> Result<i64, sqlx::Error>;
+
+    async fn explain_hot_query(&self, query: &HotQuery) -> Result<Vec<String>, sqlx::Error>;
+}
+
+#[async_trait]
+impl DoctorExt for DBClient {
+    async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn get_applied_migrations(&self) -> Result<Vec<(i64, bool, Vec<u8>)>, sqlx::Error> {
+        let migrations = sqlx::query_as::<_, (i64, bool, Vec<u8>)>(
+            "SELECT version, success, checksum FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(migrations)
+    }
+
+    async fn get_index_definitions(&self, table: &str) -> Result<Vec<String>, sqlx::Error> {
+        let definitions = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT indexdef FROM pg_indexes
+            WHERE schemaname = current_schema() AND tablename = $1
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(definitions)
+    }
+
+    async fn get_enum_labels(&self, type_name: &str) -> Result<Vec<String>, sqlx::Error> {
+        let labels = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT e.enumlabel::TEXT FROM pg_enum e
+            JOIN pg_type t ON t.oid = e.enumtypid
+            WHERE t.typname = $1
+            ORDER BY e.enumsortorder
+            "#,
+        )
+        .bind(type_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(labels)
+    }
+
+    async fn count_orphaned_rows(&self, table: &str) -> Result<i64, sqlx::Error> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} t WHERE NOT EXISTS (SELECT 1 FROM users u WHERE u.id = t.user_id)",
+            table
+        );
+
+        let count = sqlx::query_scalar::<_, i64>(&sql)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn explain_hot_query(&self, query: &HotQuery) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let warmup = sqlx::query(query.sql);
+        let warmup = match query.param {
+            HotQueryParam::Uuid => warmup.bind(Uuid::nil()),
+            HotQueryParam::Text => warmup.bind(""),
+        };
+        warmup.fetch_optional(&mut *tx).await?;
+
+        sqlx::query("SET LOCAL enable_seqscan = off")
+            .execute(&mut *tx)
+            .await?;
+
+        let sql = format!("EXPLAIN {}", query.sql);
+        let explain = sqlx::query_scalar::<_, String>(&sql);
+        let explain = match query.param {
+            HotQueryParam::Uuid => explain.bind(Uuid::nil()),
+            HotQueryParam::Text => explain.bind(""),
+        };
+        let plan = explain.fetch_all(&mut *tx).await?;
+
+        tx.rollback().await?;
+
+        Ok(plan)
+    }
+}
+
+#[async_trait]
+pub trait OutboxExt {
+    async fn enqueue_email(&self, message: &EmailMessage) -> Result<OutboxEmail, sqlx::Error>;
+
+    async fn claim_due_emails(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<OutboxEmail>, sqlx::Error>;
+
+    async fn mark_email_sent(&self, email_id: Uuid) -> Result<(), sqlx::Error>;
+
+    async fn mark_email_failed(
+        &self,
+        email_id: Uuid,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn get_outbox_emails(
+        &self,
+        status: Option<OutboxStatus>,
+        limit: i64,
+    ) -> Result<Vec<OutboxEmail>, sqlx::Error>;
+
+    async fn retry_outbox_email(&self, email_id: Uuid) -> Result<Option<OutboxEmail>, sqlx::Error>;
+
+    async fn delete_sent_emails_before(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+impl OutboxExt for DBClient {
+    async fn enqueue_email(&self, message: &EmailMessage) -> Result<OutboxEmail, sqlx::Error> {
+        let email = sqlx::query_as::<_, OutboxEmail>(
+            r#"
+            INSERT INTO email_outbox (recipient, cc, reply_to, from_name, subject, text_body, html_body)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(&message.to)
+        .bind(&message.cc)
+        .bind(&message.reply_to)
+        .bind(&message.from_name)
+        .bind(&message.subject)
+        .bind(&message.text)
+        .bind(&message.html)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(email)
+    }
+
+    async fn claim_due_emails(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<OutboxEmail>, sqlx::Error> {
+        let emails = sqlx::query_as::<_, OutboxEmail>(
+            r#"
+            UPDATE email_outbox
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM email_outbox
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(emails)
+    }
+
+    async fn mark_email_sent(&self, email_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE email_outbox
+            SET status = 'sent', attempts = attempts + 1, last_error = NULL, sent_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(email_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn mark_email_failed(
+        &self,
+        email_id: Uuid,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE email_outbox
+            SET attempts = attempts + 1,
+                last_error = $2,
+                status = CASE WHEN $3::timestamptz IS NULL THEN 'dead'::outbox_status ELSE status END,
+                next_attempt_at = COALESCE($3, next_attempt_at)
+            WHERE id = $1
+            "#,
+        )
+        .bind(email_id)
+        .bind(error)
+        .bind(next_attempt_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_outbox_emails(
+        &self,
+        status: Option<OutboxStatus>,
+        limit: i64,
+    ) -> Result<Vec<OutboxEmail>, sqlx::Error> {
+        let emails = sqlx::query_as::<_, OutboxEmail>(
+            r#"
+            SELECT * FROM email_outbox
+            WHERE $1::outbox_status IS NULL OR status = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(emails)
+    }
+
+    async fn retry_outbox_email(&self, email_id: Uuid) -> Result<Option<OutboxEmail>, sqlx::Error> {
+        let email = sqlx::query_as::<_, OutboxEmail>(
+            r#"
+            UPDATE email_outbox
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE id = $1 AND status = 'dead'
+            RETURNING *
+            "#,
+        )
+        .bind(email_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(email)
+    }
+
+    async fn delete_sent_emails_before(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM email_outbox WHERE status = 'sent' AND sent_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+pub trait RoleGrantExt {
+    async fn create_role_grant(
+        &self,
+        user_id: Uuid,
+        role: &UserRole,
+        requested_by: Uuid,
+    ) -> Result<Option<RoleGrant>, sqlx::Error>;
+
+    async fn get_pending_role_grants(&self) -> Result<Vec<RoleGrant>, sqlx::Error>;
+
+    async fn get_role_grant(&self, grant_id: Uuid) -> Result<Option<RoleGrant>, sqlx::Error>;
+
+    async fn approve_role_grant(
+        &self,
+        grant_id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<Option<(RoleGrant, Option<UserRole>)>, sqlx::Error>;
+
+    async fn reject_role_grant(
+        &self,
+        grant_id: Uuid,
+        rejected_by: Uuid,
+    ) -> Result<Option<RoleGrant>, sqlx::Error>;
+}
+
+#[async_trait]
+impl RoleGrantExt for DBClient {
+    async fn create_role_grant(
+        &self,
+        user_id: Uuid,
+        role: &UserRole,
+        requested_by: Uuid,
+    ) -> Result<Option<RoleGrant>, sqlx::Error> {
+        let grant = sqlx::query_as::<_, RoleGrant>(
+            r#"
+            INSERT INTO role_grants (user_id, role, requested_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, role) WHERE status = 'pending' DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(role)
+        .bind(requested_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(grant)
+    }
+
+    async fn get_pending_role_grants(&self) -> Result<Vec<RoleGrant>, sqlx::Error> {
+        let grants = sqlx::query_as::<_, RoleGrant>(
+            "SELECT * FROM role_grants WHERE status = 'pending' ORDER BY created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(grants)
+    }
+
+    async fn get_role_grant(&self, grant_id: Uuid) -> Result<Option<RoleGrant>, sqlx::Error> {
+        let grant = sqlx::query_as::<_, RoleGrant>("SELECT * FROM role_grants WHERE id = $1")
+            .bind(grant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(grant)
+    }
+
+    async fn approve_role_grant(
+        &self,
+        grant_id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<Option<(RoleGrant, Option<UserRole>)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let grant = sqlx::query_as::<_, RoleGrant>(
+            r#"
+            UPDATE role_grants
+            SET status = 'approved', decided_by = $2, decided_at = NOW()
+            WHERE id = $1 AND status = 'pending' AND requested_by <> $2
+            RETURNING *
+            "#,
+        )
+        .bind(grant_id)
+        .bind(approved_by)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(grant) = grant else {
+            return Ok(None);
+        };
+
+        let previous: Option<UserRole> = sqlx::query_scalar(
+            "SELECT role FROM users WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        )
+        .bind(grant.user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE users SET role = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL",
+        )
+        .bind(&grant.role)
+        .bind(grant.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some((grant, previous)))
+    }
+
+    async fn reject_role_grant(
+        &self,
+        grant_id: Uuid,
+        rejected_by: Uuid,
+    ) -> Result<Option<RoleGrant>, sqlx::Error> {
+        let grant = sqlx::query_as::<_, RoleGrant>(
+            r#"
+            UPDATE role_grants
+            SET status = 'rejected', decided_by = $2, decided_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(grant_id)
+        .bind(rejected_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(grant)
+    }
+}
+
+#[async_trait]
+pub trait NonceExt {
+    async fn create_nonce(
+        &self,
+        purpose: &str,
+        nonce: &str,
+        user_id: Option<Uuid>,
+        payload: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn consume_nonce(&self, purpose: &str, nonce: &str)
+    -> Result<Option<Nonce>, sqlx::Error>;
+
+    async fn delete_expired_nonces(&self) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+impl NonceExt for DBClient {
+    async fn create_nonce(
+        &self,
+        purpose: &str,
+        nonce: &str,
+        user_id: Option<Uuid>,
+        payload: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO nonces (purpose, nonce, user_id, payload, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (purpose, nonce) DO NOTHING
+            "#,
+        )
+        .bind(purpose)
+        .bind(nonce)
+        .bind(user_id)
+        .bind(payload)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn consume_nonce(
+        &self,
+        purpose: &str,
+        nonce: &str,
+    ) -> Result<Option<Nonce>, sqlx::Error> {
+        let nonce = sqlx::query_as::<_, Nonce>(
+            r#"
+            DELETE FROM nonces
+            WHERE purpose = $1 AND nonce = $2 AND expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(purpose)
+        .bind(nonce)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(nonce)
+    }
+
+    async fn delete_expired_nonces(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM nonces WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+pub trait AuditLogExt {
+    async fn insert_audit_log(
+        &self,
+        entry: &AuditEntry,
+        chain: &AuditChain,
+    ) -> Result<AuditLog, sqlx::Error>;
+
+    async fn get_audit_log_chain(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<AuditLog>, sqlx::Error>;
+
+    async fn get_audit_logs(
+        &self,
+        filter: &AuditLogFilter,
+        query: &PageQuery<AuditLog>,
+    ) -> Result<Vec<AuditLog>, sqlx::Error>;
+
+    async fn count_audit_logs(&self, filter: &AuditLogFilter) -> Result<i64, sqlx::Error>;
+
+    async fn get_organization_audit_logs(
+        &self,
+        organization_id: Uuid,
+        filter: &AuditLogFilter,
+        query: &PageQuery<AuditLog>,
+    ) -> Result<Vec<AuditLog>, sqlx::Error>;
+
+    async fn count_organization_audit_logs(
+        &self,
+        organization_id: Uuid,
+        filter: &AuditLogFilter,
+    ) -> Result<i64, sqlx::Error>;
+}
+
+const AUDIT_LOG_FILTER: &str = r#"
+    ($1::varchar IS NULL OR event = $1)
+    AND ($2::uuid IS NULL OR actor_id = $2)
+    AND ($3::uuid IS NULL OR target_id = $3)
+    AND ($4::timestamptz IS NULL OR created_at >= $4)
+    AND ($5::timestamptz IS NULL OR created_at < $5)
+"#;
+
+const ORGANIZATION_AUDIT_LOGS: &str = r#"
+    WITH members AS (
+        SELECT id FROM users WHERE organization_id = $6
+    )
+    SELECT
+        id,
+        event,
+        CASE WHEN actor_id IN (SELECT id FROM members) THEN actor_id END AS actor_id,
+        CASE WHEN target_id IN (SELECT id FROM members) THEN target_id END AS target_id,
+        host(network(set_masklen(
+            ip::inet,
+            CASE WHEN family(ip::inet) = 4 THEN 24 ELSE 48 END
+        ))) AS ip,
+        NULL::text AS user_agent,
+        detail - 'email' AS detail,
+        created_at,
+        NULL::varchar AS prev_hash,
+        NULL::varchar AS hash
+    FROM audit_logs
+    WHERE (
+        actor_id IN (SELECT id FROM members)
+        OR target_id IN (SELECT id FROM members)
+    )
+"#;
+
+#[async_trait]
+impl AuditLogExt for DBClient {
+    async fn insert_audit_log(
+        &self,
+        entry: &AuditEntry,
+        chain: &AuditChain,
+    ) -> Result<AuditLog, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('audit_logs'))")
+            .execute(&mut *tx)
+            .await?;
+
+        let prev_hash: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT hash FROM audit_logs
+            WHERE hash IS NOT NULL
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let mut log = sqlx::query_as::<_, AuditLog>(
+            r#"
+            INSERT INTO audit_logs (event, actor_id, target_id, ip, user_agent, detail, prev_hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(entry.event.to_str())
+        .bind(entry.actor_id)
+        .bind(entry.target_id)
+        .bind(&entry.ip)
+        .bind(&entry.user_agent)
+        .bind(&entry.detail)
+        .bind(&prev_hash)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let hash = chain.sign(&log);
+        sqlx::query("UPDATE audit_logs SET hash = $2 WHERE id = $1")
+            .bind(log.id)
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        log.hash = Some(hash);
+        Ok(log)
+    }
+
+    async fn get_audit_log_chain(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<AuditLog>, sqlx::Error> {
+        let logs = sqlx::query_as::<_, AuditLog>(
+            r#"
+            SELECT * FROM audit_logs
+            WHERE id > $1
+            ORDER BY id ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    async fn get_audit_logs(
+        &self,
+        filter: &AuditLogFilter,
+        query: &PageQuery<AuditLog>,
+    ) -> Result<Vec<AuditLog>, sqlx::Error> {
+        let sql = format!(
+            "SELECT * FROM audit_logs WHERE {} {} LIMIT $6 OFFSET $7",
+            AUDIT_LOG_FILTER,
+            query.order_by()
+        );
+
+        let logs = sqlx::query_as::<_, AuditLog>(&sql)
+            .bind(&filter.event)
+            .bind(filter.actor_id)
+            .bind(filter.target_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .bind(query.limit as i64)
+            .bind(query.offset())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(logs)
+    }
+
+    async fn count_audit_logs(&self, filter: &AuditLogFilter) -> Result<i64, sqlx::Error> {
+        let sql = format!("SELECT COUNT(*) FROM audit_logs WHERE {}", AUDIT_LOG_FILTER);
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(&filter.event)
+            .bind(filter.actor_id)
+            .bind(filter.target_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn get_organization_audit_logs(
+        &self,
+        organization_id: Uuid,
+        filter: &AuditLogFilter,
+        query: &PageQuery<AuditLog>,
+    ) -> Result<Vec<AuditLog>, sqlx::Error> {
+        let sql = format!(
+            "SELECT * FROM ({}) scoped WHERE {} {} LIMIT $7 OFFSET $8",
+            ORGANIZATION_AUDIT_LOGS,
+            AUDIT_LOG_FILTER,
+            query.order_by()
+        );
+
+        let logs = sqlx::query_as::<_, AuditLog>(&sql)
+            .bind(&filter.event)
+            .bind(filter.actor_id)
+            .bind(filter.target_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .bind(organization_id)
+            .bind(query.limit as i64)
+            .bind(query.offset())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(logs)
+    }
+
+    async fn count_organization_audit_logs(
+        &self,
+        organization_id: Uuid,
+        filter: &AuditLogFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM ({}) scoped WHERE {}",
+            ORGANIZATION_AUDIT_LOGS, AUDIT_LOG_FILTER
+        );
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(&filter.event)
+            .bind(filter.actor_id)
+            .bind(filter.target_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .bind(organization_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+pub trait PermissionExt {
+    async fn get_role_permissions(&self) -> Result<Vec<RolePermission>, sqlx::Error>;
+}
+
+#[async_trait]
+impl PermissionExt for DBClient {
+    async fn get_role_permissions(&self) -> Result<Vec<RolePermission>, sqlx::Error> {
+        let permissions = sqlx::query_as::<_, RolePermission>(
+            "SELECT role, permission FROM role_permissions ORDER BY role, permission",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(permissions)
+    }
+}
+
+#[async_trait]
+pub trait RoleExt {
+    async fn get_roles(&self) -> Result<Vec<Role>, sqlx::Error>;
+
+    async fn get_role(&self, name: &str) -> Result<Option<Role>, sqlx::Error>;
+
+    async fn get_role_ancestors(&self, name: &str) -> Result<Vec<UserRole>, sqlx::Error>;
+
+    async fn create_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent: Option<&str>,
+        permissions: &[String],
+    ) -> Result<Role, sqlx::Error>;
+
+    async fn update_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent: Option<&str>,
+        permissions: Option<&[String]>,
+    ) -> Result<Option<Role>, sqlx::Error>;
+
+    async fn delete_role(&self, name: &str) -> Result<bool, sqlx::Error>;
+
+    async fn is_role_in_use(&self, name: &str) -> Result<bool, sqlx::Error>;
+}
+
+#[async_trait]
+impl RoleExt for DBClient {
+    async fn get_roles(&self) -> Result<Vec<Role>, sqlx::Error> {
+        let roles = sqlx::query_as::<_, Role>("SELECT * FROM roles ORDER BY built_in DESC, name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(roles)
+    }
+
+    async fn get_role(&self, name: &str) -> Result<Option<Role>, sqlx::Error> {
+        let role = sqlx::query_as::<_, Role>("SELECT * FROM roles WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(role)
+    }
+
+    async fn get_role_ancestors(&self, name: &str) -> Result<Vec<UserRole>, sqlx::Error> {
+        let ancestors = sqlx::query_scalar::<_, UserRole>(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT parent, 1 AS depth FROM roles WHERE name = $1
+                UNION
+                SELECT roles.parent, ancestors.depth + 1
+                FROM roles JOIN ancestors ON roles.name = ancestors.parent
+                WHERE ancestors.depth < 50
+            )
+            SELECT parent FROM ancestors WHERE parent IS NOT NULL ORDER BY depth
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ancestors)
+    }
+
+    async fn create_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent: Option<&str>,
+        permissions: &[String],
+    ) -> Result<Role, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let role = sqlx::query_as::<_, Role>(
+            r#"
+            INSERT INTO roles (name, description, parent)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(parent)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::VARCHAR[])",
+        )
+        .bind(name)
+        .bind(permissions)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(role)
+    }
+
+    async fn update_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        parent: Option<&str>,
+        permissions: Option<&[String]>,
+    ) -> Result<Option<Role>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let role = sqlx::query_as::<_, Role>(
+            r#"
+            UPDATE roles
+            SET description = $2, parent = $3, updated_at = NOW()
+            WHERE name = $1
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(parent)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if role.is_some()
+            && let Some(permissions) = permissions
+        {
+            sqlx::query("DELETE FROM role_permissions WHERE role = $1")
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::VARCHAR[])",
+            )
+            .bind(name)
+            .bind(permissions)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(role)
+    }
+
+    async fn delete_role(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM roles WHERE name = $1 AND built_in = false")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_role_in_use(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let in_use = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM users WHERE role = $1)
+                OR EXISTS (SELECT 1 FROM api_keys WHERE role = $1)
+                OR EXISTS (SELECT 1 FROM roles WHERE parent = $1)
+            "#,
+        )
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(in_use)
+    }
+}
+
+#[async_trait]
+pub trait ScheduledJobExt {
+    async fn claim_scheduled_job(
+        &self,
+        name: &str,
+        instance_id: &str,
+        min_gap_secs: f64,
+        lease_secs: f64,
+    ) -> Result<bool, sqlx::Error>;
+
+    async fn finish_scheduled_job(
+        &self,
+        name: &str,
+        instance_id: &str,
+        duration_ms: i64,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn get_scheduled_jobs(&self) -> Result<Vec<ScheduledJob>, sqlx::Error>;
+}
+
+#[async_trait]
+impl ScheduledJobExt for DBClient {
+    async fn claim_scheduled_job(
+        &self,
+        name: &str,
+        instance_id: &str,
+        min_gap_secs: f64,
+        lease_secs: f64,
+    ) -> Result<bool, sqlx::Error> {
+        let claimed = sqlx::query_scalar::<_, String>(
+            r#"
+            INSERT INTO scheduled_jobs (name, locked_by, locked_until, last_started_at)
+            VALUES ($1, $2, NOW() + make_interval(secs => $4), NOW())
+            ON CONFLICT (name) DO UPDATE
+            SET locked_by = EXCLUDED.locked_by,
+                locked_until = EXCLUDED.locked_until,
+                last_started_at = EXCLUDED.last_started_at,
+                updated_at = NOW()
+            WHERE (scheduled_jobs.locked_until IS NULL OR scheduled_jobs.locked_until < NOW())
+              AND (scheduled_jobs.last_started_at IS NULL
+                   OR scheduled_jobs.last_started_at <= NOW() - make_interval(secs => $3))
+            RETURNING name
+            "#,
+        )
+        .bind(name)
+        .bind(instance_id)
+        .bind(min_gap_secs)
+        .bind(lease_secs)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    async fn finish_scheduled_job(
+        &self,
+        name: &str,
+        instance_id: &str,
+        duration_ms: i64,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE scheduled_jobs
+            SET locked_by = NULL,
+                locked_until = NULL,
+                last_finished_at = NOW(),
+                last_run_by = $2,
+                last_duration_ms = $3,
+                last_error = $4,
+                run_count = run_count + 1,
+                updated_at = NOW()
+            WHERE name = $1 AND locked_by = $2
+            "#,
+        )
+        .bind(name)
+        .bind(instance_id)
+        .bind(duration_ms)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_scheduled_jobs(&self) -> Result<Vec<ScheduledJob>, sqlx::Error> {
+        let jobs = sqlx::query_as::<_, ScheduledJob>("SELECT * FROM scheduled_jobs ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs)
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{
+    db::{DBClient, DoctorExt, PermissionExt, RoleExt},
+    models::{AccountStatus, UserRole},
+    permissions::Permission,
+};
+
+const USER_TABLES: [&str; 11] = [
+    "sessions",
+    "api_keys",
+    "revoked_tokens",
+    "oauth_accounts",
+    "credentials",
+    "backup_codes",
+    "password_reset_codes",
+    "security_questions",
+    "recovery_emails",
+    "user_metadata",
+    "role_grants",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+}
+
+impl CheckStatus {
+    pub fn to_str(&self) -> &str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        DoctorCheck {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    pub healthy: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+pub async fn run(db_client: &DBClient) -> Result<DoctorReport, sqlx::Error> {
+    let mut checks = vec![check_migrations(db_client).await];
+    checks.extend(check_indexes(db_client).await?);
+    checks.push(check_roles(db_client).await?);
+    checks.push(
+        check_enum(
+            db_client,
+            "account_status",
+            AccountStatus::ALL.iter().map(|status| status.to_str()),
+        )
+        .await?,
+    );
+    checks.push(check_role_permissions(db_client).await?);
+    for table in USER_TABLES {
+        checks.push(check_orphans(db_client, table).await?);
+    }
+
+    Ok(DoctorReport {
+        healthy: checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed),
+        checks,
+    })
+}
+
+pub async fn check_migrations(db_client: &DBClient) -> DoctorCheck {
+    let applied = match db_client.get_applied_migrations().await {
+        Ok(applied) => applied,
+        Err(e) => {
+            return DoctorCheck::new(
+                "migrations",
+                CheckStatus::Failed,
+                format!("Could not read applied migrations: {}", e),
+            );
+        }
+    };
+
+    let migrator = sqlx::migrate!("./migrations");
+    let known: Vec<_> = migrator
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .collect();
+
+    let mut problems = Vec::new();
+    for migration in &known {
+        match applied
+            .iter()
+            .find(|(version, _, _)| *version == migration.version)
+        {
+            None => problems.push(format!("{} is pending", migration.version)),
+            Some((_, false, _)) => problems.push(format!("{} failed", migration.version)),
+            Some((_, true, checksum)) if checksum.as_slice() != migration.checksum.as_ref() => {
+                problems.push(format!(
+                    "{} was modified after it was applied",
+                    migration.version
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let unknown: Vec<String> = applied
+        .iter()
+        .filter(|(version, _, _)| !known.iter().any(|migration| migration.version == *version))
+        .map(|(version, _, _)| version.to_string())
+        .collect();
+
+    if !problems.is_empty() {
+        DoctorCheck::new("migrations", CheckStatus::Failed, problems.join(", "))
+    } else if !unknown.is_empty() {
+        DoctorCheck::new(
+            "migrations",
+            CheckStatus::Warning,
+            format!(
+                "Applied migrations unknown to this build: {}",
+                unknown.join(", ")
+            ),
+        )
+    } else {
+        DoctorCheck::new(
+            "migrations",
+            CheckStatus::Ok,
+            format!("{} migrations applied", known.len()),
+        )
+    }
+}
+
+async fn check_indexes(db_client: &DBClient) -> Result<Vec<DoctorCheck>, sqlx::Error> {
+    let definitions: Vec<String> = db_client
+        .get_index_definitions("users")
+        .await?
+        .iter()
+        .map(|definition| definition.to_lowercase())
+        .collect();
+
+    let unique_email = definitions.iter().any(|definition| {
+        definition.starts_with("create unique index") && definition.ends_with("(email)")
+    });
+    let lower_email = definitions.iter().any(|definition| {
+        definition.starts_with("create unique index") && definition.contains("(lower((email)")
+    });
+
+    let index_check = |name: &str, present: bool| {
+        if present {
+            DoctorCheck::new(name, CheckStatus::Ok, "Index present")
+        } else {
+            DoctorCheck::new(name, CheckStatus::Failed, "Index missing")
+        }
+    };
+
+    Ok(vec![
+        index_check("index:users.email unique", unique_email),
+        index_check("index:users.lower(email) unique", lower_email),
+    ])
+}
+
+async fn check_enum<'a>(
+    db_client: &DBClient,
+    type_name: &str,
+    expected: impl Iterator<Item = &'a str>,
+) -> Result<DoctorCheck, sqlx::Error> {
+    let name = format!("enum:{}", type_name);
+    let labels: HashSet<String> = db_client
+        .get_enum_labels(type_name)
+        .await?
+        .into_iter()
+        .collect();
+    let expected: HashSet<String> = expected.map(str::to_string).collect();
+
+    let mut missing: Vec<&String> = expected.difference(&labels).collect();
+    let mut extra: Vec<&String> = labels.difference(&expected).collect();
+    missing.sort();
+    extra.sort();
+
+    let check = if labels.is_empty() {
+        DoctorCheck::new(name, CheckStatus::Failed, "Type does not exist")
+    } else if !missing.is_empty() {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Failed,
+            format!("Missing values: {:?}", missing),
+        )
+    } else if !extra.is_empty() {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Warning,
+            format!("Values unknown to this build: {:?}", extra),
+        )
+    } else {
+        DoctorCheck::new(name, CheckStatus::Ok, "Values match")
+    };
+
+    Ok(check)
+}
+
+async fn check_roles(db_client: &DBClient) -> Result<DoctorCheck, sqlx::Error> {
+    let roles = db_client.get_roles().await?;
+    let missing: Vec<&str> = [UserRole::USER, UserRole::ADMIN]
+        .into_iter()
+        .filter(|name| !roles.iter().any(|role| role.name.to_str() == *name))
+        .collect();
+
+    let check = if missing.is_empty() {
+        DoctorCheck::new(
+            "roles",
+            CheckStatus::Ok,
+            format!("{} roles defined", roles.len()),
+        )
+    } else {
+        DoctorCheck::new(
+            "roles",
+            CheckStatus::Failed,
+            format!("Built-in roles missing from the roles table: {:?}", missing),
+        )
+    };
+
+    Ok(check)
+}
+
+async fn check_role_permissions(db_client: &DBClient) -> Result<DoctorCheck, sqlx::Error> {
+    let mut unknown: Vec<String> = db_client
+        .get_role_permissions()
+        .await?
+        .into_iter()
+        .filter(|row| Permission::parse(&row.permission).is_none())
+        .map(|row| format!("{}:{}", row.role.to_str(), row.permission))
+        .collect();
+    unknown.sort();
+
+    let check = if unknown.is_empty() {
+        DoctorCheck::new("role_permissions", CheckStatus::Ok, "All permissions known")
+    } else {
+        DoctorCheck::new(
+            "role_permissions",
+            CheckStatus::Warning,
+            format!("Permissions unknown to this build: {:?}", unknown),
+        )
+    };
+
+    Ok(check)
+}
+
+async fn check_orphans(db_client: &DBClient, table: &str) -> Result<DoctorCheck, sqlx::Error> {
+    let name = format!("orphans:{}", table);
+    let count = db_client.count_orphaned_rows(table).await?;
+
+    let check = if count > 0 {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Warning,
+            format!("{} rows reference deleted users", count),
+        )
+    } else {
+        DoctorCheck::new(name, CheckStatus::Ok, "No orphaned rows")
+    };
+
+    Ok(check)
+}
@@ -1,9 +1,24 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use core::str;
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use validator::{Validate, ValidationError, ValidationErrors};
+use webauthn_rs::prelude::{PublicKeyCredential, RegisterPublicKeyCredential};
 
-use crate::models::{User, UserRole};
+use crate::audit::AuditLogFilter;
+use crate::config::{Config, RegistrationField};
+use crate::doctor::DoctorCheck;
+use crate::models::{
+    Announcement, AnnouncementSeverity, ApiKey, AuditLog, Credential, Delegation, EmailBranding,
+    Invitation, Organization, OutboxEmail, OutboxStatus, RecoveryEmail, RoleGrant, ScheduledJob,
+    SecurityQuestion, User, UserRole,
+};
+use crate::pagination::PageQuery;
+use crate::permissions::Permission;
+use crate::utils::public_id;
+
+pub const MAX_PAGE_LIMIT: usize = 50;
 
 #[derive(Debug, Validate, Default, Serialize, Deserialize, Clone)]
 pub struct LoginUserDTO {
@@ -33,6 +48,54 @@ pub struct RegisterUserDTO {
         must_match(other = "password", message = "Passwords do not match")
     )]
     pub password_confirm: String,
+
+    pub invite_token: Option<String>,
+
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+pub fn validate_registration_metadata(
+    metadata: &HashMap<String, String>,
+    fields: &[RegistrationField],
+) -> Result<(), String> {
+    if let Some(key) = metadata
+        .keys()
+        .find(|key| !fields.iter().any(|field| &field.name == *key))
+    {
+        return Err(format!("Unknown registration field: {}", key));
+    }
+
+    fields
+        .iter()
+        .try_for_each(|field| field.validate(metadata.get(&field.name).map(String::as_str)))
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct StartRegistrationDTO {
+    #[validate(
+        length(min = 6, message = "Email must be at least 6 characters long"),
+        email(message = "Email must be a valid email address")
+    )]
+    pub email: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CompleteRegistrationDTO {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+    #[validate(length(min = 3, message = "Name must be at least 3 characters long"))]
+    pub name: String,
+    #[validate(length(min = 6, message = "Password must be at least 6 characters long"))]
+    pub password: String,
+    #[validate(
+        length(min = 1, message = "Password confirmation is required"),
+        must_match(other = "password", message = "Passwords do not match")
+    )]
+    pub password_confirm: String,
+
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Validate)]
@@ -43,6 +106,100 @@ pub struct RequestQueryDTO {
     pub limit: Option<usize>,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct QueryOptions {
+    pub strict: bool,
+    pub clamp_limit: bool,
+}
+
+impl QueryOptions {
+    pub fn from_config(config: &Config) -> Self {
+        QueryOptions {
+            strict: config.query_strict,
+            clamp_limit: config.query_clamp_limit,
+        }
+    }
+}
+
+pub trait QueryDTO: Validate + Sized {
+    const FIELDS: &'static [&'static str];
+
+    fn from_params(
+        params: &HashMap<String, String>,
+        options: &QueryOptions,
+        errors: &mut ValidationErrors,
+    ) -> Self;
+
+    fn parse(
+        params: &HashMap<String, String>,
+        options: &QueryOptions,
+    ) -> Result<Self, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if options.strict {
+            for key in params.keys() {
+                if !Self::FIELDS.contains(&key.as_str()) {
+                    let mut error = ValidationError::new("unknown_param");
+                    error.message = Some(format!("Unknown query parameter '{}'", key).into());
+                    errors.add("query", error);
+                }
+            }
+        }
+
+        let dto = Self::from_params(params, options, &mut errors);
+        if let Err(validation_errors) = dto.validate() {
+            for (field, field_errors) in validation_errors.field_errors() {
+                for error in field_errors {
+                    errors.add(field, error.clone());
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(dto)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn parse_numeric_param<T: FromStr>(
+    params: &HashMap<String, String>,
+    field: &'static str,
+    errors: &mut ValidationErrors,
+) -> Option<T> {
+    let value = params.get(field)?;
+    match value.trim().parse::<T>() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            let mut error = ValidationError::new("numeric");
+            error.message = Some(format!("{} must be a positive number", field).into());
+            error.add_param("value".into(), value);
+            errors.add(field, error);
+            None
+        }
+    }
+}
+
+impl QueryDTO for RequestQueryDTO {
+    const FIELDS: &'static [&'static str] = &["page", "limit"];
+
+    fn from_params(
+        params: &HashMap<String, String>,
+        options: &QueryOptions,
+        errors: &mut ValidationErrors,
+    ) -> Self {
+        let page = parse_numeric_param(params, "page", errors);
+        let mut limit: Option<usize> = parse_numeric_param(params, "limit", errors);
+
+        if options.clamp_limit {
+            limit = limit.map(|limit| limit.min(MAX_PAGE_LIMIT));
+        }
+
+        RequestQueryDTO { page, limit }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FilterUserDTO {
     pub id: String,
@@ -50,6 +207,11 @@ pub struct FilterUserDTO {
     pub email: String,
     pub role: String,
     pub verified: bool,
+    #[serde(rename = "accountStatus")]
+    pub account_status: String,
+    #[serde(rename = "registrationState")]
+    pub registration_state: String,
+    pub plan: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
@@ -59,11 +221,14 @@ pub struct FilterUserDTO {
 impl FilterUserDTO {
     pub fn filter_user(user: &User) -> Self {
         FilterUserDTO {
-            id: user.id.to_string(),
+            id: public_id::encode(user.id),
             name: user.name.clone(),
             email: user.email.clone(),
             role: user.role.to_str().to_string(),
             verified: user.verified,
+            account_status: user.account_status.to_str().to_string(),
+            registration_state: user.registration_state.to_str().to_string(),
+            plan: user.plan.clone(),
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -95,7 +260,23 @@ pub struct UserListResponseDTO {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserLoginResponseDTO {
     pub status: String,
-    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(rename = "refreshToken", skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CookieSessionResponseDTO {
+    pub status: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct RefreshTokenDTO {
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    pub refresh_token: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -116,13 +297,186 @@ pub struct RoleUpdateDto {
     pub role: UserRole,
 }
 
+const RESERVED_ROLE_NAMES: [&str; 2] = ["grants", "bulk-assign"];
+
 fn validate_user_role(role: &UserRole) -> Result<(), validator::ValidationError> {
-    match role {
-        UserRole::Admin | UserRole::User => Ok(()),
-        _ => Err(validator::ValidationError::new("Invalid user role")),
+    let name = role.to_str();
+    let valid = (1..=50).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !RESERVED_ROLE_NAMES.contains(&name);
+
+    if valid {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("role");
+        error.message = Some(
+            "Role must be 1 to 50 lowercase letters, digits or underscores, starting with a letter"
+                .into(),
+        );
+        Err(error)
     }
 }
 
+fn validate_permissions(permissions: &[String]) -> Result<(), ValidationError> {
+    match permissions
+        .iter()
+        .find(|permission| Permission::parse(permission).is_none())
+    {
+        None => Ok(()),
+        Some(permission) => {
+            let mut error = ValidationError::new("permission");
+            error.message = Some(format!("Unknown permission '{}'", permission).into());
+            Err(error)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateRoleDTO {
+    #[validate(custom = "validate_user_role")]
+    pub name: UserRole,
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+    #[validate(custom = "validate_user_role")]
+    pub parent: Option<UserRole>,
+    #[serde(default)]
+    #[validate(custom = "validate_permissions")]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateRoleDTO {
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+    #[validate(custom = "validate_user_role")]
+    pub parent: Option<UserRole>,
+    #[validate(custom = "validate_permissions")]
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleDTO {
+    pub name: String,
+    pub description: Option<String>,
+    pub parent: Option<String>,
+    #[serde(rename = "builtIn")]
+    pub built_in: bool,
+    pub permissions: Vec<String>,
+    #[serde(rename = "effectivePermissions")]
+    pub effective_permissions: Vec<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionDTO {
+    pub name: String,
+    pub description: String,
+    pub group: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionListResponseDTO {
+    pub status: String,
+    pub permissions: Vec<PermissionDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteRateLimitDTO {
+    pub scope: String,
+    #[serde(rename = "perMinuteIp")]
+    pub per_minute_ip: u32,
+    #[serde(rename = "perMinuteEmail", skip_serializing_if = "Option::is_none")]
+    pub per_minute_email: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteDTO {
+    pub method: String,
+    pub path: String,
+    pub access: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    pub audiences: Vec<String>,
+    pub sudo: bool,
+    #[serde(rename = "apiKeys")]
+    pub api_keys: bool,
+    pub delegated: bool,
+    pub quota: bool,
+    #[serde(rename = "rateLimit")]
+    pub rate_limit: Option<RouteRateLimitDTO>,
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: Option<u64>,
+    #[serde(rename = "concurrencyLimit")]
+    pub concurrency_limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteListResponseDTO {
+    pub status: String,
+    pub results: usize,
+    pub routes: Vec<RouteDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleListResponseDTO {
+    pub status: String,
+    pub roles: Vec<RoleDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleResponseDTO {
+    pub status: String,
+    pub role: RoleDTO,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct BulkRoleAssignDTO {
+    #[validate(length(
+        min = 1,
+        max = 1000,
+        message = "Between 1 and 1000 user ids are required"
+    ))]
+    #[serde(with = "crate::utils::public_id::vec")]
+    pub user_ids: Vec<uuid::Uuid>,
+    #[validate(custom = "validate_user_role")]
+    pub role: UserRole,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleAssignmentResultDTO {
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub result: String,
+    #[serde(rename = "previousRole", skip_serializing_if = "Option::is_none")]
+    pub previous_role: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkRoleAssignResponseDTO {
+    pub status: String,
+    pub role: String,
+    pub updated: usize,
+    pub results: Vec<RoleAssignmentResultDTO>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleGrantsResponseDTO {
+    pub status: String,
+    pub grants: Vec<RoleGrant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleGrantResponseDTO {
+    pub status: String,
+    pub grant: RoleGrant,
+}
+
 #[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
 pub struct UpdatePasswordUpdateDto {
     #[validate(length(min = 1, message = "Current password is required"))]
@@ -150,6 +504,81 @@ pub struct ForgotPasswordRequestDTO {
     pub email: String,
 }
 
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct VerifyResetCodeDTO {
+    #[validate(length(min = 6, message = "Email must be at least 6 characters long"))]
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct ReauthenticateDTO {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: Option<String>,
+    pub code: Option<String>,
+    pub backup_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SudoTokenResponseDTO {
+    pub status: String,
+    pub token: String,
+    #[serde(rename = "expiresIn")]
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct MagicLinkRequestDTO {
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeResponseDTO {
+    pub status: String,
+    #[serde(rename = "mfaToken")]
+    pub mfa_token: String,
+    #[serde(rename = "expiresIn")]
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct VerifyTwoFactorDTO {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    pub mfa_token: String,
+    pub code: Option<String>,
+    pub backup_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct ConfirmTwoFactorDTO {
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorEnrollResponseDTO {
+    pub status: String,
+    pub secret: String,
+    #[serde(rename = "otpauthUrl")]
+    pub otpauth_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupCodesResponseDTO {
+    pub status: String,
+    #[serde(rename = "backupCodes")]
+    pub backup_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetTokenResponseDTO {
+    pub status: String,
+    pub token: String,
+}
+
 #[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
 pub struct ResetPasswordRequestDTO {
     #[validate(length(min = 1, message = "Token is required"))]
@@ -162,4 +591,731 @@ pub struct ResetPasswordRequestDTO {
         must_match(other = "new_password", message = "Passwords do not match")
     )]
     pub new_password_confirm: String,
+
+    #[serde(default)]
+    pub security_answers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceUpdateDTO {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaintenanceResponseDTO {
+    pub status: String,
+    pub enabled: bool,
+    #[serde(rename = "retryAfter")]
+    pub retry_after: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadOnlyUpdateDTO {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadOnlyResponseDTO {
+    pub status: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RetiredKeyDTO {
+    pub kid: String,
+    #[serde(rename = "verifiesUntil")]
+    pub verifies_until: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwtKeysResponseDTO {
+    pub status: String,
+    pub algorithm: String,
+    pub kid: String,
+    #[serde(rename = "canSign")]
+    pub can_sign: bool,
+    #[serde(rename = "retiredKeys")]
+    pub retired_keys: Vec<RetiredKeyDTO>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeapStatsResponseDTO {
+    pub status: String,
+    pub allocated: u64,
+    pub active: u64,
+    pub resident: u64,
+    pub mapped: u64,
+    pub retained: u64,
+    pub metadata: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkerStatsDTO {
+    #[serde(rename = "busyMs")]
+    pub busy_ms: u64,
+    pub parks: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuntimeStatsResponseDTO {
+    pub status: String,
+    #[serde(rename = "aliveTasks")]
+    pub alive_tasks: usize,
+    #[serde(rename = "globalQueueDepth")]
+    pub global_queue_depth: usize,
+    #[serde(rename = "consoleEnabled")]
+    pub console_enabled: bool,
+    pub workers: Vec<WorkerStatsDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDumpResponseDTO {
+    pub status: String,
+    pub tasks: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsResponseDTO {
+    pub status: String,
+    pub counters: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledJobsResponseDTO {
+    pub status: String,
+    #[serde(rename = "instanceId")]
+    pub instance_id: String,
+    pub jobs: Vec<ScheduledJob>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoctorResponseDTO {
+    pub status: String,
+    pub healthy: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+pub struct OutboxQueryDTO {
+    pub status: Option<OutboxStatus>,
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutboxResponseDTO {
+    pub status: String,
+    pub emails: Vec<OutboxEmail>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutboxEmailResponseDTO {
+    pub status: String,
+    pub email: OutboxEmail,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct CreateOrganizationDTO {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct OrganizationBrandingDTO {
+    #[validate(length(max = 100, message = "Display name must be at most 100 characters"))]
+    pub display_name: Option<String>,
+    #[validate(url(message = "Logo URL must be a valid URL"))]
+    pub logo_url: Option<String>,
+    #[validate(custom = "validate_hex_color")]
+    pub accent_color: Option<String>,
+    #[validate(email(message = "Reply-to must be a valid email address"))]
+    pub reply_to: Option<String>,
+}
+
+impl From<OrganizationBrandingDTO> for EmailBranding {
+    fn from(dto: OrganizationBrandingDTO) -> Self {
+        EmailBranding {
+            display_name: dto.display_name,
+            logo_url: dto.logo_url,
+            accent_color: dto.accent_color,
+            reply_to: dto.reply_to,
+        }
+    }
+}
+
+fn validate_hex_color(color: &str) -> Result<(), ValidationError> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if valid {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("hex_color");
+        error.message = Some("Accent color must be a hex color like #1a2b3c".into());
+        Err(error)
+    }
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SessionPolicyDTO {
+    #[validate(range(min = 1, message = "Idle timeout must be at least 1 minute"))]
+    pub idle_timeout: Option<i32>,
+    #[validate(range(min = 1, message = "Max lifetime must be at least 1 minute"))]
+    pub max_lifetime: Option<i32>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GeoPolicyDTO {
+    #[serde(default)]
+    #[validate(custom = "validate_country_codes")]
+    pub blocked_countries: Vec<String>,
+    #[serde(default)]
+    #[validate(custom = "validate_country_codes")]
+    pub mfa_countries: Vec<String>,
+    #[serde(default)]
+    pub blocked_asns: Vec<i64>,
+}
+
+fn validate_country_codes(countries: &[String]) -> Result<(), ValidationError> {
+    if countries
+        .iter()
+        .all(|country| country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        Ok(())
+    } else {
+        let mut error = ValidationError::new("country_code");
+        error.message = Some("Countries must be two-letter ISO country codes".into());
+        Err(error)
+    }
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PlanUpdateDTO {
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Plan must be between 1 and 50 characters"
+    ))]
+    pub plan: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationResponseDTO {
+    pub status: String,
+    pub organization: Organization,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct AvailabilityQueryDTO {
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvailabilityResponseDTO {
+    pub status: String,
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct CreateInvitationDTO {
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+    pub organization_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationResponseDTO {
+    pub status: String,
+    pub invitation: Invitation,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncementDTO {
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "Message must be between 1 and 500 characters"
+    ))]
+    pub message: String,
+    pub severity: AnnouncementSeverity,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnnouncementResponseDTO {
+    pub status: String,
+    pub announcement: Announcement,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnnouncementListResponseDTO {
+    pub status: String,
+    pub announcements: Vec<Announcement>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RecoveryEmailDTO {
+    #[validate(
+        length(min = 6, message = "Email must be at least 6 characters long"),
+        email(message = "Email must be a valid email address")
+    )]
+    pub email: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SecurityAnswerDTO {
+    #[validate(length(min = 1, max = 255, message = "Question must be 1-255 characters long"))]
+    pub question: String,
+    #[validate(length(min = 1, max = 64, message = "Answer must be 1-64 characters long"))]
+    pub answer: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SecurityQuestionsUpdateDTO {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: String,
+    #[validate]
+    pub questions: Vec<SecurityAnswerDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityQuestionsResponseDTO {
+    pub status: String,
+    pub questions: Vec<SecurityQuestion>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CreateDelegationDTO {
+    #[validate(
+        length(min = 6, message = "Email must be at least 6 characters long"),
+        email(message = "Email must be a valid email address")
+    )]
+    pub delegate_email: String,
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DelegationResponseDTO {
+    pub status: String,
+    pub delegation: Delegation,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DelegationListResponseDTO {
+    pub status: String,
+    pub delegations: Vec<Delegation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryEmailResponseDTO {
+    pub status: String,
+    #[serde(rename = "recoveryEmail")]
+    pub recovery_email: Option<RecoveryEmail>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+pub struct CreateApiKeyDTO {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    #[validate(custom = "validate_user_role")]
+    pub role: Option<UserRole>,
+    #[serde(default)]
+    pub require_signature: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyCreatedResponseDTO {
+    pub status: String,
+    pub key: String,
+    #[serde(rename = "signingSecret", skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    #[serde(rename = "apiKey")]
+    pub api_key: ApiKey,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyResponseDTO {
+    pub status: String,
+    #[serde(rename = "apiKey")]
+    pub api_key: ApiKey,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyListResponseDTO {
+    pub status: String,
+    #[serde(rename = "apiKeys")]
+    pub api_keys: Vec<ApiKey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakedTokenDTO {
+    pub token: String,
+    #[serde(rename = "type")]
+    pub token_type: String,
+    pub url: Option<String>,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeakedTokenResultDTO {
+    pub token_hash: String,
+    pub token_type: String,
+    pub label: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageEntryDTO {
+    pub period: String,
+    #[serde(rename = "periodStart")]
+    pub period_start: NaiveDate,
+    pub count: i64,
+    pub limit: Option<i64>,
+    #[serde(rename = "resetsAt")]
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct UserChangesQueryDTO {
+    #[validate(range(min = 0, message = "Cursor must not be negative"))]
+    pub since: Option<i64>,
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserChangeDTO {
+    pub cursor: i64,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub operation: String,
+    #[serde(rename = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<FilterUserDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserChangesResponseDTO {
+    pub status: String,
+    pub changes: Vec<UserChangeDTO>,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: i64,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageResponseDTO {
+    pub status: String,
+    pub usage: Vec<UsageEntryDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordSecurityDTO {
+    #[serde(rename = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+    #[serde(rename = "ageDays")]
+    pub age_days: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaMethodDTO {
+    pub method: String,
+    pub enabled: bool,
+    pub count: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityEventDTO {
+    pub kind: String,
+    pub count: Option<i32>,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityOverviewDTO {
+    pub password: PasswordSecurityDTO,
+    #[serde(rename = "mfaMethods")]
+    pub mfa_methods: Vec<MfaMethodDTO>,
+    #[serde(rename = "activeSessions")]
+    pub active_sessions: usize,
+    #[serde(rename = "suspiciousEvents")]
+    pub suspicious_events: Vec<SecurityEventDTO>,
+    pub recommendations: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityOverviewResponseDTO {
+    pub status: String,
+    pub data: SecurityOverviewDTO,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ActivityExportQueryDTO {
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEventDTO {
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: DateTime<Utc>,
+    pub event: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityExportDTO {
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+    pub events: Vec<ActivityEventDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityExportPendingDTO {
+    pub status: String,
+    #[serde(rename = "exportId")]
+    pub export_id: String,
+    #[serde(rename = "downloadUrl")]
+    pub download_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsrfTokenResponseDTO {
+    pub status: String,
+    #[serde(rename = "csrfToken")]
+    pub csrf_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NonceResponseDTO {
+    pub status: String,
+    pub nonce: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, Default)]
+pub struct PasskeyLoginStartDTO {
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+pub struct PasskeyLoginFinishDTO {
+    pub ceremony_id: String,
+    pub credential: PublicKeyCredential,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+pub struct PasskeyRegisterFinishDTO {
+    pub ceremony_id: String,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasskeyChallengeResponseDTO<T> {
+    pub status: String,
+    #[serde(rename = "ceremonyId")]
+    pub ceremony_id: String,
+    pub options: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialResponseDTO {
+    pub status: String,
+    pub credential: Credential,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialListResponseDTO {
+    pub status: String,
+    pub credentials: Vec<Credential>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLogQueryDTO {
+    pub page: PageQuery<AuditLog>,
+    pub filter: AuditLogFilter,
+}
+
+impl Validate for AuditLogQueryDTO {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.page.validate()
+    }
+}
+
+fn parse_public_id_param(
+    params: &HashMap<String, String>,
+    field: &'static str,
+    errors: &mut ValidationErrors,
+) -> Option<uuid::Uuid> {
+    let value = params.get(field)?;
+    let id = public_id::decode(value.trim());
+    if id.is_none() {
+        let mut error = ValidationError::new("id");
+        error.message = Some(format!("{} must be a valid user id", field).into());
+        errors.add(field, error);
+    }
+    id
+}
+
+fn parse_datetime_param(
+    params: &HashMap<String, String>,
+    field: &'static str,
+    errors: &mut ValidationErrors,
+) -> Option<DateTime<Utc>> {
+    let value = params.get(field)?;
+    match DateTime::parse_from_rfc3339(value.trim()) {
+        Ok(parsed) => Some(parsed.with_timezone(&Utc)),
+        Err(_) => {
+            let mut error = ValidationError::new("datetime");
+            error.message = Some(format!("{} must be an RFC 3339 timestamp", field).into());
+            errors.add(field, error);
+            None
+        }
+    }
+}
+
+impl QueryDTO for AuditLogQueryDTO {
+    const FIELDS: &'static [&'static str] = &[
+        "page", "limit", "sort", "order", "event", "actorId", "targetId", "since", "until",
+    ];
+
+    fn from_params(
+        params: &HashMap<String, String>,
+        options: &QueryOptions,
+        errors: &mut ValidationErrors,
+    ) -> Self {
+        let filter = AuditLogFilter {
+            event: params
+                .get("event")
+                .map(|event| event.trim().to_string())
+                .filter(|event| !event.is_empty()),
+            actor_id: parse_public_id_param(params, "actorId", errors),
+            target_id: parse_public_id_param(params, "targetId", errors),
+            since: parse_datetime_param(params, "since", errors),
+            until: parse_datetime_param(params, "until", errors),
+        };
+
+        AuditLogQueryDTO {
+            page: PageQuery::from_params(params, options, errors),
+            filter,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditVerificationResponseDTO {
+    pub status: String,
+    pub valid: bool,
+    pub checked: i64,
+    pub unchained: i64,
+    #[serde(rename = "firstInvalidId")]
+    pub first_invalid_id: Option<i64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+pub struct AttackStatsQueryDTO {
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "Window must be between 60 and 86400 seconds"
+    ))]
+    pub window: Option<u64>,
+    #[validate(range(min = 1, max = 50, message = "Limit must be between 1 and 50"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttackSourceDTO {
+    pub value: String,
+    pub failures: i32,
+    #[serde(rename = "windowStartedAt")]
+    pub window_started_at: DateTime<Utc>,
+    #[serde(rename = "lastFailureAt")]
+    pub last_failure_at: DateTime<Utc>,
+    #[serde(rename = "lockedUntil")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LockoutDTO {
+    pub kind: String,
+    pub value: String,
+    pub failures: i32,
+    #[serde(rename = "lockedUntil")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThrottledKeyDTO {
+    pub scope: String,
+    pub key: String,
+    pub rejections: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThrottleStatsDTO {
+    pub rejections: i64,
+    #[serde(rename = "byScope")]
+    pub by_scope: BTreeMap<String, i64>,
+    #[serde(rename = "topKeys")]
+    pub top_keys: Vec<ThrottledKeyDTO>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttackStatsResponseDTO {
+    pub status: String,
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+    #[serde(rename = "windowSeconds")]
+    pub window_seconds: u64,
+    #[serde(rename = "failedLogins")]
+    pub failed_logins: i64,
+    #[serde(rename = "topIps")]
+    pub top_ips: Vec<AttackSourceDTO>,
+    #[serde(rename = "targetedAccounts")]
+    pub targeted_accounts: Vec<AttackSourceDTO>,
+    #[serde(rename = "activeLockouts")]
+    pub active_lockouts: i64,
+    pub lockouts: Vec<LockoutDTO>,
+    pub throttle: ThrottleStatsDTO,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthLinkResponseDTO {
+    pub status: String,
+    #[serde(rename = "authorizeUrl")]
+    pub authorize_url: String,
 }
@@ -30,13 +30,80 @@ pub enum ErrorMessage {
     EmailExist,
     UserNoLongerExist,
     TokenNotProvided,
+    TokenMalformed,
     PermissionDenied,
     UserNotAuthenticated,
+    MaintenanceMode,
+    ReadOnlyMode,
+    IpNotAllowed,
+    TokenExpired,
+    IdempotencyKeyReused,
+    IdempotencyRequestInProgress,
+    ServerOverloaded,
+    AccountLocked,
+    TooManyRequests,
+    InvitationRequired,
+    InvalidInvitation,
+    AccountPendingApproval,
+    AccountRejected,
+    NameNotAllowed,
+    RegistrationIncomplete,
+    SecurityQuestionsDisabled,
+    WrongSecurityAnswers,
+    DelegatedAccessDenied,
+    CaptchaRequired,
+    CaptchaInvalid,
+    InvalidResetCode,
+    ResetCodeAttemptsExceeded,
+    SudoRequired,
+    SessionExpired,
+    ClientMismatch,
+    AudienceNotAllowed,
+    GeoBlocked,
+    MfaRequired,
+    InvalidSignature,
+    TokenRevoked,
+    AccountDeactivated,
+    UnknownOAuthProvider,
+    OAuthStateMismatch,
+    OAuthFailed,
+    OAuthEmailUnverified,
+    EntitlementRequired,
+    UnknownPlan,
+    InvalidTwoFactorCode,
+    TwoFactorNotEnrolled,
+    TwoFactorAlreadyEnabled,
+    PasskeyVerificationFailed,
+    PasskeyChallengeExpired,
+    InvalidApiKey,
+    ApiKeyNotAllowed,
+    ApiKeyRoleNotAllowed,
+    InvalidRequestSignature,
+    RequestReplayed,
+    UnknownPasswordPepper,
+    SecondApproverRequired,
+    AccountDeleted,
+    RiskBlocked,
+    RiskMfaRequired,
+    RoleNotFound,
+    RoleAlreadyExists,
+    RoleInUse,
+    RoleBuiltIn,
+    RoleHierarchyCycle,
+    CsrfTokenInvalid,
+    RequestTimedOut,
+    TaskDumpUnavailable,
+    TaskDumpSingleWorker,
+    InvalidTokenIssuer,
+    InvalidTokenAudience,
+    NoOrganization,
+    OAuthLinkRequiresSignIn,
+    OAuthAccountInUse,
 }
 
-impl ToString for ErrorMessage {
-    fn to_string(&self) -> String {
-        self.to_str().to_owned()
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_str())
     }
 }
 
@@ -55,8 +122,144 @@ impl ErrorMessage {
             ErrorMessage::EmailExist => "Email already exists".to_string(),
             ErrorMessage::UserNoLongerExist => "User no longer exists".to_string(),
             ErrorMessage::TokenNotProvided => "Token not provided".to_string(),
+            ErrorMessage::TokenMalformed => "Token is malformed".to_string(),
             ErrorMessage::PermissionDenied => "Permission denied".to_string(),
             ErrorMessage::UserNotAuthenticated => "User not authenticated".to_string(),
+            ErrorMessage::MaintenanceMode => {
+                "Service is temporarily unavailable due to maintenance".to_string()
+            }
+            ErrorMessage::ReadOnlyMode => {
+                "Service is in read-only mode, changes are temporarily disabled".to_string()
+            }
+            ErrorMessage::IpNotAllowed => "Access from this IP address is not allowed".to_string(),
+            ErrorMessage::TokenExpired => "Token has expired".to_string(),
+            ErrorMessage::IdempotencyKeyReused => {
+                "Idempotency key was already used with a different request".to_string()
+            }
+            ErrorMessage::IdempotencyRequestInProgress => {
+                "A request with this idempotency key is already in progress".to_string()
+            }
+            ErrorMessage::AccountLocked => "Account is locked".to_string(),
+            ErrorMessage::TooManyRequests => {
+                "Too many requests, please try again later".to_string()
+            }
+            ErrorMessage::ServerOverloaded => {
+                "Server is overloaded, please retry shortly".to_string()
+            }
+            ErrorMessage::InvitationRequired => "Registration is by invitation only".to_string(),
+            ErrorMessage::InvalidInvitation => {
+                "Invitation is invalid, expired or already used".to_string()
+            }
+            ErrorMessage::AccountPendingApproval => "Account is awaiting approval".to_string(),
+            ErrorMessage::AccountRejected => "Account registration was rejected".to_string(),
+            ErrorMessage::NameNotAllowed => "This name is not allowed".to_string(),
+            ErrorMessage::RegistrationIncomplete => {
+                "Registration has not been completed".to_string()
+            }
+            ErrorMessage::SecurityQuestionsDisabled => {
+                "Security questions are not enabled".to_string()
+            }
+            ErrorMessage::WrongSecurityAnswers => "Security answers are incorrect".to_string(),
+            ErrorMessage::DelegatedAccessDenied => {
+                "This action is not available with delegated access".to_string()
+            }
+            ErrorMessage::CaptchaRequired => "CAPTCHA verification required".to_string(),
+            ErrorMessage::CaptchaInvalid => "CAPTCHA verification failed".to_string(),
+            ErrorMessage::InvalidResetCode => "Invalid or expired code".to_string(),
+            ErrorMessage::ResetCodeAttemptsExceeded => {
+                "Too many incorrect attempts, please request a new code".to_string()
+            }
+            ErrorMessage::SudoRequired => "Re-authentication required for this action".to_string(),
+            ErrorMessage::SessionExpired => "Session has expired, please log in again".to_string(),
+            ErrorMessage::ClientMismatch => {
+                "Refresh token was issued to a different client".to_string()
+            }
+            ErrorMessage::AudienceNotAllowed => {
+                "Token audience is not allowed for this route".to_string()
+            }
+            ErrorMessage::GeoBlocked => "Access from your location is not allowed".to_string(),
+            ErrorMessage::MfaRequired => {
+                "Multi-factor authentication is required when signing in from your location"
+                    .to_string()
+            }
+            ErrorMessage::InvalidSignature => "Request signature is invalid".to_string(),
+            ErrorMessage::TokenRevoked => "This token has been revoked".to_string(),
+            ErrorMessage::AccountDeactivated => {
+                "Your account was deactivated due to inactivity. Log in again to reactivate it"
+                    .to_string()
+            }
+            ErrorMessage::UnknownOAuthProvider => "Unknown sign-in provider".to_string(),
+            ErrorMessage::OAuthStateMismatch => {
+                "Sign-in request expired or was tampered with, please try again".to_string()
+            }
+            ErrorMessage::OAuthFailed => "Could not sign in with the external provider".to_string(),
+            ErrorMessage::OAuthEmailUnverified => {
+                "Your email address is not verified with this provider".to_string()
+            }
+            ErrorMessage::EntitlementRequired => {
+                "Your plan does not include this feature".to_string()
+            }
+            ErrorMessage::UnknownPlan => "Unknown plan".to_string(),
+            ErrorMessage::InvalidTwoFactorCode => {
+                "Invalid two-factor authentication code".to_string()
+            }
+            ErrorMessage::TwoFactorNotEnrolled => {
+                "Two-factor authentication has not been set up".to_string()
+            }
+            ErrorMessage::TwoFactorAlreadyEnabled => {
+                "Two-factor authentication is already enabled".to_string()
+            }
+            ErrorMessage::PasskeyVerificationFailed => "Passkey verification failed".to_string(),
+            ErrorMessage::PasskeyChallengeExpired => {
+                "Passkey challenge has expired or is invalid".to_string()
+            }
+            ErrorMessage::InvalidApiKey => "Invalid API key".to_string(),
+            ErrorMessage::ApiKeyNotAllowed => {
+                "This action is not available with API key authentication".to_string()
+            }
+            ErrorMessage::ApiKeyRoleNotAllowed => {
+                "An API key cannot be granted more access than its owner".to_string()
+            }
+            ErrorMessage::InvalidRequestSignature => "Invalid request signature".to_string(),
+            ErrorMessage::RequestReplayed => "Request signature has already been used".to_string(),
+            ErrorMessage::UnknownPasswordPepper => {
+                "Password hash uses an unknown pepper".to_string()
+            }
+            ErrorMessage::SecondApproverRequired => {
+                "A different admin must approve this role grant".to_string()
+            }
+            ErrorMessage::AccountDeleted => "This account has been deleted".to_string(),
+            ErrorMessage::RiskBlocked => {
+                "This request was blocked for security reasons".to_string()
+            }
+            ErrorMessage::RiskMfaRequired => {
+                "Two-factor authentication is required to sign in from this device".to_string()
+            }
+            ErrorMessage::RoleNotFound => "Role does not exist".to_string(),
+            ErrorMessage::RoleAlreadyExists => "A role with this name already exists".to_string(),
+            ErrorMessage::RoleInUse => {
+                "Role is still assigned to users, API keys or child roles".to_string()
+            }
+            ErrorMessage::RoleBuiltIn => "Built-in roles cannot be deleted".to_string(),
+            ErrorMessage::RoleHierarchyCycle => {
+                "A role cannot inherit from itself or one of its descendants".to_string()
+            }
+            ErrorMessage::CsrfTokenInvalid => "Missing or invalid CSRF token".to_string(),
+            ErrorMessage::RequestTimedOut => "The request took too long to complete".to_string(),
+            ErrorMessage::TaskDumpUnavailable => {
+                "Task dumps require a build with --cfg tokio_unstable --cfg tokio_taskdump on Linux"
+                    .to_string()
+            }
+            ErrorMessage::TaskDumpSingleWorker => {
+                "Task dumps need a runtime with at least two worker threads".to_string()
+            }
+            ErrorMessage::InvalidTokenIssuer => "Token issuer is not trusted".to_string(),
+            ErrorMessage::InvalidTokenAudience => {
+                "Token is not intended for this service".to_string()
+            }
+            ErrorMessage::NoOrganization => "You do not belong to an organization".to_string(),
+            ErrorMessage::OAuthLinkRequiresSignIn => "An account with this email already exists, sign in and link the provider from your account settings".to_string(),
+            ErrorMessage::OAuthAccountInUse => "This sign-in identity is already linked to another account".to_string(),
         }
     }
 }
@@ -103,6 +306,27 @@ impl HttpError {
         }
     }
 
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        HttpError {
+            status: StatusCode::FORBIDDEN,
+            message: message.into(),
+        }
+    }
+
+    pub fn gateway_timeout(message: impl Into<String>) -> Self {
+        HttpError {
+            status: StatusCode::GATEWAY_TIMEOUT,
+            message: message.into(),
+        }
+    }
+
+    pub fn service_unavailable(message: impl Into<String>) -> Self {
+        HttpError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: message.into(),
+        }
+    }
+
     pub fn into_http_response(self) -> Response {
         let body = Json(ErrorResponse {
             status: "error".to_string(),
@@ -123,3 +347,9 @@ impl fmt::Display for HttpError {
 }
 
 impl std::error::Error for HttpError {}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        self.into_http_response()
+    }
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::{StatusCode, header},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    db::{ApiKeyExt, CredentialExt, LoginAttemptExt, RecoveryEmailExt, SessionExt},
+    dtos::{
+        ActivityEventDTO, ActivityExportDTO, ActivityExportPendingDTO, ActivityExportQueryDTO,
+        ExportFormat,
+    },
+    error::HttpError,
+    middleware::{
+        AuthenticatedUser,
+        login_throttle::account_key,
+        rate_limit::{RateLimits, rate_limit_by_ip},
+    },
+    models::User,
+    nonce::{self, NoncePurpose},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportFile {
+    format: ExportFormat,
+    body: String,
+}
+
+pub fn activity_handler() -> Router {
+    Router::new()
+        .route(
+            "/export",
+            get(export_activity).layer(middleware::from_fn(|state, req, next| {
+                rate_limit_by_ip(state, req, next, |limits: &RateLimits| {
+                    &limits.activity_export
+                })
+            })),
+        )
+        .route("/export/{id}", get(download_activity_export))
+}
+
+pub async fn export_activity(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<ActivityExportQueryDTO>,
+) -> Result<Response, HttpError> {
+    let format = query.format.unwrap_or_default();
+
+    let sessions = app_state
+        .db_client
+        .count_user_sessions(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if sessions <= app_state.env.activity_export_sync_limit {
+        let body = build_export(&app_state, &user, format)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        return Ok(file_response(format, body));
+    }
+
+    let export_id = nonce::generate();
+    let id = export_id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = store_export(&app_state, &user, format, &id).await {
+            tracing::warn!("Failed to build activity export for {}: {}", user.id, e);
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ActivityExportPendingDTO {
+            status: "pending".to_string(),
+            download_url: format!("/api/users/me/activity/export/{}", export_id),
+            export_id,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn download_activity_export(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, HttpError> {
+    let not_found = || HttpError::new(StatusCode::NOT_FOUND, "Export is not ready or has expired");
+
+    let export = nonce::consume(&app_state.db_client, NoncePurpose::ActivityExport, &id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|export| export.user_id == Some(user.id))
+        .and_then(|export| export.payload)
+        .ok_or_else(not_found)?;
+
+    let file: ExportFile =
+        serde_json::from_str(&export).map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(file_response(file.format, file.body))
+}
+
+async fn store_export(
+    app_state: &AppState,
+    user: &User,
+    format: ExportFormat,
+    id: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let body = build_export(app_state, user, format).await?;
+    let payload = serde_json::to_string(&ExportFile { format, body })?;
+
+    nonce::store(
+        &app_state.db_client,
+        NoncePurpose::ActivityExport,
+        id,
+        Some(user.id),
+        Some(&payload),
+        app_state.env.activity_export_ttl,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn build_export(
+    app_state: &AppState,
+    user: &User,
+    format: ExportFormat,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let events = collect_activity(app_state, user).await?;
+
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&ActivityExportDTO {
+            generated_at: Utc::now(),
+            events,
+        })?,
+        ExportFormat::Csv => render_csv(&events),
+    })
+}
+
+async fn collect_activity(
+    app_state: &AppState,
+    user: &User,
+) -> Result<Vec<ActivityEventDTO>, sqlx::Error> {
+    let db_client = &app_state.db_client;
+    let mut events = Vec::new();
+    let mut push = |occurred_at, event: &str, detail: Option<String>| {
+        events.push(ActivityEventDTO {
+            occurred_at,
+            event: event.to_string(),
+            detail,
+        })
+    };
+
+    for session in db_client.get_user_sessions(user.id).await? {
+        push(session.created_at, "session_started", session.audience);
+        if let Some(revoked_at) = session.revoked_at {
+            push(revoked_at, "session_revoked", None);
+        }
+    }
+
+    push(user.password_changed_at, "password_changed", None);
+    if let Some(enabled_at) = user.totp_enabled_at {
+        push(enabled_at, "two_factor_enabled", None);
+    }
+    if let Some(locked_at) = user.locked_at {
+        push(locked_at, "account_locked", None);
+    }
+
+    for credential in db_client.get_credentials(user.id).await? {
+        push(
+            credential.created_at,
+            "passkey_added",
+            Some(credential.name),
+        );
+    }
+
+    for api_key in db_client.get_api_keys(user.id).await? {
+        push(
+            api_key.created_at,
+            "api_key_created",
+            Some(api_key.name.clone()),
+        );
+        if let Some(revoked_at) = api_key.revoked_at {
+            push(revoked_at, "api_key_revoked", Some(api_key.name));
+        }
+    }
+
+    if let Some(recovery_email) = db_client.get_recovery_email(user.id).await?
+        && let Some(verified_at) = recovery_email.verified_at
+    {
+        push(
+            verified_at,
+            "recovery_email_verified",
+            Some(recovery_email.email),
+        );
+    }
+
+    if let Some(attempt) = db_client
+        .get_login_attempt(&account_key(&user.email.to_lowercase()))
+        .await?
+        && attempt.failures > 0
+    {
+        push(
+            attempt.updated_at,
+            "failed_logins",
+            Some(attempt.failures.to_string()),
+        );
+    }
+
+    events.sort_by_key(|event| std::cmp::Reverse(event.occurred_at));
+
+    Ok(events)
+}
+
+fn render_csv(events: &[ActivityEventDTO]) -> String {
+    let mut csv = String::from("occurred_at,event,detail\n");
+    for event in events {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            event.occurred_at.to_rfc3339(),
+            csv_field(&event.event),
+            csv_field(event.detail.as_deref().unwrap_or_default())
+        ));
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    let value = match value.chars().next() {
+        Some('=' | '+' | '-' | '@') => format!("'{}", value),
+        _ => value.to_string(),
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn file_response(format: ExportFormat, body: String) -> Response {
+    let (content_type, filename) = match format {
+        ExportFormat::Json => ("application/json", "activity.json"),
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "activity.csv"),
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
@@ -0,0 +1,1372 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    handler::Handler,
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    db::{
+        AuditLogExt, InvitationExt, OrganizationExt, OutboxExt, PermissionExt, RoleExt,
+        RoleGrantExt, ScheduledJobExt, UserChangeExt, UserExt,
+    },
+    doctor,
+    dtos::{
+        AuditLogQueryDTO, AuditVerificationResponseDTO, BulkRoleAssignDTO,
+        BulkRoleAssignResponseDTO, CreateInvitationDTO, CreateOrganizationDTO, CreateRoleDTO,
+        DoctorResponseDTO, FilterUserDTO, GeoPolicyDTO, InvitationResponseDTO, JwtKeysResponseDTO,
+        MaintenanceResponseDTO, MaintenanceUpdateDTO, MetricsResponseDTO, OrganizationBrandingDTO,
+        OrganizationResponseDTO, OutboxEmailResponseDTO, OutboxQueryDTO, OutboxResponseDTO,
+        PermissionDTO, PermissionListResponseDTO, PlanUpdateDTO, QueryDTO, QueryOptions,
+        ReadOnlyResponseDTO, ReadOnlyUpdateDTO, RetiredKeyDTO, RoleAssignmentResultDTO, RoleDTO,
+        RoleGrantResponseDTO, RoleGrantsResponseDTO, RoleListResponseDTO, RoleResponseDTO,
+        RouteDTO, RouteListResponseDTO, RouteRateLimitDTO, ScheduledJobsResponseDTO,
+        SessionPolicyDTO, UpdateRoleDTO, UserChangeDTO, UserChangesQueryDTO,
+        UserChangesResponseDTO, UserData, UserResponseDTO,
+    },
+    error::{ErrorMessage, HttpError},
+    handler::{
+        announcements::announcements_admin_handler, security::security_admin_handler,
+        waitlist::waitlist_handler,
+    },
+    middleware::{
+        AuthenticatedUser, ClientContext,
+        guard::{RequirePermission, require_role},
+        idempotency::idempotency,
+        require_sudo,
+    },
+    models::{Role, RoleGrant, RoleGrantStatus, UserRole},
+    notify::{Notification, NotificationKind},
+    pagination::Paginated,
+    permissions::{Permission, REGISTRY},
+    revocation,
+    route_catalog::{RouteGroupSpec, RouteSpec, mounted_groups},
+    utils::public_id::PublicId,
+};
+
+const DEFAULT_CHANGES_LIMIT: i64 = 100;
+const DEFAULT_OUTBOX_LIMIT: i64 = 100;
+
+pub fn admin_handler() -> Router {
+    Router::new()
+        .route(
+            "/maintenance",
+            get(get_maintenance).put(update_maintenance.layer(middleware::from_fn(require_sudo))),
+        )
+        .route(
+            "/read-only",
+            get(get_read_only).put(update_read_only.layer(middleware::from_fn(require_sudo))),
+        )
+        .route("/metrics", get(get_metrics))
+        .route("/jwt-keys", get(get_jwt_keys))
+        .route(
+            "/jwt-keys/rotate",
+            post(rotate_jwt_keys).layer(middleware::from_fn(require_sudo)),
+        )
+        .route("/jobs", get(get_scheduled_jobs))
+        .route("/audit-logs", get(get_audit_logs))
+        .route("/audit-logs/verify", get(verify_audit_logs))
+        .route("/doctor", get(get_doctor))
+        .route("/outbox", get(get_outbox))
+        .route("/outbox/{id}/retry", post(retry_outbox_email))
+        .route("/organizations", post(create_organization))
+        .route("/organizations/{id}", get(get_organization))
+        .route(
+            "/organizations/{id}/branding",
+            put(update_organization_branding),
+        )
+        .route(
+            "/organizations/{id}/session-policy",
+            put(update_organization_session_policy),
+        )
+        .route(
+            "/organizations/{id}/geo-policy",
+            put(update_organization_geo_policy),
+        )
+        .route("/organizations/{id}/plan", put(update_organization_plan))
+        .route(
+            "/users/changes",
+            get(get_user_changes).layer(RequirePermission(Permission::UsersRead)),
+        )
+        .route(
+            "/users/{id}",
+            delete(
+                delete_user
+                    .layer(middleware::from_fn(require_sudo))
+                    .layer(RequirePermission(Permission::UsersWrite)),
+            ),
+        )
+        .route(
+            "/users/{id}/restore",
+            post(
+                restore_user
+                    .layer(middleware::from_fn(require_sudo))
+                    .layer(RequirePermission(Permission::UsersWrite)),
+            ),
+        )
+        .route(
+            "/users/{id}/plan",
+            put(update_user_plan).layer(RequirePermission(Permission::UsersWrite)),
+        )
+        .route(
+            "/roles/bulk-assign",
+            post(bulk_assign_role)
+                .layer(middleware::from_fn(idempotency))
+                .layer(RequirePermission(Permission::RolesWrite)),
+        )
+        .route("/permissions", get(get_permissions))
+        .route("/routes", get(get_routes))
+        .route(
+            "/roles",
+            get(get_roles).post(create_role.layer(RequirePermission(Permission::RolesWrite))),
+        )
+        .route(
+            "/roles/{name}",
+            put(update_role)
+                .delete(delete_role)
+                .layer(RequirePermission(Permission::RolesWrite)),
+        )
+        .route("/roles/grants", get(get_role_grants))
+        .route(
+            "/roles/grants/{id}/approve",
+            post(approve_role_grant).layer(RequirePermission(Permission::RolesWrite)),
+        )
+        .route(
+            "/roles/grants/{id}/reject",
+            post(reject_role_grant).layer(RequirePermission(Permission::RolesWrite)),
+        )
+        .nest("/waitlist", waitlist_handler())
+        .nest("/announcements", announcements_admin_handler())
+        .nest("/security", security_admin_handler())
+        .route(
+            "/invitations",
+            post(create_invitation).layer(middleware::from_fn(idempotency)),
+        )
+        .layer(require_role(UserRole::admin()))
+}
+
+pub async fn get_audit_logs(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let query = AuditLogQueryDTO::parse(&params, &QueryOptions::from_config(&app_state.env))
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let logs = app_state
+        .db_client
+        .get_audit_logs(&query.filter, &query.page)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let count = app_state
+        .db_client
+        .count_audit_logs(&query.filter)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(Paginated::new(logs, &query.page, count)))
+}
+
+pub async fn verify_audit_logs(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let verification = audit::verify(&app_state.db_client, &app_state.audit_chain)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(id) = verification.first_invalid_id {
+        tracing::error!(
+            target: "audit",
+            event = "audit_chain_broken",
+            audit_log_id = id,
+            reason = verification.reason,
+        );
+    }
+
+    Ok(Json(AuditVerificationResponseDTO {
+        status: "success".to_string(),
+        valid: verification.is_valid(),
+        checked: verification.checked,
+        unchained: verification.unchained,
+        first_invalid_id: verification.first_invalid_id,
+        reason: verification.reason.map(str::to_string),
+    }))
+}
+
+pub async fn get_maintenance(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    Ok(Json(maintenance_response(&app_state)))
+}
+
+pub async fn update_maintenance(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<MaintenanceUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    app_state.maintenance.set_enabled(body.enabled);
+
+    Ok(Json(maintenance_response(&app_state)))
+}
+
+fn maintenance_response(app_state: &AppState) -> MaintenanceResponseDTO {
+    MaintenanceResponseDTO {
+        status: "success".to_string(),
+        enabled: app_state.maintenance.is_enabled(),
+        retry_after: app_state.maintenance.retry_after,
+    }
+}
+
+pub async fn get_read_only(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    Ok(Json(ReadOnlyResponseDTO {
+        status: "success".to_string(),
+        enabled: app_state.read_only.is_enabled(),
+    }))
+}
+
+pub async fn update_read_only(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<ReadOnlyUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    app_state.read_only.set_enabled(body.enabled);
+
+    Ok(Json(ReadOnlyResponseDTO {
+        status: "success".to_string(),
+        enabled: app_state.read_only.is_enabled(),
+    }))
+}
+
+pub async fn get_jwt_keys(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    Ok(Json(jwt_keys_response(&app_state, None)))
+}
+
+pub async fn rotate_jwt_keys(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(client): Extension<ClientContext>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let previous_kid = app_state.jwt_keys.key_id();
+    let rotated = app_state
+        .jwt_keys
+        .rotate()
+        .map_err(HttpError::bad_request)?;
+
+    if rotated {
+        let kid = app_state.jwt_keys.key_id();
+        revocation::broadcast(&app_state.db_client, &format!("keys:{}", kid))
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::JwtKeyRotated)
+                .actor(admin.id)
+                .client(&client)
+                .detail(serde_json::json!({ "kid": kid, "previousKid": previous_kid })),
+        );
+    }
+
+    Ok(Json(jwt_keys_response(&app_state, Some(rotated))))
+}
+
+fn jwt_keys_response(app_state: &AppState, rotated: Option<bool>) -> JwtKeysResponseDTO {
+    JwtKeysResponseDTO {
+        status: "success".to_string(),
+        algorithm: format!("{:?}", app_state.jwt_keys.algorithm()),
+        kid: app_state.jwt_keys.key_id(),
+        can_sign: app_state.jwt_keys.can_sign(),
+        retired_keys: app_state
+            .jwt_keys
+            .retired_keys()
+            .into_iter()
+            .map(|key| RetiredKeyDTO {
+                kid: key.kid,
+                verifies_until: key.verifies_until,
+            })
+            .collect(),
+        rotated,
+    }
+}
+
+pub async fn get_metrics(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    Ok(Json(MetricsResponseDTO {
+        status: "success".to_string(),
+        counters: app_state.metrics.snapshot(),
+    }))
+}
+
+pub async fn get_scheduled_jobs(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let jobs = app_state
+        .db_client
+        .get_scheduled_jobs()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(ScheduledJobsResponseDTO {
+        status: "success".to_string(),
+        instance_id: app_state.env.instance_id.clone(),
+        jobs,
+    }))
+}
+
+pub async fn get_doctor(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let report = doctor::run(&app_state.db_client)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(DoctorResponseDTO {
+        status: "success".to_string(),
+        healthy: report.healthy,
+        checks: report.checks,
+    }))
+}
+
+pub async fn get_outbox(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<OutboxQueryDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    query
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let emails = app_state
+        .db_client
+        .get_outbox_emails(query.status, query.limit.unwrap_or(DEFAULT_OUTBOX_LIMIT))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(OutboxResponseDTO {
+        status: "success".to_string(),
+        emails,
+    }))
+}
+
+pub async fn retry_outbox_email(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Path(email_id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let email = app_state
+        .db_client
+        .retry_outbox_email(email_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| {
+            HttpError::new(StatusCode::NOT_FOUND, "No dead-lettered email with that id")
+        })?;
+
+    tracing::warn!(
+        target: "audit",
+        event = "outbox_email_retried",
+        admin_id = %admin.id,
+        email_id = %email.id
+    );
+
+    Ok(Json(OutboxEmailResponseDTO {
+        status: "success".to_string(),
+        email,
+    }))
+}
+
+pub async fn create_organization(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<CreateOrganizationDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let organization = app_state
+        .db_client
+        .create_organization(&body.name)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(OrganizationResponseDTO {
+            status: "success".to_string(),
+            organization,
+        }),
+    ))
+}
+
+pub async fn get_organization(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let organization = app_state
+        .db_client
+        .get_organization(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?;
+
+    Ok(Json(OrganizationResponseDTO {
+        status: "success".to_string(),
+        organization,
+    }))
+}
+
+pub async fn update_organization_branding(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<OrganizationBrandingDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let organization = app_state
+        .db_client
+        .update_organization_branding(id, &body.into())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?;
+
+    Ok(Json(OrganizationResponseDTO {
+        status: "success".to_string(),
+        organization,
+    }))
+}
+
+pub async fn update_organization_session_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<SessionPolicyDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let organization = app_state
+        .db_client
+        .update_organization_session_policy(id, body.idle_timeout, body.max_lifetime)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?;
+
+    Ok(Json(OrganizationResponseDTO {
+        status: "success".to_string(),
+        organization,
+    }))
+}
+
+pub async fn update_organization_geo_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<GeoPolicyDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let upper = |countries: Vec<String>| {
+        countries
+            .into_iter()
+            .map(|country| country.to_uppercase())
+            .collect::<Vec<_>>()
+    };
+
+    let organization = app_state
+        .db_client
+        .update_organization_geo_policy(
+            id,
+            &upper(body.blocked_countries),
+            &upper(body.mfa_countries),
+            &body.blocked_asns,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?;
+
+    Ok(Json(OrganizationResponseDTO {
+        status: "success".to_string(),
+        organization,
+    }))
+}
+
+fn ensure_plan_known(app_state: &AppState, plan: Option<&str>) -> Result<(), HttpError> {
+    match plan {
+        Some(plan) if !app_state.env.plans.contains_key(plan) => Err(HttpError::bad_request(
+            ErrorMessage::UnknownPlan.to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn plan_changed(app_state: &AppState, target: String, plan: Option<&str>, changed_by: &str) {
+    let plan = plan.unwrap_or(&app_state.env.default_plan);
+    tracing::warn!(
+        target: "audit",
+        event = "plan_changed",
+        subject = %target,
+        plan = plan,
+        changed_by = %changed_by,
+    );
+    app_state.notifier.spawn(Notification::to_admins(
+        NotificationKind::PlanChanged,
+        "Plan changed",
+        format!("{} moved to the {} plan", target, plan),
+    ));
+}
+
+pub async fn delete_user(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<impl IntoResponse, HttpError> {
+    let user = app_state
+        .db_client
+        .soft_delete_user(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "User not found"))?;
+
+    tracing::warn!(target: "audit", event = "user_deleted", user_id = %user.id, actor = %admin.id);
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn restore_user(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<impl IntoResponse, HttpError> {
+    let user = app_state
+        .db_client
+        .restore_user(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Deleted user not found"))?;
+
+    tracing::warn!(target: "audit", event = "user_restored", user_id = %user.id, actor = %admin.id);
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn update_user_plan(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Path(PublicId(id)): Path<PublicId>,
+    Json(body): Json<PlanUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    ensure_plan_known(&app_state, body.plan.as_deref())?;
+
+    let user = app_state
+        .db_client
+        .set_user_plan(id, body.plan.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "User not found"))?;
+
+    plan_changed(
+        &app_state,
+        format!("user {}", user.id),
+        body.plan.as_deref(),
+        &admin.id.to_string(),
+    );
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn get_user_changes(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<UserChangesQueryDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    query
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let since = query.since.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_CHANGES_LIMIT);
+
+    let mut changes = app_state
+        .db_client
+        .get_user_changes(since, limit + 1)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let has_more = changes.len() as i64 > limit;
+    changes.truncate(limit as usize);
+
+    let mut user_ids: Vec<Uuid> = changes.iter().map(|change| change.user_id).collect();
+    user_ids.sort();
+    user_ids.dedup();
+
+    let users: HashMap<Uuid, FilterUserDTO> = app_state
+        .db_client
+        .get_users_by_ids(&user_ids)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .iter()
+        .map(|user| (user.id, FilterUserDTO::filter_user(user)))
+        .collect();
+
+    let next_cursor = changes.last().map_or(since, |change| change.id);
+    let changes = changes
+        .into_iter()
+        .map(|change| UserChangeDTO {
+            cursor: change.id,
+            user_id: change.user_id,
+            user: users.get(&change.user_id).cloned(),
+            operation: change.operation,
+            changed_at: change.changed_at,
+        })
+        .collect();
+
+    Ok(Json(UserChangesResponseDTO {
+        status: "success".to_string(),
+        changes,
+        next_cursor,
+        has_more,
+    }))
+}
+
+pub async fn bulk_assign_role(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Json(body): Json<BulkRoleAssignDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let mut user_ids = body.user_ids.clone();
+    user_ids.sort();
+    user_ids.dedup();
+
+    ensure_role_exists(&app_state, &body.role).await?;
+
+    if app_state.env.role_grant_approval
+        && app_state
+            .permission_cache
+            .lineage(&body.role)
+            .iter()
+            .any(UserRole::is_admin)
+    {
+        return request_role_grants(&app_state, &client, admin.id, &user_ids, &body.role).await;
+    }
+
+    let previous_roles = app_state
+        .db_client
+        .bulk_assign_role(&user_ids, &body.role)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut results = Vec::with_capacity(previous_roles.len());
+    for (user_id, previous) in previous_roles {
+        let result = match &previous {
+            None => "not_found",
+            Some(previous) if *previous == body.role => "unchanged",
+            Some(previous) => {
+                audit::record(
+                    &app_state,
+                    AuditEntry::new(AuditEvent::RoleChanged)
+                        .actor(admin.id)
+                        .target(user_id)
+                        .client(&client)
+                        .detail(serde_json::json!({
+                            "from": previous.to_str(),
+                            "to": body.role.to_str(),
+                        })),
+                );
+                "updated"
+            }
+        };
+
+        results.push(RoleAssignmentResultDTO {
+            user_id,
+            result: result.to_string(),
+            previous_role: previous.map(|role| role.to_str().to_string()),
+        });
+    }
+
+    let updated = results
+        .iter()
+        .filter(|result| result.result == "updated")
+        .count();
+
+    Ok(Json(BulkRoleAssignResponseDTO {
+        status: "success".to_string(),
+        role: body.role.to_str().to_string(),
+        updated,
+        results,
+    }))
+}
+
+async fn request_role_grants(
+    app_state: &AppState,
+    client: &ClientContext,
+    requested_by: Uuid,
+    user_ids: &[Uuid],
+    role: &UserRole,
+) -> Result<Json<BulkRoleAssignResponseDTO>, HttpError> {
+    let users: HashMap<Uuid, UserRole> = app_state
+        .db_client
+        .get_users_by_ids(user_ids)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .into_iter()
+        .map(|user| (user.id, user.role))
+        .collect();
+
+    let mut results = Vec::with_capacity(user_ids.len());
+    let mut requested = 0;
+    for user_id in user_ids {
+        let previous = users.get(user_id);
+        let result = match previous {
+            None => "not_found",
+            Some(previous) if previous == role => "unchanged",
+            Some(_) => {
+                let grant = app_state
+                    .db_client
+                    .create_role_grant(*user_id, role, requested_by)
+                    .await
+                    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+                if let Some(grant) = grant {
+                    audit::record(
+                        app_state,
+                        AuditEntry::new(AuditEvent::RoleGrantRequested)
+                            .actor(requested_by)
+                            .target(*user_id)
+                            .client(client)
+                            .detail(serde_json::json!({
+                                "grantId": grant.id,
+                                "role": role.to_str(),
+                            })),
+                    );
+                    requested += 1;
+                }
+                "pending_approval"
+            }
+        };
+
+        results.push(RoleAssignmentResultDTO {
+            user_id: *user_id,
+            result: result.to_string(),
+            previous_role: previous.map(|role| role.to_str().to_string()),
+        });
+    }
+
+    if requested > 0 {
+        app_state.notifier.spawn(Notification::to_admins(
+            NotificationKind::RoleGrantRequested,
+            "Role grant awaiting approval",
+            format!(
+                "{} {} role grant(s) are waiting for a second admin to approve",
+                requested,
+                role.to_str()
+            ),
+        ));
+    }
+
+    Ok(Json(BulkRoleAssignResponseDTO {
+        status: "success".to_string(),
+        role: role.to_str().to_string(),
+        updated: 0,
+        results,
+    }))
+}
+
+pub async fn get_role_grants(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let grants = app_state
+        .db_client
+        .get_pending_role_grants()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(RoleGrantsResponseDTO {
+        status: "success".to_string(),
+        grants,
+    }))
+}
+
+pub async fn approve_role_grant(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let pending = pending_role_grant(&app_state, id).await?;
+    if pending.requested_by == admin.id {
+        return Err(HttpError::forbidden(
+            ErrorMessage::SecondApproverRequired.to_string(),
+        ));
+    }
+
+    let (grant, previous) = app_state
+        .db_client
+        .approve_role_grant(id, admin.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(role_grant_not_found)?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RoleGrantApproved)
+            .actor(admin.id)
+            .target(grant.user_id)
+            .client(&client)
+            .detail(serde_json::json!({
+                "grantId": grant.id,
+                "role": grant.role.to_str(),
+                "requestedBy": grant.requested_by,
+            })),
+    );
+    if let Some(previous) = previous
+        && previous != grant.role
+    {
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::RoleChanged)
+                .actor(admin.id)
+                .target(grant.user_id)
+                .client(&client)
+                .detail(serde_json::json!({
+                    "from": previous.to_str(),
+                    "to": grant.role.to_str(),
+                    "grantId": grant.id,
+                })),
+        );
+    }
+
+    Ok(Json(RoleGrantResponseDTO {
+        status: "success".to_string(),
+        grant,
+    }))
+}
+
+pub async fn reject_role_grant(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let grant = app_state
+        .db_client
+        .reject_role_grant(id, admin.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(role_grant_not_found)?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RoleGrantRejected)
+            .actor(admin.id)
+            .target(grant.user_id)
+            .client(&client)
+            .detail(serde_json::json!({
+                "grantId": grant.id,
+                "role": grant.role.to_str(),
+                "requestedBy": grant.requested_by,
+            })),
+    );
+
+    Ok(Json(RoleGrantResponseDTO {
+        status: "success".to_string(),
+        grant,
+    }))
+}
+
+async fn pending_role_grant(app_state: &AppState, id: Uuid) -> Result<RoleGrant, HttpError> {
+    app_state
+        .db_client
+        .get_role_grant(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|grant| grant.status == RoleGrantStatus::Pending)
+        .ok_or_else(role_grant_not_found)
+}
+
+fn role_grant_not_found() -> HttpError {
+    HttpError::new(StatusCode::NOT_FOUND, "No pending role grant with that id")
+}
+
+pub async fn get_permissions() -> impl IntoResponse {
+    Json(PermissionListResponseDTO {
+        status: "success".to_string(),
+        permissions: REGISTRY
+            .iter()
+            .map(|info| PermissionDTO {
+                name: info.permission.to_str().to_string(),
+                description: info.description.to_string(),
+                group: info.group.to_string(),
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_routes(Extension(app_state): Extension<Arc<AppState>>) -> impl IntoResponse {
+    let routes: Vec<RouteDTO> = mounted_groups(&app_state)
+        .iter()
+        .flat_map(|group| {
+            group
+                .routes
+                .iter()
+                .map(|route| route_dto(&app_state, group, route))
+        })
+        .collect();
+
+    Json(RouteListResponseDTO {
+        status: "success".to_string(),
+        results: routes.len(),
+        routes,
+    })
+}
+
+fn route_dto(app_state: &AppState, group: &RouteGroupSpec, route: &RouteSpec) -> RouteDTO {
+    let config = &app_state.env;
+    let path = match route.path.trim_end_matches('/') {
+        "" => group.prefix.to_string(),
+        path => format!("{}{}", group.prefix, path),
+    };
+    let under_api = group.prefix.starts_with("/api");
+
+    let timeout_ms = group
+        .name
+        .and_then(|name| config.route_timeouts_ms.get(name).copied())
+        .filter(|timeout_ms| *timeout_ms > 0)
+        .or((under_api && config.handler_timeout_ms > 0).then_some(config.handler_timeout_ms));
+    let concurrency_limit = group
+        .name
+        .and_then(|name| config.route_concurrency_limits.get(name).copied())
+        .filter(|limit| *limit > 0)
+        .or((under_api && config.max_concurrent_requests > 0)
+            .then_some(config.max_concurrent_requests));
+
+    let rate_limit = route.rate_limit.map(|scope| {
+        let (per_minute_ip, per_minute_email) = match scope {
+            "login" => (
+                config.login_rate_limit_ip,
+                Some(config.login_rate_limit_email),
+            ),
+            "register" => (
+                config.register_rate_limit_ip,
+                Some(config.register_rate_limit_email),
+            ),
+            "forgot_password" => (
+                config.forgot_password_rate_limit_ip,
+                Some(config.forgot_password_rate_limit_email),
+            ),
+            "nonce" => (config.nonce_rate_limit, None),
+            "two_factor" => (config.two_factor_rate_limit, None),
+            "reauthenticate" => (config.reauth_rate_limit, None),
+            "reset_password" => (config.reset_password_rate_limit, None),
+            "availability" => (config.availability_rate_limit, None),
+            _ => (config.activity_export_rate_limit, None),
+        };
+        RouteRateLimitDTO {
+            scope: scope.to_string(),
+            per_minute_ip,
+            per_minute_email,
+        }
+    });
+
+    RouteDTO {
+        method: route.method.to_string(),
+        path,
+        access: group.access.to_str().to_string(),
+        roles: group.roles.iter().map(|role| role.to_string()).collect(),
+        permissions: route
+            .permission
+            .iter()
+            .map(|permission| permission.to_str().to_string())
+            .collect(),
+        audiences: group
+            .name
+            .and_then(|name| config.route_audiences.get(name).cloned())
+            .unwrap_or_default(),
+        sudo: route.sudo,
+        api_keys: route.api_keys,
+        delegated: route.delegated,
+        quota: matches!(group.name, Some("users" | "admin")),
+        rate_limit,
+        timeout_ms,
+        concurrency_limit,
+    }
+}
+
+pub async fn get_roles(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let roles = app_state
+        .db_client
+        .get_roles()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let granted = granted_permissions(&app_state).await?;
+
+    Ok(Json(RoleListResponseDTO {
+        status: "success".to_string(),
+        roles: roles
+            .into_iter()
+            .map(|role| role_dto(&app_state, role, &granted))
+            .collect(),
+    }))
+}
+
+pub async fn create_role(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Json(body): Json<CreateRoleDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    if let Some(parent) = &body.parent {
+        ensure_role_exists(&app_state, parent).await?;
+    }
+
+    let role = app_state
+        .db_client
+        .create_role(
+            body.name.to_str(),
+            body.description.as_deref(),
+            body.parent.as_ref().map(UserRole::to_str),
+            &body.permissions,
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                HttpError::unique_constraint_violation(ErrorMessage::RoleAlreadyExists.to_string())
+            }
+            e => HttpError::server_error(e.to_string()),
+        })?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RoleDefined)
+            .actor(admin.id)
+            .client(&client)
+            .detail(serde_json::json!({
+                "role": role.name.to_str(),
+                "parent": role.parent.as_ref().map(UserRole::to_str),
+                "permissions": body.permissions,
+            })),
+    );
+
+    let role = reload_role(&app_state, role).await?;
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+pub async fn update_role(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Path(name): Path<String>,
+    Json(body): Json<UpdateRoleDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let Some(parent) = &body.parent {
+        ensure_role_exists(&app_state, parent).await?;
+
+        let ancestors = app_state
+            .db_client
+            .get_role_ancestors(parent.to_str())
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if parent.to_str() == name || ancestors.iter().any(|role| role.to_str() == name) {
+            return Err(HttpError::bad_request(
+                ErrorMessage::RoleHierarchyCycle.to_string(),
+            ));
+        }
+    }
+
+    let role = app_state
+        .db_client
+        .update_role(
+            &name,
+            body.description.as_deref(),
+            body.parent.as_ref().map(UserRole::to_str),
+            body.permissions.as_deref(),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| {
+            HttpError::new(
+                StatusCode::NOT_FOUND,
+                ErrorMessage::RoleNotFound.to_string(),
+            )
+        })?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RoleDefined)
+            .actor(admin.id)
+            .client(&client)
+            .detail(serde_json::json!({
+                "role": role.name.to_str(),
+                "parent": role.parent.as_ref().map(UserRole::to_str),
+                "permissions": body.permissions,
+            })),
+    );
+
+    let role = reload_role(&app_state, role).await?;
+    Ok(Json(role))
+}
+
+pub async fn delete_role(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    let role = app_state
+        .db_client
+        .get_role(&name)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| {
+            HttpError::new(
+                StatusCode::NOT_FOUND,
+                ErrorMessage::RoleNotFound.to_string(),
+            )
+        })?;
+    if role.built_in {
+        return Err(HttpError::bad_request(
+            ErrorMessage::RoleBuiltIn.to_string(),
+        ));
+    }
+
+    let in_use = app_state
+        .db_client
+        .is_role_in_use(&name)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if in_use {
+        return Err(HttpError::new(
+            StatusCode::CONFLICT,
+            ErrorMessage::RoleInUse.to_string(),
+        ));
+    }
+
+    app_state
+        .db_client
+        .delete_role(&name)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                HttpError::new(StatusCode::CONFLICT, ErrorMessage::RoleInUse.to_string())
+            }
+            e => HttpError::server_error(e.to_string()),
+        })?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RoleDeleted)
+            .actor(admin.id)
+            .client(&client)
+            .detail(serde_json::json!({ "role": name })),
+    );
+    reload_permissions(&app_state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn ensure_role_exists(
+    app_state: &AppState,
+    role: &UserRole,
+) -> Result<(), HttpError> {
+    let exists = app_state
+        .db_client
+        .get_role(role.to_str())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .is_some();
+
+    if !exists {
+        return Err(HttpError::bad_request(
+            ErrorMessage::RoleNotFound.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn reload_permissions(app_state: &AppState) -> Result<(), HttpError> {
+    app_state
+        .permission_cache
+        .load(&app_state.db_client)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))
+}
+
+async fn reload_role(app_state: &AppState, role: Role) -> Result<RoleResponseDTO, HttpError> {
+    reload_permissions(app_state).await?;
+    let granted = granted_permissions(app_state).await?;
+
+    Ok(RoleResponseDTO {
+        status: "success".to_string(),
+        role: role_dto(app_state, role, &granted),
+    })
+}
+
+async fn granted_permissions(
+    app_state: &AppState,
+) -> Result<HashMap<UserRole, Vec<String>>, HttpError> {
+    let mut granted: HashMap<UserRole, Vec<String>> = HashMap::new();
+    for row in app_state
+        .db_client
+        .get_role_permissions()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        granted.entry(row.role).or_default().push(row.permission);
+    }
+
+    Ok(granted)
+}
+
+fn role_dto(app_state: &AppState, role: Role, granted: &HashMap<UserRole, Vec<String>>) -> RoleDTO {
+    let mut effective_permissions: Vec<String> = app_state
+        .permission_cache
+        .permissions_for(&role.name)
+        .iter()
+        .cloned()
+        .collect();
+    effective_permissions.sort();
+
+    RoleDTO {
+        permissions: granted.get(&role.name).cloned().unwrap_or_default(),
+        effective_permissions,
+        name: role.name.to_str().to_string(),
+        description: role.description,
+        parent: role.parent.map(|parent| parent.to_str().to_string()),
+        built_in: role.built_in,
+        created_at: role.created_at,
+        updated_at: role.updated_at,
+    }
+}
+
+pub async fn update_organization_plan(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(admin): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(body): Json<PlanUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    ensure_plan_known(&app_state, body.plan.as_deref())?;
+
+    let organization = app_state
+        .db_client
+        .set_organization_plan(id, body.plan.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?;
+
+    plan_changed(
+        &app_state,
+        format!("organization {}", organization.id),
+        body.plan.as_deref(),
+        &admin.id.to_string(),
+    );
+
+    Ok(Json(OrganizationResponseDTO {
+        status: "success".to_string(),
+        organization,
+    }))
+}
+
+pub async fn create_invitation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<CreateInvitationDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let organization = match body.organization_id {
+        Some(org_id) => Some(
+            app_state
+                .db_client
+                .get_organization(org_id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?
+                .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Organization not found"))?,
+        ),
+        None => None,
+    };
+
+    let token = Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::hours(app_state.env.invitation_maxage);
+
+    let invitation = app_state
+        .db_client
+        .create_invitation(
+            &body.email,
+            &token,
+            body.organization_id,
+            Some(user.id),
+            expires_at,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let invite_link = format!("{}/register?invite_token={}", app_state.env.app_url, token);
+    let message = match &organization {
+        Some(organization) => format!(
+            "You have been invited to join {}. Create your account here: {}",
+            organization.name, invite_link
+        ),
+        None => format!(
+            "You have been invited to create an account: {}",
+            invite_link
+        ),
+    };
+
+    app_state.notifier.spawn(
+        Notification::to_email(
+            NotificationKind::Invitation,
+            &body.email,
+            "You're invited",
+            message,
+        )
+        .with_branding(organization.map(|organization| organization.email_branding())),
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InvitationResponseDTO {
+            status: "success".to_string(),
+            invitation,
+        }),
+    ))
+}
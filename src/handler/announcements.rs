@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::Path,
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{get, put},
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    db::AnnouncementExt,
+    dtos::{AnnouncementDTO, AnnouncementListResponseDTO, AnnouncementResponseDTO, Response},
+    error::HttpError,
+    middleware::{AuthenticatedUser, OptionalAuth, optional_auth},
+};
+
+pub fn announcements_handler() -> Router {
+    Router::new()
+        .route("/", get(get_active_announcements))
+        .layer(middleware::from_fn(optional_auth))
+}
+
+pub fn announcements_admin_handler() -> Router {
+    Router::new()
+        .route("/", get(get_announcements).post(create_announcement))
+        .route(
+            "/{id}",
+            put(update_announcement).delete(delete_announcement),
+        )
+}
+
+fn validate_window(body: &AnnouncementDTO) -> Result<(), HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let (Some(starts_at), Some(ends_at)) = (body.starts_at, body.ends_at)
+        && ends_at <= starts_at
+    {
+        return Err(HttpError::bad_request(
+            "Announcement must end after it starts",
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_active_announcements(
+    Extension(app_state): Extension<Arc<AppState>>,
+    OptionalAuth(user): OptionalAuth,
+) -> Result<impl IntoResponse, HttpError> {
+    let mut announcements = app_state
+        .db_client
+        .get_active_announcements()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if user.is_none() {
+        for announcement in &mut announcements {
+            announcement.created_by = None;
+        }
+    }
+
+    Ok(Json(AnnouncementListResponseDTO {
+        status: "success".to_string(),
+        announcements,
+    }))
+}
+
+pub async fn get_announcements(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let announcements = app_state
+        .db_client
+        .get_announcements()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(AnnouncementListResponseDTO {
+        status: "success".to_string(),
+        announcements,
+    }))
+}
+
+pub async fn create_announcement(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<AnnouncementDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    validate_window(&body)?;
+
+    let announcement = app_state
+        .db_client
+        .create_announcement(
+            &body.message,
+            body.severity,
+            body.starts_at,
+            body.ends_at,
+            user.id,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AnnouncementResponseDTO {
+            status: "success".to_string(),
+            announcement,
+        }),
+    ))
+}
+
+pub async fn update_announcement(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<AnnouncementDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    validate_window(&body)?;
+
+    let announcement = app_state
+        .db_client
+        .update_announcement(
+            id,
+            &body.message,
+            body.severity,
+            body.starts_at,
+            body.ends_at,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Announcement not found"))?;
+
+    Ok(Json(AnnouncementResponseDTO {
+        status: "success".to_string(),
+        announcement,
+    }))
+}
+
+pub async fn delete_announcement(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let deleted = app_state
+        .db_client
+        .delete_announcement(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if !deleted {
+        return Err(HttpError::new(
+            StatusCode::NOT_FOUND,
+            "Announcement not found",
+        ));
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Announcement deleted".to_string(),
+    }))
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    body::Bytes,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    db::{ApiKeyExt, UserExt},
+    dtos::{
+        ApiKeyCreatedResponseDTO, ApiKeyListResponseDTO, ApiKeyResponseDTO, CreateApiKeyDTO,
+        LeakedTokenDTO, LeakedTokenResultDTO,
+    },
+    error::{ErrorMessage, HttpError},
+    handler::admin::ensure_role_exists,
+    middleware::{AuthenticatedUser, ClientContext},
+    notify::{Notification, NotificationKind},
+    utils::{
+        api_key, request_signature,
+        secret_scanning::{self, KEY_IDENTIFIER_HEADER, SIGNATURE_HEADER},
+    },
+};
+
+pub fn api_keys_handler() -> Router {
+    Router::new()
+        .route("/", get(get_api_keys).post(create_api_key))
+        .route("/{id}", delete(revoke_api_key))
+}
+
+pub fn api_keys_leak_handler() -> Router {
+    Router::new().route("/report-leak", post(report_leak))
+}
+
+pub async fn report_leak(
+    Extension(app_state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, HttpError> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let (Some(key_identifier), Some(signature)) =
+        (header(KEY_IDENTIFIER_HEADER), header(SIGNATURE_HEADER))
+    else {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidSignature.to_string(),
+        ));
+    };
+
+    let verified = secret_scanning::verify_signature(
+        &app_state.secret_scanning_keys,
+        key_identifier,
+        signature,
+        &body,
+    )
+    .await;
+    if !verified {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidSignature.to_string(),
+        ));
+    }
+
+    let reports: Vec<LeakedTokenDTO> =
+        serde_json::from_slice(&body).map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let mut results = Vec::with_capacity(reports.len());
+    for report in reports {
+        let token_hash = api_key::hash(&report.token);
+        let revoked = if api_key::is_well_formed(&report.token) {
+            revoke_leaked_key(&app_state, &token_hash, &report).await?
+        } else {
+            false
+        };
+
+        results.push(LeakedTokenResultDTO {
+            token_hash,
+            token_type: report.token_type,
+            label: if revoked {
+                "true_positive".to_string()
+            } else {
+                "false_positive".to_string()
+            },
+        });
+    }
+
+    Ok(Json(results))
+}
+
+pub async fn get_api_keys(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let api_keys = app_state
+        .db_client
+        .get_api_keys(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(ApiKeyListResponseDTO {
+        status: "success".to_string(),
+        api_keys,
+    }))
+}
+
+pub async fn create_api_key(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Json(body): Json<CreateApiKeyDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let Some(role) = &body.role {
+        ensure_role_exists(&app_state, role).await?;
+
+        let owner_permissions = app_state.permission_cache.permissions_for(&user.role);
+        if !app_state
+            .permission_cache
+            .permissions_for(role)
+            .is_subset(&owner_permissions)
+        {
+            return Err(HttpError::forbidden(
+                ErrorMessage::ApiKeyRoleNotAllowed.to_string(),
+            ));
+        }
+    }
+
+    let key = api_key::generate(app_state.env.environment);
+    let signing_secret = body
+        .require_signature
+        .then(request_signature::generate_secret);
+    let api_key = app_state
+        .db_client
+        .create_api_key(
+            user.id,
+            &body.name,
+            &api_key::display_prefix(&key),
+            &api_key::hash(&key),
+            body.role.as_ref(),
+            signing_secret.as_deref(),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::ApiKeyCreated)
+            .actor(user.id)
+            .target(user.id)
+            .client(&client)
+            .detail(serde_json::json!({ "apiKeyId": api_key.id })),
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiKeyCreatedResponseDTO {
+            status: "success".to_string(),
+            key,
+            signing_secret,
+            api_key,
+        }),
+    ))
+}
+
+pub async fn revoke_api_key(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let api_key = app_state
+        .db_client
+        .revoke_user_api_key(user.id, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "API key not found"))?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::ApiKeyRevoked)
+            .actor(user.id)
+            .target(user.id)
+            .client(&client)
+            .detail(serde_json::json!({ "apiKeyId": api_key.id })),
+    );
+
+    Ok(Json(ApiKeyResponseDTO {
+        status: "success".to_string(),
+        api_key,
+    }))
+}
+
+async fn revoke_leaked_key(
+    app_state: &AppState,
+    token_hash: &str,
+    report: &LeakedTokenDTO,
+) -> Result<bool, HttpError> {
+    let Some(key) = app_state
+        .db_client
+        .get_api_key_by_hash(token_hash)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    else {
+        return Ok(false);
+    };
+
+    if key.revoked_at.is_some() {
+        return Ok(true);
+    }
+
+    app_state
+        .db_client
+        .revoke_api_key(key.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    audit::record(
+        app_state,
+        AuditEntry::new(AuditEvent::ApiKeyLeaked)
+            .target(key.user_id)
+            .detail(serde_json::json!({
+                "apiKeyId": key.id,
+                "url": report.url,
+                "source": report.source,
+            })),
+    );
+
+    let owner = app_state
+        .db_client
+        .get_user(Some(key.user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(owner) = owner {
+        let location = report
+            .url
+            .as_deref()
+            .map(|url| format!(" It was found at {}.", url))
+            .unwrap_or_default();
+        app_state.notifier.spawn(Notification::to_user(
+            NotificationKind::ApiKeyLeaked,
+            owner.id,
+            &owner.email,
+            "Your API key was exposed and has been revoked",
+            format!(
+                "Your API key \"{}\" ({}...) was found in a public location and has been revoked.{} Create a new key and update any clients that used it.",
+                key.name, key.prefix, location
+            ),
+        ));
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,1959 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::Query,
+    http::{HeaderMap, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Response as AxumResponse},
+    routing::{get, post},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, PrivateCookieJar, SameSite};
+use chrono::{DateTime, Duration, Utc};
+use validator::Validate;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    config::{Config, Environment, RouteGroup, SessionBackend},
+    db::{
+        InvitationExt, LoginAttemptExt, OrganizationExt, RecoveryEmailExt, ResetCodeExt,
+        RevokedTokenExt, SecurityQuestionExt, SessionExt, TwoFactorExt, UserExt, UserMetadataExt,
+    },
+    dtos::{
+        AvailabilityQueryDTO, AvailabilityResponseDTO, CompleteRegistrationDTO,
+        CookieSessionResponseDTO, CsrfTokenResponseDTO, ForgotPasswordRequestDTO, LoginUserDTO,
+        MagicLinkRequestDTO, MfaChallengeResponseDTO, NonceResponseDTO, ReauthenticateDTO,
+        RefreshTokenDTO, RegisterUserDTO, ResetPasswordRequestDTO, ResetTokenResponseDTO, Response,
+        SecurityQuestionsResponseDTO, StartRegistrationDTO, SudoTokenResponseDTO,
+        UserLoginResponseDTO, VerifyEmailQueryDto, VerifyResetCodeDTO, VerifyTwoFactorDTO,
+        validate_registration_metadata,
+    },
+    error::{ErrorMessage, HttpError},
+    handler::{oauth::oauth_handler, users::ensure_name_allowed, webauthn::webauthn_login_handler},
+    mail::templates::EmailTemplate,
+    middleware::{
+        AuthenticatedUser, ClientContext, TOKEN_COOKIE, auth,
+        captcha::captcha,
+        cookie_session::{self, CookieSession, SESSION_COOKIE},
+        csrf, deny_api_key, deny_delegated,
+        geo::{GeoLocation, GeoPolicy, geo_login},
+        idempotency::idempotency,
+        login_throttle::login_throttle,
+        rate_limit::{RateLimits, rate_limit_by_ip, rate_limit_by_ip_and_email},
+        risk::{RiskAction, RiskDecision, risk_check},
+        tarpit::tarpit,
+    },
+    models::{AccountStatus, RegistrationState, User},
+    nonce::{self, NoncePurpose},
+    notify::{Notification, NotificationKind},
+    utils::{
+        backup_code, reset_code, security_question,
+        token::{self, TokenClaims},
+        totp,
+    },
+};
+
+pub const DEVICE_ID_HEADER: &str = "x-device-id";
+pub const CLIENT_TYPE_HEADER: &str = "x-client-type";
+
+pub enum LoginSession {
+    Token(CookieJar, UserLoginResponseDTO),
+    Cookie(PrivateCookieJar, CookieSessionResponseDTO),
+}
+
+impl IntoResponse for LoginSession {
+    fn into_response(self) -> AxumResponse {
+        match self {
+            LoginSession::Token(jar, response) => (jar, Json(response)).into_response(),
+            LoginSession::Cookie(jar, response) => (jar, Json(response)).into_response(),
+        }
+    }
+}
+
+pub fn auth_handler(config: &Config) -> Router {
+    let mut router = Router::new()
+        .route(
+            "/login",
+            post(login)
+                .layer(middleware::from_fn(tarpit))
+                .layer(middleware::from_fn(geo_login))
+                .layer(middleware::from_fn(login_throttle))
+                .layer(middleware::from_fn(captcha))
+                .layer(middleware::from_fn(|state, req, next| {
+                    risk_check(state, req, next, RiskAction::Login)
+                }))
+                .layer(middleware::from_fn(|state, req, next| {
+                    rate_limit_by_ip_and_email(state, req, next, |limits: &RateLimits| {
+                        &limits.login
+                    })
+                })),
+        )
+        .route(
+            "/magic-link",
+            post(request_magic_link).layer(middleware::from_fn(idempotency)),
+        )
+        .route(
+            "/magic-link/verify",
+            get(verify_magic_link)
+                .layer(middleware::from_fn(geo_login))
+                .layer(middleware::from_fn(login_throttle)),
+        )
+        .route(
+            "/2fa/verify",
+            post(verify_two_factor)
+                .layer(middleware::from_fn(geo_login))
+                .layer(middleware::from_fn(login_throttle))
+                .layer(middleware::from_fn(|state, req, next| {
+                    rate_limit_by_ip(state, req, next, |limits: &RateLimits| &limits.two_factor)
+                })),
+        )
+        .route(
+            "/reauthenticate",
+            post(reauthenticate)
+                .layer(middleware::from_fn(deny_delegated))
+                .layer(middleware::from_fn(deny_api_key))
+                .layer(middleware::from_fn(auth))
+                .layer(middleware::from_fn(|state, req, next| {
+                    rate_limit_by_ip(state, req, next, |limits: &RateLimits| {
+                        &limits.reauthenticate
+                    })
+                })),
+        )
+        .route("/refresh", post(refresh))
+        .nest("/webauthn", webauthn_login_handler())
+        .route(
+            "/logout",
+            post(logout)
+                .layer(middleware::from_fn(deny_api_key))
+                .layer(middleware::from_fn(auth)),
+        )
+        .route("/verify", get(verify_email))
+        .route("/verify-recovery-email", get(verify_recovery_email))
+        .route(
+            "/forgot-password",
+            post(forgot_password)
+                .layer(middleware::from_fn(idempotency))
+                .layer(middleware::from_fn(|state, req, next| {
+                    rate_limit_by_ip_and_email(state, req, next, |limits: &RateLimits| {
+                        &limits.forgot_password
+                    })
+                })),
+        )
+        .route(
+            "/reset-password",
+            post(reset_password).layer(middleware::from_fn(|state, req, next| {
+                rate_limit_by_ip(state, req, next, |limits: &RateLimits| {
+                    &limits.reset_password
+                })
+            })),
+        )
+        .route(
+            "/reset-code/request",
+            post(request_reset_code).layer(middleware::from_fn(idempotency)),
+        )
+        .route("/reset-code/verify", post(verify_reset_code))
+        .route(
+            "/reset-code/set-password",
+            post(reset_password).layer(middleware::from_fn(|state, req, next| {
+                rate_limit_by_ip(state, req, next, |limits: &RateLimits| {
+                    &limits.reset_password
+                })
+            })),
+        )
+        .route("/security-questions", get(get_recovery_questions))
+        .route("/csrf", get(issue_csrf_token))
+        .route(
+            "/nonce",
+            post(issue_nonce).layer(middleware::from_fn(|state, req, next| {
+                rate_limit_by_ip(state, req, next, |limits: &RateLimits| &limits.nonce)
+            })),
+        )
+        .route(
+            "/availability",
+            get(check_availability).layer(middleware::from_fn(|state, req, next| {
+                rate_limit_by_ip(state, req, next, |limits: &RateLimits| &limits.availability)
+            })),
+        );
+
+    if !config
+        .disabled_route_groups
+        .contains(&RouteGroup::Registration)
+    {
+        router = router
+            .route(
+                "/register",
+                post(register)
+                    .layer(middleware::from_fn(idempotency))
+                    .layer(middleware::from_fn(|state, req, next| {
+                        risk_check(state, req, next, RiskAction::Register)
+                    }))
+                    .layer(middleware::from_fn(|state, req, next| {
+                        rate_limit_by_ip_and_email(state, req, next, |limits: &RateLimits| {
+                            &limits.register
+                        })
+                    })),
+            )
+            .route(
+                "/register/start",
+                post(start_registration).layer(middleware::from_fn(idempotency)),
+            )
+            .route("/complete-registration", post(complete_registration));
+    }
+
+    if !config
+        .disabled_route_groups
+        .contains(&RouteGroup::SocialLogin)
+    {
+        router = router.nest("/oauth", oauth_handler());
+    }
+
+    router
+}
+
+pub async fn issue_csrf_token(Extension(app_state): Extension<Arc<AppState>>) -> impl IntoResponse {
+    let (jar, csrf_token) = csrf::issue(&app_state.env);
+
+    (
+        jar,
+        Json(CsrfTokenResponseDTO {
+            status: "success".to_string(),
+            csrf_token,
+        }),
+    )
+}
+
+pub async fn issue_nonce(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let issued = nonce::issue(
+        &app_state.db_client,
+        NoncePurpose::Client,
+        None,
+        None,
+        app_state.env.nonce_ttl,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(NonceResponseDTO {
+        status: "success".to_string(),
+        nonce: issued.nonce,
+        expires_at: issued.expires_at,
+    }))
+}
+
+pub async fn register(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<RegisterUserDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    ensure_name_allowed(&app_state, &body.name)?;
+
+    validate_registration_metadata(&body.metadata, &app_state.env.registration_fields)
+        .map_err(HttpError::bad_request)?;
+
+    let invitation = match &body.invite_token {
+        Some(invite_token) => {
+            let invitation = app_state
+                .db_client
+                .get_invitation_by_token(invite_token)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?
+                .filter(|invitation| invitation.is_usable_by(&body.email))
+                .ok_or_else(|| {
+                    HttpError::bad_request(ErrorMessage::InvalidInvitation.to_string())
+                })?;
+            Some(invitation)
+        }
+        None if app_state.env.invite_only => {
+            return Err(HttpError::forbidden(
+                ErrorMessage::InvitationRequired.to_string(),
+            ));
+        }
+        None => None,
+    };
+
+    let account_status = if invitation.is_none() && app_state.env.waitlist {
+        AccountStatus::PendingApproval
+    } else {
+        AccountStatus::Active
+    };
+
+    let verification_token = uuid::Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::hours(app_state.env.verification_token_ttl);
+
+    let hash_password = app_state
+        .passwords
+        .hash(&body.password)
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let result = app_state
+        .db_client
+        .save_user(
+            &body.name,
+            &body.email,
+            &hash_password,
+            &token::hash_opaque(&verification_token),
+            expires_at,
+            account_status,
+        )
+        .await;
+
+    match result {
+        Ok(user) => {
+            if !body.metadata.is_empty() {
+                app_state
+                    .db_client
+                    .set_user_metadata(user.id, &body.metadata)
+                    .await
+                    .map_err(|e| HttpError::server_error(e.to_string()))?;
+            }
+
+            if let Some(invitation) = invitation {
+                app_state
+                    .db_client
+                    .accept_invitation(&invitation, user.id)
+                    .await
+                    .map_err(|e| HttpError::server_error(e.to_string()))?;
+            }
+            app_state.hooks.user_registered(&user);
+
+            let message = match account_status {
+                AccountStatus::PendingApproval => {
+                    "Registration received! You'll get a verification email once your account is approved."
+                }
+                _ => {
+                    let branding = app_state
+                        .db_client
+                        .get_email_branding(&user)
+                        .await
+                        .unwrap_or(None);
+
+                    let action_url = format!(
+                        "{}/verify?token={}",
+                        app_state.env.app_url, verification_token
+                    );
+
+                    app_state.notifier.spawn(
+                        Notification::to_user(
+                            NotificationKind::EmailVerification,
+                            user.id,
+                            &user.email,
+                            "Verify your email",
+                            format!(
+                                "Verify your email address to activate your account: {}",
+                                action_url
+                            ),
+                        )
+                        .with_branding(branding)
+                        .with_template(
+                            EmailTemplate::Verification,
+                            serde_json::json!({
+                                "action_url": action_url,
+                                "expires_in_hours": app_state.env.verification_token_ttl,
+                                "complete_registration": false,
+                            }),
+                        ),
+                    );
+
+                    "Registration successful! Please check your email to verify your account."
+                }
+            };
+
+            Ok((
+                StatusCode::CREATED,
+                Json(Response {
+                    status: "success",
+                    message: message.to_string(),
+                }),
+            ))
+        }
+        Err(sqlx::Error::Database(db_err)) => {
+            if db_err.is_unique_violation() {
+                Err(HttpError::unique_constraint_violation(
+                    ErrorMessage::EmailExist.to_string(),
+                ))
+            } else {
+                Err(HttpError::server_error(db_err.to_string()))
+            }
+        }
+        Err(e) => Err(HttpError::server_error(e.to_string())),
+    }
+}
+
+pub async fn start_registration(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<StartRegistrationDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if app_state.env.invite_only {
+        return Err(HttpError::forbidden(
+            ErrorMessage::InvitationRequired.to_string(),
+        ));
+    }
+
+    let account_status = if app_state.env.waitlist {
+        AccountStatus::PendingApproval
+    } else {
+        AccountStatus::Active
+    };
+
+    let verification_token = uuid::Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::hours(app_state.env.verification_token_ttl);
+
+    let user = app_state
+        .db_client
+        .start_registration(
+            &body.email,
+            &token::hash_opaque(&verification_token),
+            expires_at,
+            account_status,
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                HttpError::unique_constraint_violation(ErrorMessage::EmailExist.to_string())
+            }
+            e => HttpError::server_error(e.to_string()),
+        })?;
+
+    let message = match account_status {
+        AccountStatus::PendingApproval => {
+            "Registration received! You'll get a verification email once your account is approved."
+        }
+        _ => {
+            let action_url = format!(
+                "{}/complete-registration?token={}",
+                app_state.env.app_url, verification_token
+            );
+
+            app_state.notifier.spawn(
+                Notification::to_user(
+                    NotificationKind::EmailVerification,
+                    user.id,
+                    &user.email,
+                    "Verify your email",
+                    format!(
+                        "Verify your email and finish creating your account: {}",
+                        action_url
+                    ),
+                )
+                .with_template(
+                    EmailTemplate::Verification,
+                    serde_json::json!({
+                        "action_url": action_url,
+                        "expires_in_hours": app_state.env.verification_token_ttl,
+                        "complete_registration": true,
+                    }),
+                ),
+            );
+
+            "Please check your email to verify your address and complete your registration."
+        }
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Response {
+            status: "success",
+            message: message.to_string(),
+        }),
+    ))
+}
+
+pub async fn complete_registration(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<CompleteRegistrationDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user(None, None, None, Some(&token::hash_opaque(&body.token)))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|user| user.registration_state == RegistrationState::PendingProfile)
+        .ok_or_else(|| HttpError::bad_request(ErrorMessage::InvalidToken.to_string()))?;
+
+    ensure_token_active(&user)?;
+
+    match user.account_status {
+        AccountStatus::Active => {}
+        AccountStatus::PendingApproval => {
+            return Err(HttpError::forbidden(
+                ErrorMessage::AccountPendingApproval.to_string(),
+            ));
+        }
+        AccountStatus::Rejected => {
+            return Err(HttpError::forbidden(
+                ErrorMessage::AccountRejected.to_string(),
+            ));
+        }
+    }
+
+    ensure_name_allowed(&app_state, &body.name)?;
+
+    validate_registration_metadata(&body.metadata, &app_state.env.registration_fields)
+        .map_err(HttpError::bad_request)?;
+
+    let hash_password = app_state
+        .passwords
+        .hash(&body.password)
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .complete_registration(user.id, &body.name, &hash_password)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if !body.metadata.is_empty() {
+        app_state
+            .db_client
+            .set_user_metadata(user.id, &body.metadata)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+    app_state.hooks.user_registered(&user);
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Registration completed successfully".to_string(),
+    }))
+}
+
+pub async fn login(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(location): Extension<GeoLocation>,
+    Extension(client): Extension<ClientContext>,
+    risk: Option<Extension<RiskDecision>>,
+    headers: HeaderMap,
+    Json(body): Json<LoginUserDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let result = app_state
+        .db_client
+        .get_user(None, None, Some(&body.email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let Some(user) = result else {
+        return Err(deleted_account_login(&app_state, &body).await);
+    };
+
+    let verification = app_state
+        .passwords
+        .verify(&body.password, &user.password)
+        .map_err(|_| HttpError::bad_request(ErrorMessage::WrongCredentials.to_string()))?;
+
+    if !verification.matched {
+        return Err(HttpError::bad_request(
+            ErrorMessage::WrongCredentials.to_string(),
+        ));
+    }
+
+    if verification.needs_rehash && app_state.env.password_rehash {
+        rehash_password(&app_state, &user, &body.password).await;
+    }
+
+    if matches!(risk, Some(Extension(RiskDecision::RequireMfa))) && user.totp_enabled_at.is_none() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::RiskMfaRequired.to_string(),
+        ));
+    }
+
+    complete_login(&app_state, &user, &headers, &location, &client).await
+}
+
+async fn deleted_account_login(app_state: &AppState, body: &LoginUserDTO) -> HttpError {
+    let wrong_credentials = HttpError::bad_request(ErrorMessage::WrongCredentials.to_string());
+
+    let user = match app_state
+        .db_client
+        .get_deleted_user_by_email(&body.email)
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return wrong_credentials,
+        Err(e) => return HttpError::server_error(e.to_string()),
+    };
+
+    let matched = app_state
+        .passwords
+        .verify(&body.password, &user.password)
+        .is_ok_and(|verification| verification.matched);
+
+    if matched {
+        HttpError::forbidden(ErrorMessage::AccountDeleted.to_string())
+    } else {
+        wrong_credentials
+    }
+}
+
+async fn rehash_password(app_state: &AppState, user: &User, password: &str) {
+    let hashed = match app_state.passwords.hash(password) {
+        Ok(hashed) => hashed,
+        Err(e) => {
+            tracing::warn!("Failed to rehash password for {}: {}", user.id, e);
+            return;
+        }
+    };
+
+    match app_state
+        .db_client
+        .rehash_user_password(user.id, &user.password, &hashed)
+        .await
+    {
+        Ok(true) => {
+            tracing::info!(target: "audit", event = "password_rehashed", user_id = %user.id)
+        }
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to store rehashed password for {}: {}", user.id, e),
+    }
+}
+
+pub async fn complete_login(
+    app_state: &AppState,
+    user: &User,
+    headers: &HeaderMap,
+    location: &GeoLocation,
+    client: &ClientContext,
+) -> Result<AxumResponse, HttpError> {
+    if user.totp_enabled_at.is_some() {
+        let mfa_token = token::create_mfa_token(
+            &user.id.to_string(),
+            &app_state.jwt_keys,
+            app_state.env.mfa_token_maxage,
+        )
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        return Ok(Json(MfaChallengeResponseDTO {
+            status: "mfa_required".to_string(),
+            mfa_token,
+            expires_in: app_state.env.mfa_token_maxage * 60,
+        })
+        .into_response());
+    }
+
+    let response = start_session(app_state, user, headers, location, client).await?;
+
+    Ok(response.into_response())
+}
+
+pub async fn request_magic_link(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<MagicLinkRequestDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user(None, None, Some(&body.email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|user| user.verified && user.registration_state == RegistrationState::Complete);
+
+    if let Some(user) = user {
+        let magic_token = token::create_magic_link_token(
+            &user.id.to_string(),
+            &app_state.jwt_keys,
+            app_state.env.magic_link_maxage,
+        )
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let branding = app_state
+            .db_client
+            .get_email_branding(&user)
+            .await
+            .unwrap_or(None);
+
+        app_state.notifier.spawn(
+            Notification::to_user(
+                NotificationKind::MagicLink,
+                user.id,
+                &user.email,
+                "Your sign-in link",
+                format!(
+                    "Sign in using this link within {} minutes: {}/magic-link?token={}",
+                    app_state.env.magic_link_maxage, app_state.env.app_url, magic_token
+                ),
+            )
+            .with_branding(branding),
+        );
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "If an account exists for that email, a sign-in link has been sent.".to_string(),
+    }))
+}
+
+pub async fn verify_magic_link(
+    Query(query_params): Query<VerifyEmailQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(location): Extension<GeoLocation>,
+    Extension(client): Extension<ClientContext>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, HttpError> {
+    query_params
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let claims = token::decode_claims(&query_params.token, &app_state.jwt_keys)?;
+    let (true, Some(jti)) = (claims.magic, &claims.jti) else {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidToken.to_string(),
+        ));
+    };
+
+    let user_id = uuid::Uuid::parse_str(&claims.sub)
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+    let consumed = app_state
+        .db_client
+        .consume_token(jti, user_id, expires_at)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if !consumed {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::TokenRevoked.to_string(),
+        ));
+    }
+
+    let user = app_state
+        .db_client
+        .get_user(Some(user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::unauthorized(
+            ErrorMessage::UserNoLongerExist.to_string(),
+        ))?;
+
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::MagicLinkUsed)
+            .actor(user.id)
+            .target(user.id)
+            .client(&client),
+    );
+
+    complete_login(&app_state, &user, &headers, &location, &client).await
+}
+
+pub async fn verify_two_factor(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(location): Extension<GeoLocation>,
+    Extension(client): Extension<ClientContext>,
+    headers: HeaderMap,
+    Json(body): Json<VerifyTwoFactorDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let claims = token::decode_claims(&body.mfa_token, &app_state.jwt_keys)?;
+    if !claims.mfa {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidToken.to_string(),
+        ));
+    }
+
+    let user_id = uuid::Uuid::parse_str(&claims.sub)
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user(Some(user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::unauthorized(
+            ErrorMessage::UserNoLongerExist.to_string(),
+        ))?;
+
+    let secret = match (&user.totp_secret, user.totp_enabled_at) {
+        (Some(secret), Some(_)) => secret,
+        _ => {
+            return Err(HttpError::bad_request(
+                ErrorMessage::TwoFactorNotEnrolled.to_string(),
+            ));
+        }
+    };
+
+    let jti = claims
+        .jti
+        .as_deref()
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+    let attempts_key = mfa_attempts_key(user.id);
+    let locked_until = app_state
+        .db_client
+        .get_login_lockout(std::slice::from_ref(&attempts_key))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if locked_until.is_some() {
+        return Err(HttpError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorMessage::TooManyRequests.to_string(),
+        ));
+    }
+
+    let verified = match (&body.code, &body.backup_code) {
+        (Some(code), _) => totp::verify(secret, code, Utc::now().timestamp()),
+        (None, Some(backup_code)) => app_state
+            .db_client
+            .consume_backup_code(user.id, &backup_code::hash(backup_code))
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+        (None, None) => false,
+    };
+
+    if !verified {
+        record_mfa_failure(&app_state, &user, &client, jti).await;
+        return Err(HttpError::bad_request(
+            ErrorMessage::InvalidTwoFactorCode.to_string(),
+        ));
+    }
+
+    let first_use = nonce::claim(
+        &app_state.db_client,
+        NoncePurpose::MfaToken,
+        jti,
+        app_state.env.mfa_token_maxage * 60,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if !first_use {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidToken.to_string(),
+        ));
+    }
+
+    if let Err(err) = app_state
+        .db_client
+        .reset_login_failures(&attempts_key)
+        .await
+    {
+        tracing::warn!("failed to reset 2FA failures: {}", err);
+    }
+
+    if body.code.is_none() {
+        let remaining = app_state
+            .db_client
+            .count_unused_backup_codes(user.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::BackupCodeUsed)
+                .actor(user.id)
+                .target(user.id)
+                .client(&client)
+                .detail(serde_json::json!({ "remaining": remaining })),
+        );
+
+        app_state.notifier.spawn(Notification::to_user(
+            NotificationKind::SecurityAlert,
+            user.id,
+            user.email.clone(),
+            "A backup code was used to sign in",
+            format!(
+                "A backup code was used to sign in to your account. You have {} backup codes remaining.",
+                remaining
+            ),
+        ));
+    }
+
+    let response = start_session(&app_state, &user, &headers, &location, &client).await?;
+
+    Ok(response)
+}
+
+fn mfa_attempts_key(user_id: uuid::Uuid) -> String {
+    format!("mfa:{}", user_id)
+}
+
+async fn record_mfa_failure(app_state: &AppState, user: &User, client: &ClientContext, jti: &str) {
+    let env = &app_state.env;
+    let attempt = match app_state
+        .db_client
+        .record_login_failure(
+            &mfa_attempts_key(user.id),
+            (env.mfa_token_maxage * 60) as u64,
+            env.mfa_max_attempts,
+            env.login_lockout_duration,
+        )
+        .await
+    {
+        Ok(attempt) => attempt,
+        Err(err) => {
+            tracing::warn!("failed to record 2FA failure: {}", err);
+            return;
+        }
+    };
+
+    if attempt.locked_until.is_some() {
+        audit::record(
+            app_state,
+            AuditEntry::new(AuditEvent::MfaLockedOut)
+                .target(user.id)
+                .client(client)
+                .detail(serde_json::json!({ "failures": attempt.failures })),
+        );
+        if let Err(err) = nonce::claim(
+            &app_state.db_client,
+            NoncePurpose::MfaToken,
+            jti,
+            env.mfa_token_maxage * 60,
+        )
+        .await
+        {
+            tracing::warn!("failed to invalidate MFA token: {}", err);
+        }
+    }
+}
+
+pub async fn start_session(
+    app_state: &AppState,
+    user: &User,
+    headers: &HeaderMap,
+    location: &GeoLocation,
+    client: &ClientContext,
+) -> Result<LoginSession, HttpError> {
+    if user.locked_at.is_some() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::AccountLocked.to_string(),
+        ));
+    }
+
+    match user.account_status {
+        AccountStatus::Active => {}
+        AccountStatus::PendingApproval => {
+            return Err(HttpError::forbidden(
+                ErrorMessage::AccountPendingApproval.to_string(),
+            ));
+        }
+        AccountStatus::Rejected => {
+            return Err(HttpError::forbidden(
+                ErrorMessage::AccountRejected.to_string(),
+            ));
+        }
+    }
+
+    app_state
+        .hooks
+        .check_login(user, client)
+        .await
+        .map_err(|e| HttpError::forbidden(e.0))?;
+
+    let organization = match user.organization_id {
+        Some(org_id) => app_state
+            .db_client
+            .get_organization(org_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+        None => None,
+    };
+    if let Some(organization) = &organization {
+        GeoPolicy::organization(organization).enforce(location, "organization")?;
+    }
+
+    let idle_timeout = organization
+        .as_ref()
+        .and_then(|organization| organization.session_idle_timeout)
+        .or(app_state.env.session_idle_timeout);
+    let max_lifetime = organization
+        .as_ref()
+        .and_then(|organization| organization.session_max_lifetime)
+        .map(i64::from)
+        .unwrap_or(app_state.env.session_max_lifetime);
+
+    let client_type = match headers
+        .get(CLIENT_TYPE_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(name) => Some(
+            app_state
+                .env
+                .client_types
+                .iter()
+                .find(|client| client.name == name)
+                .ok_or_else(|| HttpError::bad_request("Unknown client type"))?,
+        ),
+        None => app_state.env.client_types.first(),
+    };
+    let token_lifetime = client_type
+        .map(|client| client.token_lifetime)
+        .unwrap_or(app_state.env.jwt_maxage)
+        .min(max_lifetime);
+    let audience = client_type.map(|client| client.name.as_str());
+
+    let login_session = match (
+        app_state.env.session_backend,
+        &app_state.env.session_cookie_key,
+    ) {
+        (SessionBackend::Cookie, Some(key)) => {
+            let ttl = idle_timeout.map(i64::from).unwrap_or(token_lifetime);
+            let session = CookieSession::new(user.id, audience, ttl, max_lifetime);
+            let jar = cookie_session::store(
+                PrivateCookieJar::new(key.clone()),
+                &session,
+                &app_state.env,
+            )?;
+
+            LoginSession::Cookie(
+                jar,
+                CookieSessionResponseDTO {
+                    status: "success".to_string(),
+                    expires_at: session.expires_at(),
+                },
+            )
+        }
+        _ => {
+            let refresh_token = token::generate_opaque();
+            let fingerprint = app_state
+                .env
+                .refresh_token_binding
+                .then(|| client_fingerprint(headers));
+
+            let session = app_state
+                .db_client
+                .create_session(
+                    user.id,
+                    idle_timeout,
+                    Utc::now() + Duration::minutes(max_lifetime),
+                    &token::hash_opaque(&refresh_token),
+                    fingerprint.as_deref(),
+                    audience,
+                )
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+            let scope = app_state.permission_cache.resolve(user).scope();
+            let token = token::create_session_token(
+                &user.id.to_string(),
+                &session.id.to_string(),
+                audience,
+                Some(&scope),
+                &app_state.jwt_keys,
+                token_lifetime,
+            )
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+            let (jar, token) = deliver_token(&app_state.env, token, token_lifetime);
+            LoginSession::Token(
+                jar,
+                UserLoginResponseDTO {
+                    status: "success".to_string(),
+                    token,
+                    refresh_token: Some(refresh_token),
+                },
+            )
+        }
+    };
+
+    app_state
+        .db_client
+        .record_login(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    audit::record(
+        app_state,
+        AuditEntry::new(AuditEvent::Login)
+            .actor(user.id)
+            .target(user.id)
+            .client(client),
+    );
+    app_state.hooks.logged_in(user, client);
+    if user.deactivated_at.is_some() {
+        audit::record(
+            app_state,
+            AuditEntry::new(AuditEvent::AccountReactivated)
+                .actor(user.id)
+                .target(user.id)
+                .client(client),
+        );
+    }
+
+    Ok(login_session)
+}
+
+fn client_fingerprint(headers: &HeaderMap) -> String {
+    let value_of = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    token::hash_opaque(&format!(
+        "{}|{}",
+        value_of(header::USER_AGENT.as_str()),
+        value_of(DEVICE_ID_HEADER)
+    ))
+}
+
+pub async fn refresh(
+    Extension(app_state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<RefreshTokenDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let current_hash = token::hash_opaque(&body.refresh_token);
+    let session = app_state
+        .db_client
+        .get_session_by_refresh_token(&current_hash)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    if !session.is_active(Utc::now()) {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::SessionExpired.to_string(),
+        ));
+    }
+
+    if let Some(expected) = &session.client_fingerprint
+        && *expected != client_fingerprint(&headers)
+    {
+        tracing::warn!(
+            target: "audit",
+            event = "refresh_client_mismatch",
+            user_id = %session.user_id,
+            session_id = %session.id
+        );
+        return Err(HttpError::unauthorized(
+            ErrorMessage::ClientMismatch.to_string(),
+        ));
+    }
+
+    let user = app_state
+        .db_client
+        .get_user(Some(session.user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNoLongerExist.to_string()))?;
+
+    if user.locked_at.is_some()
+        || user.account_status != AccountStatus::Active
+        || user.deactivated_at.is_some()
+    {
+        return Err(HttpError::forbidden(
+            ErrorMessage::PermissionDenied.to_string(),
+        ));
+    }
+
+    let token_lifetime = session
+        .audience
+        .as_deref()
+        .and_then(|audience| {
+            app_state
+                .env
+                .client_types
+                .iter()
+                .find(|client| client.name == audience)
+        })
+        .map(|client| client.token_lifetime)
+        .unwrap_or(app_state.env.jwt_maxage);
+
+    let refresh_token = token::generate_opaque();
+    let rotated = app_state
+        .db_client
+        .rotate_refresh_token(
+            session.id,
+            &current_hash,
+            &token::hash_opaque(&refresh_token),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if !rotated {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidToken.to_string(),
+        ));
+    }
+
+    let scope = app_state.permission_cache.resolve(&user).scope();
+    let token = token::create_session_token(
+        &user.id.to_string(),
+        &session.id.to_string(),
+        session.audience.as_deref(),
+        Some(&scope),
+        &app_state.jwt_keys,
+        token_lifetime,
+    )
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let (jar, token) = deliver_token(&app_state.env, token, token_lifetime);
+
+    Ok((
+        jar,
+        Json(UserLoginResponseDTO {
+            status: "success".to_string(),
+            token,
+            refresh_token: Some(refresh_token),
+        }),
+    ))
+}
+
+fn deliver_token(config: &Config, token: String, lifetime: i64) -> (CookieJar, Option<String>) {
+    let mut jar = CookieJar::new();
+    if config.token_delivery.in_cookie() {
+        let cookie = Cookie::build((TOKEN_COOKIE, token.clone()))
+            .path("/")
+            .http_only(true)
+            .secure(
+                config.environment == Environment::Production
+                    || config.token_cookie_same_site == SameSite::None,
+            )
+            .same_site(config.token_cookie_same_site)
+            .max_age(time::Duration::minutes(lifetime));
+        jar = jar.add(cookie);
+    }
+
+    (jar, config.token_delivery.in_body().then_some(token))
+}
+
+pub async fn logout(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    claims: Option<Extension<TokenClaims>>,
+    session: Option<Extension<CookieSession>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let subject = match (&claims, &session) {
+        (Some(Extension(claims)), _) => &claims.sub,
+        (None, Some(Extension(session))) => &session.sub,
+        (None, None) => {
+            return Err(HttpError::unauthorized(
+                ErrorMessage::UserNotAuthenticated.to_string(),
+            ));
+        }
+    };
+    let user_id = uuid::Uuid::parse_str(subject)
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    if let Some(Extension(session)) = &session {
+        app_state
+            .db_client
+            .revoke_token(&session.jti, user_id, session.max_expires_at())
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        app_state.revocations.evict_token(&session.jti);
+    }
+
+    if let Some(Extension(claims)) = &claims
+        && let Some(jti) = &claims.jti
+    {
+        let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+        app_state
+            .db_client
+            .revoke_token(jti, user_id, expires_at)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        app_state.revocations.evict_token(jti);
+    }
+
+    if let Some(session_id) = claims
+        .as_ref()
+        .and_then(|Extension(claims)| claims.sid.as_deref())
+        .and_then(|sid| uuid::Uuid::parse_str(sid).ok())
+    {
+        app_state
+            .db_client
+            .revoke_session(session_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        app_state.revocations.evict_session(session_id);
+    }
+
+    tracing::info!(target: "audit", event = "logout", user_id = %user_id);
+
+    let cookie_jar = cookie_jar
+        .remove(Cookie::build(TOKEN_COOKIE).path("/"))
+        .remove(Cookie::build(SESSION_COOKIE).path("/"));
+
+    Ok((
+        cookie_jar,
+        Json(Response {
+            status: "success",
+            message: "Logged out successfully".to_string(),
+        }),
+    ))
+}
+
+pub async fn reauthenticate(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(client): Extension<ClientContext>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<ReauthenticateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let attempts_key = reauth_attempts_key(user.id);
+    let locked_until = app_state
+        .db_client
+        .get_login_lockout(std::slice::from_ref(&attempts_key))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if locked_until.is_some() {
+        return Err(HttpError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorMessage::TooManyRequests.to_string(),
+        ));
+    }
+
+    let (verified, rejection) = match (&body.password, &body.code, &body.backup_code) {
+        (Some(password), _, _) => (
+            app_state
+                .passwords
+                .verify(password, &user.password)
+                .map(|verification| verification.matched)
+                .unwrap_or(false),
+            ErrorMessage::WrongCredentials,
+        ),
+        (None, Some(code), _) => match (&user.totp_secret, user.totp_enabled_at) {
+            (Some(secret), Some(_)) => (
+                totp::verify(secret, code, Utc::now().timestamp()),
+                ErrorMessage::InvalidTwoFactorCode,
+            ),
+            _ => {
+                return Err(HttpError::bad_request(
+                    ErrorMessage::TwoFactorNotEnrolled.to_string(),
+                ));
+            }
+        },
+        (None, None, Some(backup_code)) => (
+            app_state
+                .db_client
+                .consume_backup_code(user.id, &backup_code::hash(backup_code))
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?,
+            ErrorMessage::InvalidTwoFactorCode,
+        ),
+        (None, None, None) => {
+            return Err(HttpError::bad_request(
+                "A password or two-factor code is required",
+            ));
+        }
+    };
+
+    if !verified {
+        record_reauth_failure(&app_state, &user, &client).await;
+        return Err(HttpError::bad_request(rejection.to_string()));
+    }
+
+    if let Err(err) = app_state
+        .db_client
+        .reset_login_failures(&attempts_key)
+        .await
+    {
+        tracing::warn!("failed to reset reauthentication failures: {}", err);
+    }
+
+    let token = token::create_sudo_token(
+        &user.id.to_string(),
+        &app_state.jwt_keys,
+        app_state.env.sudo_maxage,
+    )
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SudoTokenResponseDTO {
+        status: "success".to_string(),
+        token,
+        expires_in: app_state.env.sudo_maxage * 60,
+    }))
+}
+
+fn reauth_attempts_key(user_id: uuid::Uuid) -> String {
+    format!("reauth:{}", user_id)
+}
+
+async fn record_reauth_failure(app_state: &AppState, user: &User, client: &ClientContext) {
+    let env = &app_state.env;
+    let attempt = match app_state
+        .db_client
+        .record_login_failure(
+            &reauth_attempts_key(user.id),
+            env.captcha_window,
+            env.reauth_max_attempts,
+            env.login_lockout_duration,
+        )
+        .await
+    {
+        Ok(attempt) => attempt,
+        Err(err) => {
+            tracing::warn!("failed to record reauthentication failure: {}", err);
+            return;
+        }
+    };
+
+    audit::record(
+        app_state,
+        AuditEntry::new(AuditEvent::ReauthenticationFailed)
+            .actor(user.id)
+            .target(user.id)
+            .client(client)
+            .detail(serde_json::json!({ "failures": attempt.failures })),
+    );
+
+    if attempt.locked_until.is_some() {
+        audit::record(
+            app_state,
+            AuditEntry::new(AuditEvent::LoginLockedOut)
+                .target(user.id)
+                .client(client)
+                .detail(serde_json::json!({
+                    "key": attempt.key,
+                    "failures": attempt.failures,
+                })),
+        );
+    }
+}
+
+pub async fn verify_email(
+    Query(query_params): Query<VerifyEmailQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    query_params
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let result = app_state
+        .db_client
+        .get_user(
+            None,
+            None,
+            None,
+            Some(&token::hash_opaque(&query_params.token)),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let user = result.ok_or(HttpError::unauthorized(
+        ErrorMessage::InvalidToken.to_string(),
+    ))?;
+
+    if user.registration_state == RegistrationState::PendingProfile {
+        return Err(HttpError::bad_request(
+            ErrorMessage::RegistrationIncomplete.to_string(),
+        ));
+    }
+
+    ensure_token_active(&user)?;
+
+    app_state
+        .db_client
+        .verify_user_token(&token::hash_opaque(&query_params.token))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let branding = app_state
+        .db_client
+        .get_email_branding(&user)
+        .await
+        .unwrap_or(None);
+
+    app_state.notifier.spawn(
+        Notification::to_user(
+            NotificationKind::Welcome,
+            user.id,
+            &user.email,
+            "Welcome",
+            format!(
+                "Hi {}, your email address is verified and your account is ready: {}/login",
+                user.name, app_state.env.app_url
+            ),
+        )
+        .with_branding(branding)
+        .with_template(
+            EmailTemplate::Welcome,
+            serde_json::json!({
+                "name": user.name,
+                "action_url": format!("{}/login", app_state.env.app_url),
+            }),
+        ),
+    );
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Email verified successfully".to_string(),
+    }))
+}
+
+pub async fn verify_recovery_email(
+    Query(query_params): Query<VerifyEmailQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    query_params
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    app_state
+        .db_client
+        .verify_recovery_email(&token::hash_opaque(&query_params.token))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request(ErrorMessage::InvalidToken.to_string()))?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Recovery email verified successfully".to_string(),
+    }))
+}
+
+fn ensure_token_active(user: &User) -> Result<(), HttpError> {
+    ensure_active(user.token_expires_at)
+}
+
+fn ensure_active(expires_at: Option<DateTime<Utc>>) -> Result<(), HttpError> {
+    match expires_at {
+        Some(expires_at) if Utc::now() > expires_at => Err(HttpError::bad_request(
+            ErrorMessage::TokenExpired.to_string(),
+        )),
+        Some(_) => Ok(()),
+        None => Err(HttpError::bad_request(
+            ErrorMessage::InvalidToken.to_string(),
+        )),
+    }
+}
+
+async fn recoverable_user(app_state: &AppState, email: &str) -> Result<Option<User>, HttpError> {
+    let user = match app_state
+        .db_client
+        .get_user(None, None, Some(email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        Some(user) => Some(user),
+        None => app_state
+            .db_client
+            .get_user_by_recovery_email(email)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+    };
+
+    Ok(user.filter(|user| user.registration_state == RegistrationState::Complete))
+}
+
+pub async fn request_reset_code(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<ForgotPasswordRequestDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let Some(user) = recoverable_user(&app_state, &body.email).await? {
+        let code = reset_code::generate();
+        let expires_at = Utc::now() + Duration::minutes(app_state.env.reset_code_ttl);
+
+        app_state
+            .db_client
+            .create_reset_code(user.id, &reset_code::hash(&code), expires_at)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let branding = app_state
+            .db_client
+            .get_email_branding(&user)
+            .await
+            .unwrap_or(None);
+
+        app_state.notifier.spawn(
+            Notification::to_email(
+                NotificationKind::PasswordReset,
+                &body.email,
+                "Your password reset code",
+                format!(
+                    "Your password reset code is {}. It expires in {} minutes.",
+                    code, app_state.env.reset_code_ttl
+                ),
+            )
+            .with_branding(branding)
+            .with_template(
+                EmailTemplate::PasswordReset,
+                serde_json::json!({
+                    "code": code,
+                    "expires_in_minutes": app_state.env.reset_code_ttl,
+                }),
+            ),
+        );
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "If an account exists for that email, a reset code has been sent.".to_string(),
+    }))
+}
+
+pub async fn verify_reset_code(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<VerifyResetCodeDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let invalid_code = || HttpError::bad_request(ErrorMessage::InvalidResetCode.to_string());
+    let attempts_exceeded = || {
+        HttpError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorMessage::ResetCodeAttemptsExceeded.to_string(),
+        )
+    };
+
+    let user = recoverable_user(&app_state, &body.email)
+        .await?
+        .ok_or_else(invalid_code)?;
+
+    let code = app_state
+        .db_client
+        .get_active_reset_code(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(invalid_code)?;
+
+    let max_attempts = app_state.env.reset_code_max_attempts;
+    if code.attempts >= max_attempts {
+        return Err(attempts_exceeded());
+    }
+
+    if !reset_code::matches(&body.code, &code.code_hash) {
+        let attempts = app_state
+            .db_client
+            .increment_reset_code_attempts(code.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        tracing::warn!(
+            target: "audit",
+            event = "reset_code_rejected",
+            user_id = %user.id,
+            attempts
+        );
+
+        return Err(if attempts >= max_attempts {
+            attempts_exceeded()
+        } else {
+            invalid_code()
+        });
+    }
+
+    let consumed = app_state
+        .db_client
+        .consume_reset_code(code.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if !consumed {
+        return Err(invalid_code());
+    }
+
+    let reset_token = uuid::Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::minutes(app_state.env.reset_token_ttl);
+
+    app_state
+        .db_client
+        .add_reset_token(user.id, &token::hash_opaque(&reset_token), expires_at)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(ResetTokenResponseDTO {
+        status: "success".to_string(),
+        token: reset_token,
+    }))
+}
+
+pub async fn forgot_password(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<ForgotPasswordRequestDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let Some(user) = recoverable_user(&app_state, &body.email).await? {
+        let reset_token = uuid::Uuid::new_v4().to_string();
+        let expires_at = Utc::now() + Duration::minutes(app_state.env.reset_token_ttl);
+
+        app_state
+            .db_client
+            .add_reset_token(user.id, &token::hash_opaque(&reset_token), expires_at)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let branding = app_state
+            .db_client
+            .get_email_branding(&user)
+            .await
+            .unwrap_or(None);
+
+        let action_url = format!(
+            "{}/reset-password?token={}",
+            app_state.env.app_url, reset_token
+        );
+
+        app_state.notifier.spawn(
+            Notification::to_email(
+                NotificationKind::PasswordReset,
+                &body.email,
+                "Reset your password",
+                format!(
+                    "Reset your password using this link within {} minutes: {}",
+                    app_state.env.reset_token_ttl, action_url
+                ),
+            )
+            .with_branding(branding)
+            .with_template(
+                EmailTemplate::PasswordReset,
+                serde_json::json!({
+                    "action_url": action_url,
+                    "expires_in_minutes": app_state.env.reset_token_ttl,
+                }),
+            ),
+        );
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "If an account exists for that email, a password reset link has been sent."
+            .to_string(),
+    }))
+}
+
+pub async fn get_recovery_questions(
+    Query(query_params): Query<VerifyEmailQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    if !app_state.env.security_questions {
+        return Err(HttpError::forbidden(
+            ErrorMessage::SecurityQuestionsDisabled.to_string(),
+        ));
+    }
+
+    query_params
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user_by_reset_token(&token::hash_opaque(&query_params.token))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request(ErrorMessage::InvalidToken.to_string()))?;
+
+    ensure_active(user.reset_token_expires_at)?;
+
+    let questions = app_state
+        .db_client
+        .get_security_questions(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SecurityQuestionsResponseDTO {
+        status: "success".to_string(),
+        questions,
+    }))
+}
+
+fn security_answer_attempts_key(user_id: uuid::Uuid) -> String {
+    format!("security_answers:{}", user_id)
+}
+
+async fn record_security_answer_failure(app_state: &AppState, user: &User, client: &ClientContext) {
+    let env = &app_state.env;
+    let attempt = match app_state
+        .db_client
+        .record_login_failure(
+            &security_answer_attempts_key(user.id),
+            (env.reset_token_ttl * 60) as u64,
+            env.security_answer_max_attempts,
+            env.login_lockout_duration,
+        )
+        .await
+    {
+        Ok(attempt) => attempt,
+        Err(err) => {
+            tracing::warn!("failed to record security answer failure: {}", err);
+            return;
+        }
+    };
+
+    audit::record(
+        app_state,
+        AuditEntry::new(AuditEvent::SecurityAnswersRejected)
+            .target(user.id)
+            .client(client)
+            .detail(serde_json::json!({ "failures": attempt.failures })),
+    );
+
+    if attempt.locked_until.is_none() {
+        return;
+    }
+
+    if let Err(err) = app_state.db_client.clear_reset_token(user.id).await {
+        tracing::warn!("failed to invalidate reset token: {}", err);
+    }
+
+    app_state.notifier.spawn(Notification::to_user(
+        NotificationKind::SecurityAlert,
+        user.id,
+        user.email.clone(),
+        "Password reset blocked",
+        "Someone tried to reset your password but answered your security questions incorrectly too many times. The reset link has been cancelled. If this wasn't you, consider changing your password.",
+    ));
+}
+
+pub async fn reset_password(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(client): Extension<ClientContext>,
+    Json(body): Json<ResetPasswordRequestDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let result = app_state
+        .db_client
+        .get_user_by_reset_token(&token::hash_opaque(&body.token))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let user = result.ok_or(HttpError::bad_request(
+        ErrorMessage::InvalidToken.to_string(),
+    ))?;
+
+    ensure_active(user.reset_token_expires_at)?;
+
+    if app_state.env.security_questions {
+        let attempts_key = security_answer_attempts_key(user.id);
+        let locked_until = app_state
+            .db_client
+            .get_login_lockout(std::slice::from_ref(&attempts_key))
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if locked_until.is_some() {
+            return Err(HttpError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorMessage::TooManyRequests.to_string(),
+            ));
+        }
+
+        let questions = app_state
+            .db_client
+            .get_security_questions(user.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        if !questions.is_empty() {
+            let answered = questions.len() == body.security_answers.len()
+                && questions
+                    .iter()
+                    .zip(&body.security_answers)
+                    .all(|(question, answer)| {
+                        security_question::compare_answer(
+                            &app_state.passwords,
+                            answer,
+                            &question.answer_hash,
+                        )
+                        .unwrap_or(false)
+                    });
+
+            if !answered {
+                record_security_answer_failure(&app_state, &user, &client).await;
+                return Err(HttpError::bad_request(
+                    ErrorMessage::WrongSecurityAnswers.to_string(),
+                ));
+            }
+        }
+
+        if let Err(err) = app_state
+            .db_client
+            .reset_login_failures(&attempts_key)
+            .await
+        {
+            tracing::warn!("failed to reset security answer failures: {}", err);
+        }
+    }
+
+    let hash_password = app_state
+        .passwords
+        .hash(&body.new_password)
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    app_state
+        .db_client
+        .update_user_password(user.id, hash_password)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::PasswordChanged)
+            .target(user.id)
+            .client(&client)
+            .detail(serde_json::json!({ "method": "reset" })),
+    );
+    app_state.hooks.password_changed(&user);
+
+    let branding = app_state
+        .db_client
+        .get_email_branding(&user)
+        .await
+        .unwrap_or(None);
+
+    let recovery_email = app_state
+        .db_client
+        .get_recovery_email(user.id)
+        .await
+        .unwrap_or(None)
+        .filter(|recovery_email| recovery_email.verified_at.is_some())
+        .map(|recovery_email| recovery_email.email);
+
+    app_state.notifier.spawn(
+        Notification::to_user(
+            NotificationKind::PasswordChanged,
+            user.id,
+            &user.email,
+            "Your password was changed",
+            "The password for your account was just reset. If this wasn't you, contact support immediately.",
+        )
+        .with_branding(branding)
+        .with_cc(recovery_email),
+    );
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Password has been successfully reset.".to_string(),
+    }))
+}
+
+pub async fn check_availability(
+    Query(query_params): Query<AvailabilityQueryDTO>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    if !app_state.env.availability_check {
+        return Err(HttpError::new(StatusCode::NOT_FOUND, "Not found"));
+    }
+
+    query_params
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let taken = app_state
+        .db_client
+        .email_exists(&query_params.email)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(AvailabilityResponseDTO {
+        status: "success".to_string(),
+        available: !taken,
+    }))
+}
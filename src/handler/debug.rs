@@ -0,0 +1,129 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{ConnectInfo, Request},
+    http::header,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use tikv_jemalloc_ctl::{epoch, stats};
+
+use crate::{
+    AppState,
+    dtos::{HeapStatsResponseDTO, RuntimeStatsResponseDTO, TaskDumpResponseDTO, WorkerStatsDTO},
+    error::{ErrorMessage, HttpError},
+    middleware::optional_auth,
+    models::UserRole,
+    rbac::AuthContext,
+};
+
+pub fn debug_handler() -> Router {
+    Router::new()
+        .route("/heap", get(get_heap_stats))
+        .route("/runtime", get(get_runtime_stats))
+        .route("/tasks", get(get_task_dump))
+        .layer(middleware::from_fn(local_or_admin))
+        .layer(middleware::from_fn(optional_auth))
+}
+
+async fn local_or_admin(req: Request, next: Next) -> Result<Response, HttpError> {
+    let forwarded = req.headers().contains_key("x-forwarded-for")
+        || req.headers().contains_key("x-real-ip")
+        || req.headers().contains_key(header::FORWARDED);
+    let local = !forwarded
+        && req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .is_some_and(|ConnectInfo(addr)| addr.ip().is_loopback());
+
+    if !local {
+        req.extensions()
+            .get::<AuthContext>()
+            .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))?
+            .require_any_role(&[UserRole::admin()])?;
+    }
+
+    Ok(next.run(req).await)
+}
+
+pub async fn get_heap_stats() -> Result<impl IntoResponse, HttpError> {
+    let read = || -> Result<HeapStatsResponseDTO, tikv_jemalloc_ctl::Error> {
+        epoch::advance()?;
+        Ok(HeapStatsResponseDTO {
+            status: "success".to_string(),
+            allocated: stats::allocated::read()? as u64,
+            active: stats::active::read()? as u64,
+            resident: stats::resident::read()? as u64,
+            mapped: stats::mapped::read()? as u64,
+            retained: stats::retained::read()? as u64,
+            metadata: stats::metadata::read()? as u64,
+        })
+    };
+
+    read()
+        .map(Json)
+        .map_err(|e| HttpError::server_error(e.to_string()))
+}
+
+pub async fn get_runtime_stats(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let metrics = tokio::runtime::Handle::current().metrics();
+
+    Ok(Json(RuntimeStatsResponseDTO {
+        status: "success".to_string(),
+        alive_tasks: metrics.num_alive_tasks(),
+        global_queue_depth: metrics.global_queue_depth(),
+        console_enabled: cfg!(tokio_unstable) && app_state.env.profiling_console_addr.is_some(),
+        workers: (0..metrics.num_workers())
+            .map(|worker| WorkerStatsDTO {
+                busy_ms: metrics.worker_total_busy_duration(worker).as_millis() as u64,
+                parks: metrics.worker_park_count(worker),
+            })
+            .collect(),
+    }))
+}
+
+#[cfg(all(
+    tokio_unstable,
+    tokio_taskdump,
+    target_os = "linux",
+    any(target_arch = "aarch64", target_arch = "x86", target_arch = "x86_64")
+))]
+pub async fn get_task_dump() -> Result<impl IntoResponse, HttpError> {
+    let handle = tokio::runtime::Handle::current();
+    if handle.metrics().num_workers() < 2 {
+        return Err(HttpError::new(
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            ErrorMessage::TaskDumpSingleWorker.to_string(),
+        ));
+    }
+
+    let dump = tokio::time::timeout(std::time::Duration::from_secs(5), handle.dump())
+        .await
+        .map_err(|_| HttpError::gateway_timeout(ErrorMessage::RequestTimedOut.to_string()))?;
+
+    Ok(Json(TaskDumpResponseDTO {
+        status: "success".to_string(),
+        tasks: dump
+            .tasks()
+            .iter()
+            .map(|task| task.trace().to_string())
+            .collect(),
+    }))
+}
+
+#[cfg(not(all(
+    tokio_unstable,
+    tokio_taskdump,
+    target_os = "linux",
+    any(target_arch = "aarch64", target_arch = "x86", target_arch = "x86_64")
+)))]
+pub async fn get_task_dump() -> Result<Json<TaskDumpResponseDTO>, HttpError> {
+    Err(HttpError::new(
+        axum::http::StatusCode::NOT_IMPLEMENTED,
+        ErrorMessage::TaskDumpUnavailable.to_string(),
+    ))
+}
@@ -0,0 +1,14 @@
+pub mod activity;
+pub mod admin;
+pub mod announcements;
+pub mod api_keys;
+pub mod auth;
+#[cfg(feature = "profiling")]
+pub mod debug;
+pub mod oauth;
+pub mod security;
+pub mod users;
+pub mod waitlist;
+pub mod webauthn;
+#[cfg(feature = "stripe")]
+pub mod webhooks;
@@ -0,0 +1,323 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    config::Environment,
+    db::{OAuthAccountExt, UserExt},
+    dtos::{OAuthLinkResponseDTO, Response},
+    error::{ErrorMessage, HttpError},
+    handler::auth::complete_login,
+    middleware::{
+        AuthenticatedUser, ClientContext,
+        geo::{GeoLocation, GeoPolicy},
+        login_throttle::login_throttle,
+        risk::{RiskAction, RiskDecision, risk_check},
+    },
+    models::{AccountStatus, User},
+    nonce::{self, NoncePurpose},
+    oauth::{OAuthProfile, OAuthProvider},
+    utils::token,
+};
+
+const STATE_COOKIE: &str = "oauth_state";
+const STATE_COOKIE_PATH: &str = "/api/auth/oauth";
+const STATE_COOKIE_MINUTES: i64 = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+pub fn oauth_handler() -> Router {
+    Router::new().route("/{provider}", get(authorize)).route(
+        "/{provider}/callback",
+        get(callback)
+            .layer(middleware::from_fn(login_throttle))
+            .layer(middleware::from_fn(|state, req, next| {
+                risk_check(state, req, next, RiskAction::Login)
+            })),
+    )
+}
+
+pub fn oauth_link_handler() -> Router {
+    Router::new().route("/{provider}/link", post(start_link))
+}
+
+fn provider(app_state: &AppState, name: &str) -> Result<Arc<dyn OAuthProvider>, HttpError> {
+    app_state.oauth.get(name).ok_or_else(|| {
+        HttpError::new(
+            StatusCode::NOT_FOUND,
+            ErrorMessage::UnknownOAuthProvider.to_string(),
+        )
+    })
+}
+
+pub async fn authorize(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    let provider = provider(&app_state, &provider_name)?;
+    let state = token::generate_opaque();
+    let authorize_url = authorize_url(provider.as_ref(), &state).await?;
+
+    Ok((
+        cookie_jar.add(state_cookie(&app_state, state)),
+        Redirect::to(&authorize_url),
+    ))
+}
+
+pub async fn start_link(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(provider_name): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    let provider = provider(&app_state, &provider_name)?;
+    let state = token::generate_opaque();
+
+    nonce::store(
+        &app_state.db_client,
+        NoncePurpose::OAuthLink,
+        &state,
+        Some(user.id),
+        Some(provider.name()),
+        STATE_COOKIE_MINUTES * 60,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let authorize_url = authorize_url(provider.as_ref(), &state).await?;
+
+    Ok((
+        cookie_jar.add(state_cookie(&app_state, state)),
+        Json(OAuthLinkResponseDTO {
+            status: "success".to_string(),
+            authorize_url,
+        }),
+    ))
+}
+
+fn state_cookie(app_state: &AppState, state: String) -> Cookie<'static> {
+    Cookie::build((STATE_COOKIE, state))
+        .path(STATE_COOKIE_PATH)
+        .http_only(true)
+        .secure(app_state.env.environment == Environment::Production)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::minutes(STATE_COOKIE_MINUTES))
+        .build()
+}
+
+async fn authorize_url(provider: &dyn OAuthProvider, state: &str) -> Result<String, HttpError> {
+    provider.authorize_url(state).await.map_err(|err| {
+        tracing::warn!("{} sign-in could not start: {}", provider.name(), err);
+        HttpError::server_error(ErrorMessage::OAuthFailed.to_string())
+    })
+}
+
+pub async fn callback(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+    Extension(client): Extension<ClientContext>,
+    risk: Option<Extension<RiskDecision>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, HttpError> {
+    let provider = provider(&app_state, &provider_name)?;
+
+    let expected_state = cookie_jar
+        .get(STATE_COOKIE)
+        .map(|cookie| cookie.value().to_string());
+    let cookie_jar = cookie_jar.remove(Cookie::build(STATE_COOKIE).path(STATE_COOKIE_PATH));
+    if expected_state.as_deref() != Some(query.state.as_str()) {
+        return Err(HttpError::bad_request(
+            ErrorMessage::OAuthStateMismatch.to_string(),
+        ));
+    }
+
+    let location = GeoLocation::from_headers(&headers, &app_state.env);
+    GeoPolicy::login(&app_state.env).enforce(&location, "deployment")?;
+
+    let profile = provider
+        .exchange(&query.code, &query.state)
+        .await
+        .map_err(|err| {
+            tracing::warn!("{} sign-in failed: {}", provider.name(), err);
+            HttpError::bad_request(ErrorMessage::OAuthFailed.to_string())
+        })?;
+
+    let link = nonce::consume(&app_state.db_client, NoncePurpose::OAuthLink, &query.state)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|link| link.payload.as_deref() == Some(provider.name()));
+    if let Some(user_id) = link.and_then(|link| link.user_id) {
+        link_account(&app_state, user_id, provider.name(), &profile, &client).await?;
+        return Ok((
+            cookie_jar,
+            Json(Response {
+                status: "success",
+                message: format!("{} account linked", provider.name()),
+            }),
+        )
+            .into_response());
+    }
+
+    let user = resolve_user(&app_state, provider.name(), &profile, &client).await?;
+
+    if matches!(risk, Some(Extension(RiskDecision::RequireMfa))) && user.totp_enabled_at.is_none() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::RiskMfaRequired.to_string(),
+        ));
+    }
+
+    let response = complete_login(&app_state, &user, &headers, &location, &client).await?;
+
+    Ok((cookie_jar, response).into_response())
+}
+
+async fn link_account(
+    app_state: &AppState,
+    user_id: uuid::Uuid,
+    provider: &str,
+    profile: &OAuthProfile,
+    client: &ClientContext,
+) -> Result<(), HttpError> {
+    let account = app_state
+        .db_client
+        .get_oauth_account(provider, &profile.provider_user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    match account {
+        Some(account) if account.user_id == user_id => return Ok(()),
+        Some(_) => {
+            return Err(HttpError::new(
+                StatusCode::CONFLICT,
+                ErrorMessage::OAuthAccountInUse.to_string(),
+            ));
+        }
+        None => {}
+    }
+
+    app_state
+        .db_client
+        .link_oauth_account(user_id, provider, &profile.provider_user_id, &profile.email)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    audit::record(
+        app_state,
+        AuditEntry::new(AuditEvent::OAuthLinked)
+            .actor(user_id)
+            .target(user_id)
+            .client(client)
+            .detail(serde_json::json!({ "provider": provider })),
+    );
+
+    Ok(())
+}
+
+async fn resolve_user(
+    app_state: &AppState,
+    provider: &str,
+    profile: &OAuthProfile,
+    client: &ClientContext,
+) -> Result<User, HttpError> {
+    let account = app_state
+        .db_client
+        .get_oauth_account(provider, &profile.provider_user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(account) = account {
+        return app_state
+            .db_client
+            .get_user(Some(account.user_id), None, None, None)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNoLongerExist.to_string()));
+    }
+
+    if !profile.email_verified {
+        return Err(HttpError::forbidden(
+            ErrorMessage::OAuthEmailUnverified.to_string(),
+        ));
+    }
+
+    let existing = app_state
+        .db_client
+        .get_user(None, None, Some(&profile.email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(user) = existing {
+        if !user.verified {
+            return Err(HttpError::new(
+                StatusCode::CONFLICT,
+                ErrorMessage::OAuthLinkRequiresSignIn.to_string(),
+            ));
+        }
+
+        link_account(app_state, user.id, provider, profile, client).await?;
+        return Ok(user);
+    }
+
+    if app_state.env.invite_only {
+        return Err(HttpError::forbidden(
+            ErrorMessage::InvitationRequired.to_string(),
+        ));
+    }
+
+    let account_status = if app_state.env.waitlist {
+        AccountStatus::PendingApproval
+    } else {
+        AccountStatus::Active
+    };
+
+    let name = profile
+        .name
+        .clone()
+        .filter(|name| app_state.name_filter.check(name).is_ok())
+        .unwrap_or_else(|| {
+            profile
+                .email
+                .split('@')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+    let hash_password = app_state
+        .passwords
+        .hash(token::generate_opaque())
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .create_oauth_user(
+            &name,
+            &profile.email,
+            &hash_password,
+            account_status,
+            provider,
+            &profile.provider_user_id,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    tracing::warn!(target: "audit", event = "oauth_registered", provider = provider, user_id = %user.id);
+    app_state.hooks.user_registered(&user);
+
+    Ok(user)
+}
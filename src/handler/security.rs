@@ -0,0 +1,139 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use axum::{
+    Extension, Json, Router, extract::Query, http::header, response::IntoResponse, routing::get,
+};
+use chrono::{Duration, Utc};
+use validator::Validate;
+
+use crate::{
+    AppState,
+    audit::{AuditEvent, AuditLogFilter},
+    db::{AuditLogExt, LoginAttemptExt},
+    dtos::{
+        AttackSourceDTO, AttackStatsQueryDTO, AttackStatsResponseDTO, LockoutDTO, ThrottleStatsDTO,
+        ThrottledKeyDTO,
+    },
+    error::HttpError,
+    models::LoginAttempt,
+};
+
+const DEFAULT_ATTACK_LIMIT: i64 = 10;
+
+pub fn security_admin_handler() -> Router {
+    Router::new().route("/attacks", get(get_attack_stats))
+}
+
+pub async fn get_attack_stats(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<AttackStatsQueryDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    query
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let window = query.window.unwrap_or(app_state.env.captcha_window);
+    let limit = query.limit.unwrap_or(DEFAULT_ATTACK_LIMIT);
+    let now = Utc::now();
+    let since = now - Duration::seconds(window as i64);
+    let db_client = &app_state.db_client;
+
+    let top_ips = db_client
+        .get_top_login_failures("ip:", since, limit)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let targeted_accounts = db_client
+        .get_top_login_failures("account:", since, limit)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let lockouts = db_client
+        .get_active_lockouts(limit)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let active_lockouts = db_client
+        .count_active_lockouts()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let failed_logins = db_client
+        .count_audit_logs(&AuditLogFilter {
+            event: Some(AuditEvent::LoginFailed.to_str().to_string()),
+            since: Some(since),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let response = AttackStatsResponseDTO {
+        status: "success".to_string(),
+        generated_at: now,
+        window_seconds: window,
+        failed_logins,
+        top_ips: top_ips.into_iter().map(attack_source).collect(),
+        targeted_accounts: targeted_accounts.into_iter().map(attack_source).collect(),
+        active_lockouts,
+        lockouts: lockouts
+            .into_iter()
+            .map(|attempt| {
+                let (kind, value) = split_key(&attempt.key);
+                LockoutDTO {
+                    kind: kind.to_string(),
+                    value: value.to_string(),
+                    failures: attempt.failures,
+                    locked_until: attempt.locked_until,
+                }
+            })
+            .collect(),
+        throttle: throttle_stats(&app_state, since, limit),
+    };
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+fn split_key(key: &str) -> (&str, &str) {
+    key.split_once(':').unwrap_or(("", key))
+}
+
+fn attack_source(attempt: LoginAttempt) -> AttackSourceDTO {
+    AttackSourceDTO {
+        value: split_key(&attempt.key).1.to_string(),
+        failures: attempt.failures,
+        window_started_at: attempt.window_started_at,
+        last_failure_at: attempt.updated_at,
+        locked_until: attempt.locked_until,
+    }
+}
+
+fn throttle_stats(
+    app_state: &AppState,
+    since: chrono::DateTime<Utc>,
+    limit: i64,
+) -> ThrottleStatsDTO {
+    let events = app_state.rate_limits.throttled.since(since);
+
+    let mut by_scope: BTreeMap<String, i64> = BTreeMap::new();
+    let mut by_key: HashMap<(&str, &str), i64> = HashMap::new();
+    for event in &events {
+        *by_scope.entry(event.scope.to_string()).or_default() += 1;
+        *by_key.entry((event.scope, &event.key)).or_default() += 1;
+    }
+
+    let mut top_keys: Vec<ThrottledKeyDTO> = by_key
+        .into_iter()
+        .map(|((scope, key), rejections)| ThrottledKeyDTO {
+            scope: scope.to_string(),
+            key: key.to_string(),
+            rejections,
+        })
+        .collect();
+    top_keys.sort_by(|a, b| b.rejections.cmp(&a.rejections).then(a.key.cmp(&b.key)));
+    top_keys.truncate(limit as usize);
+
+    ThrottleStatsDTO {
+        rejections: events.len() as i64,
+        by_scope,
+        top_keys,
+    }
+}
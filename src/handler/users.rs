@@ -0,0 +1,823 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    handler::Handler,
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    db::{
+        AuditLogExt, CredentialExt, DelegationExt, LoginAttemptExt, RecoveryEmailExt,
+        SecurityQuestionExt, SessionExt, TwoFactorExt, UsageExt, UserExt,
+    },
+    dtos::{
+        AuditLogQueryDTO, BackupCodesResponseDTO, ConfirmTwoFactorDTO, CreateDelegationDTO,
+        DelegationListResponseDTO, DelegationResponseDTO, FilterUserDTO, MfaMethodDTO,
+        NewUpdateDTO, PasswordSecurityDTO, QueryDTO, QueryOptions, RecoveryEmailDTO,
+        RecoveryEmailResponseDTO, Response, SecurityEventDTO, SecurityOverviewDTO,
+        SecurityOverviewResponseDTO, SecurityQuestionsResponseDTO, SecurityQuestionsUpdateDTO,
+        TwoFactorEnrollResponseDTO, UsageEntryDTO, UsageResponseDTO, UserData,
+        UserLoginResponseDTO, UserResponseDTO,
+    },
+    error::{ErrorMessage, HttpError},
+    handler::{
+        activity::activity_handler, api_keys::api_keys_handler, oauth::oauth_link_handler,
+        webauthn::webauthn_credentials_handler,
+    },
+    middleware::{
+        AuthenticatedUser, ClientContext, TOKEN_COOKIE,
+        cookie_session::SESSION_COOKIE,
+        deny_api_key, deny_delegated,
+        guard::RequirePermission,
+        login_throttle::account_key,
+        quota::{QuotaPeriod, user_subject},
+        require_sudo,
+    },
+    models::Delegation,
+    notify::{Notification, NotificationKind},
+    pagination::Paginated,
+    permissions::Permission,
+    rbac::AuthContext,
+    utils::{backup_code, security_question, token, totp},
+};
+
+const LOW_BACKUP_CODES: i64 = 3;
+
+pub fn users_handler() -> Router {
+    let account_routes = Router::new()
+        .route(
+            "/recovery-email",
+            get(get_recovery_email)
+                .put(set_recovery_email.layer(middleware::from_fn(require_sudo)))
+                .delete(delete_recovery_email.layer(middleware::from_fn(require_sudo))),
+        )
+        .route(
+            "/security-questions",
+            get(get_security_questions).put(update_security_questions),
+        )
+        .route("/delegations", get(get_delegations).post(create_delegation))
+        .route("/delegations/{id}", delete(revoke_delegation))
+        .route("/delegations/{id}/token", post(create_delegation_token))
+        .route(
+            "/2fa/enroll",
+            post(enroll_two_factor).layer(middleware::from_fn(require_sudo)),
+        )
+        .route("/2fa/confirm", post(confirm_two_factor))
+        .route(
+            "/2fa/backup-codes",
+            post(regenerate_backup_codes).layer(middleware::from_fn(require_sudo)),
+        )
+        .nest("/webauthn", webauthn_credentials_handler())
+        .nest("/api-keys", api_keys_handler())
+        .nest("/me/activity", activity_handler())
+        .nest("/oauth", oauth_link_handler())
+        .layer(middleware::from_fn(deny_delegated))
+        .layer(middleware::from_fn(deny_api_key));
+
+    Router::new()
+        .route(
+            "/me",
+            get(get_me)
+                .layer(RequirePermission(Permission::ProfileRead))
+                .delete(
+                    delete_me
+                        .layer(middleware::from_fn(require_sudo))
+                        .layer(middleware::from_fn(deny_delegated))
+                        .layer(middleware::from_fn(deny_api_key))
+                        .layer(RequirePermission(Permission::ProfileWrite)),
+                ),
+        )
+        .route(
+            "/me/security",
+            get(get_security_overview)
+                .layer(middleware::from_fn(deny_delegated))
+                .layer(RequirePermission(Permission::ProfileRead)),
+        )
+        .route(
+            "/me/usage",
+            get(get_usage).layer(RequirePermission(Permission::ProfileRead)),
+        )
+        .route(
+            "/name",
+            put(update_user_name).layer(RequirePermission(Permission::ProfileWrite)),
+        )
+        .route(
+            "/organization/audit-logs",
+            get(get_organization_audit_logs)
+                .layer(middleware::from_fn(deny_delegated))
+                .layer(RequirePermission(Permission::AuditOrgRead)),
+        )
+        .merge(account_routes)
+}
+
+pub fn ensure_name_allowed(app_state: &AppState, name: &str) -> Result<(), HttpError> {
+    app_state.name_filter.check(name).map_err(|violation| {
+        tracing::warn!(target: "audit", event = "name_rejected", reason = ?violation);
+        HttpError::bad_request(ErrorMessage::NameNotAllowed.to_string())
+    })
+}
+
+pub async fn get_organization_audit_logs(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let organization_id = user
+        .organization_id
+        .ok_or_else(|| HttpError::forbidden(ErrorMessage::NoOrganization.to_string()))?;
+
+    let query = AuditLogQueryDTO::parse(&params, &QueryOptions::from_config(&app_state.env))
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let logs = app_state
+        .db_client
+        .get_organization_audit_logs(organization_id, &query.filter, &query.page)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let count = app_state
+        .db_client
+        .count_organization_audit_logs(organization_id, &query.filter)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(Paginated::new(logs, &query.page, count)))
+}
+
+pub async fn get_usage(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let subject = user_subject(user.id);
+    let role = user.role.to_str();
+    let now = Utc::now();
+
+    let mut usage = Vec::with_capacity(QuotaPeriod::ALL.len());
+    for period in QuotaPeriod::ALL {
+        let period_start = period.start(now);
+        let count = app_state
+            .db_client
+            .get_usage(&subject, period.to_str(), period_start)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        usage.push(UsageEntryDTO {
+            period: period.to_str().to_string(),
+            period_start,
+            count,
+            limit: period.limit(&app_state.env, role),
+            resets_at: period.resets_at(now),
+        });
+    }
+
+    Ok(Json(UsageResponseDTO {
+        status: "success".to_string(),
+        usage,
+    }))
+}
+
+pub async fn get_security_overview(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let now = Utc::now();
+    let db_client = &app_state.db_client;
+
+    let credentials = db_client
+        .get_credentials(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let backup_codes = db_client
+        .count_unused_backup_codes(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let security_questions = db_client
+        .get_security_questions(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let recovery_email = db_client
+        .get_recovery_email(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let active_sessions = db_client
+        .get_open_sessions(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .iter()
+        .filter(|session| session.is_active(now))
+        .count();
+    let login_attempt = db_client
+        .get_login_attempt(&account_key(&user.email.to_lowercase()))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let totp_enabled = user.totp_enabled_at.is_some();
+    let mfa_methods = vec![
+        MfaMethodDTO {
+            method: "totp".to_string(),
+            enabled: totp_enabled,
+            count: None,
+        },
+        MfaMethodDTO {
+            method: "webauthn".to_string(),
+            enabled: !credentials.is_empty(),
+            count: Some(credentials.len() as i64),
+        },
+        MfaMethodDTO {
+            method: "backup_codes".to_string(),
+            enabled: backup_codes > 0,
+            count: Some(backup_codes),
+        },
+    ];
+
+    let mut suspicious_events = Vec::new();
+    if let Some(attempt) = &login_attempt
+        && attempt.failures > 0
+    {
+        suspicious_events.push(SecurityEventDTO {
+            kind: "failed_logins".to_string(),
+            count: Some(attempt.failures),
+            occurred_at: attempt.updated_at,
+            until: None,
+        });
+        if let Some(locked_until) = attempt.locked_until.filter(|until| *until > now) {
+            suspicious_events.push(SecurityEventDTO {
+                kind: "login_locked_out".to_string(),
+                count: None,
+                occurred_at: attempt.updated_at,
+                until: Some(locked_until),
+            });
+        }
+    }
+    if let Some(locked_at) = user.locked_at {
+        suspicious_events.push(SecurityEventDTO {
+            kind: "account_locked".to_string(),
+            count: None,
+            occurred_at: locked_at,
+            until: None,
+        });
+    }
+
+    let age_days = (now - user.password_changed_at).num_days();
+    let mut recommendations = Vec::new();
+    if !totp_enabled && credentials.is_empty() {
+        recommendations.push("enable_mfa");
+    }
+    if totp_enabled && backup_codes < LOW_BACKUP_CODES {
+        recommendations.push("regenerate_backup_codes");
+    }
+    if app_state.env.password_max_age_days > 0 && age_days >= app_state.env.password_max_age_days {
+        recommendations.push("change_password");
+    }
+    match &recovery_email {
+        None => recommendations.push("add_recovery_email"),
+        Some(recovery_email) if recovery_email.verified_at.is_none() => {
+            recommendations.push("verify_recovery_email")
+        }
+        Some(_) => {}
+    }
+    if security_questions.is_empty() {
+        recommendations.push("set_security_questions");
+    }
+    if !suspicious_events.is_empty() {
+        recommendations.push("review_recent_activity");
+    }
+
+    Ok(Json(SecurityOverviewResponseDTO {
+        status: "success".to_string(),
+        data: SecurityOverviewDTO {
+            password: PasswordSecurityDTO {
+                changed_at: user.password_changed_at,
+                age_days,
+            },
+            mfa_methods,
+            active_sessions,
+            suspicious_events,
+            recommendations: recommendations.into_iter().map(String::from).collect(),
+        },
+    }))
+}
+
+pub async fn get_me(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn delete_me(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    app_state
+        .db_client
+        .soft_delete_user(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNoLongerExist.to_string()))?;
+
+    tracing::warn!(target: "audit", event = "user_deleted", user_id = %user.id, actor = %user.id);
+
+    let cookie_jar = cookie_jar
+        .remove(Cookie::build(TOKEN_COOKIE).path("/"))
+        .remove(Cookie::build(SESSION_COOKIE).path("/"));
+
+    Ok((
+        cookie_jar,
+        Json(Response {
+            status: "success",
+            message: "Account deleted".to_string(),
+        }),
+    ))
+}
+
+pub async fn update_user_name(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<NewUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    ensure_name_allowed(&app_state, &body.name)?;
+
+    let user = app_state
+        .db_client
+        .update_user_name(user.id, &body.name)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn get_recovery_email(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let recovery_email = app_state
+        .db_client
+        .get_recovery_email(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(RecoveryEmailResponseDTO {
+        status: "success".to_string(),
+        recovery_email,
+    }))
+}
+
+pub async fn set_recovery_email(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+    Json(body): Json<RecoveryEmailDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if body.email.eq_ignore_ascii_case(&user.email) {
+        return Err(HttpError::bad_request(
+            "Recovery email must differ from your primary email",
+        ));
+    }
+
+    let verification_token = uuid::Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::hours(app_state.env.verification_token_ttl);
+
+    let recovery_email = app_state
+        .db_client
+        .set_recovery_email(
+            user.id,
+            &body.email,
+            &token::hash_opaque(&verification_token),
+            expires_at,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    audit::record(
+        &app_state,
+        AuditEntry::new(AuditEvent::RecoveryEmailChanged)
+            .actor(user.id)
+            .target(user.id)
+            .client(&client)
+            .detail(serde_json::json!({ "email": recovery_email.email })),
+    );
+
+    app_state.notifier.spawn(Notification::to_email(
+        NotificationKind::EmailVerification,
+        &recovery_email.email,
+        "Verify your recovery email",
+        format!(
+            "Confirm this address as the recovery email for your account: {}/verify-recovery-email?token={}",
+            app_state.env.app_url, verification_token
+        ),
+    ));
+
+    app_state.notifier.spawn(Notification::to_user(
+        NotificationKind::SecurityAlert,
+        user.id,
+        user.email.clone(),
+        "Your recovery email was changed",
+        format!(
+            "{} was set as the recovery email for your account. If this wasn't you, contact support immediately.",
+            recovery_email.email
+        ),
+    ));
+
+    Ok(Json(RecoveryEmailResponseDTO {
+        status: "success".to_string(),
+        recovery_email: Some(recovery_email),
+    }))
+}
+
+pub async fn delete_recovery_email(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Extension(client): Extension<ClientContext>,
+) -> Result<impl IntoResponse, HttpError> {
+    let removed = app_state
+        .db_client
+        .delete_recovery_email(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(email) = removed {
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::RecoveryEmailChanged)
+                .actor(user.id)
+                .target(user.id)
+                .client(&client)
+                .detail(serde_json::json!({ "email": null, "removed": email })),
+        );
+
+        app_state.notifier.spawn(Notification::to_user(
+            NotificationKind::SecurityAlert,
+            user.id,
+            user.email.clone(),
+            "Your recovery email was removed",
+            format!(
+                "{email} is no longer the recovery email for your account. If this wasn't you, contact support immediately."
+            ),
+        ));
+    }
+
+    Ok(Json(RecoveryEmailResponseDTO {
+        status: "success".to_string(),
+        recovery_email: None,
+    }))
+}
+
+fn ensure_security_questions_enabled(app_state: &AppState) -> Result<(), HttpError> {
+    if !app_state.env.security_questions {
+        return Err(HttpError::forbidden(
+            ErrorMessage::SecurityQuestionsDisabled.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn get_security_questions(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_security_questions_enabled(&app_state)?;
+
+    let questions = app_state
+        .db_client
+        .get_security_questions(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SecurityQuestionsResponseDTO {
+        status: "success".to_string(),
+        questions,
+    }))
+}
+
+pub async fn update_security_questions(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<SecurityQuestionsUpdateDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_security_questions_enabled(&app_state)?;
+
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let required = app_state.env.security_questions_count;
+    if body.questions.len() != required {
+        return Err(HttpError::bad_request(format!(
+            "Exactly {} security questions are required",
+            required
+        )));
+    }
+
+    let password_matched = app_state
+        .passwords
+        .verify(&body.password, &user.password)
+        .map(|verification| verification.matched)
+        .map_err(|_| HttpError::bad_request(ErrorMessage::WrongCredentials.to_string()))?;
+
+    if !password_matched {
+        return Err(HttpError::bad_request(
+            ErrorMessage::WrongCredentials.to_string(),
+        ));
+    }
+
+    let questions = body
+        .questions
+        .iter()
+        .map(|entry| {
+            security_question::hash_answer(&app_state.passwords, &entry.answer)
+                .map(|answer_hash| (entry.question.trim().to_string(), answer_hash))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let questions = app_state
+        .db_client
+        .set_security_questions(user.id, &questions)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SecurityQuestionsResponseDTO {
+        status: "success".to_string(),
+        questions,
+    }))
+}
+
+pub async fn get_delegations(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let delegations = app_state
+        .db_client
+        .get_delegations_for_user(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(DelegationListResponseDTO {
+        status: "success".to_string(),
+        delegations,
+    }))
+}
+
+pub async fn create_delegation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Extension(auth_context): Extension<AuthContext>,
+    Json(body): Json<CreateDelegationDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if let Some(scope) = body
+        .scopes
+        .iter()
+        .find(|scope| !auth_context.has_permission(scope))
+    {
+        return Err(HttpError::bad_request(format!(
+            "Scope cannot be delegated: {}",
+            scope
+        )));
+    }
+
+    let delegate = app_state
+        .db_client
+        .get_user(None, None, Some(&body.delegate_email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "User not found"))?;
+
+    if delegate.id == user.id {
+        return Err(HttpError::bad_request(
+            "You cannot delegate access to yourself",
+        ));
+    }
+
+    let delegation = app_state
+        .db_client
+        .create_delegation(user.id, delegate.id, &body.scopes)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                HttpError::unique_constraint_violation(
+                    "An active delegation already exists for this user",
+                )
+            }
+            e => HttpError::server_error(e.to_string()),
+        })?;
+
+    tracing::warn!(
+        target: "audit",
+        event = "delegation_granted",
+        owner_id = %delegation.owner_id,
+        delegate_id = %delegation.delegate_id,
+        scopes = ?delegation.scopes
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(DelegationResponseDTO {
+            status: "success".to_string(),
+            delegation,
+        }),
+    ))
+}
+
+async fn active_delegation_for(
+    app_state: &AppState,
+    delegation_id: Uuid,
+    user_id: Uuid,
+) -> Result<Delegation, HttpError> {
+    app_state
+        .db_client
+        .get_delegation(delegation_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|delegation| {
+            delegation.revoked_at.is_none()
+                && (delegation.owner_id == user_id || delegation.delegate_id == user_id)
+        })
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Delegation not found"))
+}
+
+pub async fn revoke_delegation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let delegation = active_delegation_for(&app_state, id, user.id).await?;
+
+    let delegation = app_state
+        .db_client
+        .revoke_delegation(delegation.id, user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tracing::warn!(
+        target: "audit",
+        event = "delegation_revoked",
+        owner_id = %delegation.owner_id,
+        delegate_id = %delegation.delegate_id,
+        revoked_by = %user.id
+    );
+
+    Ok(Json(DelegationResponseDTO {
+        status: "success".to_string(),
+        delegation,
+    }))
+}
+
+pub async fn create_delegation_token(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let delegation = active_delegation_for(&app_state, id, user.id).await?;
+
+    if delegation.delegate_id != user.id {
+        return Err(HttpError::forbidden(
+            ErrorMessage::PermissionDenied.to_string(),
+        ));
+    }
+
+    let token = token::create_delegated_token(
+        &user.id.to_string(),
+        Some(&delegation.owner_id.to_string()),
+        &app_state.jwt_keys,
+        app_state.env.jwt_maxage,
+    )
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(UserLoginResponseDTO {
+        status: "success".to_string(),
+        token: Some(token),
+        refresh_token: None,
+    }))
+}
+
+pub async fn enroll_two_factor(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    if user.totp_enabled_at.is_some() {
+        return Err(HttpError::new(
+            StatusCode::CONFLICT,
+            ErrorMessage::TwoFactorAlreadyEnabled.to_string(),
+        ));
+    }
+
+    let secret = totp::generate_secret();
+    app_state
+        .db_client
+        .set_totp_secret(user.id, &secret)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let otpauth_url = totp::otpauth_url(&app_state.env.totp_issuer, &user.email, &secret);
+
+    Ok(Json(TwoFactorEnrollResponseDTO {
+        status: "success".to_string(),
+        secret,
+        otpauth_url,
+    }))
+}
+
+pub async fn confirm_two_factor(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<ConfirmTwoFactorDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(HttpError::new(
+            StatusCode::CONFLICT,
+            ErrorMessage::TwoFactorAlreadyEnabled.to_string(),
+        ));
+    }
+
+    let secret = user.totp_secret.as_deref().ok_or(HttpError::bad_request(
+        ErrorMessage::TwoFactorNotEnrolled.to_string(),
+    ))?;
+
+    if !totp::verify(secret, &body.code, Utc::now().timestamp()) {
+        return Err(HttpError::bad_request(
+            ErrorMessage::InvalidTwoFactorCode.to_string(),
+        ));
+    }
+
+    let backup_codes = backup_code::generate(app_state.env.backup_code_count);
+    let hashes: Vec<String> = backup_codes
+        .iter()
+        .map(|code| backup_code::hash(code))
+        .collect();
+
+    app_state
+        .db_client
+        .enable_totp(user.id, &hashes)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tracing::warn!(target: "audit", event = "two_factor_enabled", user_id = %user.id);
+
+    Ok(Json(BackupCodesResponseDTO {
+        status: "success".to_string(),
+        backup_codes,
+    }))
+}
+
+pub async fn regenerate_backup_codes(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    if user.totp_enabled_at.is_none() {
+        return Err(HttpError::bad_request(
+            ErrorMessage::TwoFactorNotEnrolled.to_string(),
+        ));
+    }
+
+    let backup_codes = backup_code::generate(app_state.env.backup_code_count);
+    let hashes: Vec<String> = backup_codes
+        .iter()
+        .map(|code| backup_code::hash(code))
+        .collect();
+
+    app_state
+        .db_client
+        .replace_backup_codes(user.id, &hashes)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tracing::warn!(target: "audit", event = "backup_codes_regenerated", user_id = %user.id);
+
+    Ok(Json(BackupCodesResponseDTO {
+        status: "success".to_string(),
+        backup_codes,
+    }))
+}
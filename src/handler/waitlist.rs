@@ -0,0 +1,176 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    middleware,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::{OrganizationExt, UserExt},
+    dtos::{FilterUserDTO, QueryDTO, QueryOptions, UserData, UserResponseDTO},
+    error::{ErrorMessage, HttpError},
+    mail::templates::EmailTemplate,
+    middleware::require_sudo,
+    models::{AccountStatus, RegistrationState, User},
+    notify::{Notification, NotificationKind},
+    pagination::{PageQuery, Paginated},
+    utils::{public_id::PublicId, token},
+};
+
+pub fn waitlist_handler() -> Router {
+    Router::new()
+        .route("/", get(get_waitlist))
+        .route("/{id}/approve", post(approve_user))
+        .route(
+            "/{id}/reject",
+            post(reject_user).layer(middleware::from_fn(require_sudo)),
+        )
+}
+
+pub async fn get_waitlist(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let query = PageQuery::<User>::parse(&params, &QueryOptions::from_config(&app_state.env))
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let users = app_state
+        .db_client
+        .get_users_by_status(AccountStatus::PendingApproval, &query)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let count = app_state
+        .db_client
+        .count_users_by_status(AccountStatus::PendingApproval)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(Paginated::new(
+        FilterUserDTO::filter_users(&users),
+        &query,
+        count,
+    )))
+}
+
+async fn pending_user(app_state: &AppState, user_id: Uuid) -> Result<User, HttpError> {
+    let user = app_state
+        .db_client
+        .get_user(Some(user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request(ErrorMessage::UserNoLongerExist.to_string()))?;
+
+    if user.account_status != AccountStatus::PendingApproval {
+        return Err(HttpError::bad_request("User is not awaiting approval"));
+    }
+
+    Ok(user)
+}
+
+pub async fn approve_user(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<impl IntoResponse, HttpError> {
+    let user = pending_user(&app_state, id).await?;
+
+    let user = app_state
+        .db_client
+        .set_account_status(user.id, AccountStatus::Active)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let verification_token = Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::hours(app_state.env.verification_token_ttl);
+
+    app_state
+        .db_client
+        .add_verification_token(
+            user.id,
+            &token::hash_opaque(&verification_token),
+            expires_at,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let branding = app_state
+        .db_client
+        .get_email_branding(&user)
+        .await
+        .unwrap_or(None);
+
+    let complete_registration = user.registration_state == RegistrationState::PendingProfile;
+    let action_url = if complete_registration {
+        format!(
+            "{}/complete-registration?token={}",
+            app_state.env.app_url, verification_token
+        )
+    } else {
+        format!(
+            "{}/verify?token={}",
+            app_state.env.app_url, verification_token
+        )
+    };
+
+    app_state.notifier.spawn(
+        Notification::to_user(
+            NotificationKind::EmailVerification,
+            user.id,
+            &user.email,
+            "Your account has been approved",
+            if complete_registration {
+                format!(
+                    "Your account has been approved. Verify your email and finish creating your account: {}",
+                    action_url
+                )
+            } else {
+                format!(
+                    "Your account has been approved. Verify your email to get started: {}",
+                    action_url
+                )
+            },
+        )
+        .with_branding(branding)
+        .with_template(
+            EmailTemplate::Verification,
+            serde_json::json!({
+                "action_url": action_url,
+                "expires_in_hours": app_state.env.verification_token_ttl,
+                "complete_registration": complete_registration,
+            }),
+        ),
+    );
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
+
+pub async fn reject_user(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(PublicId(id)): Path<PublicId>,
+) -> Result<impl IntoResponse, HttpError> {
+    let user = pending_user(&app_state, id).await?;
+
+    let user = app_state
+        .db_client
+        .set_account_status(user.id, AccountStatus::Rejected)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(UserResponseDTO {
+        status: "success".to_string(),
+        data: UserData {
+            user: FilterUserDTO::filter_user(&user),
+        },
+    }))
+}
@@ -0,0 +1,362 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+use uuid::Uuid;
+use validator::Validate;
+use webauthn_rs::prelude::{Passkey, PasskeyAuthentication, PasskeyRegistration};
+
+use crate::{
+    AppState,
+    db::{CredentialExt, UserExt},
+    dtos::{
+        CredentialListResponseDTO, CredentialResponseDTO, PasskeyChallengeResponseDTO,
+        PasskeyLoginFinishDTO, PasskeyLoginStartDTO, PasskeyRegisterFinishDTO, Response,
+    },
+    error::{ErrorMessage, HttpError},
+    handler::auth::start_session,
+    middleware::{
+        AuthenticatedUser, ClientContext,
+        geo::{GeoLocation, geo_login},
+        login_throttle::login_throttle,
+        require_sudo,
+    },
+    models::Credential,
+    nonce::{self, NoncePurpose},
+    notify::{Notification, NotificationKind},
+    webauthn,
+};
+
+pub fn webauthn_login_handler() -> Router {
+    Router::new()
+        .route(
+            "/login/start",
+            post(start_login).layer(middleware::from_fn(login_throttle)),
+        )
+        .route(
+            "/login/finish",
+            post(finish_login)
+                .layer(middleware::from_fn(geo_login))
+                .layer(middleware::from_fn(login_throttle)),
+        )
+}
+
+pub fn webauthn_credentials_handler() -> Router {
+    Router::new()
+        .route(
+            "/register/start",
+            post(start_registration).layer(middleware::from_fn(require_sudo)),
+        )
+        .route(
+            "/register/finish",
+            post(finish_registration).layer(middleware::from_fn(require_sudo)),
+        )
+        .route("/credentials", get(get_credentials))
+        .route(
+            "/credentials/{id}",
+            delete(delete_credential).layer(middleware::from_fn(require_sudo)),
+        )
+}
+
+pub async fn start_registration(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = app_state
+        .db_client
+        .get_credentials(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let exclude_credentials = existing
+        .iter()
+        .filter_map(|credential| decode_passkey(credential).ok())
+        .map(|passkey| passkey.cred_id().clone())
+        .collect();
+
+    let (options, state) = app_state
+        .webauthn
+        .start_passkey_registration(user.id, &user.email, &user.name, Some(exclude_credentials))
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let ceremony_id = save_ceremony(
+        &app_state,
+        user.id,
+        NoncePurpose::WebAuthnRegistration,
+        &state,
+    )
+    .await?;
+
+    Ok(Json(PasskeyChallengeResponseDTO {
+        status: "success".to_string(),
+        ceremony_id,
+        options,
+    }))
+}
+
+pub async fn finish_registration(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<PasskeyRegisterFinishDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let (user_id, state): (Uuid, PasskeyRegistration) = take_ceremony(
+        &app_state,
+        &body.ceremony_id,
+        NoncePurpose::WebAuthnRegistration,
+    )
+    .await?;
+    if user_id != user.id {
+        return Err(HttpError::bad_request(
+            ErrorMessage::PasskeyChallengeExpired.to_string(),
+        ));
+    }
+
+    let passkey = app_state
+        .webauthn
+        .finish_passkey_registration(&body.credential, &state)
+        .map_err(|_| HttpError::bad_request(ErrorMessage::PasskeyVerificationFailed.to_string()))?;
+
+    let serialized =
+        serde_json::to_string(&passkey).map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let credential = app_state
+        .db_client
+        .create_credential(
+            user.id,
+            &body.name,
+            &webauthn::credential_id(&passkey),
+            &serialized,
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                HttpError::new(StatusCode::CONFLICT, "This passkey is already registered")
+            }
+            e => HttpError::server_error(e.to_string()),
+        })?;
+
+    tracing::warn!(target: "audit", event = "passkey_registered", user_id = %user.id, credential_id = %credential.id);
+
+    app_state.notifier.spawn(Notification::to_user(
+        NotificationKind::SecurityAlert,
+        user.id,
+        user.email.clone(),
+        "A passkey was added to your account",
+        format!(
+            "The passkey \"{}\" can now be used to sign in to your account.",
+            credential.name
+        ),
+    ));
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CredentialResponseDTO {
+            status: "success".to_string(),
+            credential,
+        }),
+    ))
+}
+
+pub async fn get_credentials(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let credentials = app_state
+        .db_client
+        .get_credentials(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(CredentialListResponseDTO {
+        status: "success".to_string(),
+        credentials,
+    }))
+}
+
+pub async fn delete_credential(
+    Extension(app_state): Extension<Arc<AppState>>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let credential = app_state
+        .db_client
+        .delete_credential(user.id, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::new(StatusCode::NOT_FOUND, "Passkey not found"))?;
+
+    tracing::warn!(target: "audit", event = "passkey_removed", user_id = %user.id, credential_id = %credential.id);
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Passkey removed".to_string(),
+    }))
+}
+
+pub async fn start_login(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<PasskeyLoginStartDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    body.validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user(None, None, Some(&body.email), None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request(
+            ErrorMessage::WrongCredentials.to_string(),
+        ))?;
+
+    let passkeys: Vec<Passkey> = app_state
+        .db_client
+        .get_credentials(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .iter()
+        .filter_map(|credential| decode_passkey(credential).ok())
+        .collect();
+
+    if passkeys.is_empty() {
+        return Err(HttpError::bad_request(
+            ErrorMessage::WrongCredentials.to_string(),
+        ));
+    }
+
+    let (options, state) = app_state
+        .webauthn
+        .start_passkey_authentication(&passkeys)
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let ceremony_id = save_ceremony(
+        &app_state,
+        user.id,
+        NoncePurpose::WebAuthnAuthentication,
+        &state,
+    )
+    .await?;
+
+    Ok(Json(PasskeyChallengeResponseDTO {
+        status: "success".to_string(),
+        ceremony_id,
+        options,
+    }))
+}
+
+pub async fn finish_login(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(location): Extension<GeoLocation>,
+    Extension(client): Extension<ClientContext>,
+    headers: HeaderMap,
+    Json(body): Json<PasskeyLoginFinishDTO>,
+) -> Result<impl IntoResponse, HttpError> {
+    let (user_id, state): (Uuid, PasskeyAuthentication) = take_ceremony(
+        &app_state,
+        &body.ceremony_id,
+        NoncePurpose::WebAuthnAuthentication,
+    )
+    .await?;
+
+    let result = app_state
+        .webauthn
+        .finish_passkey_authentication(&body.credential, &state)
+        .map_err(|_| HttpError::bad_request(ErrorMessage::PasskeyVerificationFailed.to_string()))?;
+
+    let user = app_state
+        .db_client
+        .get_user(Some(user_id), None, None, None)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::unauthorized(
+            ErrorMessage::UserNoLongerExist.to_string(),
+        ))?;
+
+    let credentials = app_state
+        .db_client
+        .get_credentials(user.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    for credential in &credentials {
+        let Ok(mut passkey) = decode_passkey(credential) else {
+            continue;
+        };
+        let Some(changed) = passkey.update_credential(&result) else {
+            continue;
+        };
+
+        let updated = if changed {
+            Some(
+                serde_json::to_string(&passkey)
+                    .map_err(|e| HttpError::server_error(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+
+        app_state
+            .db_client
+            .record_credential_use(credential.id, updated.as_deref())
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        break;
+    }
+
+    let response = start_session(&app_state, &user, &headers, &location, &client).await?;
+
+    Ok(response)
+}
+
+fn decode_passkey(credential: &Credential) -> Result<Passkey, serde_json::Error> {
+    serde_json::from_str(&credential.passkey)
+}
+
+async fn save_ceremony<T: serde::Serialize>(
+    app_state: &AppState,
+    user_id: Uuid,
+    purpose: NoncePurpose,
+    state: &T,
+) -> Result<String, HttpError> {
+    let state = serde_json::to_string(state).map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let ceremony = nonce::issue(
+        &app_state.db_client,
+        purpose,
+        Some(user_id),
+        Some(&state),
+        app_state.env.webauthn_ceremony_ttl,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(ceremony.nonce)
+}
+
+async fn take_ceremony<T: serde::de::DeserializeOwned>(
+    app_state: &AppState,
+    id: &str,
+    purpose: NoncePurpose,
+) -> Result<(Uuid, T), HttpError> {
+    let expired = || HttpError::bad_request(ErrorMessage::PasskeyChallengeExpired.to_string());
+
+    let ceremony = nonce::consume(&app_state.db_client, purpose, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(expired)?;
+    let (Some(user_id), Some(state)) = (ceremony.user_id, ceremony.payload) else {
+        return Err(expired());
+    };
+
+    let state = serde_json::from_str(&state).map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok((user_id, state))
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Extension, Json, Router, body::Bytes, http::HeaderMap, response::IntoResponse, routing::post,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::{OrganizationExt, UserExt},
+    dtos::Response,
+    error::{ErrorMessage, HttpError},
+    handler::admin::plan_changed,
+};
+
+const SIGNATURE_HEADER: &str = "stripe-signature";
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+#[derive(Debug, Deserialize)]
+pub struct StripeEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub created: i64,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeEventData {
+    pub object: StripeSubscription,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeSubscription {
+    pub status: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    pub items: StripeSubscriptionItems,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeSubscriptionItems {
+    pub data: Vec<StripeSubscriptionItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeSubscriptionItem {
+    pub price: StripePrice,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripePrice {
+    pub id: String,
+}
+
+pub fn webhooks_handler() -> Router {
+    Router::new().route("/stripe", post(stripe_webhook))
+}
+
+fn verify_signature(secret: &str, header: &str, payload: &[u8]) -> bool {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(hex::decode(value).ok()),
+            _ => {}
+        }
+    }
+
+    let Some(timestamp) = timestamp else {
+        return false;
+    };
+    if (Utc::now().timestamp() - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        return false;
+    }
+
+    signatures.iter().any(|signature| {
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        mac.verify_slice(signature).is_ok()
+    })
+}
+
+pub async fn stripe_webhook(
+    Extension(app_state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, HttpError> {
+    let Some(secret) = &app_state.env.stripe_webhook_secret else {
+        return Err(HttpError::server_error(
+            "Stripe webhooks are not configured",
+        ));
+    };
+
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !verify_signature(secret, signature, &body) {
+        return Err(HttpError::bad_request(
+            ErrorMessage::InvalidSignature.to_string(),
+        ));
+    }
+
+    let event = match serde_json::from_slice::<StripeEvent>(&body) {
+        Ok(event) if event.event_type.starts_with("customer.subscription.") => event,
+        _ => {
+            return Ok(Json(Response {
+                status: "success",
+                message: "Event ignored".to_string(),
+            }));
+        }
+    };
+
+    let subscription = event.data.object;
+    let plan = if event.event_type == "customer.subscription.deleted"
+        || !matches!(subscription.status.as_str(), "active" | "trialing")
+    {
+        None
+    } else {
+        subscription
+            .items
+            .data
+            .iter()
+            .find_map(|item| app_state.env.stripe_price_plans.get(&item.price.id))
+            .cloned()
+    };
+
+    let metadata_id = |key: &str| {
+        subscription
+            .metadata
+            .get(key)
+            .and_then(|id| Uuid::parse_str(id).ok())
+    };
+
+    let Some(event_at) = DateTime::from_timestamp(event.created, 0) else {
+        return Err(HttpError::bad_request("Invalid event timestamp"));
+    };
+
+    let target = if let Some(org_id) = metadata_id("organization_id") {
+        app_state
+            .db_client
+            .sync_organization_plan(org_id, plan.as_deref(), event_at)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .map(|organization| format!("organization {}", organization.id))
+    } else if let Some(user_id) = metadata_id("user_id") {
+        app_state
+            .db_client
+            .sync_user_plan(user_id, plan.as_deref(), event_at)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .map(|user| format!("user {}", user.id))
+    } else {
+        None
+    };
+
+    match target {
+        Some(target) => plan_changed(&app_state, target, plan.as_deref(), "stripe"),
+        None => tracing::warn!(
+            "stripe {} event did not match a user or organization, or is older than the last applied event",
+            event.event_type
+        ),
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "Event processed".to_string(),
+    }))
+}
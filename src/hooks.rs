@@ -0,0 +1,130 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{middleware::ClientContext, models::User};
+
+#[derive(Debug)]
+pub struct HookError(pub String);
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HookError: {}", self.0)
+    }
+}
+
+impl std::error::Error for HookError {}
+
+#[async_trait]
+pub trait OnUserRegistered: Send + Sync {
+    async fn on_user_registered(&self, user: &User) -> Result<(), HookError>;
+}
+
+#[async_trait]
+pub trait BeforeLogin: Send + Sync {
+    async fn before_login(&self, user: &User, client: &ClientContext) -> Result<(), HookError>;
+}
+
+#[async_trait]
+pub trait AfterLogin: Send + Sync {
+    async fn after_login(&self, user: &User, client: &ClientContext) -> Result<(), HookError>;
+}
+
+#[async_trait]
+pub trait OnPasswordChanged: Send + Sync {
+    async fn on_password_changed(&self, user: &User) -> Result<(), HookError>;
+}
+
+#[derive(Clone, Default)]
+pub struct Hooks {
+    user_registered: Vec<Arc<dyn OnUserRegistered>>,
+    before_login: Vec<Arc<dyn BeforeLogin>>,
+    after_login: Vec<Arc<dyn AfterLogin>>,
+    password_changed: Vec<Arc<dyn OnPasswordChanged>>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("user_registered", &self.user_registered.len())
+            .field("before_login", &self.before_login.len())
+            .field("after_login", &self.after_login.len())
+            .field("password_changed", &self.password_changed.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    pub fn on_user_registered(&mut self, hook: Arc<dyn OnUserRegistered>) {
+        self.user_registered.push(hook);
+    }
+
+    pub fn before_login(&mut self, hook: Arc<dyn BeforeLogin>) {
+        self.before_login.push(hook);
+    }
+
+    pub fn after_login(&mut self, hook: Arc<dyn AfterLogin>) {
+        self.after_login.push(hook);
+    }
+
+    pub fn on_password_changed(&mut self, hook: Arc<dyn OnPasswordChanged>) {
+        self.password_changed.push(hook);
+    }
+
+    pub fn user_registered(&self, user: &User) {
+        if self.user_registered.is_empty() {
+            return;
+        }
+
+        let hooks = self.user_registered.clone();
+        let user = user.clone();
+        tokio::spawn(async move {
+            for hook in hooks {
+                if let Err(err) = hook.on_user_registered(&user).await {
+                    tracing::warn!("user registered hook failed for {}: {}", user.id, err);
+                }
+            }
+        });
+    }
+
+    pub async fn check_login(&self, user: &User, client: &ClientContext) -> Result<(), HookError> {
+        for hook in &self.before_login {
+            hook.before_login(user, client).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn logged_in(&self, user: &User, client: &ClientContext) {
+        if self.after_login.is_empty() {
+            return;
+        }
+
+        let hooks = self.after_login.clone();
+        let user = user.clone();
+        let client = client.clone();
+        tokio::spawn(async move {
+            for hook in hooks {
+                if let Err(err) = hook.after_login(&user, &client).await {
+                    tracing::warn!("after login hook failed for {}: {}", user.id, err);
+                }
+            }
+        });
+    }
+
+    pub fn password_changed(&self, user: &User) {
+        if self.password_changed.is_empty() {
+            return;
+        }
+
+        let hooks = self.password_changed.clone();
+        let user = user.clone();
+        tokio::spawn(async move {
+            for hook in hooks {
+                if let Err(err) = hook.on_password_changed(&user).await {
+                    tracing::warn!("password changed hook failed for {}: {}", user.id, err);
+                }
+            }
+        });
+    }
+}
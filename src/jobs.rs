@@ -0,0 +1,277 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+
+use crate::{
+    AppState,
+    db::{
+        LoginAttemptExt, NonceExt, OutboxExt, RevokedTokenExt, ScheduledJobExt, UsageExt, UserExt,
+    },
+    mail::{self, outbox},
+    notify::{Notification, NotificationKind},
+};
+
+const USAGE_RETENTION_DAYS: i64 = 400;
+const SENT_EMAIL_RETENTION_DAYS: i64 = 30;
+
+pub const CLEANUP_JOB: &str = "cleanup";
+pub const STALE_ACCOUNTS_JOB: &str = "stale_accounts";
+pub const ACCOUNT_PURGE_JOB: &str = "account_purge";
+
+pub fn spawn_cleanup(app_state: Arc<AppState>) {
+    let interval = Duration::from_secs(app_state.env.cleanup_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            run_exclusive(&app_state, CLEANUP_JOB, interval, run_cleanup(&app_state)).await;
+            if app_state.env.stale_account_months > 0 {
+                run_exclusive(
+                    &app_state,
+                    STALE_ACCOUNTS_JOB,
+                    interval,
+                    run_stale_account_sweep(&app_state),
+                )
+                .await;
+            }
+            if app_state.env.deleted_account_retention_days > 0 {
+                run_exclusive(
+                    &app_state,
+                    ACCOUNT_PURGE_JOB,
+                    interval,
+                    run_deleted_account_purge(&app_state),
+                )
+                .await;
+            }
+        }
+    });
+}
+
+async fn run_exclusive(
+    app_state: &AppState,
+    name: &str,
+    interval: Duration,
+    job: impl Future<Output = Result<(), String>>,
+) {
+    let env = &app_state.env;
+    let claimed = app_state
+        .db_client
+        .claim_scheduled_job(
+            name,
+            &env.instance_id,
+            interval.as_secs_f64() * 0.9,
+            env.job_lease_ttl as f64,
+        )
+        .await;
+
+    match claimed {
+        Ok(true) => {}
+        Ok(false) => {
+            app_state
+                .metrics
+                .increment(&format!("jobs.{}.skipped", name));
+            return;
+        }
+        Err(err) => {
+            tracing::warn!("failed to claim scheduled job {}: {}", name, err);
+            return;
+        }
+    }
+
+    let started = Instant::now();
+    let result = job.await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    app_state.metrics.increment(&format!("jobs.{}.runs", name));
+    if result.is_err() {
+        app_state
+            .metrics
+            .increment(&format!("jobs.{}.failed", name));
+    }
+    tracing::info!(
+        job = name,
+        instance_id = %env.instance_id,
+        duration_ms,
+        ok = result.is_ok(),
+        "scheduled job finished"
+    );
+
+    if let Err(err) = app_state
+        .db_client
+        .finish_scheduled_job(name, &env.instance_id, duration_ms, result.err().as_deref())
+        .await
+    {
+        tracing::warn!("failed to release scheduled job {}: {}", name, err);
+    }
+}
+
+pub fn spawn_outbox(app_state: Arc<AppState>) {
+    if !app_state.env.email_outbox {
+        return;
+    }
+    let Some(sender) = mail::guarded(&app_state.env, &app_state.metrics) else {
+        return;
+    };
+    let interval = Duration::from_secs(app_state.env.outbox_poll_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            loop {
+                match outbox::deliver_due(&app_state.db_client, sender.as_ref(), &app_state.env)
+                    .await
+                {
+                    Ok(claimed) if claimed as i64 >= app_state.env.outbox_batch_size => continue,
+                    Ok(_) => break,
+                    Err(err) => {
+                        tracing::warn!("failed to process email outbox: {}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_cleanup(app_state: &AppState) -> Result<(), String> {
+    let mut failed = Vec::new();
+
+    match app_state
+        .db_client
+        .delete_stale_login_attempts(app_state.env.captcha_window)
+        .await
+    {
+        Ok(0) => {}
+        Ok(deleted) => tracing::info!("Removed {} stale login attempt records", deleted),
+        Err(err) => {
+            tracing::warn!("failed to clean up login attempts: {}", err);
+            failed.push("login attempts");
+        }
+    }
+
+    let usage_cutoff = Utc::now().date_naive() - chrono::Duration::days(USAGE_RETENTION_DAYS);
+    match app_state.db_client.delete_usage_before(usage_cutoff).await {
+        Ok(0) => {}
+        Ok(deleted) => tracing::info!("Removed {} old usage counters", deleted),
+        Err(err) => {
+            tracing::warn!("failed to clean up usage counters: {}", err);
+            failed.push("usage counters");
+        }
+    }
+
+    match app_state.db_client.delete_expired_revoked_tokens().await {
+        Ok(0) => {}
+        Ok(deleted) => tracing::info!("Removed {} expired token revocations", deleted),
+        Err(err) => {
+            tracing::warn!("failed to clean up revoked tokens: {}", err);
+            failed.push("revoked tokens");
+        }
+    }
+
+    match app_state.db_client.delete_expired_nonces().await {
+        Ok(0) => {}
+        Ok(deleted) => tracing::info!("Removed {} expired nonces", deleted),
+        Err(err) => {
+            tracing::warn!("failed to clean up nonces: {}", err);
+            failed.push("nonces");
+        }
+    }
+
+    let sent_cutoff = Utc::now() - chrono::Duration::days(SENT_EMAIL_RETENTION_DAYS);
+    match app_state
+        .db_client
+        .delete_sent_emails_before(sent_cutoff)
+        .await
+    {
+        Ok(0) => {}
+        Ok(deleted) => tracing::info!("Removed {} delivered outbox emails", deleted),
+        Err(err) => {
+            tracing::warn!("failed to clean up email outbox: {}", err);
+            failed.push("email outbox");
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("failed to clean up {}", failed.join(", ")))
+    }
+}
+
+async fn run_stale_account_sweep(app_state: &AppState) -> Result<(), String> {
+    let env = &app_state.env;
+    let mut failed = Vec::new();
+
+    match app_state
+        .db_client
+        .flag_dormant_users(env.stale_account_months)
+        .await
+    {
+        Ok(users) => {
+            for user in users {
+                tracing::warn!(target: "audit", event = "account_dormant", user_id = %user.id);
+                app_state.notifier.spawn(Notification::to_user(
+                    NotificationKind::AccountDormant,
+                    user.id,
+                    &user.email,
+                    "Your account will be deactivated",
+                    format!(
+                        "We haven't seen you in over {} months. Your account will be deactivated in {} days unless you log in at {}. You can reactivate it later by logging in again.",
+                        env.stale_account_months, env.stale_account_grace_days, env.app_url
+                    ),
+                ));
+            }
+        }
+        Err(err) => {
+            tracing::warn!("failed to flag dormant accounts: {}", err);
+            failed.push(format!("flag dormant accounts: {}", err));
+        }
+    }
+
+    match app_state
+        .db_client
+        .deactivate_dormant_users(env.stale_account_grace_days)
+        .await
+    {
+        Ok(users) => {
+            for user in users {
+                tracing::warn!(target: "audit", event = "account_deactivated", user_id = %user.id);
+            }
+        }
+        Err(err) => {
+            tracing::warn!("failed to deactivate dormant accounts: {}", err);
+            failed.push(format!("deactivate dormant accounts: {}", err));
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed.join("; "))
+    }
+}
+
+async fn run_deleted_account_purge(app_state: &AppState) -> Result<(), String> {
+    match app_state
+        .db_client
+        .purge_deleted_users(app_state.env.deleted_account_retention_days)
+        .await
+    {
+        Ok(user_ids) => {
+            for user_id in user_ids {
+                tracing::warn!(target: "audit", event = "account_purged", user_id = %user_id);
+            }
+            Ok(())
+        }
+        Err(err) => {
+            tracing::warn!("failed to purge deleted accounts: {}", err);
+            Err(err.to_string())
+        }
+    }
+}
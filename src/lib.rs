@@ -0,0 +1,180 @@
+pub mod audit;
+pub mod bootstrap;
+pub mod circuit;
+pub mod config;
+pub mod db;
+pub mod doctor;
+pub mod dtos;
+pub mod error;
+pub mod handler;
+pub mod hooks;
+pub mod jobs;
+pub mod mail;
+pub mod metrics;
+pub mod middleware;
+pub mod models;
+pub mod nonce;
+pub mod notify;
+pub mod oauth;
+pub mod pagination;
+pub mod permissions;
+pub mod rbac;
+#[cfg(feature = "redis")]
+pub mod redis_client;
+pub mod revocation;
+pub mod route_catalog;
+pub mod routes;
+pub mod startup;
+pub mod user_cache;
+pub mod utils;
+pub mod webauthn;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+use std::sync::Arc;
+
+use audit::AuditChain;
+use config::Config;
+use db::DBClient;
+use hooks::{AfterLogin, BeforeLogin, Hooks, OnPasswordChanged, OnUserRegistered};
+use metrics::Metrics;
+use middleware::{
+    captcha::CaptchaPolicy,
+    idempotency::IdempotencyStore,
+    maintenance::MaintenanceMode,
+    rate_limit::RateLimits,
+    read_only::ReadOnlyMode,
+    risk::{RiskAssessor, RiskPolicy},
+    tarpit::Tarpit,
+};
+use notify::NotificationDispatcher;
+use oauth::OAuthProviders;
+use rbac::PermissionCache;
+use revocation::RevocationCache;
+use user_cache::UserCache;
+use utils::{
+    name_filter::NameFilter, password::Passwords, secret_scanning::PublicKeyCache, token::JwtKeys,
+};
+use webauthn_rs::prelude::Webauthn;
+
+#[derive(Debug, Clone)]
+pub struct AppState {
+    pub env: Config,
+    pub db_client: DBClient,
+    pub permission_cache: PermissionCache,
+    pub maintenance: MaintenanceMode,
+    pub read_only: ReadOnlyMode,
+    pub idempotency: IdempotencyStore,
+    pub metrics: Metrics,
+    pub tarpit: Tarpit,
+    pub notifier: NotificationDispatcher,
+    pub rate_limits: RateLimits,
+    pub name_filter: NameFilter,
+    pub captcha: CaptchaPolicy,
+    pub risk: RiskPolicy,
+    pub oauth: OAuthProviders,
+    pub webauthn: Arc<Webauthn>,
+    pub jwt_keys: JwtKeys,
+    pub passwords: Passwords,
+    pub hooks: Hooks,
+    pub audit_chain: AuditChain,
+    pub revocations: RevocationCache,
+    pub user_cache: UserCache,
+    pub secret_scanning_keys: PublicKeyCache,
+}
+
+impl AppState {
+    pub fn new(env: Config, db_client: DBClient) -> Self {
+        AppBuilder::new(env, db_client).build()
+    }
+}
+
+pub struct AppBuilder {
+    env: Config,
+    db_client: DBClient,
+    hooks: Hooks,
+    risk_assessor: Option<Arc<dyn RiskAssessor>>,
+}
+
+impl AppBuilder {
+    pub fn new(env: Config, db_client: DBClient) -> Self {
+        AppBuilder {
+            env,
+            db_client,
+            hooks: Hooks::default(),
+            risk_assessor: None,
+        }
+    }
+
+    pub fn risk_assessor(mut self, assessor: impl RiskAssessor + 'static) -> Self {
+        self.risk_assessor = Some(Arc::new(assessor));
+        self
+    }
+
+    pub fn on_user_registered(mut self, hook: impl OnUserRegistered + 'static) -> Self {
+        self.hooks.on_user_registered(Arc::new(hook));
+        self
+    }
+
+    pub fn before_login(mut self, hook: impl BeforeLogin + 'static) -> Self {
+        self.hooks.before_login(Arc::new(hook));
+        self
+    }
+
+    pub fn after_login(mut self, hook: impl AfterLogin + 'static) -> Self {
+        self.hooks.after_login(Arc::new(hook));
+        self
+    }
+
+    pub fn on_password_changed(mut self, hook: impl OnPasswordChanged + 'static) -> Self {
+        self.hooks.on_password_changed(Arc::new(hook));
+        self
+    }
+
+    pub fn build(self) -> AppState {
+        let AppBuilder {
+            env,
+            db_client,
+            hooks,
+            risk_assessor,
+        } = self;
+        let mut risk = RiskPolicy::new(&env);
+        if let Some(assessor) = risk_assessor {
+            risk = risk.with_assessor(assessor);
+        }
+        let metrics = Metrics::new();
+        let notifier = NotificationDispatcher::from_config(&env, &db_client, &metrics);
+        let captcha = CaptchaPolicy::new(&env, &metrics);
+        utils::public_id::init(&env);
+
+        AppState {
+            permission_cache: PermissionCache::new(),
+            maintenance: MaintenanceMode::new(&env),
+            read_only: ReadOnlyMode::new(&env),
+            idempotency: IdempotencyStore::new(env.idempotency_ttl, env.idempotency_max_entries),
+            revocations: RevocationCache::new(env.revocation_cache_ttl),
+            user_cache: UserCache::new(env.user_cache_ttl, env.user_cache_max_entries),
+            secret_scanning_keys: PublicKeyCache::new(
+                &env.secret_scanning_keys_url,
+                env.secret_scanning_keys_ttl,
+            ),
+            jwt_keys: JwtKeys::new(&env, &metrics),
+            passwords: Passwords::from_config(&env),
+            metrics,
+            tarpit: Tarpit::new(&env),
+            notifier,
+            rate_limits: RateLimits::new(&env),
+            name_filter: NameFilter::new(&env),
+            captcha,
+            audit_chain: AuditChain::new(&env.audit_log_key),
+            risk,
+            oauth: OAuthProviders::from_config(&env),
+            webauthn: webauthn::from_config(&env),
+            hooks,
+            env,
+            db_client,
+        }
+    }
+}
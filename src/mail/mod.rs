@@ -0,0 +1,70 @@
+pub mod outbox;
+pub mod smtp;
+pub mod templates;
+
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{circuit::CircuitBreaker, config::Config, metrics::Metrics};
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub cc: Vec<String>,
+    pub reply_to: Option<String>,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl fmt::Display for MailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MailError: {}", self.0)
+    }
+}
+
+impl std::error::Error for MailError {}
+
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError>;
+
+    async fn test_connection(&self) -> Result<bool, MailError>;
+}
+
+pub fn from_config(config: &Config) -> Option<Arc<dyn EmailSender>> {
+    let sender = smtp::SmtpSender::from_config(config)?;
+    Some(Arc::new(sender))
+}
+
+pub fn guarded(config: &Config, metrics: &Metrics) -> Option<Arc<dyn EmailSender>> {
+    let sender = from_config(config)?;
+    Some(Arc::new(GuardedSender {
+        inner: sender,
+        breaker: CircuitBreaker::new("email", config, metrics),
+    }))
+}
+
+pub struct GuardedSender {
+    inner: Arc<dyn EmailSender>,
+    breaker: CircuitBreaker,
+}
+
+#[async_trait]
+impl EmailSender for GuardedSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        self.breaker
+            .call(self.inner.send(message))
+            .await
+            .map_err(|e| e.flatten(MailError))
+    }
+
+    async fn test_connection(&self) -> Result<bool, MailError> {
+        self.inner.test_connection().await
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::{
+    config::Config,
+    db::{DBClient, DoctorExt, OutboxExt},
+    mail::{EmailMessage, EmailSender, MailError},
+    models::OutboxEmail,
+};
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+const CLAIM_LEASE_SECONDS: i64 = 300;
+
+pub struct OutboxSender {
+    db_client: DBClient,
+}
+
+impl OutboxSender {
+    pub fn new(db_client: DBClient) -> Self {
+        OutboxSender { db_client }
+    }
+}
+
+#[async_trait]
+impl EmailSender for OutboxSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        self.db_client
+            .enqueue_email(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| MailError(e.to_string()))
+    }
+
+    async fn test_connection(&self) -> Result<bool, MailError> {
+        Ok(self.db_client.ping().await.is_ok())
+    }
+}
+
+pub fn retry_delay(attempts: i32, base: u64) -> Duration {
+    let exponent = attempts.clamp(0, 20) as u32;
+    Duration::from_secs(base.saturating_mul(2u64.saturating_pow(exponent))).min(MAX_RETRY_DELAY)
+}
+
+fn message(email: &OutboxEmail) -> EmailMessage {
+    EmailMessage {
+        to: email.recipient.clone(),
+        cc: email.cc.clone(),
+        reply_to: email.reply_to.clone(),
+        from_name: email.from_name.clone(),
+        subject: email.subject.clone(),
+        text: email.text_body.clone(),
+        html: email.html_body.clone(),
+    }
+}
+
+pub async fn deliver_due(
+    db_client: &DBClient,
+    sender: &dyn EmailSender,
+    config: &Config,
+) -> Result<usize, sqlx::Error> {
+    let emails = db_client
+        .claim_due_emails(config.outbox_batch_size, CLAIM_LEASE_SECONDS)
+        .await?;
+
+    for email in &emails {
+        match sender.send(&message(email)).await {
+            Ok(()) => db_client.mark_email_sent(email.id).await?,
+            Err(e) => {
+                let attempts = email.attempts + 1;
+                let next_attempt_at = (attempts < config.outbox_max_attempts).then(|| {
+                    Utc::now()
+                        + chrono::Duration::from_std(retry_delay(
+                            email.attempts,
+                            config.outbox_retry_base,
+                        ))
+                        .unwrap_or_default()
+                });
+
+                if next_attempt_at.is_none() {
+                    tracing::error!(
+                        email_id = %email.id,
+                        attempts,
+                        "email moved to dead letter queue: {}",
+                        e
+                    );
+                } else {
+                    tracing::warn!(
+                        email_id = %email.id,
+                        attempts,
+                        "failed to send email, will retry: {}",
+                        e
+                    );
+                }
+
+                db_client
+                    .mark_email_failed(email.id, &e.to_string(), next_attempt_at)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(emails.len())
+}
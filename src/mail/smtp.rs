@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::{
+    config::{Config, SmtpTls},
+    mail::{EmailMessage, EmailSender, MailError},
+};
+
+pub struct SmtpSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpSender {
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let server = config.smtp_server.as_ref()?;
+        let from = config.smtp_from.parse::<Mailbox>().ok()?;
+
+        let builder = match config.smtp_tls {
+            SmtpTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(server).ok()?
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(server).ok()?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(server),
+        };
+
+        let mut builder = builder.port(config.smtp_port);
+        if !config.smtp_username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                config.smtp_username.clone(),
+                config.smtp_password.clone(),
+            ));
+        }
+
+        Some(SmtpSender {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+fn parse_mailbox(value: &str) -> Result<Mailbox, MailError> {
+    value.parse().map_err(|e| MailError(format!("{}", e)))
+}
+
+#[async_trait]
+impl EmailSender for SmtpSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        let from = match &message.from_name {
+            Some(name) => Mailbox::new(Some(name.clone()), self.from.email.clone()),
+            None => self.from.clone(),
+        };
+
+        let mut builder = Message::builder()
+            .from(from)
+            .to(parse_mailbox(&message.to)?)
+            .subject(&message.subject);
+
+        for cc in &message.cc {
+            builder = builder.cc(parse_mailbox(cc)?);
+        }
+
+        if let Some(reply_to) = &message.reply_to {
+            builder = builder.reply_to(parse_mailbox(reply_to)?);
+        }
+
+        let email = match &message.html {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+                message.text.clone(),
+                html.clone(),
+            )),
+            None => builder.singlepart(SinglePart::plain(message.text.clone())),
+        }
+        .map_err(|e| MailError(e.to_string()))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| MailError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn test_connection(&self) -> Result<bool, MailError> {
+        self.transport
+            .test_connection()
+            .await
+            .map_err(|e| MailError(e.to_string()))
+    }
+}
@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::{config::Config, mail::MailError, models::EmailBranding};
+
+const DEFAULT_ACCENT_COLOR: &str = "#2563eb";
+const DEFAULT_BRAND_NAME: &str = "Axum Auth";
+
+const DEFAULT_TEMPLATES: [(&str, &str); 7] = [
+    (
+        "layout.html",
+        include_str!("../../templates/email/layout.html"),
+    ),
+    (
+        "verification.html",
+        include_str!("../../templates/email/verification.html"),
+    ),
+    (
+        "verification.txt",
+        include_str!("../../templates/email/verification.txt"),
+    ),
+    (
+        "password_reset.html",
+        include_str!("../../templates/email/password_reset.html"),
+    ),
+    (
+        "password_reset.txt",
+        include_str!("../../templates/email/password_reset.txt"),
+    ),
+    (
+        "welcome.html",
+        include_str!("../../templates/email/welcome.html"),
+    ),
+    (
+        "welcome.txt",
+        include_str!("../../templates/email/welcome.txt"),
+    ),
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplate {
+    Verification,
+    PasswordReset,
+    Welcome,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 3] = [
+        EmailTemplate::Verification,
+        EmailTemplate::PasswordReset,
+        EmailTemplate::Welcome,
+    ];
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            EmailTemplate::Verification => "verification",
+            EmailTemplate::PasswordReset => "password_reset",
+            EmailTemplate::Welcome => "welcome",
+        }
+    }
+
+    fn subject<'a>(&self, config: &'a Config) -> &'a str {
+        match self {
+            EmailTemplate::Verification => &config.email_subject_verification,
+            EmailTemplate::PasswordReset => &config.email_subject_password_reset,
+            EmailTemplate::Welcome => &config.email_subject_welcome,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    tera: Tera,
+}
+
+impl EmailTemplates {
+    pub fn from_config(config: &Config) -> Result<Self, MailError> {
+        let mut templates = Vec::new();
+        for (name, default) in DEFAULT_TEMPLATES {
+            let source = match &config.email_template_dir {
+                Some(dir) => read_override(Path::new(dir), name)?,
+                None => None,
+            };
+            templates.push((
+                name.to_string(),
+                source.unwrap_or_else(|| default.to_string()),
+            ));
+        }
+        for template in EmailTemplate::ALL {
+            templates.push((
+                format!("{}.subject", template.to_str()),
+                template.subject(config).to_string(),
+            ));
+        }
+
+        let mut tera = Tera::default();
+        tera.add_raw_templates(templates)
+            .map_err(|e| MailError(format!("Invalid email template: {:?}", e)))?;
+
+        Ok(EmailTemplates { tera })
+    }
+
+    pub fn render(
+        &self,
+        template: EmailTemplate,
+        values: &serde_json::Value,
+        branding: &EmailBranding,
+    ) -> Result<RenderedEmail, MailError> {
+        let mut context = Context::from_value(values.clone())
+            .map_err(|e| MailError(format!("Invalid email template context: {}", e)))?;
+        context.insert(
+            "brand_name",
+            branding
+                .display_name
+                .as_deref()
+                .unwrap_or(DEFAULT_BRAND_NAME),
+        );
+        context.insert(
+            "accent_color",
+            branding
+                .accent_color
+                .as_deref()
+                .unwrap_or(DEFAULT_ACCENT_COLOR),
+        );
+        context.insert("logo_url", &branding.logo_url);
+
+        let name = template.to_str();
+        let subject = self.render_one(&format!("{}.subject", name), &context)?;
+        context.insert("subject", subject.trim());
+
+        Ok(RenderedEmail {
+            text: self.render_one(&format!("{}.txt", name), &context)?,
+            html: self.render_one(&format!("{}.html", name), &context)?,
+            subject: subject.trim().to_string(),
+        })
+    }
+
+    fn render_one(&self, name: &str, context: &Context) -> Result<String, MailError> {
+        self.tera
+            .render(name, context)
+            .map_err(|e| MailError(format!("Failed to render {}: {:?}", name, e)))
+    }
+}
+
+fn read_override(dir: &Path, name: &str) -> Result<Option<String>, MailError> {
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    std::fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|e| MailError(format!("Failed to read {}: {}", path.display(), e)))
+}
@@ -0,0 +1,123 @@
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
+
+use axum::http::{
+    HeaderValue, Method,
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+};
+use axum_auth_backend::{
+    AppState, bootstrap, config::Config, db::DBClient, jobs, middleware::idempotency, revocation,
+    routes::create_router, startup, user_cache,
+};
+use dotenv::dotenv;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use tower_http::cors::CorsLayer;
+use tracing_subscriber::{
+    Layer, filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+
+    let config = Config::init();
+
+    let registry = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO));
+    #[cfg(all(feature = "profiling", tokio_unstable))]
+    let registry = registry.with(config.profiling_console_addr.map(|addr| {
+        console_subscriber::ConsoleLayer::builder()
+            .server_addr(addr)
+            .spawn()
+    }));
+    registry.init();
+    #[cfg(all(feature = "profiling", not(tokio_unstable)))]
+    if config.profiling_console_addr.is_some() {
+        tracing::warn!(
+            "PROFILING_CONSOLE_ADDR is ignored, tokio-console needs a build with --cfg tokio_unstable"
+        );
+    }
+
+    let mut connect_options = match PgConnectOptions::from_str(&config.database_url) {
+        Ok(options) => options,
+        Err(err) => {
+            tracing::error!("Invalid DATABASE_URL: {:?}", err);
+            std::process::exit(1);
+        }
+    };
+    if config.db_statement_timeout_ms > 0 {
+        connect_options = connect_options.options([(
+            "statement_timeout",
+            config.db_statement_timeout_ms.to_string(),
+        )]);
+    }
+
+    let pool = match PgPoolOptions::new()
+        .max_connections(10)
+        .connect_with(connect_options)
+        .await
+    {
+        Ok(pool) => {
+            tracing::info!("Connection to the database is successful");
+            pool
+        }
+        Err(err) => {
+            tracing::error!("Failed to connect to the database: {:?}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let db_client = DBClient::new(pool);
+
+    let report = startup::probe(&config, &db_client).await;
+    report.log();
+    let failures = report.critical_failures();
+    if config.startup_strict && !failures.is_empty() {
+        for failure in failures {
+            tracing::error!(
+                "Critical dependency {} is unavailable: {}",
+                failure.name,
+                failure.detail
+            );
+        }
+        std::process::exit(1);
+    }
+
+    match bootstrap::seed_admin(&db_client, &config).await {
+        Ok(Some(user)) => tracing::info!("Seeded initial admin account {}", user.email),
+        Ok(None) => {}
+        Err(err) => {
+            tracing::error!("Failed to seed initial admin account: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    let cors = CorsLayer::new()
+        .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE])
+        .allow_credentials(true)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE]);
+
+    let app_state = Arc::new(AppState::new(config.clone(), db_client));
+    if let Err(err) = app_state.permission_cache.load(&app_state.db_client).await {
+        tracing::warn!("Failed to load role permissions, using defaults: {}", err);
+    }
+    jobs::spawn_cleanup(app_state.clone());
+    jobs::spawn_outbox(app_state.clone());
+    revocation::spawn_listener(app_state.clone());
+    user_cache::spawn_preloader(app_state.clone());
+    user_cache::spawn_sweeper(app_state.clone());
+    idempotency::spawn_sweeper(app_state.clone());
+    let app = create_router(app_state).layer(cors);
+
+    tracing::info!("Server is running on http://localhost:{}", config.port);
+
+    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", config.port))
+        .await
+        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,44 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    counters: Arc<RwLock<HashMap<String, Arc<AtomicU64>>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    pub fn counter(&self, name: &str) -> Arc<AtomicU64> {
+        if let Some(counter) = self.counters.read().unwrap().get(name) {
+            return counter.clone();
+        }
+
+        self.counters
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    pub fn increment(&self, name: &str) {
+        self.counter(name).fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.counters
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, counter)| (name.clone(), counter.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
@@ -0,0 +1,103 @@
+use std::{collections::HashMap, time::Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+use crate::middleware::{AuthenticatedUserId, request_path};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    sample_rates: [f64; 5],
+}
+
+impl AccessLog {
+    pub fn new(sample_rates: &HashMap<String, f64>) -> Self {
+        let rate = |class: &str| sample_rates.get(class).copied().unwrap_or(1.0);
+
+        AccessLog {
+            sample_rates: [
+                rate("1xx"),
+                rate("2xx"),
+                rate("3xx"),
+                rate("4xx"),
+                rate("5xx"),
+            ],
+        }
+    }
+
+    fn should_log(&self, status: StatusCode) -> bool {
+        let class = (status.as_u16() / 100).clamp(1, 5) as usize - 1;
+        let rate = self.sample_rates[class];
+
+        if rate >= 1.0 {
+            return true;
+        }
+        if rate <= 0.0 {
+            return false;
+        }
+
+        let roll = (Uuid::new_v4().as_u128() as u64) as f64 / u64::MAX as f64;
+        roll < rate
+    }
+}
+
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = next.run(req).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+pub async fn access_log(State(access_log): State<AccessLog>, req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().clone();
+    let path = request_path(&req);
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone())
+        .unwrap_or_default();
+
+    let response = next.run(req).await;
+    let status = response.status();
+
+    if access_log.should_log(status) {
+        let user_id = response
+            .extensions()
+            .get::<AuthenticatedUserId>()
+            .map(|AuthenticatedUserId(id)| id.to_string());
+
+        tracing::info!(
+            target: "access",
+            method = %method,
+            path = %path,
+            status = status.as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            user_id = user_id.as_deref().unwrap_or("-"),
+            request_id = %request_id,
+        );
+    }
+
+    response
+}
@@ -0,0 +1,209 @@
+use std::{fmt, net::IpAddr, sync::Arc};
+
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    circuit::CircuitBreaker,
+    config::Config,
+    db::LoginAttemptExt,
+    error::{ErrorMessage, HttpError},
+    metrics::Metrics,
+    middleware::{
+        client_ip,
+        login_throttle::{account_from_body, account_key, ip_key},
+    },
+};
+
+pub const CAPTCHA_TOKEN_HEADER: &str = "x-captcha-token";
+pub const CAPTCHA_REQUIRED_HEADER: &str = "x-captcha-required";
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> bool;
+}
+
+pub struct HttpCaptchaVerifier {
+    client: reqwest::Client,
+    url: String,
+    secret: String,
+    breaker: CircuitBreaker,
+}
+
+#[derive(Deserialize)]
+struct VerifyResponse {
+    success: bool,
+}
+
+impl HttpCaptchaVerifier {
+    pub fn new(url: impl Into<String>, secret: impl Into<String>, breaker: CircuitBreaker) -> Self {
+        HttpCaptchaVerifier {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            secret: secret.into(),
+            breaker,
+        }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for HttpCaptchaVerifier {
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> bool {
+        let mut form = vec![
+            ("secret", self.secret.clone()),
+            ("response", token.to_string()),
+        ];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip.to_string()));
+        }
+
+        let request = async {
+            self.client
+                .post(&self.url)
+                .form(&form)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<VerifyResponse>()
+                .await
+        };
+
+        match self.breaker.call(request).await {
+            Ok(body) => body.success,
+            Err(err) => {
+                tracing::warn!("captcha verification request failed: {}", err);
+                false
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CaptchaPolicy {
+    verifier: Option<Arc<dyn CaptchaVerifier>>,
+    pub ip_threshold: u32,
+    pub account_threshold: u32,
+}
+
+impl fmt::Debug for CaptchaPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptchaPolicy")
+            .field("enabled", &self.verifier.is_some())
+            .field("ip_threshold", &self.ip_threshold)
+            .field("account_threshold", &self.account_threshold)
+            .finish()
+    }
+}
+
+impl CaptchaPolicy {
+    pub fn new(config: &Config, metrics: &Metrics) -> Self {
+        let verifier = config.captcha_secret.as_ref().map(|secret| {
+            Arc::new(HttpCaptchaVerifier::new(
+                &config.captcha_verify_url,
+                secret,
+                CircuitBreaker::new("captcha", config, metrics),
+            )) as Arc<dyn CaptchaVerifier>
+        });
+
+        CaptchaPolicy {
+            verifier,
+            ip_threshold: config.captcha_ip_threshold,
+            account_threshold: config.captcha_account_threshold,
+        }
+    }
+
+    pub fn with_verifier(mut self, verifier: Arc<dyn CaptchaVerifier>) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    pub async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> Option<bool> {
+        let verifier = self.verifier.as_ref()?;
+        Some(verifier.verify(token, remote_ip).await)
+    }
+
+    async fn is_required(
+        &self,
+        app_state: &AppState,
+        ip: &str,
+        account: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let db_client = &app_state.db_client;
+        let window = app_state.env.captcha_window;
+
+        if db_client.get_login_failures(&ip_key(ip), window).await? >= self.ip_threshold as i32 {
+            return Ok(true);
+        }
+
+        match account {
+            Some(account) => Ok(db_client
+                .get_login_failures(&account_key(account), window)
+                .await?
+                >= self.account_threshold as i32),
+            None => Ok(false),
+        }
+    }
+}
+
+pub(crate) fn captcha_challenge(message: ErrorMessage) -> Response {
+    let mut response = HttpError::forbidden(message.to_string()).into_response();
+    response
+        .headers_mut()
+        .insert(CAPTCHA_REQUIRED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+pub async fn captcha(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let policy = &app_state.captcha;
+    let Some(verifier) = &policy.verifier else {
+        return Ok(next.run(req).await);
+    };
+
+    let remote_ip = client_ip(&req, app_state.env.trusted_proxy_hops);
+    let ip = remote_ip
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let captcha_token = req
+        .headers()
+        .get(CAPTCHA_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+    let account = account_from_body(&body);
+
+    let required = policy
+        .is_required(&app_state, &ip, account.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if required {
+        let Some(captcha_token) = captcha_token else {
+            return Ok(captcha_challenge(ErrorMessage::CaptchaRequired));
+        };
+
+        if !verifier.verify(&captcha_token, remote_ip).await {
+            tracing::warn!(target: "audit", event = "captcha_failed", ip = %ip);
+            return Ok(captcha_challenge(ErrorMessage::CaptchaInvalid));
+        }
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
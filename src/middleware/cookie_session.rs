@@ -0,0 +1,93 @@
+use axum::http::HeaderMap;
+use axum_extra::extract::cookie::{Cookie, PrivateCookieJar, SameSite};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, Environment, SessionBackend},
+    error::HttpError,
+    utils::token,
+};
+
+pub const SESSION_COOKIE: &str = "session";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookieSession {
+    pub sub: String,
+    pub jti: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    pub ttl: i64,
+    pub exp: i64,
+    pub max: i64,
+}
+
+impl CookieSession {
+    pub fn new(user_id: uuid::Uuid, audience: Option<&str>, ttl: i64, max_lifetime: i64) -> Self {
+        let now = Utc::now();
+        let session = CookieSession {
+            sub: user_id.to_string(),
+            jti: token::generate_opaque(),
+            aud: audience.map(str::to_string),
+            ttl,
+            exp: 0,
+            max: (now + Duration::minutes(max_lifetime)).timestamp(),
+        };
+
+        session.rolled(now)
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        now.timestamp() < self.exp.min(self.max)
+    }
+
+    pub fn rolled(mut self, now: DateTime<Utc>) -> Self {
+        self.exp = (now + Duration::minutes(self.ttl))
+            .timestamp()
+            .min(self.max);
+        self
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_else(Utc::now)
+    }
+
+    pub fn max_expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.max, 0).unwrap_or_else(Utc::now)
+    }
+}
+
+pub fn jar(config: &Config, headers: &HeaderMap) -> Option<PrivateCookieJar> {
+    if config.session_backend != SessionBackend::Cookie {
+        return None;
+    }
+
+    config
+        .session_cookie_key
+        .clone()
+        .map(|key| PrivateCookieJar::from_headers(headers, key))
+}
+
+pub fn read(jar: &PrivateCookieJar) -> Option<CookieSession> {
+    jar.get(SESSION_COOKIE)
+        .and_then(|cookie| serde_json::from_str(cookie.value()).ok())
+}
+
+pub fn store(
+    jar: PrivateCookieJar,
+    session: &CookieSession,
+    config: &Config,
+) -> Result<PrivateCookieJar, HttpError> {
+    let value =
+        serde_json::to_string(session).map_err(|e| HttpError::server_error(e.to_string()))?;
+    let max_age = (session.exp - Utc::now().timestamp()).max(0);
+
+    let cookie = Cookie::build((SESSION_COOKIE, value))
+        .path("/")
+        .http_only(true)
+        .secure(config.environment == Environment::Production)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(max_age));
+
+    Ok(jar.add(cookie))
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use axum::{Extension, extract::Request, http::header, middleware::Next, response::Response};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+
+use crate::{
+    AppState,
+    config::{Config, Environment, SessionBackend},
+    error::{ErrorMessage, HttpError},
+    middleware::{
+        API_KEY_HEADER, TOKEN_COOKIE, cookie_session::SESSION_COOKIE, read_only::is_mutating,
+    },
+    utils::token,
+};
+
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+pub fn enabled(config: &Config) -> bool {
+    config.token_delivery.in_cookie() || config.session_backend == SessionBackend::Cookie
+}
+
+pub fn issue(config: &Config) -> (CookieJar, String) {
+    let csrf_token = token::generate_opaque();
+    let cookie = Cookie::build((CSRF_COOKIE, csrf_token.clone()))
+        .path("/")
+        .secure(config.environment == Environment::Production)
+        .same_site(config.token_cookie_same_site);
+
+    (CookieJar::new().add(cookie), csrf_token)
+}
+
+pub async fn csrf(
+    Extension(app_state): Extension<Arc<AppState>>,
+    cookie_jar: CookieJar,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    if !enabled(&app_state.env) || !is_mutating(req.method()) {
+        return Ok(next.run(req).await);
+    }
+
+    let headers = req.headers();
+    let bearer =
+        headers.contains_key(header::AUTHORIZATION) || headers.contains_key(API_KEY_HEADER);
+    let cookie_auth =
+        cookie_jar.get(TOKEN_COOKIE).is_some() || cookie_jar.get(SESSION_COOKIE).is_some();
+    if bearer || !cookie_auth {
+        return Ok(next.run(req).await);
+    }
+
+    let submitted = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty());
+    let expected = cookie_jar.get(CSRF_COOKIE).map(|cookie| cookie.value());
+
+    match (submitted, expected) {
+        (Some(submitted), Some(expected))
+            if token::hash_opaque(submitted) == token::hash_opaque(expected) =>
+        {
+            Ok(next.run(req).await)
+        }
+        _ => Err(HttpError::forbidden(
+            ErrorMessage::CsrfTokenInvalid.to_string(),
+        )),
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use axum::{Extension, extract::Request, middleware::Next, response::IntoResponse};
+
+use crate::{
+    AppState,
+    db::OrganizationExt,
+    error::{ErrorMessage, HttpError},
+    middleware::JWTAuthMiddeware,
+    models::User,
+};
+
+pub async fn effective_plan(app_state: &AppState, user: &User) -> Result<String, HttpError> {
+    let organization_plan = match user.organization_id {
+        Some(org_id) => app_state
+            .db_client
+            .get_organization(org_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .and_then(|organization| organization.plan),
+        None => None,
+    };
+
+    Ok(organization_plan
+        .or_else(|| user.plan.clone())
+        .unwrap_or_else(|| app_state.env.default_plan.clone()))
+}
+
+pub fn plan_includes(app_state: &AppState, plan: &str, entitlement: &str) -> bool {
+    app_state
+        .env
+        .plans
+        .get(plan)
+        .is_some_and(|entitlements| entitlements.iter().any(|e| e == entitlement))
+}
+
+pub async fn require_entitlement(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+    entitlement: &'static str,
+) -> Result<impl IntoResponse, HttpError> {
+    let user = req
+        .extensions()
+        .get::<JWTAuthMiddeware>()
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))?;
+
+    let plan = effective_plan(&app_state, &user.user).await?;
+    if !plan_includes(&app_state, &plan, entitlement) {
+        return Err(HttpError::forbidden(
+            ErrorMessage::EntitlementRequired.to_string(),
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, extract::Request, http::HeaderMap, middleware::Next, response::IntoResponse,
+};
+
+use crate::{
+    AppState,
+    config::Config,
+    error::{ErrorMessage, HttpError},
+    middleware::request_path,
+    models::Organization,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoLocation {
+    pub country: Option<String>,
+    pub asn: Option<i64>,
+}
+
+impl GeoLocation {
+    pub fn from_headers(headers: &HeaderMap, config: &Config) -> Self {
+        if !config.trust_proxy_headers {
+            return GeoLocation::default();
+        }
+
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        GeoLocation {
+            country: header(&config.geo_country_header)
+                .map(str::to_uppercase)
+                .filter(|country| country.len() == 2 && country != "XX"),
+            asn: header(&config.geo_asn_header)
+                .and_then(|asn| asn.trim_start_matches("AS").parse::<i64>().ok()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoDecision {
+    Allow,
+    Block,
+    RequireMfa,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeoPolicy<'a> {
+    pub blocked_countries: &'a [String],
+    pub mfa_countries: &'a [String],
+    pub blocked_asns: &'a [i64],
+}
+
+impl<'a> GeoPolicy<'a> {
+    pub fn login(config: &'a Config) -> Self {
+        GeoPolicy {
+            blocked_countries: &config.login_blocked_countries,
+            mfa_countries: &config.login_mfa_countries,
+            blocked_asns: &config.login_blocked_asns,
+        }
+    }
+
+    pub fn organization(organization: &'a Organization) -> Self {
+        GeoPolicy {
+            blocked_countries: &organization.geo_blocked_countries,
+            mfa_countries: &organization.geo_mfa_countries,
+            blocked_asns: &organization.geo_blocked_asns,
+        }
+    }
+
+    pub fn evaluate(&self, location: &GeoLocation) -> GeoDecision {
+        let country_in = |countries: &[String]| {
+            location
+                .country
+                .as_ref()
+                .is_some_and(|country| countries.contains(country))
+        };
+
+        if country_in(self.blocked_countries)
+            || location
+                .asn
+                .is_some_and(|asn| self.blocked_asns.contains(&asn))
+        {
+            GeoDecision::Block
+        } else if country_in(self.mfa_countries) {
+            GeoDecision::RequireMfa
+        } else {
+            GeoDecision::Allow
+        }
+    }
+
+    pub fn enforce(&self, location: &GeoLocation, scope: &str) -> Result<(), HttpError> {
+        let (event, message) = match self.evaluate(location) {
+            GeoDecision::Allow => return Ok(()),
+            GeoDecision::Block => ("geo_blocked", ErrorMessage::GeoBlocked),
+            GeoDecision::RequireMfa => ("geo_mfa_required", ErrorMessage::MfaRequired),
+        };
+
+        tracing::warn!(
+            target: "audit",
+            event = event,
+            scope = scope,
+            country = ?location.country,
+            asn = ?location.asn,
+            "login rejected by location policy"
+        );
+        Err(HttpError::forbidden(message.to_string()))
+    }
+}
+
+pub async fn geo_login(
+    Extension(app_state): Extension<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, HttpError> {
+    let location = GeoLocation::from_headers(req.headers(), &app_state.env);
+    GeoPolicy::login(&app_state.env).enforce(&location, "deployment")?;
+
+    req.extensions_mut().insert(location);
+    Ok(next.run(req).await)
+}
+
+pub async fn geo_admin(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, HttpError> {
+    let env = &app_state.env;
+    if env.admin_allowed_countries.is_empty() && env.admin_allowed_asns.is_empty() {
+        return Ok(next.run(req).await);
+    }
+
+    let location = GeoLocation::from_headers(req.headers(), env);
+    let country_allowed = location
+        .country
+        .as_ref()
+        .is_some_and(|country| env.admin_allowed_countries.contains(country));
+    let asn_allowed = location
+        .asn
+        .is_some_and(|asn| env.admin_allowed_asns.contains(&asn));
+
+    if !country_allowed && !asn_allowed {
+        tracing::warn!(
+            target: "audit",
+            event = "geo_blocked",
+            scope = "admin",
+            country = ?location.country,
+            asn = ?location.asn,
+            method = %req.method(),
+            path = %request_path(&req),
+            "blocked admin request from disallowed location"
+        );
+        return Err(HttpError::forbidden(ErrorMessage::GeoBlocked.to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,124 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use tower::{Layer, Service};
+
+use crate::{
+    error::{ErrorMessage, HttpError},
+    models::UserRole,
+    permissions::Permission,
+    rbac::AuthContext,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission(pub Permission);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: Permission,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let rejection = match req.extensions().get::<AuthContext>() {
+            Some(auth_context) => auth_context.require(self.permission.to_str()).err(),
+            None => Some(HttpError::unauthorized(
+                ErrorMessage::UserNotAuthenticated.to_string(),
+            )),
+        };
+
+        match rejection {
+            Some(err) => Box::pin(async move { Ok(err.into_response()) }),
+            None => Box::pin(self.inner.call(req)),
+        }
+    }
+}
+
+pub fn require_role(role: UserRole) -> RequireRole {
+    require_any_role([role])
+}
+
+pub fn require_any_role(roles: impl IntoIterator<Item = UserRole>) -> RequireRole {
+    RequireRole(roles.into_iter().collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct RequireRole(Arc<[UserRole]>);
+
+impl<S> Layer<S> for RequireRole {
+    type Service = RequireRoleService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireRoleService {
+            inner,
+            roles: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequireRoleService<S> {
+    inner: S,
+    roles: Arc<[UserRole]>,
+}
+
+impl<S> Service<Request> for RequireRoleService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let rejection = match req.extensions().get::<AuthContext>() {
+            Some(auth_context) => auth_context.require_any_role(&self.roles).err(),
+            None => Some(HttpError::unauthorized(
+                ErrorMessage::UserNotAuthenticated.to_string(),
+            )),
+        };
+
+        match rejection {
+            Some(err) => Box::pin(async move { Ok(err.into_response()) }),
+            None => Box::pin(self.inner.call(req)),
+        }
+    }
+}
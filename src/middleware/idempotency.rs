@@ -0,0 +1,406 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Extension,
+    body::{Body, Bytes, to_bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    AppState,
+    error::{ErrorMessage, HttpError},
+    middleware::{ClientContext, JWTAuthMiddeware, request_path},
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+const MAX_KEY_LENGTH: usize = 255;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+#[derive(Debug, Clone)]
+enum IdempotencyState {
+    InProgress,
+    Completed(CachedResponse),
+}
+
+#[derive(Debug, Clone)]
+struct IdempotencyEntry {
+    request_hash: String,
+    state: IdempotencyState,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_key: HashMap<String, IdempotencyEntry>,
+    order: VecDeque<(Instant, String)>,
+}
+
+impl Entries {
+    fn remove_front(&mut self) {
+        if let Some((expires_at, key)) = self.order.pop_front()
+            && self
+                .by_key
+                .get(&key)
+                .is_some_and(|entry| entry.expires_at == expires_at)
+        {
+            self.by_key.remove(&key);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyStore {
+    entries: Arc<Mutex<Entries>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl IdempotencyStore {
+    pub fn new(ttl_seconds: u64, max_entries: usize) -> Self {
+        IdempotencyStore {
+            entries: Arc::new(Mutex::new(Entries::default())),
+            ttl: Duration::from_secs(ttl_seconds),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    pub fn sweep(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let before = entries.by_key.len();
+        while entries
+            .order
+            .front()
+            .is_some_and(|(expires_at, _)| *expires_at <= now)
+        {
+            entries.remove_front();
+        }
+
+        before - entries.by_key.len()
+    }
+
+    fn begin(&self, key: &str, request_hash: &str) -> Result<Option<CachedResponse>, HttpError> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        if let Some(entry) = entries
+            .by_key
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+        {
+            if entry.request_hash != request_hash {
+                return Err(HttpError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorMessage::IdempotencyKeyReused.to_string(),
+                ));
+            }
+
+            return match &entry.state {
+                IdempotencyState::InProgress => Err(HttpError::new(
+                    StatusCode::CONFLICT,
+                    ErrorMessage::IdempotencyRequestInProgress.to_string(),
+                )),
+                IdempotencyState::Completed(response) => Ok(Some(response.clone())),
+            };
+        }
+
+        while entries.by_key.len() >= self.max_entries && !entries.order.is_empty() {
+            entries.remove_front();
+        }
+
+        let expires_at = now + self.ttl;
+        entries.by_key.insert(
+            key.to_string(),
+            IdempotencyEntry {
+                request_hash: request_hash.to_string(),
+                state: IdempotencyState::InProgress,
+                expires_at,
+            },
+        );
+        entries.order.push_back((expires_at, key.to_string()));
+
+        Ok(None)
+    }
+
+    fn complete(&self, key: &str, response: CachedResponse) {
+        if let Some(entry) = self.entries.lock().unwrap().by_key.get_mut(key) {
+            entry.state = IdempotencyState::Completed(response);
+        }
+    }
+
+    fn abandon(&self, key: &str) {
+        self.entries.lock().unwrap().by_key.remove(key);
+    }
+}
+
+pub fn spawn_sweeper(app_state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let expired = app_state.idempotency.sweep();
+            if expired > 0 {
+                tracing::debug!(expired, "Expired idempotency keys");
+            }
+        }
+    });
+}
+
+fn subject(req: &Request) -> String {
+    if let Some(JWTAuthMiddeware { user }) = req.extensions().get::<JWTAuthMiddeware>() {
+        return format!("user:{}", user.id);
+    }
+
+    match req
+        .extensions()
+        .get::<ClientContext>()
+        .and_then(|context| context.ip.as_deref())
+    {
+        Some(ip) => format!("ip:{}", ip),
+        None => "anonymous".to_string(),
+    }
+}
+
+struct PendingRequest {
+    store: IdempotencyStore,
+    key: String,
+    completed: bool,
+}
+
+impl PendingRequest {
+    fn complete(mut self, response: CachedResponse) {
+        self.store.complete(&self.key, response);
+        self.completed = true;
+    }
+}
+
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.store.abandon(&self.key);
+        }
+    }
+}
+
+fn request_hash(method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn replay(cached: CachedResponse) -> Response {
+    let mut response = (cached.status, cached.headers, cached.body).into_response();
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+pub async fn idempotency(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let Some(key) = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        return Ok(next.run(req).await);
+    };
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(HttpError::bad_request(format!(
+            "Idempotency-Key must be between 1 and {} characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+
+    let path = request_path(&req);
+    let subject = subject(&req);
+    let method = req.method().to_string();
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+
+    let store = &app_state.idempotency;
+    let store_key = format!("{}:{}:{}", subject, path, key);
+    let hash = request_hash(&method, &path, &body);
+
+    if let Some(cached) = store.begin(&store_key, &hash)? {
+        return Ok(replay(cached));
+    }
+
+    let pending = PendingRequest {
+        store: store.clone(),
+        key: store_key,
+        completed: false,
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    if response.status().is_server_error() {
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::server_error(ErrorMessage::ServerError.to_string()))?;
+
+    pending.complete(CachedResponse {
+        status: parts.status,
+        headers: parts.headers.clone(),
+        body: body.clone(),
+    });
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(body: &'static str) -> CachedResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert("location", HeaderValue::from_static("/api/users/1"));
+        CachedResponse {
+            status: StatusCode::CREATED,
+            headers,
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    fn status(result: Result<Option<CachedResponse>, HttpError>) -> StatusCode {
+        result.unwrap_err().status
+    }
+
+    #[test]
+    fn first_request_starts_in_progress() {
+        let store = IdempotencyStore::new(60, 10);
+
+        assert!(store.begin("key", "hash").unwrap().is_none());
+        assert_eq!(status(store.begin("key", "hash")), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn completed_requests_replay_the_cached_response() {
+        let store = IdempotencyStore::new(60, 10);
+        store.begin("key", "hash").unwrap();
+        store.complete("key", cached("created"));
+
+        let replayed = store.begin("key", "hash").unwrap().unwrap();
+        assert_eq!(replayed.status, StatusCode::CREATED);
+        assert_eq!(replayed.headers["location"], "/api/users/1");
+        assert_eq!(replayed.body, Bytes::from_static(b"created"));
+    }
+
+    #[test]
+    fn reusing_a_key_with_a_different_request_is_rejected() {
+        let store = IdempotencyStore::new(60, 10);
+        store.begin("key", "hash").unwrap();
+
+        assert_eq!(
+            status(store.begin("key", "other")),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        store.complete("key", cached("created"));
+        assert_eq!(
+            status(store.begin("key", "other")),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[test]
+    fn abandoned_requests_can_be_retried() {
+        let store = IdempotencyStore::new(60, 10);
+        store.begin("key", "hash").unwrap();
+        store.abandon("key");
+
+        assert!(store.begin("key", "hash").unwrap().is_none());
+    }
+
+    #[test]
+    fn dropping_a_pending_request_releases_the_key() {
+        let store = IdempotencyStore::new(60, 10);
+        store.begin("key", "hash").unwrap();
+        drop(PendingRequest {
+            store: store.clone(),
+            key: "key".to_string(),
+            completed: false,
+        });
+
+        assert!(store.begin("key", "hash").unwrap().is_none());
+    }
+
+    #[test]
+    fn completing_a_pending_request_keeps_the_response() {
+        let store = IdempotencyStore::new(60, 10);
+        store.begin("key", "hash").unwrap();
+        PendingRequest {
+            store: store.clone(),
+            key: "key".to_string(),
+            completed: false,
+        }
+        .complete(cached("created"));
+
+        assert!(store.begin("key", "hash").unwrap().is_some());
+    }
+
+    #[test]
+    fn expired_entries_are_swept() {
+        let store = IdempotencyStore::new(0, 10);
+        store.begin("key", "hash").unwrap();
+        store.complete("key", cached("created"));
+
+        assert!(store.begin("key", "other").unwrap().is_none());
+        assert_eq!(store.sweep(), 1);
+        assert_eq!(store.sweep(), 0);
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_at_capacity() {
+        let store = IdempotencyStore::new(60, 2);
+        store.begin("first", "hash").unwrap();
+        store.begin("second", "hash").unwrap();
+        store.begin("third", "hash").unwrap();
+
+        assert!(store.begin("first", "hash").unwrap().is_none());
+        assert_eq!(status(store.begin("third", "hash")), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn request_hash_covers_method_path_and_body() {
+        let hash = request_hash("POST", "/api/admin/invitations", b"{}");
+
+        assert_eq!(hash, request_hash("POST", "/api/admin/invitations", b"{}"));
+        assert_ne!(hash, request_hash("PUT", "/api/admin/invitations", b"{}"));
+        assert_ne!(hash, request_hash("POST", "/api/admin/roles", b"{}"));
+        assert_ne!(hash, request_hash("POST", "/api/admin/invitations", b"[]"));
+    }
+}
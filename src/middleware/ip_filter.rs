@@ -0,0 +1,117 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::IntoResponse,
+};
+use ipnet::IpNet;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    error::{ErrorMessage, HttpError},
+    middleware::{client_ip, request_path},
+};
+
+#[derive(Debug, Clone)]
+pub struct IpFilter {
+    allow: Arc<Vec<IpNet>>,
+    deny: Arc<Vec<IpNet>>,
+    trusted_proxy_hops: usize,
+}
+
+impl IpFilter {
+    pub fn new(allow: Vec<IpNet>, deny: Vec<IpNet>, trusted_proxy_hops: usize) -> Self {
+        IpFilter {
+            allow: Arc::new(allow),
+            deny: Arc::new(deny),
+            trusted_proxy_hops,
+        }
+    }
+
+    pub fn is_allowed(&self, ip: Option<IpAddr>) -> bool {
+        let Some(ip) = ip else {
+            return self.allow.is_empty() && self.deny.is_empty();
+        };
+
+        if self.deny.iter().any(|network| network.contains(&ip)) {
+            return false;
+        }
+
+        self.allow.is_empty() || self.allow.iter().any(|network| network.contains(&ip))
+    }
+}
+
+pub async fn ip_filter(
+    State(filter): State<IpFilter>,
+    req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, HttpError> {
+    let ip = client_ip(&req, filter.trusted_proxy_hops);
+
+    if !filter.is_allowed(ip) {
+        let detail = serde_json::json!({
+            "method": req.method().as_str(),
+            "path": request_path(&req),
+        });
+        if let Some(app_state) = req.extensions().get::<Arc<AppState>>() {
+            audit::record(
+                app_state,
+                AuditEntry::new(AuditEvent::IpBlocked).ip(ip).detail(detail),
+            );
+        }
+        return Err(HttpError::forbidden(ErrorMessage::IpNotAllowed.to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn networks(cidrs: &[&str]) -> Vec<IpNet> {
+        cidrs.iter().map(|cidr| cidr.parse().unwrap()).collect()
+    }
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn allows_everything_without_rules() {
+        let filter = IpFilter::new(Vec::new(), Vec::new(), 0);
+        assert!(filter.is_allowed(ip("203.0.113.9")));
+        assert!(filter.is_allowed(None));
+    }
+
+    #[test]
+    fn allowlist_matches_cidr_ranges() {
+        let filter = IpFilter::new(networks(&["10.0.0.0/8", "2001:db8::/32"]), Vec::new(), 0);
+        assert!(filter.is_allowed(ip("10.1.2.3")));
+        assert!(filter.is_allowed(ip("2001:db8::42")));
+        assert!(!filter.is_allowed(ip("11.0.0.1")));
+        assert!(!filter.is_allowed(ip("2001:db9::1")));
+    }
+
+    #[test]
+    fn single_host_networks_match_only_that_host() {
+        let filter = IpFilter::new(networks(&["192.0.2.10/32"]), Vec::new(), 0);
+        assert!(filter.is_allowed(ip("192.0.2.10")));
+        assert!(!filter.is_allowed(ip("192.0.2.11")));
+    }
+
+    #[test]
+    fn denylist_wins_over_allowlist() {
+        let filter = IpFilter::new(networks(&["10.0.0.0/8"]), networks(&["10.0.5.0/24"]), 0);
+        assert!(filter.is_allowed(ip("10.0.4.1")));
+        assert!(!filter.is_allowed(ip("10.0.5.1")));
+    }
+
+    #[test]
+    fn unknown_addresses_are_rejected_when_rules_exist() {
+        let filter = IpFilter::new(Vec::new(), networks(&["10.0.5.0/24"]), 0);
+        assert!(!filter.is_allowed(None));
+    }
+}
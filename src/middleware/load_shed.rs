@@ -0,0 +1,48 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+use crate::{
+    error::{ErrorMessage, HttpError},
+    metrics::Metrics,
+};
+
+#[derive(Debug, Clone)]
+pub struct LoadShedder {
+    semaphore: Arc<Semaphore>,
+    shed: Arc<AtomicU64>,
+}
+
+impl LoadShedder {
+    pub fn new(name: &str, limit: usize, metrics: &Metrics) -> Self {
+        LoadShedder {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            shed: metrics.counter(&format!("load_shed.{}", name)),
+        }
+    }
+}
+
+pub async fn load_shed(State(shedder): State<LoadShedder>, req: Request, next: Next) -> Response {
+    let Ok(_permit) = shedder.semaphore.clone().try_acquire_owned() else {
+        shedder.shed.fetch_add(1, Ordering::Relaxed);
+
+        let mut response =
+            HttpError::service_unavailable(ErrorMessage::ServerOverloaded.to_string())
+                .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, header::HeaderValue::from_static("1"));
+        return response;
+    };
+
+    next.run(req).await
+}
@@ -0,0 +1,132 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    db::LoginAttemptExt,
+    error::HttpError,
+    middleware::{ClientContext, client_ip, rate_limit::too_many_requests},
+};
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+pub(crate) fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+pub(crate) fn account_key(account: &str) -> String {
+    format!("account:{}", account)
+}
+
+pub(crate) fn account_from_body(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    value
+        .get("email")
+        .and_then(|email| email.as_str())
+        .map(|email| email.trim().to_lowercase())
+}
+
+pub async fn login_throttle(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let ip = client_ip(&req, app_state.env.trusted_proxy_hops)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let client = req
+        .extensions()
+        .get::<ClientContext>()
+        .cloned()
+        .unwrap_or_default();
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+    let account = account_from_body(&body);
+
+    let mut keys = vec![ip_key(&ip)];
+    if let Some(account) = &account {
+        keys.push(account_key(account));
+    }
+
+    let locked_until = app_state
+        .db_client
+        .get_login_lockout(&keys)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if let Some(locked_until) = locked_until {
+        let retry_after = (locked_until - Utc::now()).num_seconds().max(1) as u64;
+        return Ok(too_many_requests(Duration::from_secs(retry_after)));
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    let env = &app_state.env;
+    if response.status().is_client_error() && response.status() != StatusCode::TOO_MANY_REQUESTS {
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::LoginFailed)
+                .client(&client)
+                .detail(serde_json::json!({
+                    "email": account,
+                    "status": response.status().as_u16(),
+                })),
+        );
+
+        for key in &keys {
+            let lock_threshold = if key.starts_with("account:") {
+                env.login_lockout_threshold
+            } else {
+                0
+            };
+
+            match app_state
+                .db_client
+                .record_login_failure(
+                    key,
+                    env.captcha_window,
+                    lock_threshold,
+                    env.login_lockout_duration,
+                )
+                .await
+            {
+                Ok(attempt) if attempt.locked_until.is_some() => {
+                    audit::record(
+                        &app_state,
+                        AuditEntry::new(AuditEvent::LoginLockedOut)
+                            .client(&client)
+                            .detail(serde_json::json!({
+                                "key": attempt.key,
+                                "failures": attempt.failures,
+                            })),
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => tracing::warn!("failed to record login failure: {}", err),
+            }
+        }
+    } else if response.status().is_success()
+        && let Some(account) = &account
+        && let Err(err) = app_state
+            .db_client
+            .reset_login_failures(&account_key(account))
+            .await
+    {
+        tracing::warn!("failed to reset login failures: {}", err);
+    }
+
+    Ok(response)
+}
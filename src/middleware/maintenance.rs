@@ -0,0 +1,125 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+    Extension, Json,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    AppState,
+    config::Config,
+    error::{ErrorMessage, ErrorResponse},
+    middleware::request_path,
+};
+
+pub const HEALTH_CHECK_PATH: &str = "/api/healthchecker";
+pub const MAINTENANCE_ADMIN_PATH: &str = "/api/admin/maintenance";
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+    pub retry_after: u64,
+    pub allowlist: Vec<String>,
+}
+
+impl MaintenanceMode {
+    pub fn new(config: &Config) -> Self {
+        MaintenanceMode {
+            enabled: Arc::new(AtomicBool::new(config.maintenance_mode)),
+            retry_after: config.maintenance_retry_after,
+            allowlist: config.maintenance_allowlist.clone(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_allowed(&self, path: &str) -> bool {
+        path == HEALTH_CHECK_PATH
+            || path == MAINTENANCE_ADMIN_PATH
+            || self
+                .allowlist
+                .iter()
+                .any(|allowed| matches_prefix(path, allowed))
+    }
+}
+
+fn matches_prefix(path: &str, allowed: &str) -> bool {
+    match path.strip_prefix(allowed) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || allowed.ends_with('/'),
+        None => false,
+    }
+}
+
+pub async fn maintenance(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let maintenance = &app_state.maintenance;
+
+    if !maintenance.is_enabled() {
+        return next.run(req).await;
+    }
+
+    if maintenance.is_allowed(&request_path(&req)) {
+        return next.run(req).await;
+    }
+
+    let body = Json(ErrorResponse {
+        status: "error".to_string(),
+        message: ErrorMessage::MaintenanceMode.to_string(),
+    });
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, maintenance.retry_after.to_string())],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(allowlist: &[&str]) -> MaintenanceMode {
+        MaintenanceMode {
+            enabled: Arc::new(AtomicBool::new(true)),
+            retry_after: 60,
+            allowlist: allowlist.iter().map(|path| path.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn allowlist_matches_whole_segments() {
+        let mode = mode(&["/api/auth/login", "/api/status/"]);
+
+        assert!(mode.is_allowed("/api/auth/login"));
+        assert!(mode.is_allowed("/api/auth/login/verify"));
+        assert!(mode.is_allowed("/api/status/db"));
+        assert!(!mode.is_allowed("/api/auth/login-anything"));
+        assert!(!mode.is_allowed("/api/auth/loginx"));
+        assert!(!mode.is_allowed("/api/users/me"));
+    }
+
+    #[test]
+    fn health_and_toggle_are_always_allowed() {
+        let mode = mode(&[]);
+
+        assert!(mode.is_allowed(HEALTH_CHECK_PATH));
+        assert!(mode.is_allowed(MAINTENANCE_ADMIN_PATH));
+        assert!(!mode.is_allowed("/api/healthchecker/extra"));
+    }
+}
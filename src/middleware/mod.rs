@@ -0,0 +1,722 @@
+pub mod access_log;
+pub mod captcha;
+pub mod cookie_session;
+pub mod csrf;
+pub mod entitlement;
+pub mod geo;
+pub mod guard;
+pub mod idempotency;
+pub mod ip_filter;
+pub mod load_shed;
+pub mod login_throttle;
+pub mod maintenance;
+pub mod quota;
+pub mod rate_limit;
+pub mod rate_limit_store;
+pub mod read_only;
+pub mod request_signature;
+pub mod risk;
+pub mod security_headers;
+pub mod tarpit;
+pub mod timeout;
+
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    Extension,
+    extract::{ConnectInfo, FromRequestParts, OriginalUri, Request},
+    http::{HeaderMap, header, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    config::TokenSource,
+    db::{ApiKeyExt, DelegationExt, SessionExt},
+    error::{ErrorMessage, HttpError},
+    middleware::request_signature::verify_signed_request,
+    models::{AccountStatus, ApiKey, User},
+    rbac::AuthContext,
+    utils::{api_key, token},
+};
+
+pub const TOKEN_COOKIE: &str = "token";
+pub const SUDO_TOKEN_HEADER: &str = "x-sudo-token";
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_AUDIENCE: &str = "api_key";
+const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+#[cfg(feature = "query-token")]
+pub const TOKEN_QUERY_PARAM: &str = "access_token";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JWTAuthMiddeware {
+    pub user: User,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUserId(pub uuid::Uuid);
+
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser(pub User);
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthenticatedUser {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<JWTAuthMiddeware>()
+            .map(|auth| AuthenticatedUser(auth.user.clone()))
+            .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OptionalAuth(pub Option<AuthenticatedUser>);
+
+impl<S: Send + Sync> FromRequestParts<S> for OptionalAuth {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(OptionalAuth(
+            parts
+                .extensions
+                .get::<JWTAuthMiddeware>()
+                .map(|auth| AuthenticatedUser(auth.user.clone())),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Claims(pub token::TokenClaims);
+
+impl<S: Send + Sync> FromRequestParts<S> for Claims {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<token::TokenClaims>()
+            .cloned()
+            .map(Claims)
+            .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenAudience(pub Option<String>);
+
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyAuth {
+    pub key_id: uuid::Uuid,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientContext {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ActingFor {
+    pub delegate_id: uuid::Uuid,
+    pub delegation_id: uuid::Uuid,
+}
+
+pub fn request_path(req: &Request) -> String {
+    req.extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string())
+}
+
+pub fn forwarded_ip(forwarded_for: &str, trusted_proxy_hops: usize) -> Option<IpAddr> {
+    if trusted_proxy_hops == 0 {
+        return None;
+    }
+
+    let hops: Vec<&str> = forwarded_for.split(',').map(str::trim).collect();
+    let index = hops.len().checked_sub(trusted_proxy_hops)?;
+
+    hops[index].parse().ok()
+}
+
+pub fn client_ip(req: &Request, trusted_proxy_hops: usize) -> Option<IpAddr> {
+    let forwarded = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| forwarded_ip(value, trusted_proxy_hops));
+
+    if forwarded.is_some() {
+        return forwarded;
+    }
+
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+pub async fn client_context(
+    Extension(app_state): Extension<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> impl IntoResponse {
+    let context = ClientContext {
+        ip: client_ip(&req, app_state.env.trusted_proxy_hops).map(|ip| ip.to_string()),
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(512).collect()),
+    };
+    req.extensions_mut().insert(context);
+
+    next.run(req).await
+}
+
+enum TokenLookup {
+    Found(String),
+    Malformed,
+    Missing,
+}
+
+fn token_from_header(headers: &HeaderMap) -> TokenLookup {
+    let Some(value) = headers.get(header::AUTHORIZATION) else {
+        return TokenLookup::Missing;
+    };
+
+    match value.to_str().ok().and_then(|v| v.strip_prefix("Bearer ")) {
+        Some(token) if !token.trim().is_empty() => TokenLookup::Found(token.trim().to_string()),
+        _ => TokenLookup::Malformed,
+    }
+}
+
+fn token_from_cookie(cookie_jar: &CookieJar) -> TokenLookup {
+    match cookie_jar.get(TOKEN_COOKIE) {
+        Some(cookie) if !cookie.value().trim().is_empty() => {
+            TokenLookup::Found(cookie.value().to_string())
+        }
+        Some(_) => TokenLookup::Malformed,
+        None => TokenLookup::Missing,
+    }
+}
+
+#[cfg(feature = "query-token")]
+fn token_from_query(query: Option<&str>) -> TokenLookup {
+    let Some(query) = query else {
+        return TokenLookup::Missing;
+    };
+
+    let value = query.split('&').find_map(|pair| {
+        pair.split_once('=')
+            .filter(|(key, _)| *key == TOKEN_QUERY_PARAM)
+            .map(|(_, value)| value)
+    });
+
+    match value {
+        Some(token) if !token.is_empty() => TokenLookup::Found(token.to_string()),
+        Some(_) => TokenLookup::Malformed,
+        None => TokenLookup::Missing,
+    }
+}
+
+#[cfg(not(feature = "query-token"))]
+fn token_from_query(_query: Option<&str>) -> TokenLookup {
+    TokenLookup::Missing
+}
+
+pub fn extract_token(
+    req: &Request,
+    cookie_jar: &CookieJar,
+    sources: &[TokenSource],
+) -> Result<String, HttpError> {
+    for source in sources {
+        let lookup = match source {
+            TokenSource::Header => token_from_header(req.headers()),
+            TokenSource::Cookie => token_from_cookie(cookie_jar),
+            TokenSource::Query => token_from_query(req.uri().query()),
+        };
+
+        match lookup {
+            TokenLookup::Found(token) => return Ok(token),
+            TokenLookup::Malformed => {
+                return Err(HttpError::unauthorized(
+                    ErrorMessage::TokenMalformed.to_string(),
+                ));
+            }
+            TokenLookup::Missing => continue,
+        }
+    }
+
+    Err(HttpError::unauthorized(
+        ErrorMessage::TokenNotProvided.to_string(),
+    ))
+}
+
+fn has_credentials(req: &Request, cookie_jar: &CookieJar, app_state: &AppState) -> bool {
+    if req.headers().contains_key(API_KEY_HEADER) {
+        return true;
+    }
+
+    if cookie_session::jar(&app_state.env, req.headers())
+        .is_some_and(|jar| cookie_session::read(&jar).is_some())
+    {
+        return true;
+    }
+
+    app_state.env.token_sources.iter().any(|source| {
+        let lookup = match source {
+            TokenSource::Header => token_from_header(req.headers()),
+            TokenSource::Cookie => token_from_cookie(cookie_jar),
+            TokenSource::Query => token_from_query(req.uri().query()),
+        };
+        !matches!(lookup, TokenLookup::Missing)
+    })
+}
+
+async fn active_user(app_state: &AppState, user_id: &str) -> Result<User, HttpError> {
+    let user_id = uuid::Uuid::parse_str(user_id)
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    let user = app_state
+        .user_cache
+        .get_user(&app_state.db_client, user_id)
+        .await
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::UserNoLongerExist.to_string()))?;
+
+    let user =
+        user.ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNoLongerExist.to_string()))?;
+
+    if user.locked_at.is_some() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::AccountLocked.to_string(),
+        ));
+    }
+
+    if user.account_status != AccountStatus::Active {
+        return Err(HttpError::forbidden(
+            ErrorMessage::PermissionDenied.to_string(),
+        ));
+    }
+
+    if user.deactivated_at.is_some() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::AccountDeactivated.to_string(),
+        ));
+    }
+
+    Ok(user)
+}
+
+async fn check_session(
+    app_state: &AppState,
+    session_id: &str,
+    user_id: uuid::Uuid,
+) -> Result<(), HttpError> {
+    let session_id = uuid::Uuid::parse_str(session_id)
+        .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    let session = app_state
+        .revocations
+        .get_session(&app_state.db_client, session_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|session| session.user_id == user_id)
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+    let now = Utc::now();
+    if !session.is_active(now) {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::SessionExpired.to_string(),
+        ));
+    }
+
+    if now - session.last_seen_at > chrono::Duration::seconds(SESSION_TOUCH_INTERVAL_SECS) {
+        app_state
+            .db_client
+            .touch_session(session.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        app_state.revocations.evict_session(session.id);
+    }
+
+    Ok(())
+}
+
+async fn api_key_user(app_state: &AppState, key: &str) -> Result<(User, ApiKey), HttpError> {
+    if !api_key::is_well_formed(key) {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidApiKey.to_string(),
+        ));
+    }
+
+    let api_key = app_state
+        .db_client
+        .get_api_key_by_hash(&api_key::hash(key))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|api_key| api_key.revoked_at.is_none())
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::InvalidApiKey.to_string()))?;
+
+    let user = active_user(app_state, &api_key.user_id.to_string()).await?;
+
+    let stale = api_key.last_used_at.is_none_or(|last_used_at| {
+        Utc::now() - last_used_at > chrono::Duration::seconds(SESSION_TOUCH_INTERVAL_SECS)
+    });
+    if stale {
+        app_state
+            .db_client
+            .touch_api_key(api_key.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    Ok((user, api_key))
+}
+
+pub async fn auth(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, HttpError> {
+    if let Some(value) = req.headers().get(API_KEY_HEADER) {
+        let key = value
+            .to_str()
+            .map_err(|_| HttpError::unauthorized(ErrorMessage::InvalidApiKey.to_string()))?
+            .trim()
+            .to_string();
+        let (user, api_key) = api_key_user(&app_state, &key).await?;
+
+        if let Some(secret) = &api_key.signing_secret {
+            req = verify_signed_request(&app_state, api_key.id, secret, req).await?;
+        }
+
+        let role = match &api_key.role {
+            Some(role) => {
+                let owner_permissions = app_state.permission_cache.permissions_for(&user.role);
+                if !app_state
+                    .permission_cache
+                    .permissions_for(role)
+                    .is_subset(&owner_permissions)
+                {
+                    return Err(HttpError::forbidden(
+                        ErrorMessage::ApiKeyRoleNotAllowed.to_string(),
+                    ));
+                }
+                role
+            }
+            None => &user.role,
+        };
+        let auth_context = AuthContext {
+            roles: app_state.permission_cache.lineage(role),
+            permissions: app_state.permission_cache.permissions_for(role),
+        };
+
+        req.extensions_mut()
+            .insert(JWTAuthMiddeware { user: user.clone() });
+        req.extensions_mut().insert(auth_context);
+        req.extensions_mut()
+            .insert(TokenAudience(Some(API_KEY_AUDIENCE.to_string())));
+        req.extensions_mut()
+            .insert(ApiKeyAuth { key_id: api_key.id });
+
+        let mut response = next.run(req).await;
+        response
+            .extensions_mut()
+            .insert(AuthenticatedUserId(user.id));
+
+        return Ok(response);
+    }
+
+    if let Some(jar) = cookie_session::jar(&app_state.env, req.headers())
+        && let Some(session) = cookie_session::read(&jar)
+    {
+        let now = Utc::now();
+        if !session.is_active(now) {
+            return Err(HttpError::unauthorized(
+                ErrorMessage::SessionExpired.to_string(),
+            ));
+        }
+
+        let revoked = app_state
+            .revocations
+            .is_token_revoked(&app_state.db_client, &session.jti)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if revoked {
+            return Err(HttpError::unauthorized(
+                ErrorMessage::TokenRevoked.to_string(),
+            ));
+        }
+
+        let user = active_user(&app_state, &session.sub).await?;
+        let auth_context = app_state.permission_cache.resolve(&user);
+        let jar = cookie_session::store(jar, &session.clone().rolled(now), &app_state.env)?;
+
+        req.extensions_mut()
+            .insert(JWTAuthMiddeware { user: user.clone() });
+        req.extensions_mut().insert(auth_context);
+        req.extensions_mut()
+            .insert(TokenAudience(session.aud.clone()));
+        req.extensions_mut().insert(session);
+
+        let mut response = next.run(req).await;
+        response
+            .extensions_mut()
+            .insert(AuthenticatedUserId(user.id));
+
+        return Ok((jar, response).into_response());
+    }
+
+    let token = extract_token(&req, &cookie_jar, &app_state.env.token_sources)?;
+
+    let claims = token::decode_claims(token, &app_state.jwt_keys)?;
+
+    if claims.sudo || claims.mfa || claims.magic {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidToken.to_string(),
+        ));
+    }
+
+    if let Some(jti) = &claims.jti {
+        let revoked = app_state
+            .revocations
+            .is_token_revoked(&app_state.db_client, jti)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if revoked {
+            return Err(HttpError::unauthorized(
+                ErrorMessage::TokenRevoked.to_string(),
+            ));
+        }
+    }
+
+    let user = active_user(&app_state, &claims.sub).await?;
+
+    if let Some(session_id) = &claims.sid {
+        check_session(&app_state, session_id, user.id).await?;
+    }
+
+    let (user, auth_context) = match &claims.acting_for {
+        Some(owner_id) => {
+            let owner = active_user(&app_state, owner_id).await?;
+            let delegation = app_state
+                .db_client
+                .get_active_delegation(owner.id, user.id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?
+                .ok_or_else(|| HttpError::unauthorized(ErrorMessage::InvalidToken.to_string()))?;
+
+            let owner_permissions = app_state.permission_cache.permissions_for(&owner.role);
+            let auth_context = AuthContext {
+                roles: Vec::new(),
+                permissions: Arc::new(
+                    delegation
+                        .scopes
+                        .iter()
+                        .filter(|scope| owner_permissions.contains(*scope))
+                        .cloned()
+                        .collect(),
+                ),
+            };
+
+            req.extensions_mut().insert(ActingFor {
+                delegate_id: user.id,
+                delegation_id: delegation.id,
+            });
+
+            (owner, auth_context)
+        }
+        None => {
+            let auth_context = app_state.permission_cache.resolve(&user);
+            let auth_context = match &claims.scope {
+                Some(scope) => auth_context.restrict(scope),
+                None => auth_context,
+            };
+            (user, auth_context)
+        }
+    };
+
+    req.extensions_mut()
+        .insert(JWTAuthMiddeware { user: user.clone() });
+    req.extensions_mut().insert(auth_context);
+    req.extensions_mut().insert(TokenAudience(
+        claims
+            .aud
+            .iter()
+            .find(|audience| Some(audience.as_str()) != app_state.jwt_keys.audience())
+            .cloned(),
+    ));
+    req.extensions_mut().insert(claims);
+
+    let mut response = next.run(req).await;
+    response
+        .extensions_mut()
+        .insert(AuthenticatedUserId(user.id));
+
+    Ok(response)
+}
+
+pub async fn optional_auth(
+    cookie_jar: CookieJar,
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    if !has_credentials(&req, &cookie_jar, &app_state) {
+        return Ok(next.run(req).await);
+    }
+
+    auth(cookie_jar, Extension(app_state), req, next)
+        .await
+        .map(IntoResponse::into_response)
+}
+
+pub async fn deny_delegated(req: Request, next: Next) -> Result<impl IntoResponse, HttpError> {
+    if req.extensions().get::<ActingFor>().is_some() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::DelegatedAccessDenied.to_string(),
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
+
+pub async fn deny_api_key(req: Request, next: Next) -> Result<impl IntoResponse, HttpError> {
+    if req.extensions().get::<ApiKeyAuth>().is_some() {
+        return Err(HttpError::forbidden(
+            ErrorMessage::ApiKeyNotAllowed.to_string(),
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
+
+pub async fn require_sudo(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, HttpError> {
+    let user_id = req
+        .extensions()
+        .get::<JWTAuthMiddeware>()
+        .map(|auth| auth.user.id)
+        .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))?;
+
+    let elevated = req
+        .headers()
+        .get(SUDO_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| token::decode_claims(value, &app_state.jwt_keys).ok())
+        .is_some_and(|claims| claims.sudo && claims.sub == user_id.to_string());
+
+    if !elevated || req.extensions().get::<ActingFor>().is_some() {
+        return Err(HttpError::forbidden(ErrorMessage::SudoRequired.to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
+
+pub async fn require_audience(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+    route_group: &'static str,
+) -> Result<impl IntoResponse, HttpError> {
+    if let Some(allowed) = app_state.env.route_audiences.get(route_group) {
+        let audience = req
+            .extensions()
+            .get::<TokenAudience>()
+            .and_then(|TokenAudience(audience)| audience.as_ref());
+
+        if !audience.is_some_and(|audience| allowed.contains(audience)) {
+            return Err(HttpError::forbidden(
+                ErrorMessage::AudienceNotAllowed.to_string(),
+            ));
+        }
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+
+    use super::*;
+
+    fn request(forwarded_for: Option<&str>, peer: &str) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(value) = forwarded_for {
+            builder = builder.header("x-forwarded-for", value);
+        }
+        let mut req = builder.body(Body::empty()).unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        req
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn forwarded_ip_takes_the_entry_added_by_the_trusted_proxy() {
+        let header = "198.51.100.7, 203.0.113.9, 10.0.0.2";
+        assert_eq!(forwarded_ip(header, 1), Some(ip("10.0.0.2")));
+        assert_eq!(forwarded_ip(header, 2), Some(ip("203.0.113.9")));
+        assert_eq!(forwarded_ip(header, 3), Some(ip("198.51.100.7")));
+    }
+
+    #[test]
+    fn forwarded_ip_ignores_the_header_without_trusted_hops() {
+        assert_eq!(forwarded_ip("198.51.100.7", 0), None);
+    }
+
+    #[test]
+    fn forwarded_ip_rejects_chains_shorter_than_the_trusted_hops() {
+        assert_eq!(forwarded_ip("198.51.100.7", 2), None);
+    }
+
+    #[test]
+    fn forwarded_ip_rejects_malformed_entries() {
+        assert_eq!(forwarded_ip("198.51.100.7, not-an-ip", 1), None);
+        assert_eq!(forwarded_ip("", 1), None);
+    }
+
+    #[test]
+    fn forwarded_ip_parses_ipv6() {
+        assert_eq!(
+            forwarded_ip("198.51.100.7, 2001:db8::1", 1),
+            Some(ip("2001:db8::1"))
+        );
+    }
+
+    #[test]
+    fn client_ip_ignores_spoofed_leading_entries() {
+        let req = request(Some("1.2.3.4, 203.0.113.9"), "10.0.0.1:443");
+        assert_eq!(client_ip(&req, 1), Some(ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn client_ip_falls_back_to_the_socket_address() {
+        let req = request(None, "192.0.2.10:5000");
+        assert_eq!(client_ip(&req, 1), Some(ip("192.0.2.10")));
+
+        let req = request(Some("203.0.113.9"), "192.0.2.10:5000");
+        assert_eq!(client_ip(&req, 0), Some(ip("192.0.2.10")));
+
+        let req = request(Some("garbage"), "192.0.2.10:5000");
+        assert_eq!(client_ip(&req, 1), Some(ip("192.0.2.10")));
+    }
+}
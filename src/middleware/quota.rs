@@ -0,0 +1,198 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{Extension, extract::Request, middleware::Next, response::Response};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    config::Config,
+    db::UsageExt,
+    error::HttpError,
+    middleware::{
+        ClientContext, JWTAuthMiddeware,
+        rate_limit::{RateLimitStatus, too_many_requests},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+impl QuotaPeriod {
+    pub const ALL: [QuotaPeriod; 2] = [QuotaPeriod::Day, QuotaPeriod::Month];
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            QuotaPeriod::Day => "day",
+            QuotaPeriod::Month => "month",
+        }
+    }
+
+    pub fn start(&self, now: DateTime<Utc>) -> NaiveDate {
+        let today = now.date_naive();
+        match self {
+            QuotaPeriod::Day => today,
+            QuotaPeriod::Month => today.with_day(1).unwrap_or(today),
+        }
+    }
+
+    pub fn resets_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start(now);
+        let next = match self {
+            QuotaPeriod::Day => start.succ_opt(),
+            QuotaPeriod::Month => start.checked_add_months(Months::new(1)),
+        };
+
+        next.and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc())
+            .unwrap_or(now)
+    }
+
+    pub fn limit(&self, config: &Config, role: &str) -> Option<i64> {
+        let limits = match self {
+            QuotaPeriod::Day => &config.quota_daily_limits,
+            QuotaPeriod::Month => &config.quota_monthly_limits,
+        };
+
+        limits.get(role).copied()
+    }
+}
+
+pub fn user_subject(user_id: uuid::Uuid) -> String {
+    format!("user:{}", user_id)
+}
+
+pub async fn quota(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let Some(user) = req.extensions().get::<JWTAuthMiddeware>() else {
+        return Ok(next.run(req).await);
+    };
+
+    let subject = user_subject(user.user.id);
+    let role = user.user.role.to_str().to_string();
+    let now = Utc::now();
+    let mut status: Option<RateLimitStatus> = None;
+
+    for period in QuotaPeriod::ALL {
+        let limit = period.limit(&app_state.env, &role);
+        let count = app_state
+            .db_client
+            .increment_usage(&subject, period.to_str(), period.start(now), limit)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let Some(count) = count else {
+            let mut entry = AuditEntry::new(AuditEvent::QuotaExceeded)
+                .actor(user.user.id)
+                .detail(serde_json::json!({
+                    "period": period.to_str(),
+                    "limit": limit,
+                }));
+            if let Some(client) = req.extensions().get::<ClientContext>() {
+                entry = entry.client(client);
+            }
+            audit::record(&app_state, entry);
+            let retry_after = (period.resets_at(now) - now).num_seconds().max(1) as u64;
+            let mut response = too_many_requests(Duration::from_secs(retry_after));
+            if let Some(limit) = limit {
+                RateLimitStatus {
+                    limit,
+                    remaining: 0,
+                    reset: period.resets_at(now).timestamp(),
+                }
+                .apply(&mut response);
+            }
+            return Ok(response);
+        };
+
+        if let Some(limit) = limit {
+            let remaining = limit - count;
+            if status.is_none_or(|current| remaining < current.remaining) {
+                status = Some(RateLimitStatus {
+                    limit,
+                    remaining,
+                    reset: period.resets_at(now).timestamp(),
+                });
+            }
+        }
+    }
+
+    let mut response = next.run(req).await;
+    if let Some(status) = status {
+        status.apply(&mut response);
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn daily_quota_resets_at_the_next_midnight() {
+        assert_eq!(
+            QuotaPeriod::Day.resets_at(at(2026, 3, 14, 15, 30)),
+            at(2026, 3, 15, 0, 0)
+        );
+        assert_eq!(
+            QuotaPeriod::Day.resets_at(at(2026, 3, 14, 0, 0)),
+            at(2026, 3, 15, 0, 0)
+        );
+    }
+
+    #[test]
+    fn daily_quota_rolls_over_month_and_year_ends() {
+        assert_eq!(
+            QuotaPeriod::Day.resets_at(at(2026, 4, 30, 23, 59)),
+            at(2026, 5, 1, 0, 0)
+        );
+        assert_eq!(
+            QuotaPeriod::Day.resets_at(at(2026, 12, 31, 23, 59)),
+            at(2027, 1, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn monthly_quota_resets_on_the_first_of_next_month() {
+        assert_eq!(
+            QuotaPeriod::Month.resets_at(at(2026, 1, 31, 12, 0)),
+            at(2026, 2, 1, 0, 0)
+        );
+        assert_eq!(
+            QuotaPeriod::Month.resets_at(at(2026, 12, 1, 0, 0)),
+            at(2027, 1, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn monthly_quota_handles_leap_years() {
+        assert_eq!(
+            QuotaPeriod::Month.resets_at(at(2028, 2, 29, 8, 0)),
+            at(2028, 3, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn periods_start_on_their_first_day() {
+        let now = at(2026, 7, 19, 10, 0);
+        assert_eq!(QuotaPeriod::Day.start(now), now.date_naive());
+        assert_eq!(
+            QuotaPeriod::Month.start(now),
+            NaiveDate::from_ymd_opt(2026, 7, 1).unwrap()
+        );
+    }
+}
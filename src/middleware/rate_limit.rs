@@ -0,0 +1,288 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    AppState,
+    config::Config,
+    error::{ErrorMessage, HttpError},
+    middleware::{
+        client_ip,
+        login_throttle::account_from_body,
+        rate_limit_store::{self, RateLimitStore},
+    },
+};
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const THROTTLE_LOG_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct ThrottleEvent {
+    pub scope: &'static str,
+    pub key: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleLog {
+    events: Arc<Mutex<VecDeque<ThrottleEvent>>>,
+}
+
+impl ThrottleLog {
+    pub fn record(&self, scope: &'static str, key: &str) {
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= THROTTLE_LOG_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(ThrottleEvent {
+            scope,
+            key: key.to_string(),
+            at: Utc::now(),
+        });
+    }
+
+    pub fn since(&self, since: DateTime<Utc>) -> Vec<ThrottleEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.at >= since)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    log: ThrottleLog,
+    scope: &'static str,
+    capacity: f64,
+    refill_per_second: f64,
+}
+
+impl RateLimiter {
+    pub fn per_minute(
+        store: &Arc<dyn RateLimitStore>,
+        log: &ThrottleLog,
+        scope: &'static str,
+        requests: u32,
+    ) -> Self {
+        RateLimiter {
+            store: store.clone(),
+            log: log.clone(),
+            scope,
+            capacity: requests.max(1) as f64,
+            refill_per_second: requests.max(1) as f64 / 60.0,
+        }
+    }
+
+    pub async fn check(&self, key: &str) -> Result<(), Duration> {
+        let result = self
+            .store
+            .take(
+                &format!("{}:{}", self.scope, key),
+                self.capacity,
+                self.refill_per_second,
+            )
+            .await;
+        if result.is_err() {
+            self.log.record(self.scope, key);
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EndpointRateLimit {
+    pub ip: RateLimiter,
+    pub email: RateLimiter,
+}
+
+impl EndpointRateLimit {
+    pub fn per_minute(
+        store: &Arc<dyn RateLimitStore>,
+        log: &ThrottleLog,
+        scope: &'static str,
+        ip_requests: u32,
+        email_requests: u32,
+    ) -> Self {
+        EndpointRateLimit {
+            ip: RateLimiter::per_minute(store, log, scope, ip_requests),
+            email: RateLimiter::per_minute(store, log, scope, email_requests),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub availability: RateLimiter,
+    pub nonce: RateLimiter,
+    pub two_factor: RateLimiter,
+    pub reauthenticate: RateLimiter,
+    pub reset_password: RateLimiter,
+    pub activity_export: RateLimiter,
+    pub login: EndpointRateLimit,
+    pub register: EndpointRateLimit,
+    pub forgot_password: EndpointRateLimit,
+    pub throttled: ThrottleLog,
+}
+
+impl RateLimits {
+    pub fn new(config: &Config) -> Self {
+        let store = rate_limit_store::from_config(config);
+        let throttled = ThrottleLog::default();
+
+        RateLimits {
+            availability: RateLimiter::per_minute(
+                &store,
+                &throttled,
+                "availability",
+                config.availability_rate_limit,
+            ),
+            nonce: RateLimiter::per_minute(&store, &throttled, "nonce", config.nonce_rate_limit),
+            two_factor: RateLimiter::per_minute(
+                &store,
+                &throttled,
+                "two_factor",
+                config.two_factor_rate_limit,
+            ),
+            reauthenticate: RateLimiter::per_minute(
+                &store,
+                &throttled,
+                "reauthenticate",
+                config.reauth_rate_limit,
+            ),
+            reset_password: RateLimiter::per_minute(
+                &store,
+                &throttled,
+                "reset_password",
+                config.reset_password_rate_limit,
+            ),
+            activity_export: RateLimiter::per_minute(
+                &store,
+                &throttled,
+                "activity_export",
+                config.activity_export_rate_limit,
+            ),
+            login: EndpointRateLimit::per_minute(
+                &store,
+                &throttled,
+                "login",
+                config.login_rate_limit_ip,
+                config.login_rate_limit_email,
+            ),
+            register: EndpointRateLimit::per_minute(
+                &store,
+                &throttled,
+                "register",
+                config.register_rate_limit_ip,
+                config.register_rate_limit_email,
+            ),
+            forgot_password: EndpointRateLimit::per_minute(
+                &store,
+                &throttled,
+                "forgot_password",
+                config.forgot_password_rate_limit_ip,
+                config.forgot_password_rate_limit_email,
+            ),
+            throttled,
+        }
+    }
+}
+
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+pub const RATE_LIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitStatus {
+    pub limit: i64,
+    pub remaining: i64,
+    pub reset: i64,
+}
+
+impl RateLimitStatus {
+    pub fn apply(&self, response: &mut Response) {
+        let headers = response.headers_mut();
+        for (name, value) in [
+            (RATE_LIMIT_LIMIT_HEADER, self.limit),
+            (RATE_LIMIT_REMAINING_HEADER, self.remaining.max(0)),
+            (RATE_LIMIT_RESET_HEADER, self.reset),
+        ] {
+            headers.insert(name, HeaderValue::from(value));
+        }
+    }
+}
+
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let mut response = HttpError::new(
+        axum::http::StatusCode::TOO_MANY_REQUESTS,
+        ErrorMessage::TooManyRequests.to_string(),
+    )
+    .into_response();
+    let seconds = retry_after.as_secs().max(1).to_string();
+    if let Ok(value) = HeaderValue::from_str(&seconds) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+pub async fn rate_limit_by_ip(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+    select: fn(&RateLimits) -> &RateLimiter,
+) -> Response {
+    let key = client_ip(&req, app_state.env.trusted_proxy_hops)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    if let Err(retry_after) = select(&app_state.rate_limits).check(&key).await {
+        return too_many_requests(retry_after);
+    }
+
+    next.run(req).await
+}
+
+pub async fn rate_limit_by_ip_and_email(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+    select: fn(&RateLimits) -> &EndpointRateLimit,
+) -> Result<Response, HttpError> {
+    let limits = select(&app_state.rate_limits);
+    let ip = client_ip(&req, app_state.env.trusted_proxy_hops)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    if let Err(retry_after) = limits.ip.check(&ip).await {
+        return Ok(too_many_requests(retry_after));
+    }
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+
+    if let Some(email) = account_from_body(&body)
+        && let Err(retry_after) = limits.email.check(&format!("email:{}", email)).await
+    {
+        tracing::warn!(target: "audit", event = "email_rate_limited", email = %email, ip = %ip);
+        return Ok(too_many_requests(retry_after));
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
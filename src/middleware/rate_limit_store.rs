@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::config::{Config, RateLimitBackend};
+
+#[async_trait]
+pub trait RateLimitStore: std::fmt::Debug + Send + Sync {
+    async fn take(&self, key: &str, capacity: f64, refill_per_second: f64) -> Result<(), Duration>;
+}
+
+pub fn from_config(config: &Config) -> Arc<dyn RateLimitStore> {
+    match config.rate_limit_backend {
+        RateLimitBackend::Memory => Arc::new(MemoryStore::new()),
+        RateLimitBackend::Redis => redis_store(config),
+    }
+}
+
+#[cfg(feature = "redis")]
+fn redis_store(config: &Config) -> Arc<dyn RateLimitStore> {
+    if config.redis_url.is_none() {
+        panic!("REDIS_URL must be set when RATE_LIMIT_STORE is redis");
+    }
+
+    let client = crate::redis_client::RedisClient::from_config(config)
+        .unwrap_or_else(|e| panic!("Invalid redis configuration: {}", e));
+    Arc::new(RedisStore::new(client))
+}
+
+#[cfg(not(feature = "redis"))]
+fn redis_store(_config: &Config) -> Arc<dyn RateLimitStore> {
+    panic!("RATE_LIMIT_STORE=redis requires building with the redis feature");
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    idle: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn take(&self, key: &str, capacity: f64, refill_per_second: f64) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let now = Instant::now();
+
+        if buckets.len() > 10_000 {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < bucket.idle);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+            idle: Duration::from_secs_f64(capacity / refill_per_second),
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_second).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / refill_per_second;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local refill = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or capacity
+local updated_at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated_at) / 1000 * refill)
+
+local wait = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    wait = math.ceil((1 - tokens) / refill * 1000)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / refill * 1000))
+return wait
+"#;
+
+#[cfg(feature = "redis")]
+pub struct RedisStore {
+    client: crate::redis_client::RedisClient,
+    script: redis::Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisStore {
+    pub fn new(client: crate::redis_client::RedisClient) -> Self {
+        RedisStore {
+            client,
+            script: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+        }
+    }
+
+    async fn wait_for(
+        &self,
+        key: &str,
+        capacity: f64,
+        refill_per_second: f64,
+    ) -> redis::RedisResult<u64> {
+        let mut connection = self.client.connection().await?;
+
+        let result = self
+            .script
+            .key(format!("ratelimit:{}", key))
+            .arg(capacity)
+            .arg(refill_per_second)
+            .invoke_async(&mut connection)
+            .await;
+        if let Err(e) = &result {
+            self.client.recover(e).await;
+        }
+
+        result
+    }
+}
+
+#[cfg(feature = "redis")]
+impl std::fmt::Debug for RedisStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn take(&self, key: &str, capacity: f64, refill_per_second: f64) -> Result<(), Duration> {
+        match self.wait_for(key, capacity, refill_per_second).await {
+            Ok(0) => Ok(()),
+            Ok(wait) => Err(Duration::from_millis(wait)),
+            Err(e) => {
+                tracing::warn!("Rate limit store unavailable, allowing request: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
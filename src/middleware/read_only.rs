@@ -0,0 +1,71 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+    Extension,
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    AppState,
+    config::Config,
+    error::{ErrorMessage, HttpError},
+    middleware::request_path,
+};
+
+pub const READ_ONLY_EXEMPT_PATHS: &[&str] = &[
+    "/api/auth/login",
+    "/api/auth/2fa/verify",
+    "/api/auth/refresh",
+    "/api/admin/read-only",
+];
+
+#[derive(Debug, Clone)]
+pub struct ReadOnlyMode {
+    enabled: Arc<AtomicBool>,
+}
+
+impl ReadOnlyMode {
+    pub fn new(config: &Config) -> Self {
+        ReadOnlyMode {
+            enabled: Arc::new(AtomicBool::new(config.read_only_mode)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+pub fn is_mutating(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+pub async fn read_only(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !app_state.read_only.is_enabled() || !is_mutating(req.method()) {
+        return next.run(req).await;
+    }
+
+    let path = request_path(&req);
+    if READ_ONLY_EXEMPT_PATHS.contains(&path.as_str()) {
+        return next.run(req).await;
+    }
+
+    HttpError::service_unavailable(ErrorMessage::ReadOnlyMode.to_string()).into_response()
+}
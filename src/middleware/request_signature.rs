@@ -0,0 +1,87 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{OriginalUri, Request},
+};
+use chrono::Utc;
+
+use crate::{
+    AppState,
+    error::{ErrorMessage, HttpError},
+    nonce::{self, NoncePurpose},
+    utils::request_signature::{self, NONCE_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+const MAX_NONCE_LENGTH: usize = 128;
+
+fn signature_headers(req: &Request) -> Option<(String, String, String)> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+
+    Some((
+        header(SIGNATURE_HEADER)?,
+        header(TIMESTAMP_HEADER)?,
+        header(NONCE_HEADER)?,
+    ))
+}
+
+pub async fn verify_signed_request(
+    app_state: &AppState,
+    key_id: uuid::Uuid,
+    secret: &str,
+    req: Request,
+) -> Result<Request, HttpError> {
+    let invalid = || HttpError::unauthorized(ErrorMessage::InvalidRequestSignature.to_string());
+    let (signature, timestamp, nonce) = signature_headers(&req).ok_or_else(invalid)?;
+
+    let timestamp = timestamp.trim().parse::<i64>().map_err(|_| invalid())?;
+    let tolerance = app_state.env.request_signature_tolerance as i64;
+    if (Utc::now().timestamp() - timestamp).abs() > tolerance {
+        return Err(invalid());
+    }
+
+    if nonce.is_empty() || nonce.len() > MAX_NONCE_LENGTH {
+        return Err(invalid());
+    }
+
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.clone())
+        .unwrap_or_else(|| req.uri().clone());
+    let path = path
+        .path_and_query()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| path.path().to_string());
+    let method = req.method().to_string();
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+
+    if !request_signature::verify(secret, &signature, &method, &path, timestamp, &nonce, &body) {
+        return Err(invalid());
+    }
+
+    let claimed = nonce::claim(
+        &app_state.db_client,
+        NoncePurpose::RequestSignature,
+        &format!("{}:{}", key_id, nonce),
+        tolerance * 2,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if !claimed {
+        tracing::warn!(target: "audit", event = "signed_request_replayed", api_key_id = %key_id);
+        return Err(HttpError::unauthorized(
+            ErrorMessage::RequestReplayed.to_string(),
+        ));
+    }
+
+    Ok(Request::from_parts(parts, Body::from(body)))
+}
@@ -0,0 +1,271 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    audit::{self, AuditEntry, AuditEvent},
+    config::Config,
+    db::LoginAttemptExt,
+    error::{ErrorMessage, HttpError},
+    handler::auth::DEVICE_ID_HEADER,
+    middleware::{
+        ClientContext,
+        captcha::{CAPTCHA_TOKEN_HEADER, captcha_challenge},
+        client_ip,
+        login_throttle::{account_from_body, account_key, ip_key},
+    },
+};
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskAction {
+    Login,
+    Register,
+}
+
+impl RiskAction {
+    pub fn to_str(&self) -> &str {
+        match self {
+            RiskAction::Login => "login",
+            RiskAction::Register => "register",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskDecision {
+    Allow,
+    RequireCaptcha,
+    RequireMfa,
+    Block,
+}
+
+impl RiskDecision {
+    pub fn to_str(&self) -> &str {
+        match self {
+            RiskDecision::Allow => "allow",
+            RiskDecision::RequireCaptcha => "require_captcha",
+            RiskDecision::RequireMfa => "require_mfa",
+            RiskDecision::Block => "block",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RiskContext {
+    pub action: RiskAction,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>,
+    pub account: Option<String>,
+    pub ip_failures: i32,
+    pub account_failures: i32,
+}
+
+#[derive(Debug)]
+pub struct RiskError(pub String);
+
+impl fmt::Display for RiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RiskError: {}", self.0)
+    }
+}
+
+impl std::error::Error for RiskError {}
+
+#[async_trait]
+pub trait RiskAssessor: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn assess(&self, context: &RiskContext) -> Result<u8, RiskError>;
+}
+
+#[derive(Clone)]
+pub struct RiskPolicy {
+    assessor: Option<Arc<dyn RiskAssessor>>,
+    pub captcha_threshold: u8,
+    pub mfa_threshold: u8,
+    pub block_threshold: u8,
+    pub timeout: Duration,
+}
+
+impl fmt::Debug for RiskPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RiskPolicy")
+            .field("assessor", &self.assessor.as_ref().map(|a| a.name()))
+            .field("captcha_threshold", &self.captcha_threshold)
+            .field("mfa_threshold", &self.mfa_threshold)
+            .field("block_threshold", &self.block_threshold)
+            .finish()
+    }
+}
+
+impl RiskPolicy {
+    pub fn new(config: &Config) -> Self {
+        RiskPolicy {
+            assessor: None,
+            captcha_threshold: config.risk_captcha_threshold,
+            mfa_threshold: config.risk_mfa_threshold,
+            block_threshold: config.risk_block_threshold,
+            timeout: Duration::from_millis(config.risk_timeout_ms),
+        }
+    }
+
+    pub fn with_assessor(mut self, assessor: Arc<dyn RiskAssessor>) -> Self {
+        self.assessor = Some(assessor);
+        self
+    }
+
+    pub fn decide(&self, action: RiskAction, score: u8) -> RiskDecision {
+        if score >= self.block_threshold {
+            RiskDecision::Block
+        } else if score >= self.mfa_threshold && action == RiskAction::Login {
+            RiskDecision::RequireMfa
+        } else if score >= self.captcha_threshold {
+            RiskDecision::RequireCaptcha
+        } else {
+            RiskDecision::Allow
+        }
+    }
+
+    async fn score(&self, assessor: &dyn RiskAssessor, context: &RiskContext) -> Option<u8> {
+        match tokio::time::timeout(self.timeout, assessor.assess(context)).await {
+            Ok(Ok(score)) => Some(score.min(100)),
+            Ok(Err(err)) => {
+                tracing::warn!(
+                    assessor = assessor.name(),
+                    "risk assessment failed: {}",
+                    err
+                );
+                None
+            }
+            Err(_) => {
+                tracing::warn!(assessor = assessor.name(), "risk assessment timed out");
+                None
+            }
+        }
+    }
+}
+
+pub async fn risk_check(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+    action: RiskAction,
+) -> Result<Response, HttpError> {
+    let policy = &app_state.risk;
+    let Some(assessor) = &policy.assessor else {
+        return Ok(next.run(req).await);
+    };
+
+    let remote_ip = client_ip(&req, app_state.env.trusted_proxy_hops);
+    let ip = remote_ip.map(|ip| ip.to_string());
+    let headers = req.headers();
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let (user_agent, device_id, captcha_token) = (
+        header_value(header::USER_AGENT.as_str()),
+        header_value(DEVICE_ID_HEADER),
+        header_value(CAPTCHA_TOKEN_HEADER),
+    );
+    let client = req
+        .extensions()
+        .get::<ClientContext>()
+        .cloned()
+        .unwrap_or_default();
+
+    let (mut parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+    let account = account_from_body(&body);
+
+    let db_client = &app_state.db_client;
+    let window = app_state.env.captcha_window;
+    let ip_failures = db_client
+        .get_login_failures(&ip_key(ip.as_deref().unwrap_or("unknown")), window)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let account_failures = match &account {
+        Some(account) => db_client
+            .get_login_failures(&account_key(account), window)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+        None => 0,
+    };
+
+    let context = RiskContext {
+        action,
+        ip,
+        user_agent,
+        device_id,
+        account,
+        ip_failures,
+        account_failures,
+    };
+
+    let score = policy.score(assessor.as_ref(), &context).await;
+    let decision = score.map_or(RiskDecision::Allow, |score| policy.decide(action, score));
+
+    tracing::info!(
+        target: "audit",
+        event = "risk_assessed",
+        action = action.to_str(),
+        assessor = assessor.name(),
+        score = ?score,
+        decision = decision.to_str(),
+        ip = ?context.ip,
+    );
+    if decision != RiskDecision::Allow {
+        audit::record(
+            &app_state,
+            AuditEntry::new(AuditEvent::RiskDecision)
+                .client(&client)
+                .detail(serde_json::json!({
+                    "action": action.to_str(),
+                    "assessor": assessor.name(),
+                    "score": score,
+                    "decision": decision.to_str(),
+                    "email": context.account,
+                })),
+        );
+    }
+
+    match decision {
+        RiskDecision::Allow | RiskDecision::RequireMfa => {}
+        RiskDecision::Block => {
+            return Err(HttpError::forbidden(ErrorMessage::RiskBlocked.to_string()));
+        }
+        RiskDecision::RequireCaptcha => {
+            let Some(captcha_token) = captcha_token else {
+                return Ok(captcha_challenge(ErrorMessage::CaptchaRequired));
+            };
+
+            match app_state.captcha.verify(&captcha_token, remote_ip).await {
+                Some(true) => {}
+                Some(false) => return Ok(captcha_challenge(ErrorMessage::CaptchaInvalid)),
+                None => {
+                    tracing::warn!("risk policy requires a CAPTCHA but none is configured");
+                    return Err(HttpError::forbidden(ErrorMessage::RiskBlocked.to_string()));
+                }
+            }
+        }
+    }
+
+    parts.extensions.insert(decision);
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl SecurityHeaders {
+    pub fn new(content_security_policy: &str) -> Self {
+        let mut headers = vec![
+            (
+                header::STRICT_TRANSPORT_SECURITY,
+                HeaderValue::from_static("max-age=63072000; includeSubDomains"),
+            ),
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+            (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+            (
+                header::REFERRER_POLICY,
+                HeaderValue::from_static("no-referrer"),
+            ),
+        ];
+
+        if !content_security_policy.is_empty() {
+            let policy = HeaderValue::from_str(content_security_policy)
+                .expect("CONTENT_SECURITY_POLICY must be a valid header value");
+            headers.push((header::CONTENT_SECURITY_POLICY, policy));
+        }
+
+        SecurityHeaders { headers }
+    }
+}
+
+pub async fn security_headers(
+    State(security_headers): State<SecurityHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+
+    let headers = response.headers_mut();
+    for (name, value) in &security_headers.headers {
+        if !headers.contains_key(name) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+
+    response
+}
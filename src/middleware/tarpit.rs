@@ -0,0 +1,147 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    AppState,
+    config::Config,
+    error::{ErrorMessage, HttpError},
+    middleware::client_ip,
+};
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+struct Offender {
+    accounts: HashSet<String>,
+    window_start: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tarpit {
+    offenders: Arc<Mutex<HashMap<IpAddr, Offender>>>,
+    pub enabled: bool,
+    pub account_threshold: usize,
+    pub window: Duration,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub decoy: bool,
+}
+
+impl Tarpit {
+    pub fn new(config: &Config) -> Self {
+        Tarpit {
+            offenders: Arc::new(Mutex::new(HashMap::new())),
+            enabled: config.tarpit_enabled,
+            account_threshold: config.tarpit_account_threshold,
+            window: Duration::from_secs(config.tarpit_window),
+            base_delay: Duration::from_millis(config.tarpit_base_delay_ms),
+            max_delay: Duration::from_millis(config.tarpit_max_delay_ms),
+            decoy: config.tarpit_decoy,
+        }
+    }
+
+    fn excess(&self, ip: IpAddr) -> usize {
+        let mut offenders = self.offenders.lock().unwrap();
+        let now = Instant::now();
+        offenders.retain(|_, offender| now.duration_since(offender.window_start) < self.window);
+
+        offenders
+            .get(&ip)
+            .map(|offender| {
+                offender
+                    .accounts
+                    .len()
+                    .saturating_sub(self.account_threshold)
+            })
+            .unwrap_or(0)
+    }
+
+    fn record_failure(&self, ip: IpAddr, account: String) {
+        let mut offenders = self.offenders.lock().unwrap();
+        offenders
+            .entry(ip)
+            .or_insert_with(|| Offender {
+                accounts: HashSet::new(),
+                window_start: Instant::now(),
+            })
+            .accounts
+            .insert(account);
+    }
+
+    fn delay_for(&self, excess: usize) -> Duration {
+        let factor = 1u32.checked_shl(excess.min(16) as u32).unwrap_or(u32::MAX);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+fn account_from_body(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    value
+        .get("email")
+        .and_then(|email| email.as_str())
+        .map(|email| email.trim().to_lowercase())
+}
+
+pub async fn tarpit(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let tarpit = &app_state.tarpit;
+    let ip = client_ip(&req, app_state.env.trusted_proxy_hops);
+
+    let (Some(ip), true) = (ip, tarpit.enabled) else {
+        return Ok(next.run(req).await);
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| HttpError::bad_request("Request body is too large"))?;
+    let account = account_from_body(&body);
+
+    let excess = tarpit.excess(ip);
+    if excess > 0 {
+        let delay = tarpit.delay_for(excess);
+        tracing::warn!(
+            target: "audit",
+            event = "tarpit",
+            ip = %ip,
+            excess,
+            delay_ms = delay.as_millis() as u64,
+            "slowing down suspected credential stuffing"
+        );
+        tokio::time::sleep(delay).await;
+
+        if tarpit.decoy && excess > tarpit.account_threshold {
+            if let Some(account) = account {
+                tarpit.record_failure(ip, account);
+            }
+            return Ok(
+                HttpError::bad_request(ErrorMessage::WrongCredentials.to_string()).into_response(),
+            );
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    if response.status().is_client_error()
+        && let Some(account) = account
+    {
+        tarpit.record_failure(ip, account);
+    }
+
+    Ok(response)
+}
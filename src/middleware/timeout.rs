@@ -0,0 +1,65 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    error::{ErrorMessage, HttpError},
+    metrics::Metrics,
+    middleware::{access_log::RequestId, request_path},
+};
+
+#[derive(Debug, Clone)]
+pub struct HandlerTimeout {
+    name: String,
+    duration: Duration,
+    timeouts: Arc<AtomicU64>,
+}
+
+impl HandlerTimeout {
+    pub fn new(name: &str, timeout_ms: u64, metrics: &Metrics) -> Self {
+        HandlerTimeout {
+            name: name.to_string(),
+            duration: Duration::from_millis(timeout_ms),
+            timeouts: metrics.counter(&format!("timeouts.{}", name)),
+        }
+    }
+}
+
+pub async fn handler_timeout(
+    State(timeout): State<HandlerTimeout>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone())
+        .unwrap_or_default();
+    let path = request_path(&req);
+
+    match tokio::time::timeout(timeout.duration, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            timeout.timeouts.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                route = %timeout.name,
+                path = %path,
+                request_id = %request_id,
+                timeout_ms = timeout.duration.as_millis() as u64,
+                "Handler timed out"
+            );
+
+            HttpError::gateway_timeout(ErrorMessage::RequestTimedOut.to_string()).into_response()
+        }
+    }
+}
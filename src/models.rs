@@ -1,34 +1,584 @@
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
-pub enum UserRole {
-    User,
-    Admin,
-}
+use crate::{pagination::Sortable, permissions::Permission};
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+#[serde(transparent)]
+pub struct UserRole(String);
 
 impl UserRole {
+    pub const USER: &'static str = "user";
+    pub const ADMIN: &'static str = "admin";
+
+    pub fn new(name: impl Into<String>) -> Self {
+        UserRole(name.into())
+    }
+
+    pub fn user() -> Self {
+        UserRole::new(UserRole::USER)
+    }
+
+    pub fn admin() -> Self {
+        UserRole::new(UserRole::ADMIN)
+    }
+
+    pub fn to_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.0 == UserRole::ADMIN
+    }
+
+    pub fn is_built_in(&self) -> bool {
+        self.0 == UserRole::USER || self.0 == UserRole::ADMIN
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self.0.as_str() {
+            UserRole::USER => &[Permission::ProfileRead, Permission::ProfileWrite],
+            UserRole::ADMIN => &Permission::ALL,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Role {
+    pub name: UserRole,
+    pub description: Option<String>,
+    pub parent: Option<UserRole>,
+    #[serde(rename = "builtIn")]
+    pub built_in: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RolePermission {
+    pub role: UserRole,
+    pub permission: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "account_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStatus {
+    Active,
+    PendingApproval,
+    Rejected,
+}
+
+impl AccountStatus {
+    pub const ALL: [AccountStatus; 3] = [
+        AccountStatus::Active,
+        AccountStatus::PendingApproval,
+        AccountStatus::Rejected,
+    ];
+
     pub fn to_str(&self) -> &str {
         match self {
-            UserRole::User => "user",
-            UserRole::Admin => "admin",
+            AccountStatus::Active => "active",
+            AccountStatus::PendingApproval => "pending_approval",
+            AccountStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "role_grant_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RoleGrantStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl RoleGrantStatus {
+    pub fn to_str(&self) -> &str {
+        match self {
+            RoleGrantStatus::Pending => "pending",
+            RoleGrantStatus::Approved => "approved",
+            RoleGrantStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "outbox_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    Dead,
+}
+
+impl OutboxStatus {
+    pub fn to_str(&self) -> &str {
+        match self {
+            OutboxStatus::Pending => "pending",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Dead => "dead",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "announcement_severity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AnnouncementSeverity {
+    pub fn to_str(&self) -> &str {
+        match self {
+            AnnouncementSeverity::Info => "info",
+            AnnouncementSeverity::Warning => "warning",
+            AnnouncementSeverity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "registration_state", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationState {
+    PendingProfile,
+    Complete,
+}
+
+impl RegistrationState {
+    pub fn to_str(&self) -> &str {
+        match self {
+            RegistrationState::PendingProfile => "pending_profile",
+            RegistrationState::Complete => "complete",
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow, sqlx::Type)]
 pub struct User {
+    #[serde(with = "crate::utils::public_id")]
     pub id: uuid::Uuid,
     pub name: String,
     pub email: String,
     pub password: String,
     pub role: UserRole,
     pub verified: bool,
-    pub verification_code: Option<String>,
+    pub verification_token_hash: Option<String>,
     pub token_expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub reset_token_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub reset_token_expires_at: Option<DateTime<Utc>>,
+    #[serde(rename = "lockedAt")]
+    pub locked_at: Option<DateTime<Utc>>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<uuid::Uuid>,
+    #[serde(rename = "accountStatus")]
+    pub account_status: AccountStatus,
+    #[serde(rename = "registrationState")]
+    pub registration_state: RegistrationState,
+    #[serde(rename = "lastLoginAt")]
+    pub last_login_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub dormant_notified_at: Option<DateTime<Utc>>,
+    #[serde(rename = "deactivatedAt")]
+    pub deactivated_at: Option<DateTime<Utc>>,
+    pub plan: Option<String>,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    #[serde(rename = "totpEnabledAt")]
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+    #[serde(rename = "passwordChangedAt")]
+    pub password_changed_at: DateTime<Utc>,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<DateTime<Utc>>,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Organization {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub branding_display_name: Option<String>,
+    pub branding_logo_url: Option<String>,
+    pub branding_accent_color: Option<String>,
+    pub branding_reply_to: Option<String>,
+    pub session_idle_timeout: Option<i32>,
+    pub session_max_lifetime: Option<i32>,
+    pub geo_blocked_countries: Vec<String>,
+    pub geo_mfa_countries: Vec<String>,
+    pub geo_blocked_asns: Vec<i64>,
+    pub plan: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct EmailBranding {
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    #[serde(rename = "logoUrl")]
+    pub logo_url: Option<String>,
+    #[serde(rename = "accentColor")]
+    pub accent_color: Option<String>,
+    #[serde(rename = "replyTo")]
+    pub reply_to: Option<String>,
+}
+
+impl Organization {
+    pub fn email_branding(&self) -> EmailBranding {
+        EmailBranding {
+            display_name: self.branding_display_name.clone(),
+            logo_url: self.branding_logo_url.clone(),
+            accent_color: self.branding_accent_color.clone(),
+            reply_to: self.branding_reply_to.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Invitation {
+    pub id: uuid::Uuid,
+    pub email: String,
+    #[serde(skip_serializing)]
+    pub token: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<uuid::Uuid>,
+    #[serde(rename = "invitedBy")]
+    pub invited_by: Option<uuid::Uuid>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "acceptedAt")]
+    pub accepted_at: Option<DateTime<Utc>>,
+    #[serde(rename = "acceptedBy")]
+    pub accepted_by: Option<uuid::Uuid>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn is_usable_by(&self, email: &str) -> bool {
+        self.accepted_at.is_none()
+            && self.expires_at > Utc::now()
+            && self.email.eq_ignore_ascii_case(email)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RecoveryEmail {
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub email: String,
+    #[serde(rename = "verifiedAt")]
+    pub verified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub verification_token_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub token_expires_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct SecurityQuestion {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub position: i16,
+    pub question: String,
+    #[serde(skip_serializing)]
+    pub answer_hash: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Delegation {
+    pub id: uuid::Uuid,
+    #[serde(rename = "ownerId", with = "crate::utils::public_id")]
+    pub owner_id: uuid::Uuid,
+    #[serde(rename = "delegateId", with = "crate::utils::public_id")]
+    pub delegate_id: uuid::Uuid,
+    pub scopes: Vec<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(rename = "revokedBy")]
+    pub revoked_by: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ResetCode {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    #[serde(skip_serializing)]
+    pub code_hash: String,
+    pub attempts: i32,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "usedAt")]
+    pub used_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Session {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    #[serde(rename = "idleTimeout")]
+    pub idle_timeout: Option<i32>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub refresh_token_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub client_fingerprint: Option<String>,
+    pub audience: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct LoginAttempt {
+    pub key: String,
+    pub failures: i32,
+    #[serde(rename = "windowStartedAt")]
+    pub window_started_at: DateTime<Utc>,
+    #[serde(rename = "lockedUntil")]
+    pub locked_until: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        let idle = self.idle_timeout.is_some_and(|minutes| {
+            now - self.last_seen_at > chrono::Duration::minutes(minutes as i64)
+        });
+
+        self.revoked_at.is_none() && self.expires_at > now && !idle
+    }
+}
+
+impl Sortable for User {
+    const SORT_KEYS: &'static [(&'static str, &'static str)] = &[
+        ("name", "name"),
+        ("email", "email"),
+        ("role", "role"),
+        ("createdAt", "created_at"),
+        ("updatedAt", "updated_at"),
+    ];
+    const DEFAULT_SORT: &'static str = "createdAt";
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct ApiKey {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub role: Option<UserRole>,
+    #[serde(skip_serializing)]
+    pub signing_secret: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Announcement {
+    pub id: uuid::Uuid,
+    pub message: String,
+    pub severity: AnnouncementSeverity,
+    #[serde(rename = "startsAt")]
+    pub starts_at: DateTime<Utc>,
+    #[serde(rename = "endsAt")]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdBy", with = "crate::utils::public_id::option")]
+    pub created_by: Option<uuid::Uuid>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct OAuthAccount {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub provider: String,
+    #[serde(rename = "providerUserId")]
+    pub provider_user_id: String,
+    pub email: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct UsageCounter {
+    pub subject: String,
+    pub period: String,
+    #[serde(rename = "periodStart")]
+    pub period_start: NaiveDate,
+    pub count: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct UserChange {
+    pub id: i64,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub operation: String,
+    #[serde(rename = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Credential {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    #[serde(rename = "credentialId")]
+    pub credential_id: String,
+    #[serde(skip_serializing)]
+    pub passkey: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Nonce {
+    pub purpose: String,
+    pub nonce: String,
+    pub user_id: Option<uuid::Uuid>,
+    pub payload: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ScheduledJob {
+    pub name: String,
+    #[serde(rename = "lockedBy")]
+    pub locked_by: Option<String>,
+    #[serde(rename = "lockedUntil")]
+    pub locked_until: Option<DateTime<Utc>>,
+    #[serde(rename = "lastStartedAt")]
+    pub last_started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "lastFinishedAt")]
+    pub last_finished_at: Option<DateTime<Utc>>,
+    #[serde(rename = "lastRunBy")]
+    pub last_run_by: Option<String>,
+    #[serde(rename = "lastDurationMs")]
+    pub last_duration_ms: Option<i64>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "runCount")]
+    pub run_count: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct OutboxEmail {
+    pub id: uuid::Uuid,
+    pub recipient: String,
+    pub cc: Vec<String>,
+    #[serde(rename = "replyTo")]
+    pub reply_to: Option<String>,
+    #[serde(rename = "fromName")]
+    pub from_name: Option<String>,
+    pub subject: String,
+    #[serde(skip_serializing)]
+    pub text_body: String,
+    #[serde(skip_serializing)]
+    pub html_body: Option<String>,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(rename = "sentAt")]
+    pub sent_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RoleGrant {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId", with = "crate::utils::public_id")]
+    pub user_id: uuid::Uuid,
+    pub role: UserRole,
+    pub status: RoleGrantStatus,
+    #[serde(rename = "requestedBy", with = "crate::utils::public_id")]
+    pub requested_by: uuid::Uuid,
+    #[serde(rename = "decidedBy", with = "crate::utils::public_id::option")]
+    pub decided_by: Option<uuid::Uuid>,
+    #[serde(rename = "decidedAt")]
+    pub decided_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct AuditLog {
+    pub id: i64,
+    pub event: String,
+    #[serde(rename = "actorId", with = "crate::utils::public_id::option")]
+    pub actor_id: Option<uuid::Uuid>,
+    #[serde(rename = "targetId", with = "crate::utils::public_id::option")]
+    pub target_id: Option<uuid::Uuid>,
+    pub ip: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    pub detail: serde_json::Value,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "prevHash")]
+    pub prev_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl Sortable for AuditLog {
+    const SORT_KEYS: &'static [(&'static str, &'static str)] =
+        &[("createdAt", "created_at"), ("event", "event")];
+    const DEFAULT_SORT: &'static str = "createdAt";
+}
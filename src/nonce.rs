@@ -0,0 +1,115 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    db::{DBClient, NonceExt},
+    models::Nonce,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoncePurpose {
+    Client,
+    WebAuthnRegistration,
+    WebAuthnAuthentication,
+    RequestSignature,
+    ActivityExport,
+    OAuthLink,
+    MfaToken,
+}
+
+impl NoncePurpose {
+    pub fn to_str(&self) -> &str {
+        match self {
+            NoncePurpose::Client => "client",
+            NoncePurpose::WebAuthnRegistration => "webauthn_registration",
+            NoncePurpose::WebAuthnAuthentication => "webauthn_authentication",
+            NoncePurpose::RequestSignature => "request_signature",
+            NoncePurpose::ActivityExport => "activity_export",
+            NoncePurpose::OAuthLink => "oauth_link",
+            NoncePurpose::MfaToken => "mfa_token",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IssuedNonce {
+    pub nonce: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub async fn issue(
+    db_client: &DBClient,
+    purpose: NoncePurpose,
+    user_id: Option<Uuid>,
+    payload: Option<&str>,
+    ttl_seconds: i64,
+) -> Result<IssuedNonce, sqlx::Error> {
+    let nonce = generate();
+    let expires_at = Utc::now() + Duration::seconds(ttl_seconds);
+
+    db_client
+        .create_nonce(purpose.to_str(), &nonce, user_id, payload, expires_at)
+        .await?;
+
+    Ok(IssuedNonce { nonce, expires_at })
+}
+
+pub async fn store(
+    db_client: &DBClient,
+    purpose: NoncePurpose,
+    nonce: &str,
+    user_id: Option<Uuid>,
+    payload: Option<&str>,
+    ttl_seconds: i64,
+) -> Result<bool, sqlx::Error> {
+    if nonce.is_empty() {
+        return Ok(false);
+    }
+
+    db_client
+        .create_nonce(
+            purpose.to_str(),
+            nonce,
+            user_id,
+            payload,
+            Utc::now() + Duration::seconds(ttl_seconds),
+        )
+        .await
+}
+
+pub async fn claim(
+    db_client: &DBClient,
+    purpose: NoncePurpose,
+    nonce: &str,
+    ttl_seconds: i64,
+) -> Result<bool, sqlx::Error> {
+    store(db_client, purpose, nonce, None, None, ttl_seconds).await
+}
+
+pub async fn consume(
+    db_client: &DBClient,
+    purpose: NoncePurpose,
+    nonce: &str,
+) -> Result<Option<Nonce>, sqlx::Error> {
+    if nonce.is_empty() {
+        return Ok(None);
+    }
+
+    db_client.consume_nonce(purpose.to_str(), nonce).await
+}
+
+pub async fn verify(
+    db_client: &DBClient,
+    purpose: NoncePurpose,
+    nonce: &str,
+) -> Result<bool, sqlx::Error> {
+    Ok(consume(db_client, purpose, nonce).await?.is_some())
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    mail::{
+        EmailMessage, EmailSender,
+        templates::{EmailTemplates, RenderedEmail},
+    },
+    models::EmailBranding,
+    notify::{Audience, Notification, Notifier, NotifyError},
+};
+
+const DEFAULT_ACCENT_COLOR: &str = "#2563eb";
+
+pub struct EmailNotifier {
+    sender: Arc<dyn EmailSender>,
+    templates: EmailTemplates,
+}
+
+impl EmailNotifier {
+    pub fn new(sender: Arc<dyn EmailSender>, templates: EmailTemplates) -> Self {
+        EmailNotifier { sender, templates }
+    }
+
+    fn render(&self, notification: &Notification, branding: &EmailBranding) -> RenderedEmail {
+        if let Some((template, values)) = &notification.template {
+            match self.templates.render(*template, values, branding) {
+                Ok(rendered) => return rendered,
+                Err(e) => tracing::warn!(
+                    template = template.to_str(),
+                    "falling back to plain email: {}",
+                    e
+                ),
+            }
+        }
+
+        RenderedEmail {
+            subject: notification.subject.clone(),
+            text: notification.message.clone(),
+            html: render_html(notification, branding),
+        }
+    }
+}
+
+fn render_html(notification: &Notification, branding: &EmailBranding) -> String {
+    let accent = branding
+        .accent_color
+        .as_deref()
+        .unwrap_or(DEFAULT_ACCENT_COLOR);
+    let logo = branding
+        .logo_url
+        .as_deref()
+        .map(|url| {
+            format!(
+                r#"<img src="{}" alt="" style="max-height:48px"><br>"#,
+                escape(url)
+            )
+        })
+        .unwrap_or_default();
+    let message = escape(&notification.message).replace('\n', "<br>");
+
+    format!(
+        r#"<div style="font-family:sans-serif;border-top:4px solid {accent};padding:16px">{logo}<h2 style="color:{accent}">{subject}</h2><p>{message}</p></div>"#,
+        accent = escape(accent),
+        logo = logo,
+        subject = escape(&notification.subject),
+        message = message,
+    )
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn channel(&self) -> &'static str {
+        "email"
+    }
+
+    fn accepts(&self, audience: &Audience) -> bool {
+        matches!(audience, Audience::User { .. } | Audience::Email { .. })
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let email = match &notification.audience {
+            Audience::User { email, .. } | Audience::Email { email } => email,
+            Audience::Admins => return Ok(()),
+        };
+
+        let branding = notification.branding.clone().unwrap_or_default();
+        let rendered = self.render(notification, &branding);
+
+        let message = EmailMessage {
+            to: email.clone(),
+            cc: notification.cc.clone(),
+            reply_to: branding.reply_to.clone(),
+            from_name: branding.display_name.clone(),
+            subject: rendered.subject,
+            text: rendered.text,
+            html: Some(rendered.html),
+        };
+
+        self.sender
+            .send(&message)
+            .await
+            .map_err(|e| NotifyError(e.to_string()))
+    }
+}
@@ -0,0 +1,249 @@
+pub mod email;
+pub mod webhook;
+
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    circuit::CircuitBreaker,
+    config::Config,
+    db::DBClient,
+    mail::{self, EmailSender, outbox::OutboxSender, templates::EmailTemplate},
+    metrics::Metrics,
+    models::EmailBranding,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    NewDevice,
+    AccountLocked,
+    PasswordChanged,
+    SecurityAlert,
+    Invitation,
+    EmailVerification,
+    PasswordReset,
+    ApiKeyLeaked,
+    AccountDormant,
+    PlanChanged,
+    MagicLink,
+    Welcome,
+    RoleGrantRequested,
+}
+
+impl NotificationKind {
+    pub fn to_str(&self) -> &str {
+        match self {
+            NotificationKind::NewDevice => "new_device",
+            NotificationKind::AccountLocked => "account_locked",
+            NotificationKind::PasswordChanged => "password_changed",
+            NotificationKind::SecurityAlert => "security_alert",
+            NotificationKind::Invitation => "invitation",
+            NotificationKind::EmailVerification => "email_verification",
+            NotificationKind::PasswordReset => "password_reset",
+            NotificationKind::ApiKeyLeaked => "api_key_leaked",
+            NotificationKind::AccountDormant => "account_dormant",
+            NotificationKind::PlanChanged => "plan_changed",
+            NotificationKind::MagicLink => "magic_link",
+            NotificationKind::Welcome => "welcome",
+            NotificationKind::RoleGrantRequested => "role_grant_requested",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Audience {
+    User {
+        #[serde(rename = "userId", with = "crate::utils::public_id")]
+        user_id: Uuid,
+        email: String,
+    },
+    Email {
+        email: String,
+    },
+    Admins,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub audience: Audience,
+    pub subject: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding: Option<EmailBranding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    #[serde(skip)]
+    pub template: Option<(EmailTemplate, serde_json::Value)>,
+}
+
+impl Notification {
+    pub fn to_user(
+        kind: NotificationKind,
+        user_id: Uuid,
+        email: impl Into<String>,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Notification {
+            kind,
+            audience: Audience::User {
+                user_id,
+                email: email.into(),
+            },
+            subject: subject.into(),
+            message: message.into(),
+            branding: None,
+            cc: Vec::new(),
+            template: None,
+        }
+    }
+
+    pub fn to_email(
+        kind: NotificationKind,
+        email: impl Into<String>,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Notification {
+            kind,
+            audience: Audience::Email {
+                email: email.into(),
+            },
+            subject: subject.into(),
+            message: message.into(),
+            branding: None,
+            cc: Vec::new(),
+            template: None,
+        }
+    }
+
+    pub fn to_admins(
+        kind: NotificationKind,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Notification {
+            kind,
+            audience: Audience::Admins,
+            subject: subject.into(),
+            message: message.into(),
+            branding: None,
+            cc: Vec::new(),
+            template: None,
+        }
+    }
+
+    pub fn with_branding(mut self, branding: Option<EmailBranding>) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    pub fn with_cc(mut self, email: Option<String>) -> Self {
+        self.cc.extend(email);
+        self
+    }
+
+    pub fn with_template(mut self, template: EmailTemplate, values: serde_json::Value) -> Self {
+        self.template = Some((template, values));
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct NotifyError(pub String);
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NotifyError: {}", self.0)
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn channel(&self) -> &'static str;
+
+    fn accepts(&self, audience: &Audience) -> bool;
+
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError>;
+}
+
+#[derive(Clone, Default)]
+pub struct NotificationDispatcher {
+    notifiers: Vec<Arc<dyn Notifier>>,
+}
+
+impl fmt::Debug for NotificationDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let channels: Vec<&str> = self.notifiers.iter().map(|n| n.channel()).collect();
+        f.debug_struct("NotificationDispatcher")
+            .field("channels", &channels)
+            .finish()
+    }
+}
+
+impl NotificationDispatcher {
+    pub fn new(notifiers: Vec<Arc<dyn Notifier>>) -> Self {
+        NotificationDispatcher { notifiers }
+    }
+
+    pub fn from_config(config: &Config, db_client: &DBClient, metrics: &Metrics) -> Self {
+        let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+
+        if let Some(sender) = mail::guarded(config, metrics) {
+            let sender: Arc<dyn EmailSender> = if config.email_outbox {
+                Arc::new(OutboxSender::new(db_client.clone()))
+            } else {
+                sender
+            };
+            let templates = mail::templates::EmailTemplates::from_config(config)
+                .expect("EMAIL_TEMPLATE_DIR must contain valid email templates");
+            notifiers.push(Arc::new(email::EmailNotifier::new(sender, templates)));
+        }
+        if let Some(url) = &config.slack_webhook_url {
+            notifiers.push(Arc::new(webhook::SlackNotifier::new(
+                url,
+                CircuitBreaker::new("slack", config, metrics),
+            )));
+        }
+        if let Some(url) = &config.notify_webhook_url {
+            notifiers.push(Arc::new(webhook::WebhookNotifier::new(
+                url,
+                CircuitBreaker::new("webhook", config, metrics),
+            )));
+        }
+
+        NotificationDispatcher::new(notifiers)
+    }
+
+    pub async fn dispatch(&self, notification: &Notification) {
+        for notifier in &self.notifiers {
+            if !notifier.accepts(&notification.audience) {
+                continue;
+            }
+
+            if let Err(err) = notifier.notify(notification).await {
+                tracing::warn!(
+                    channel = notifier.channel(),
+                    kind = notification.kind.to_str(),
+                    "failed to deliver notification: {}",
+                    err
+                );
+            }
+        }
+    }
+
+    pub fn spawn(&self, notification: Notification) {
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            dispatcher.dispatch(&notification).await;
+        });
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::{
+    circuit::CircuitBreaker,
+    notify::{Audience, Notification, Notifier, NotifyError},
+};
+
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    url: String,
+    breaker: CircuitBreaker,
+}
+
+impl SlackNotifier {
+    pub fn new(url: impl Into<String>, breaker: CircuitBreaker) -> Self {
+        SlackNotifier {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            breaker,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn channel(&self) -> &'static str {
+        "slack"
+    }
+
+    fn accepts(&self, audience: &Audience) -> bool {
+        matches!(audience, Audience::Admins)
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let payload = json!({
+            "text": format!("*{}*\n{}", notification.subject, notification.message),
+        });
+
+        self.breaker
+            .call(post_json(&self.client, &self.url, &payload))
+            .await
+            .map_err(|e| e.flatten(NotifyError))
+    }
+}
+
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    breaker: CircuitBreaker,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>, breaker: CircuitBreaker) -> Self {
+        WebhookNotifier {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            breaker,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn channel(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn accepts(&self, _audience: &Audience) -> bool {
+        true
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let payload = serde_json::to_value(notification).map_err(|e| NotifyError(e.to_string()))?;
+
+        self.breaker
+            .call(post_json(&self.client, &self.url, &payload))
+            .await
+            .map_err(|e| e.flatten(NotifyError))
+    }
+}
+
+async fn post_json(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), NotifyError> {
+    let response = client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| NotifyError(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(NotifyError(format!(
+            "webhook responded with status {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::oauth::{OAuthError, OAuthProfile, OAuthProvider};
+
+const AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const USER_URL: &str = "https://api.github.com/user";
+const EMAILS_URL: &str = "https://api.github.com/user/emails";
+const USER_AGENT: &str = "axum-auth";
+
+pub struct GitHubProvider {
+    client: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+impl GitHubProvider {
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Self {
+        GitHubProvider {
+            client: reqwest::Client::new(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: redirect_uri.into(),
+        }
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        access_token: &str,
+    ) -> Result<T, OAuthError> {
+        let value = self
+            .client
+            .get(url)
+            .bearer_auth(access_token)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<T>()
+            .await?;
+
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GitHubProvider {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    async fn authorize_url(&self, state: &str) -> Result<String, OAuthError> {
+        reqwest::Url::parse_with_params(
+            AUTHORIZE_URL,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", "read:user user:email"),
+                ("state", state),
+            ],
+        )
+        .map(String::from)
+        .map_err(|_| OAuthError::MissingField("authorize_url"))
+    }
+
+    async fn exchange(&self, code: &str, _state: &str) -> Result<OAuthProfile, OAuthError> {
+        let token = self
+            .client
+            .post(TOKEN_URL)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("code", code),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?
+            .access_token
+            .ok_or(OAuthError::MissingField("access_token"))?;
+
+        let user: GitHubUser = self.get(USER_URL, &token).await?;
+        let emails: Vec<GitHubEmail> = self.get(EMAILS_URL, &token).await?;
+        let email = emails
+            .into_iter()
+            .find(|email| email.primary)
+            .ok_or(OAuthError::MissingField("email"))?;
+
+        Ok(OAuthProfile {
+            provider_user_id: user.id.to_string(),
+            email: email.email,
+            email_verified: email.verified,
+            name: user.name.or(Some(user.login)),
+        })
+    }
+}
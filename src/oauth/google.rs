@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::oauth::{OAuthError, OAuthProfile, OAuthProvider};
+
+const AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+pub struct GoogleProvider {
+    client: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+impl GoogleProvider {
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Self {
+        GoogleProvider {
+            client: reqwest::Client::new(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: redirect_uri.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GoogleProvider {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    async fn authorize_url(&self, state: &str) -> Result<String, OAuthError> {
+        reqwest::Url::parse_with_params(
+            AUTHORIZE_URL,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("scope", "openid email profile"),
+                ("state", state),
+            ],
+        )
+        .map(String::from)
+        .map_err(|_| OAuthError::MissingField("authorize_url"))
+    }
+
+    async fn exchange(&self, code: &str, _state: &str) -> Result<OAuthProfile, OAuthError> {
+        let token = self
+            .client
+            .post(TOKEN_URL)
+            .form(&[
+                ("code", code),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?;
+
+        let info = self
+            .client
+            .get(USERINFO_URL)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<UserInfo>()
+            .await?;
+
+        Ok(OAuthProfile {
+            provider_user_id: info.sub,
+            email: info.email.ok_or(OAuthError::MissingField("email"))?,
+            email_verified: info.email_verified,
+            name: info.name,
+        })
+    }
+}
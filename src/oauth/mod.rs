@@ -0,0 +1,115 @@
+pub mod github;
+pub mod google;
+pub mod oidc;
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthProfile {
+    pub provider_user_id: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum OAuthError {
+    Request(reqwest::Error),
+    MissingField(&'static str),
+    InvalidIdToken,
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::Request(err) => write!(f, "provider request failed: {}", err),
+            OAuthError::MissingField(field) => write!(f, "provider response missing {}", field),
+            OAuthError::InvalidIdToken => write!(f, "provider returned an invalid ID token"),
+        }
+    }
+}
+
+impl std::error::Error for OAuthError {}
+
+impl From<reqwest::Error> for OAuthError {
+    fn from(err: reqwest::Error) -> Self {
+        OAuthError::Request(err)
+    }
+}
+
+#[async_trait]
+pub trait OAuthProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn authorize_url(&self, state: &str) -> Result<String, OAuthError>;
+
+    async fn exchange(&self, code: &str, state: &str) -> Result<OAuthProfile, OAuthError>;
+}
+
+#[derive(Clone, Default)]
+pub struct OAuthProviders {
+    providers: HashMap<&'static str, Arc<dyn OAuthProvider>>,
+}
+
+impl fmt::Debug for OAuthProviders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthProviders")
+            .field("providers", &self.providers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl OAuthProviders {
+    pub fn from_config(config: &Config) -> Self {
+        let mut providers = OAuthProviders::default();
+
+        if let (Some(client_id), Some(client_secret)) =
+            (&config.google_client_id, &config.google_client_secret)
+        {
+            providers.register(Arc::new(google::GoogleProvider::new(
+                client_id,
+                client_secret,
+                redirect_uri(config, "google"),
+            )));
+        }
+
+        if let (Some(client_id), Some(client_secret)) =
+            (&config.github_client_id, &config.github_client_secret)
+        {
+            providers.register(Arc::new(github::GitHubProvider::new(
+                client_id,
+                client_secret,
+                redirect_uri(config, "github"),
+            )));
+        }
+
+        if let Some(settings) = &config.oidc {
+            providers.register(Arc::new(oidc::OidcProvider::new(
+                settings.clone(),
+                redirect_uri(config, "oidc"),
+            )));
+        }
+
+        providers
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn OAuthProvider>) {
+        self.providers.insert(provider.name(), provider);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn OAuthProvider>> {
+        self.providers.get(name).cloned()
+    }
+}
+
+pub fn redirect_uri(config: &Config, provider: &str) -> String {
+    format!(
+        "{}/api/auth/oauth/{}/callback",
+        config.oauth_redirect_base.trim_end_matches('/'),
+        provider
+    )
+}
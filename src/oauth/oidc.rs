@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use crate::{
+    config::OidcSettings,
+    oauth::{OAuthError, OAuthProfile, OAuthProvider},
+};
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+pub struct OidcProvider {
+    client: reqwest::Client,
+    settings: OidcSettings,
+    redirect_uri: String,
+    discovery: OnceCell<Discovery>,
+}
+
+impl OidcProvider {
+    pub fn new(settings: OidcSettings, redirect_uri: impl Into<String>) -> Self {
+        OidcProvider {
+            client: reqwest::Client::new(),
+            settings,
+            redirect_uri: redirect_uri.into(),
+            discovery: OnceCell::new(),
+        }
+    }
+
+    async fn discovery(&self) -> Result<&Discovery, OAuthError> {
+        self.discovery
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.settings.issuer_url.trim_end_matches('/')
+                );
+                let discovery = self
+                    .client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<Discovery>()
+                    .await?;
+                Ok(discovery)
+            })
+            .await
+    }
+
+    async fn validate_id_token(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<HashMap<String, Value>, OAuthError> {
+        let header = decode_header(id_token).map_err(|_| OAuthError::InvalidIdToken)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OAuthError::InvalidIdToken);
+        }
+
+        let jwks = self
+            .client
+            .get(&discovery.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or(OAuthError::InvalidIdToken)?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| OAuthError::InvalidIdToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&discovery.issuer]);
+        validation.set_audience(&[&self.settings.client_id]);
+
+        let claims = decode::<HashMap<String, Value>>(id_token, &key, &validation)
+            .map_err(|_| OAuthError::InvalidIdToken)?
+            .claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err(OAuthError::InvalidIdToken);
+        }
+
+        Ok(claims)
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for OidcProvider {
+    fn name(&self) -> &'static str {
+        "oidc"
+    }
+
+    async fn authorize_url(&self, state: &str) -> Result<String, OAuthError> {
+        let discovery = self.discovery().await?;
+
+        reqwest::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("client_id", self.settings.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("scope", self.settings.scopes.as_str()),
+                ("state", state),
+                ("nonce", state),
+            ],
+        )
+        .map(String::from)
+        .map_err(|_| OAuthError::MissingField("authorization_endpoint"))
+    }
+
+    async fn exchange(&self, code: &str, state: &str) -> Result<OAuthProfile, OAuthError> {
+        let discovery = self.discovery().await?;
+
+        let id_token = self
+            .client
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("code", code),
+                ("client_id", &self.settings.client_id),
+                ("client_secret", &self.settings.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?
+            .id_token
+            .ok_or(OAuthError::MissingField("id_token"))?;
+
+        let claims = self.validate_id_token(discovery, &id_token, state).await?;
+        let claim = |name: &str| claims.get(name).and_then(Value::as_str).map(str::to_string);
+
+        Ok(OAuthProfile {
+            provider_user_id: claim("sub").ok_or(OAuthError::MissingField("sub"))?,
+            email: claim(&self.settings.email_claim).ok_or(OAuthError::MissingField("email"))?,
+            email_verified: claims
+                .get("email_verified")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            name: claim(&self.settings.name_claim),
+        })
+    }
+}
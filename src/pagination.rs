@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::dtos::{MAX_PAGE_LIMIT, QueryDTO, QueryOptions, parse_numeric_param};
+
+pub const DEFAULT_PAGE_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn to_str(&self) -> &str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+pub trait Sortable {
+    const SORT_KEYS: &'static [(&'static str, &'static str)];
+    const DEFAULT_SORT: &'static str;
+
+    fn sort_column(key: &str) -> Option<&'static str> {
+        Self::SORT_KEYS
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, column)| *column)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PageQuery<S: Sortable> {
+    pub page: usize,
+    pub limit: usize,
+    pub sort_column: &'static str,
+    pub order: SortOrder,
+    _sortable: PhantomData<S>,
+}
+
+impl<S: Sortable> PageQuery<S> {
+    pub fn offset(&self) -> i64 {
+        self.checked_offset().unwrap_or(i64::MAX)
+    }
+
+    fn checked_offset(&self) -> Option<i64> {
+        self.page
+            .checked_sub(1)?
+            .checked_mul(self.limit)
+            .and_then(|offset| i64::try_from(offset).ok())
+    }
+
+    pub fn order_by(&self) -> String {
+        format!("ORDER BY {} {}", self.sort_column, self.order.to_sql())
+    }
+}
+
+impl<S: Sortable> Default for PageQuery<S> {
+    fn default() -> Self {
+        PageQuery {
+            page: 1,
+            limit: DEFAULT_PAGE_LIMIT,
+            sort_column: S::sort_column(S::DEFAULT_SORT).unwrap_or("created_at"),
+            order: SortOrder::default(),
+            _sortable: PhantomData,
+        }
+    }
+}
+
+impl<S: Sortable> Validate for PageQuery<S> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if self.page < 1 {
+            let mut error = ValidationError::new("range");
+            error.message = Some("Page must be at least 1".into());
+            errors.add("page", error);
+        } else if self.checked_offset().is_none() {
+            let mut error = ValidationError::new("range");
+            error.message = Some("Page is out of range".into());
+            errors.add("page", error);
+        }
+        if self.limit < 1 || self.limit > MAX_PAGE_LIMIT {
+            let mut error = ValidationError::new("range");
+            error.message = Some(format!("Limit must be between 1 and {}", MAX_PAGE_LIMIT).into());
+            errors.add("limit", error);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<S: Sortable> QueryDTO for PageQuery<S> {
+    const FIELDS: &'static [&'static str] = &["page", "limit", "sort", "order"];
+
+    fn from_params(
+        params: &HashMap<String, String>,
+        options: &QueryOptions,
+        errors: &mut ValidationErrors,
+    ) -> Self {
+        let mut query = PageQuery::<S>::default();
+
+        if let Some(page) = parse_numeric_param(params, "page", errors) {
+            query.page = page;
+        }
+        if let Some(limit) = parse_numeric_param::<usize>(params, "limit", errors) {
+            query.limit = if options.clamp_limit {
+                limit.min(MAX_PAGE_LIMIT)
+            } else {
+                limit
+            };
+        }
+
+        if let Some(sort) = params.get("sort") {
+            match S::sort_column(sort) {
+                Some(column) => query.sort_column = column,
+                None => {
+                    let allowed: Vec<&str> = S::SORT_KEYS.iter().map(|(name, _)| *name).collect();
+                    let mut error = ValidationError::new("sort");
+                    error.message =
+                        Some(format!("Sort must be one of: {}", allowed.join(", ")).into());
+                    errors.add("sort", error);
+                }
+            }
+        }
+
+        if let Some(order) = params.get("order") {
+            match order.to_lowercase().as_str() {
+                "asc" => query.order = SortOrder::Asc,
+                "desc" => query.order = SortOrder::Desc,
+                _ => {
+                    let mut error = ValidationError::new("order");
+                    error.message = Some("Order must be either 'asc' or 'desc'".into());
+                    errors.add("order", error);
+                }
+            }
+        }
+
+        query
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub status: String,
+    pub data: Vec<T>,
+    pub page: usize,
+    pub limit: usize,
+    pub results: i64,
+    #[serde(rename = "totalPages")]
+    pub total_pages: i64,
+}
+
+impl<T> Paginated<T> {
+    pub fn new<S: Sortable>(data: Vec<T>, query: &PageQuery<S>, results: i64) -> Self {
+        let limit = query.limit.max(1) as i64;
+        Paginated {
+            status: "success".to_string(),
+            data,
+            page: query.page,
+            limit: query.limit,
+            results,
+            total_pages: (results + limit - 1) / limit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Items;
+
+    impl Sortable for Items {
+        const SORT_KEYS: &'static [(&'static str, &'static str)] = &[("createdAt", "created_at")];
+        const DEFAULT_SORT: &'static str = "createdAt";
+    }
+
+    fn parse(page: &str, limit: &str) -> Result<PageQuery<Items>, ValidationErrors> {
+        let params = HashMap::from([
+            ("page".to_string(), page.to_string()),
+            ("limit".to_string(), limit.to_string()),
+        ]);
+        let options = QueryOptions {
+            strict: true,
+            clamp_limit: false,
+        };
+        PageQuery::<Items>::parse(&params, &options)
+    }
+
+    #[test]
+    fn offset_starts_at_zero_on_the_first_page() {
+        let query = parse("1", "10").unwrap();
+        assert_eq!(query.offset(), 0);
+    }
+
+    #[test]
+    fn offset_skips_previous_pages() {
+        let query = parse("4", "25").unwrap();
+        assert_eq!(query.offset(), 75);
+    }
+
+    #[test]
+    fn rejects_page_zero() {
+        let errors = parse("0", "10").unwrap_err();
+        assert!(errors.field_errors().contains_key("page"));
+    }
+
+    #[test]
+    fn rejects_pages_whose_offset_overflows() {
+        let errors = parse(&usize::MAX.to_string(), "50").unwrap_err();
+        assert!(errors.field_errors().contains_key("page"));
+    }
+
+    #[test]
+    fn accepts_the_largest_representable_offset() {
+        let page = (i64::MAX as usize) / MAX_PAGE_LIMIT + 1;
+        let query = parse(&page.to_string(), &MAX_PAGE_LIMIT.to_string()).unwrap();
+        assert!(query.offset() >= 0);
+    }
+
+    #[test]
+    fn rejects_limits_above_the_maximum() {
+        let errors = parse("1", &(MAX_PAGE_LIMIT + 1).to_string()).unwrap_err();
+        assert!(errors.field_errors().contains_key("limit"));
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+pub const PROFILE_READ: &str = "profile:read";
+pub const PROFILE_WRITE: &str = "profile:write";
+pub const USERS_READ: &str = "users:read";
+pub const USERS_WRITE: &str = "users:write";
+pub const ROLES_WRITE: &str = "roles:write";
+pub const AUDIT_ORG_READ: &str = "audit:org:read";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "profile:write")]
+    ProfileWrite,
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    #[serde(rename = "roles:write")]
+    RolesWrite,
+    #[serde(rename = "audit:org:read")]
+    AuditOrgRead,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PermissionInfo {
+    pub permission: Permission,
+    pub description: &'static str,
+    pub group: &'static str,
+}
+
+pub const REGISTRY: [PermissionInfo; 6] = [
+    PermissionInfo {
+        permission: Permission::ProfileRead,
+        description: "View your own profile, sessions and usage",
+        group: "profile",
+    },
+    PermissionInfo {
+        permission: Permission::ProfileWrite,
+        description: "Update or delete your own account",
+        group: "profile",
+    },
+    PermissionInfo {
+        permission: Permission::UsersRead,
+        description: "List and view other user accounts",
+        group: "users",
+    },
+    PermissionInfo {
+        permission: Permission::UsersWrite,
+        description: "Create, update and lock other user accounts",
+        group: "users",
+    },
+    PermissionInfo {
+        permission: Permission::RolesWrite,
+        description: "Define roles and assign them to users",
+        group: "roles",
+    },
+    PermissionInfo {
+        permission: Permission::AuditOrgRead,
+        description: "View audit events for members of your organization",
+        group: "audit",
+    },
+];
+
+impl Permission {
+    pub const ALL: [Permission; 6] = [
+        Permission::ProfileRead,
+        Permission::ProfileWrite,
+        Permission::UsersRead,
+        Permission::UsersWrite,
+        Permission::RolesWrite,
+        Permission::AuditOrgRead,
+    ];
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Permission::ProfileRead => PROFILE_READ,
+            Permission::ProfileWrite => PROFILE_WRITE,
+            Permission::UsersRead => USERS_READ,
+            Permission::UsersWrite => USERS_WRITE,
+            Permission::RolesWrite => ROLES_WRITE,
+            Permission::AuditOrgRead => AUDIT_ORG_READ,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Permission> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.to_str() == value)
+    }
+
+    pub fn info(&self) -> &'static PermissionInfo {
+        REGISTRY
+            .iter()
+            .find(|info| info.permission == *self)
+            .expect("every permission is registered")
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::{
+    db::{DBClient, PermissionExt, RoleExt},
+    error::{ErrorMessage, HttpError},
+    models::{User, UserRole},
+};
+
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub roles: Vec<UserRole>,
+    pub permissions: Arc<HashSet<String>>,
+}
+
+impl AuthContext {
+    pub fn has_role(&self, role: &UserRole) -> bool {
+        self.roles.contains(role)
+    }
+
+    pub fn has_any_role(&self, roles: &[UserRole]) -> bool {
+        roles.iter().any(|role| self.has_role(role))
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    pub fn require_any_role(&self, roles: &[UserRole]) -> Result<(), HttpError> {
+        if !self.has_any_role(roles) {
+            return Err(HttpError::forbidden(
+                ErrorMessage::PermissionDenied.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn require(&self, permission: &str) -> Result<(), HttpError> {
+        if !self.has_permission(permission) {
+            return Err(HttpError::forbidden(
+                ErrorMessage::PermissionDenied.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn restrict(self, scope: &str) -> Self {
+        let scopes: HashSet<&str> = scope.split_whitespace().collect();
+
+        AuthContext {
+            roles: self.roles,
+            permissions: Arc::new(
+                self.permissions
+                    .iter()
+                    .filter(|permission| scopes.contains(permission.as_str()))
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn scope(&self) -> String {
+        let mut permissions: Vec<&str> = self.permissions.iter().map(String::as_str).collect();
+        permissions.sort_unstable();
+        permissions.join(" ")
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthContext {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthContext>()
+            .cloned()
+            .ok_or_else(|| HttpError::unauthorized(ErrorMessage::UserNotAuthenticated.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PermissionCache {
+    roles: Arc<RwLock<HashMap<UserRole, Arc<HashSet<String>>>>>,
+    granted: Arc<RwLock<HashMap<UserRole, HashSet<String>>>>,
+    parents: Arc<RwLock<HashMap<UserRole, UserRole>>>,
+}
+
+impl PermissionCache {
+    pub fn new() -> Self {
+        PermissionCache::default()
+    }
+
+    pub async fn load(&self, db_client: &DBClient) -> Result<(), sqlx::Error> {
+        let mut granted: HashMap<UserRole, HashSet<String>> = HashMap::new();
+        for row in db_client.get_role_permissions().await? {
+            granted.entry(row.role).or_default().insert(row.permission);
+        }
+
+        let parents: HashMap<UserRole, UserRole> = db_client
+            .get_roles()
+            .await?
+            .into_iter()
+            .filter_map(|role| role.parent.map(|parent| (role.name, parent)))
+            .collect();
+
+        *self.granted.write().unwrap() = granted;
+        *self.parents.write().unwrap() = parents;
+        self.invalidate();
+        Ok(())
+    }
+
+    pub fn resolve(&self, user: &User) -> AuthContext {
+        AuthContext {
+            roles: self.lineage(&user.role),
+            permissions: self.permissions_for(&user.role),
+        }
+    }
+
+    pub fn lineage(&self, role: &UserRole) -> Vec<UserRole> {
+        let parents = self.parents.read().unwrap();
+        let mut lineage = vec![role.clone()];
+        while let Some(parent) = parents.get(lineage.last().unwrap()) {
+            if lineage.contains(parent) {
+                break;
+            }
+            lineage.push(parent.clone());
+        }
+
+        lineage
+    }
+
+    pub fn permissions_for(&self, role: &UserRole) -> Arc<HashSet<String>> {
+        if let Some(permissions) = self.roles.read().unwrap().get(role) {
+            return permissions.clone();
+        }
+
+        let lineage = self.lineage(role);
+        let granted = self.granted.read().unwrap();
+        let permissions: Arc<HashSet<String>> = Arc::new(
+            lineage
+                .iter()
+                .flat_map(|role| match granted.get(role) {
+                    Some(granted) => granted.iter().cloned().collect::<Vec<_>>(),
+                    None => role
+                        .permissions()
+                        .iter()
+                        .map(|permission| permission.to_str().to_string())
+                        .collect(),
+                })
+                .collect(),
+        );
+        drop(granted);
+
+        self.roles
+            .write()
+            .unwrap()
+            .insert(role.clone(), permissions.clone());
+        permissions
+    }
+
+    pub fn invalidate(&self) {
+        self.roles.write().unwrap().clear();
+    }
+}
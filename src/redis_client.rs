@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use redis::{
+    Client, Cmd, ErrorKind, IntoConnectionInfo, Pipeline, RedisError, RedisFuture, RedisResult,
+    Value,
+    aio::{ConnectionLike, ConnectionManager, ConnectionManagerConfig},
+    cluster::{ClusterClient, ClusterClientBuilder},
+    cluster_async::ClusterConnection,
+    sentinel::{SentinelClient, SentinelNodeConnectionInfo, SentinelServerType},
+};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::config::{Config, RedisMode};
+
+enum Target {
+    Single(Client),
+    Sentinel(Mutex<SentinelClient>),
+    Cluster(ClusterClient),
+}
+
+#[derive(Clone)]
+pub enum RedisConnection {
+    Single(ConnectionManager),
+    Cluster(ClusterConnection),
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            RedisConnection::Single(connection) => connection.req_packed_command(cmd),
+            RedisConnection::Cluster(connection) => connection.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            RedisConnection::Single(connection) => {
+                connection.req_packed_commands(cmd, offset, count)
+            }
+            RedisConnection::Cluster(connection) => {
+                connection.req_packed_commands(cmd, offset, count)
+            }
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            RedisConnection::Single(connection) => connection.get_db(),
+            RedisConnection::Cluster(connection) => connection.get_db(),
+        }
+    }
+}
+
+pub struct RedisClient {
+    mode: RedisMode,
+    target: Target,
+    connect_timeout: Duration,
+    response_timeout: Duration,
+    max_retries: usize,
+    connection: RwLock<Option<RedisConnection>>,
+}
+
+impl RedisClient {
+    pub fn from_config(config: &Config) -> RedisResult<Self> {
+        let nodes: Vec<&str> = config
+            .redis_url
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|node| !node.is_empty())
+            .collect();
+        if nodes.is_empty() {
+            return Err(RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "REDIS_URL must list at least one node",
+            )));
+        }
+
+        let connect_timeout = Duration::from_millis(config.redis_connect_timeout_ms);
+        let response_timeout = Duration::from_millis(config.redis_response_timeout_ms);
+
+        let target = match config.redis_mode {
+            RedisMode::Single => {
+                if nodes.len() > 1 {
+                    return Err(RedisError::from((
+                        ErrorKind::InvalidClientConfig,
+                        "REDIS_URL lists several nodes, set REDIS_MODE to sentinel or cluster",
+                    )));
+                }
+                Target::Single(Client::open(nodes[0])?)
+            }
+            RedisMode::Sentinel => {
+                let master = config.redis_sentinel_master.clone().ok_or_else(|| {
+                    RedisError::from((
+                        ErrorKind::InvalidClientConfig,
+                        "REDIS_SENTINEL_MASTER must be set when REDIS_MODE is sentinel",
+                    ))
+                })?;
+                let node_info = SentinelNodeConnectionInfo {
+                    tls_mode: None,
+                    redis_connection_info: Some(nodes[0].into_connection_info()?.redis),
+                };
+                Target::Sentinel(Mutex::new(SentinelClient::build(
+                    nodes,
+                    master,
+                    Some(node_info),
+                    SentinelServerType::Master,
+                )?))
+            }
+            RedisMode::Cluster => Target::Cluster(
+                ClusterClientBuilder::new(nodes)
+                    .connection_timeout(connect_timeout)
+                    .response_timeout(response_timeout)
+                    .retries(config.redis_max_retries as u32)
+                    .build()?,
+            ),
+        };
+
+        Ok(RedisClient {
+            mode: config.redis_mode,
+            target,
+            connect_timeout,
+            response_timeout,
+            max_retries: config.redis_max_retries,
+            connection: RwLock::new(None),
+        })
+    }
+
+    pub fn mode(&self) -> RedisMode {
+        self.mode
+    }
+
+    pub async fn connection(&self) -> RedisResult<RedisConnection> {
+        if let Some(connection) = self.connection.read().await.as_ref() {
+            return Ok(connection.clone());
+        }
+
+        let mut slot = self.connection.write().await;
+        if let Some(connection) = slot.as_ref() {
+            return Ok(connection.clone());
+        }
+
+        let connection = self.connect().await?;
+        *slot = Some(connection.clone());
+        Ok(connection)
+    }
+
+    pub async fn recover(&self, err: &RedisError) {
+        if self.mode != RedisMode::Sentinel {
+            return;
+        }
+
+        if err.is_io_error() || err.is_timeout() || err.kind() == ErrorKind::ReadOnly {
+            tracing::warn!(
+                "Redis master unreachable, resolving it again through sentinel: {}",
+                err
+            );
+            *self.connection.write().await = None;
+        }
+    }
+
+    pub async fn ping(&self) -> RedisResult<()> {
+        let mut connection = self.connection().await?;
+        let result = redis::cmd("PING")
+            .query_async::<String>(&mut connection)
+            .await;
+        if let Err(err) = &result {
+            self.recover(err).await;
+        }
+
+        result.map(|_| ())
+    }
+
+    async fn connect(&self) -> RedisResult<RedisConnection> {
+        match &self.target {
+            Target::Single(client) => self.manage(client.clone()).await,
+            Target::Sentinel(sentinel) => {
+                let client = tokio::time::timeout(
+                    self.connect_timeout,
+                    sentinel.lock().await.async_get_client(),
+                )
+                .await
+                .map_err(|_| {
+                    RedisError::from((
+                        ErrorKind::IoError,
+                        "Timed out resolving the sentinel master",
+                    ))
+                })??;
+                self.manage(client).await
+            }
+            Target::Cluster(client) => Ok(RedisConnection::Cluster(
+                client.get_async_connection().await?,
+            )),
+        }
+    }
+
+    async fn manage(&self, client: Client) -> RedisResult<RedisConnection> {
+        let config = ConnectionManagerConfig::new()
+            .set_connection_timeout(self.connect_timeout)
+            .set_response_timeout(self.response_timeout)
+            .set_number_of_retries(self.max_retries);
+
+        Ok(RedisConnection::Single(
+            ConnectionManager::new_with_config(client, config).await?,
+        ))
+    }
+}
+
+impl std::fmt::Debug for RedisClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisClient")
+            .field("mode", &self.mode)
+            .field(
+                "connected",
+                &self
+                    .connection
+                    .try_read()
+                    .map(|connection| connection.is_some())
+                    .unwrap_or(true),
+            )
+            .field("connect_timeout", &self.connect_timeout)
+            .field("response_timeout", &self.response_timeout)
+            .finish()
+    }
+}
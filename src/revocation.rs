@@ -0,0 +1,175 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use sqlx::postgres::PgListener;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::{DBClient, RevokedTokenExt, SessionExt},
+    models::Session,
+};
+
+pub const REVOCATION_CHANNEL: &str = "auth_revocations";
+
+#[derive(Debug, Clone)]
+pub struct RevocationCache {
+    ttl: Duration,
+    tokens: Arc<Mutex<HashMap<String, Instant>>>,
+    sessions: Arc<Mutex<HashMap<Uuid, (Session, Instant)>>>,
+}
+
+impl RevocationCache {
+    pub fn new(ttl_seconds: u64) -> Self {
+        RevocationCache {
+            ttl: Duration::from_secs(ttl_seconds),
+            tokens: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    pub async fn is_token_revoked(
+        &self,
+        db_client: &DBClient,
+        jti: &str,
+    ) -> Result<bool, sqlx::Error> {
+        if self.is_enabled()
+            && let Some(checked_at) = self.tokens.lock().unwrap().get(jti)
+            && checked_at.elapsed() < self.ttl
+        {
+            return Ok(false);
+        }
+
+        let revoked = db_client.is_token_revoked(jti).await?;
+        if self.is_enabled() && !revoked {
+            let mut tokens = self.tokens.lock().unwrap();
+            tokens.retain(|_, checked_at| checked_at.elapsed() < self.ttl);
+            tokens.insert(jti.to_string(), Instant::now());
+        }
+
+        Ok(revoked)
+    }
+
+    pub async fn get_session(
+        &self,
+        db_client: &DBClient,
+        session_id: Uuid,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        if self.is_enabled()
+            && let Some((session, checked_at)) = self.sessions.lock().unwrap().get(&session_id)
+            && checked_at.elapsed() < self.ttl
+        {
+            return Ok(Some(session.clone()));
+        }
+
+        let session = db_client.get_session(session_id).await?;
+        if self.is_enabled()
+            && let Some(session) = session.as_ref().filter(|s| s.revoked_at.is_none())
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            sessions.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+            sessions.insert(session_id, (session.clone(), Instant::now()));
+        }
+
+        Ok(session)
+    }
+
+    pub fn evict_token(&self, jti: &str) {
+        self.tokens.lock().unwrap().remove(jti);
+    }
+
+    pub fn evict_session(&self, session_id: Uuid) {
+        self.sessions.lock().unwrap().remove(&session_id);
+    }
+
+    pub fn clear(&self) {
+        self.tokens.lock().unwrap().clear();
+        self.sessions.lock().unwrap().clear();
+    }
+}
+
+fn clear_all(app_state: &AppState) {
+    app_state.revocations.clear();
+    app_state.user_cache.clear();
+    reload_keys(app_state);
+}
+
+fn reload_keys(app_state: &AppState) {
+    if !app_state.env.jwt_key_files {
+        return;
+    }
+
+    if let Err(e) = app_state.jwt_keys.rotate() {
+        tracing::warn!("JWT key reload failed: {}", e);
+    }
+}
+
+pub async fn broadcast(db_client: &DBClient, payload: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(REVOCATION_CHANNEL)
+        .bind(payload)
+        .execute(db_client.pool())
+        .await?;
+
+    Ok(())
+}
+
+fn apply(app_state: &AppState, payload: &str) {
+    let parse = |id: &str| Uuid::parse_str(id).ok();
+
+    match payload.split_once(':') {
+        Some(("token", jti)) => app_state.revocations.evict_token(jti),
+        Some(("session", id)) => match parse(id) {
+            Some(id) => app_state.revocations.evict_session(id),
+            None => clear_all(app_state),
+        },
+        Some(("user", id)) => match parse(id) {
+            Some(id) => app_state.user_cache.evict(id),
+            None => clear_all(app_state),
+        },
+        Some(("keys", _)) => reload_keys(app_state),
+        _ => clear_all(app_state),
+    }
+}
+
+pub fn spawn_listener(app_state: Arc<AppState>) {
+    if !app_state.revocations.is_enabled()
+        && !app_state.user_cache.is_enabled()
+        && !app_state.env.jwt_key_files
+    {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen(&app_state).await {
+                tracing::warn!("Revocation listener failed: {}", e);
+            }
+            clear_all(&app_state);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
+
+async fn listen(app_state: &AppState) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(app_state.db_client.pool()).await?;
+    listener.listen(REVOCATION_CHANNEL).await?;
+    clear_all(app_state);
+
+    loop {
+        match listener.try_recv().await? {
+            Some(notification) => apply(app_state, notification.payload()),
+            None => {
+                tracing::warn!("Revocation listener reconnected, clearing cached lookups");
+                clear_all(app_state);
+            }
+        }
+    }
+}
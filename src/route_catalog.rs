@@ -0,0 +1,351 @@
+use crate::{AppState, config::RouteGroup, models::UserRole, permissions::Permission};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Public,
+    Optional,
+    Signature,
+    Authenticated,
+    LocalOrAdmin,
+}
+
+impl Access {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Access::Public => "public",
+            Access::Optional => "optional",
+            Access::Signature => "signature",
+            Access::Authenticated => "authenticated",
+            Access::LocalOrAdmin => "local-or-admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RouteSpec {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub permission: Option<Permission>,
+    pub sudo: bool,
+    pub api_keys: bool,
+    pub delegated: bool,
+    pub rate_limit: Option<&'static str>,
+}
+
+impl RouteSpec {
+    const fn new(method: &'static str, path: &'static str) -> Self {
+        RouteSpec {
+            method,
+            path,
+            permission: None,
+            sudo: false,
+            api_keys: true,
+            delegated: true,
+            rate_limit: None,
+        }
+    }
+
+    const fn permission(self, permission: Permission) -> Self {
+        RouteSpec {
+            permission: Some(permission),
+            ..self
+        }
+    }
+
+    const fn sudo(self) -> Self {
+        RouteSpec { sudo: true, ..self }
+    }
+
+    const fn account(self) -> Self {
+        RouteSpec {
+            api_keys: false,
+            delegated: false,
+            ..self
+        }
+    }
+
+    const fn no_api_key(self) -> Self {
+        RouteSpec {
+            api_keys: false,
+            ..self
+        }
+    }
+
+    const fn no_delegation(self) -> Self {
+        RouteSpec {
+            delegated: false,
+            ..self
+        }
+    }
+
+    const fn rate_limit(self, scope: &'static str) -> Self {
+        RouteSpec {
+            rate_limit: Some(scope),
+            ..self
+        }
+    }
+}
+
+const fn get(path: &'static str) -> RouteSpec {
+    RouteSpec::new("GET", path)
+}
+
+const fn post(path: &'static str) -> RouteSpec {
+    RouteSpec::new("POST", path)
+}
+
+const fn put(path: &'static str) -> RouteSpec {
+    RouteSpec::new("PUT", path)
+}
+
+const fn delete(path: &'static str) -> RouteSpec {
+    RouteSpec::new("DELETE", path)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RouteGroupSpec {
+    pub prefix: &'static str,
+    pub name: Option<&'static str>,
+    pub access: Access,
+    pub roles: &'static [&'static str],
+    pub routes: &'static [RouteSpec],
+}
+
+const HEALTH: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api",
+    name: None,
+    access: Access::Public,
+    roles: &[],
+    routes: &[get("/healthchecker")],
+};
+
+const AUTH: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/auth",
+    name: Some("auth"),
+    access: Access::Public,
+    roles: &[],
+    routes: &[
+        post("/login").rate_limit("login"),
+        post("/magic-link"),
+        get("/magic-link/verify"),
+        post("/2fa/verify").rate_limit("two_factor"),
+        post("/refresh"),
+        post("/webauthn/login/start"),
+        post("/webauthn/login/finish"),
+        get("/verify"),
+        get("/verify-recovery-email"),
+        post("/forgot-password").rate_limit("forgot_password"),
+        post("/reset-password").rate_limit("reset_password"),
+        post("/reset-code/request"),
+        post("/reset-code/verify"),
+        post("/reset-code/set-password").rate_limit("reset_password"),
+        get("/security-questions"),
+        get("/csrf"),
+        post("/nonce").rate_limit("nonce"),
+        get("/availability").rate_limit("availability"),
+    ],
+};
+
+const AUTH_SESSION: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/auth",
+    name: Some("auth"),
+    access: Access::Authenticated,
+    roles: &[],
+    routes: &[
+        post("/reauthenticate")
+            .rate_limit("reauthenticate")
+            .account(),
+        post("/logout").no_api_key(),
+    ],
+};
+
+const REGISTRATION: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/auth",
+    name: Some("auth"),
+    access: Access::Public,
+    roles: &[],
+    routes: &[
+        post("/register").rate_limit("register"),
+        post("/register/start"),
+        post("/complete-registration"),
+    ],
+};
+
+const SOCIAL_LOGIN: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/auth/oauth",
+    name: Some("auth"),
+    access: Access::Public,
+    roles: &[],
+    routes: &[get("/{provider}"), get("/{provider}/callback")],
+};
+
+const ANNOUNCEMENTS: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/announcements",
+    name: None,
+    access: Access::Optional,
+    roles: &[],
+    routes: &[get("/")],
+};
+
+const WEBHOOKS: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/webhooks",
+    name: None,
+    access: Access::Signature,
+    roles: &[],
+    routes: &[post("/stripe")],
+};
+
+const USERS: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/users",
+    name: Some("users"),
+    access: Access::Authenticated,
+    roles: &[],
+    routes: &[
+        get("/me").permission(Permission::ProfileRead),
+        delete("/me")
+            .permission(Permission::ProfileWrite)
+            .sudo()
+            .account(),
+        get("/me/security")
+            .permission(Permission::ProfileRead)
+            .no_delegation(),
+        get("/me/usage").permission(Permission::ProfileRead),
+        put("/name").permission(Permission::ProfileWrite),
+        get("/organization/audit-logs")
+            .permission(Permission::AuditOrgRead)
+            .no_delegation(),
+        get("/recovery-email").account(),
+        put("/recovery-email").sudo().account(),
+        delete("/recovery-email").sudo().account(),
+        get("/security-questions").account(),
+        put("/security-questions").account(),
+        get("/delegations").account(),
+        post("/delegations").account(),
+        delete("/delegations/{id}").account(),
+        post("/delegations/{id}/token").account(),
+        post("/2fa/enroll").sudo().account(),
+        post("/2fa/confirm").account(),
+        post("/2fa/backup-codes").sudo().account(),
+        post("/webauthn/register/start").sudo().account(),
+        post("/webauthn/register/finish").sudo().account(),
+        get("/webauthn/credentials").account(),
+        delete("/webauthn/credentials/{id}").sudo().account(),
+        get("/api-keys").account(),
+        post("/api-keys").account(),
+        delete("/api-keys/{id}").account(),
+        get("/me/activity/export")
+            .rate_limit("activity_export")
+            .account(),
+        get("/me/activity/export/{id}").account(),
+        post("/oauth/{provider}/link").account(),
+    ],
+};
+
+const API_KEY_LEAKS: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/admin/api-keys",
+    name: None,
+    access: Access::Signature,
+    roles: &[],
+    routes: &[post("/report-leak")],
+};
+
+const ADMIN: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/api/admin",
+    name: Some("admin"),
+    access: Access::Authenticated,
+    roles: &[UserRole::ADMIN],
+    routes: &[
+        get("/maintenance"),
+        put("/maintenance").sudo(),
+        get("/read-only"),
+        put("/read-only").sudo(),
+        get("/metrics"),
+        get("/jwt-keys"),
+        post("/jwt-keys/rotate").sudo(),
+        get("/jobs"),
+        get("/audit-logs"),
+        get("/audit-logs/verify"),
+        get("/doctor"),
+        get("/outbox"),
+        post("/outbox/{id}/retry"),
+        post("/organizations"),
+        get("/organizations/{id}"),
+        put("/organizations/{id}/branding"),
+        put("/organizations/{id}/session-policy"),
+        put("/organizations/{id}/geo-policy"),
+        put("/organizations/{id}/plan"),
+        get("/users/changes").permission(Permission::UsersRead),
+        delete("/users/{id}")
+            .permission(Permission::UsersWrite)
+            .sudo(),
+        post("/users/{id}/restore")
+            .permission(Permission::UsersWrite)
+            .sudo(),
+        put("/users/{id}/plan").permission(Permission::UsersWrite),
+        post("/roles/bulk-assign").permission(Permission::RolesWrite),
+        get("/permissions"),
+        get("/routes"),
+        get("/roles"),
+        post("/roles").permission(Permission::RolesWrite),
+        put("/roles/{name}").permission(Permission::RolesWrite),
+        delete("/roles/{name}").permission(Permission::RolesWrite),
+        get("/roles/grants"),
+        post("/roles/grants/{id}/approve").permission(Permission::RolesWrite),
+        post("/roles/grants/{id}/reject").permission(Permission::RolesWrite),
+        get("/waitlist/"),
+        post("/waitlist/{id}/approve"),
+        post("/waitlist/{id}/reject").sudo(),
+        get("/announcements/"),
+        post("/announcements/"),
+        put("/announcements/{id}"),
+        delete("/announcements/{id}"),
+        get("/security/attacks"),
+        post("/invitations"),
+    ],
+};
+
+const PROFILING: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/debug/pprof",
+    name: None,
+    access: Access::LocalOrAdmin,
+    roles: &[UserRole::ADMIN],
+    routes: &[get("/heap"), get("/runtime"), get("/tasks")],
+};
+
+const JWKS: RouteGroupSpec = RouteGroupSpec {
+    prefix: "/.well-known",
+    name: None,
+    access: Access::Public,
+    roles: &[],
+    routes: &[get("/jwks.json")],
+};
+
+pub fn mounted_groups(app_state: &AppState) -> Vec<RouteGroupSpec> {
+    let disabled = &app_state.env.disabled_route_groups;
+    let mut groups = vec![HEALTH, AUTH, AUTH_SESSION];
+
+    if !disabled.contains(&RouteGroup::Registration) {
+        groups.push(REGISTRATION);
+    }
+    if !disabled.contains(&RouteGroup::SocialLogin) {
+        groups.push(SOCIAL_LOGIN);
+    }
+
+    groups.push(ANNOUNCEMENTS);
+    if cfg!(feature = "stripe") {
+        groups.push(WEBHOOKS);
+    }
+    groups.extend([USERS, API_KEY_LEAKS]);
+
+    if !disabled.contains(&RouteGroup::Admin) {
+        groups.push(ADMIN);
+    }
+    if cfg!(feature = "profiling") && app_state.env.profiling_enabled {
+        groups.push(PROFILING);
+    }
+    if app_state.jwt_keys.jwks().is_some() {
+        groups.push(JWKS);
+    }
+
+    groups
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    http::{StatusCode, header},
+    middleware,
+    response::IntoResponse,
+    routing::get,
+};
+use tower_http::trace::TraceLayer;
+
+use crate::{
+    AppState,
+    config::RouteGroup,
+    handler::{
+        admin::admin_handler, announcements::announcements_handler,
+        api_keys::api_keys_leak_handler, auth::auth_handler, users::users_handler,
+    },
+    middleware::{
+        access_log::{AccessLog, access_log, request_id},
+        auth, client_context,
+        csrf::csrf,
+        geo::geo_admin,
+        ip_filter::{IpFilter, ip_filter},
+        load_shed::{LoadShedder, load_shed},
+        maintenance::maintenance,
+        quota::quota,
+        read_only::read_only,
+        require_audience,
+        security_headers::{SecurityHeaders, security_headers},
+        timeout::{HandlerTimeout, handler_timeout},
+    },
+};
+
+pub fn create_router(app_state: Arc<AppState>) -> Router {
+    let admin_ip_filter = IpFilter::new(
+        app_state.env.admin_ip_allowlist.clone(),
+        app_state.env.admin_ip_denylist.clone(),
+        app_state.env.trusted_proxy_hops,
+    );
+
+    let mut api_route = Router::new()
+        .route("/healthchecker", get(health_checker_handler))
+        .nest(
+            "/auth",
+            limit_route(auth_handler(&app_state.env), "auth", &app_state),
+        )
+        .nest("/announcements", announcements_handler())
+        .merge(webhook_routes())
+        .nest(
+            "/users",
+            limit_route(
+                users_handler()
+                    .layer(middleware::from_fn(quota))
+                    .layer(middleware::from_fn(|state, req, next| {
+                        require_audience(state, req, next, "users")
+                    }))
+                    .layer(middleware::from_fn(auth)),
+                "users",
+                &app_state,
+            ),
+        )
+        .nest("/admin/api-keys", api_keys_leak_handler());
+
+    if !app_state
+        .env
+        .disabled_route_groups
+        .contains(&RouteGroup::Admin)
+    {
+        api_route = api_route.nest(
+            "/admin",
+            limit_route(
+                admin_handler()
+                    .layer(middleware::from_fn(quota))
+                    .layer(middleware::from_fn(|state, req, next| {
+                        require_audience(state, req, next, "admin")
+                    }))
+                    .layer(middleware::from_fn(auth))
+                    .layer(middleware::from_fn(geo_admin))
+                    .layer(middleware::from_fn_with_state(
+                        admin_ip_filter.clone(),
+                        ip_filter,
+                    )),
+                "admin",
+                &app_state,
+            ),
+        );
+    }
+
+    let api_route = api_route
+        .layer(middleware::from_fn(read_only))
+        .layer(middleware::from_fn(csrf));
+
+    let api_route = limit_route(api_route, "global", &app_state)
+        .layer(middleware::from_fn(maintenance))
+        .layer(middleware::from_fn(client_context))
+        .layer(TraceLayer::new_for_http())
+        .layer(Extension(app_state.clone()));
+
+    let mut router = Router::new()
+        .nest("/api", api_route)
+        .merge(profiling_routes(&app_state, admin_ip_filter));
+
+    if app_state.jwt_keys.jwks().is_some() {
+        router = router.route(
+            "/.well-known/jwks.json",
+            get(jwks_handler).layer(Extension(app_state.clone())),
+        );
+    }
+
+    if app_state.env.access_log {
+        let access_log_config = AccessLog::new(&app_state.env.access_log_sample_rates);
+        router = router.layer(middleware::from_fn_with_state(
+            access_log_config,
+            access_log,
+        ));
+    }
+
+    if app_state.env.security_headers {
+        let headers = SecurityHeaders::new(&app_state.env.content_security_policy);
+        router = router.layer(middleware::from_fn_with_state(headers, security_headers));
+    }
+
+    router.layer(middleware::from_fn(request_id))
+}
+
+#[cfg(feature = "profiling")]
+fn profiling_routes(app_state: &Arc<AppState>, admin_ip_filter: IpFilter) -> Router {
+    if !app_state.env.profiling_enabled {
+        return Router::new();
+    }
+
+    Router::new().nest(
+        "/debug/pprof",
+        crate::handler::debug::debug_handler()
+            .layer(middleware::from_fn_with_state(admin_ip_filter, ip_filter))
+            .layer(Extension(app_state.clone())),
+    )
+}
+
+#[cfg(not(feature = "profiling"))]
+fn profiling_routes(_app_state: &Arc<AppState>, _admin_ip_filter: IpFilter) -> Router {
+    Router::new()
+}
+
+#[cfg(feature = "stripe")]
+fn webhook_routes() -> Router {
+    Router::new().nest("/webhooks", crate::handler::webhooks::webhooks_handler())
+}
+
+#[cfg(not(feature = "stripe"))]
+fn webhook_routes() -> Router {
+    Router::new()
+}
+
+fn limit_route(router: Router, name: &str, app_state: &AppState) -> Router {
+    let timeout_ms = match name {
+        "global" => app_state.env.handler_timeout_ms,
+        _ => app_state
+            .env
+            .route_timeouts_ms
+            .get(name)
+            .copied()
+            .unwrap_or(0),
+    };
+    let router = match timeout_ms {
+        0 => router,
+        timeout_ms => router.layer(middleware::from_fn_with_state(
+            HandlerTimeout::new(name, timeout_ms, &app_state.metrics),
+            handler_timeout,
+        )),
+    };
+
+    let limit = match name {
+        "global" => Some(app_state.env.max_concurrent_requests),
+        _ => app_state.env.route_concurrency_limits.get(name).copied(),
+    };
+
+    match limit {
+        Some(limit) if limit > 0 => {
+            let shedder = LoadShedder::new(name, limit, &app_state.metrics);
+            router.layer(middleware::from_fn_with_state(shedder, load_shed))
+        }
+        _ => router,
+    }
+}
+
+pub async fn health_checker_handler() -> impl IntoResponse {
+    Json(serde_json::json!({
+        "status": "success",
+        "message": "Server is healthy"
+    }))
+}
+
+pub async fn jwks_handler(Extension(app_state): Extension<Arc<AppState>>) -> impl IntoResponse {
+    match app_state.jwt_keys.jwks() {
+        Some(jwks) => {
+            ([(header::CACHE_CONTROL, "public, max-age=300")], Json(jwks)).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
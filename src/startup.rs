@@ -0,0 +1,215 @@
+use std::time::Instant;
+
+use crate::{
+    config::{Config, RateLimitBackend},
+    db::{DBClient, DoctorExt, HOT_QUERIES},
+    doctor::{self, CheckStatus},
+    mail::{self, EmailSender},
+};
+
+#[derive(Debug, Clone)]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub ok: bool,
+    pub critical: bool,
+    pub latency_ms: Option<u128>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StartupReport {
+    pub version: &'static str,
+    pub environment: String,
+    pub features: Vec<&'static str>,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+impl StartupReport {
+    pub fn log(&self) {
+        tracing::info!(
+            version = self.version,
+            environment = %self.environment,
+            features = ?self.features,
+            "Starting axum-auth"
+        );
+
+        for dependency in &self.dependencies {
+            if dependency.ok {
+                tracing::info!(
+                    dependency = dependency.name,
+                    latency_ms = ?dependency.latency_ms,
+                    detail = %dependency.detail,
+                    "Dependency is available"
+                );
+            } else {
+                tracing::warn!(
+                    dependency = dependency.name,
+                    critical = dependency.critical,
+                    latency_ms = ?dependency.latency_ms,
+                    detail = %dependency.detail,
+                    "Dependency check failed"
+                );
+            }
+        }
+    }
+
+    pub fn critical_failures(&self) -> Vec<&DependencyStatus> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| dependency.critical && !dependency.ok)
+            .collect()
+    }
+}
+
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "query-token") {
+        features.push("query-token");
+    }
+    if cfg!(feature = "stripe") {
+        features.push("stripe");
+    }
+    if cfg!(feature = "redis") {
+        features.push("redis");
+    }
+    features
+}
+
+pub async fn probe(config: &Config, db_client: &DBClient) -> StartupReport {
+    let mut dependencies = vec![probe_database(db_client).await];
+
+    let migrations = doctor::check_migrations(db_client).await;
+    dependencies.push(DependencyStatus {
+        name: "migrations",
+        ok: migrations.status != CheckStatus::Failed,
+        critical: true,
+        latency_ms: None,
+        detail: migrations.detail,
+    });
+
+    if config.startup_query_plan_check {
+        dependencies.push(probe_query_plans(db_client).await);
+    }
+
+    if config.rate_limit_backend == RateLimitBackend::Redis {
+        dependencies.push(probe_redis(config).await);
+    }
+
+    if let Some(sender) = mail::from_config(config) {
+        dependencies.push(probe_email(sender.as_ref()).await);
+    }
+
+    StartupReport {
+        version: env!("CARGO_PKG_VERSION"),
+        environment: config.environment.to_str().to_string(),
+        features: enabled_features(),
+        dependencies,
+    }
+}
+
+async fn probe_database(db_client: &DBClient) -> DependencyStatus {
+    let started = Instant::now();
+    let result = db_client.ping().await;
+
+    DependencyStatus {
+        name: "database",
+        ok: result.is_ok(),
+        critical: true,
+        latency_ms: Some(started.elapsed().as_millis()),
+        detail: match result {
+            Ok(()) => "Connected".to_string(),
+            Err(e) => e.to_string(),
+        },
+    }
+}
+
+async fn probe_query_plans(db_client: &DBClient) -> DependencyStatus {
+    let started = Instant::now();
+    let mut problems = Vec::new();
+
+    for query in HOT_QUERIES {
+        match db_client.explain_hot_query(query).await {
+            Ok(plan) => {
+                let scans: Vec<&str> = plan
+                    .iter()
+                    .filter(|line| line.contains("Seq Scan"))
+                    .map(|line| line.trim())
+                    .collect();
+                if !scans.is_empty() {
+                    tracing::warn!(
+                        query = query.name,
+                        plan = %plan.join("\n"),
+                        "Hot query falls back to a sequential scan, check its indexes"
+                    );
+                    problems.push(format!("{}: {}", query.name, scans.join(", ")));
+                }
+            }
+            Err(e) => {
+                tracing::warn!(query = query.name, "Failed to explain hot query: {}", e);
+                problems.push(format!("{}: {}", query.name, e));
+            }
+        }
+    }
+
+    DependencyStatus {
+        name: "query_plans",
+        ok: problems.is_empty(),
+        critical: false,
+        latency_ms: Some(started.elapsed().as_millis()),
+        detail: if problems.is_empty() {
+            format!("{} hot queries use indexes", HOT_QUERIES.len())
+        } else {
+            problems.join("; ")
+        },
+    }
+}
+
+#[cfg(feature = "redis")]
+async fn probe_redis(config: &Config) -> DependencyStatus {
+    let started = Instant::now();
+    let result = async {
+        let client = crate::redis_client::RedisClient::from_config(config)?;
+        client.ping().await?;
+        Ok::<_, redis::RedisError>(client.mode())
+    }
+    .await;
+
+    DependencyStatus {
+        name: "redis",
+        ok: result.is_ok(),
+        critical: true,
+        latency_ms: Some(started.elapsed().as_millis()),
+        detail: match result {
+            Ok(mode) => format!("Connected ({})", mode.to_str()),
+            Err(e) => e.to_string(),
+        },
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+async fn probe_redis(_config: &Config) -> DependencyStatus {
+    DependencyStatus {
+        name: "redis",
+        ok: false,
+        critical: true,
+        latency_ms: None,
+        detail: "Built without the redis feature".to_string(),
+    }
+}
+
+async fn probe_email(sender: &dyn EmailSender) -> DependencyStatus {
+    let started = Instant::now();
+    let result = sender.test_connection().await;
+
+    DependencyStatus {
+        name: "email",
+        ok: matches!(result, Ok(true)),
+        critical: false,
+        latency_ms: Some(started.elapsed().as_millis()),
+        detail: match result {
+            Ok(true) => "SMTP server reachable".to_string(),
+            Ok(false) => "SMTP server did not accept the connection".to_string(),
+            Err(e) => e.to_string(),
+        },
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::{DBClient, UserExt},
+    models::User,
+};
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+struct Entries {
+    users: HashMap<Uuid, (User, Instant)>,
+    generation: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl UserCache {
+    pub fn new(ttl_seconds: u64, max_entries: usize) -> Self {
+        UserCache {
+            ttl: Duration::from_secs(ttl_seconds),
+            max_entries,
+            entries: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    pub async fn get_user(
+        &self,
+        db_client: &DBClient,
+        user_id: Uuid,
+    ) -> Result<Option<User>, sqlx::Error> {
+        if !self.is_enabled() {
+            return db_client.get_user(Some(user_id), None, None, None).await;
+        }
+
+        let generation = {
+            let entries = self.entries.lock().unwrap();
+            if let Some((user, cached_at)) = entries.users.get(&user_id)
+                && cached_at.elapsed() < self.ttl
+            {
+                return Ok(Some(user.clone()));
+            }
+            entries.generation
+        };
+
+        let user = db_client.get_user(Some(user_id), None, None, None).await?;
+        if let Some(user) = &user {
+            self.insert(user.clone(), generation);
+        }
+
+        Ok(user)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.entries.lock().unwrap().generation
+    }
+
+    pub fn insert(&self, user: User, generation: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation {
+            return;
+        }
+        if entries.users.len() >= self.max_entries && !entries.users.contains_key(&user.id) {
+            return;
+        }
+        entries.users.insert(user.id, (user, Instant::now()));
+    }
+
+    pub fn evict(&self, user_id: Uuid) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.users.remove(&user_id);
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.users.clear();
+    }
+
+    pub fn sweep(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.users.len();
+        entries
+            .users
+            .retain(|_, (_, cached_at)| cached_at.elapsed() < self.ttl);
+        before - entries.users.len()
+    }
+}
+
+pub async fn preload(app_state: &AppState) -> Result<usize, sqlx::Error> {
+    let generation = app_state.user_cache.generation();
+    let users = app_state
+        .db_client
+        .get_recently_active_users(app_state.env.user_cache_preload as i64)
+        .await?;
+    let count = users.len();
+    for user in users {
+        app_state.user_cache.insert(user, generation);
+    }
+
+    Ok(count)
+}
+
+pub fn spawn_preloader(app_state: Arc<AppState>) {
+    if !app_state.user_cache.is_enabled() || app_state.env.user_cache_preload == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let interval = app_state.env.user_cache_preload_interval;
+        loop {
+            let started = Instant::now();
+            match preload(&app_state).await {
+                Ok(count) => tracing::info!(
+                    users = count,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    "Preloaded user cache"
+                ),
+                Err(e) => tracing::warn!("Failed to preload user cache: {}", e),
+            }
+
+            if interval == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+pub fn spawn_sweeper(app_state: Arc<AppState>) {
+    if !app_state.user_cache.is_enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let expired = app_state.user_cache.sweep();
+            if expired > 0 {
+                tracing::debug!(expired, "Expired cached users");
+            }
+        }
+    });
+}
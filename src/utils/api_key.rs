@@ -0,0 +1,120 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use crc::{CRC_32_ISO_HDLC, Crc};
+use sha2::{Digest, Sha256};
+
+use crate::config::Environment;
+
+const PREFIX: &str = "akp";
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const SECRET_LENGTH: usize = 30;
+const CHECKSUM_LENGTH: usize = 6;
+const DISPLAY_PREFIX_LENGTH: usize = 13;
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+pub fn generate(environment: Environment) -> String {
+    let mode = match environment {
+        Environment::Production => "live",
+        Environment::Development => "test",
+    };
+
+    let mut secret = String::with_capacity(SECRET_LENGTH);
+    while secret.len() < SECRET_LENGTH {
+        let byte = OsRng.next_u32() as u8;
+        if (byte as usize) < ALPHABET.len() * 4 {
+            secret.push(ALPHABET[byte as usize % ALPHABET.len()] as char);
+        }
+    }
+
+    let body = format!("{}_{}_{}", PREFIX, mode, secret);
+    let checksum = checksum(&body);
+    body + &checksum
+}
+
+pub fn is_well_formed(key: &str) -> bool {
+    let Some(rest) = key
+        .strip_prefix("akp_live_")
+        .or_else(|| key.strip_prefix("akp_test_"))
+    else {
+        return false;
+    };
+
+    if rest.len() != SECRET_LENGTH + CHECKSUM_LENGTH || !rest.bytes().all(|b| ALPHABET.contains(&b))
+    {
+        return false;
+    }
+
+    let (body, checksum_part) = key.split_at(key.len() - CHECKSUM_LENGTH);
+    checksum(body) == checksum_part
+}
+
+pub fn display_prefix(key: &str) -> String {
+    key.chars().take(DISPLAY_PREFIX_LENGTH).collect()
+}
+
+pub fn hash(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn checksum(body: &str) -> String {
+    let mut value = CRC32.checksum(body.as_bytes());
+    let mut encoded = [b'0'; CHECKSUM_LENGTH];
+    for slot in encoded.iter_mut().rev() {
+        *slot = ALPHABET[(value % ALPHABET.len() as u32) as usize];
+        value /= ALPHABET.len() as u32;
+    }
+    String::from_utf8_lossy(&encoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN_KEY: &str = "akp_test_aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa2TvkuP";
+
+    #[test]
+    fn checksum_matches_a_known_key() {
+        assert_eq!(
+            checksum("akp_test_aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            "2TvkuP"
+        );
+        assert!(is_well_formed(KNOWN_KEY));
+    }
+
+    #[test]
+    fn generated_keys_are_well_formed() {
+        let live = generate(Environment::Production);
+        let test = generate(Environment::Development);
+
+        assert!(live.starts_with("akp_live_"));
+        assert!(test.starts_with("akp_test_"));
+        assert!(is_well_formed(&live));
+        assert!(is_well_formed(&test));
+    }
+
+    #[test]
+    fn rejects_a_corrupted_checksum() {
+        let mut key = KNOWN_KEY.to_string();
+        key.replace_range(key.len() - 1.., "Q");
+        assert!(!is_well_formed(&key));
+    }
+
+    #[test]
+    fn rejects_a_changed_secret() {
+        let key = KNOWN_KEY.replacen("aaaa", "aaab", 1);
+        assert!(!is_well_formed(&key));
+    }
+
+    #[test]
+    fn rejects_unknown_prefixes_and_lengths() {
+        assert!(!is_well_formed(
+            &KNOWN_KEY.replace("akp_test_", "akp_prod_")
+        ));
+        assert!(!is_well_formed(&KNOWN_KEY[..KNOWN_KEY.len() - 1]));
+        assert!(!is_well_formed(&format!("{}a", KNOWN_KEY)));
+    }
+
+    #[test]
+    fn display_prefix_hides_the_secret() {
+        assert_eq!(display_prefix(KNOWN_KEY), "akp_test_aaaa");
+    }
+}
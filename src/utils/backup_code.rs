@@ -0,0 +1,38 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const GROUP_LENGTH: usize = 5;
+
+pub fn generate(count: usize) -> Vec<String> {
+    (0..count).map(|_| generate_one()).collect()
+}
+
+fn generate_one() -> String {
+    let mut chars = Vec::with_capacity(GROUP_LENGTH * 2);
+    let limit = 256 - 256 % ALPHABET.len();
+    while chars.len() < GROUP_LENGTH * 2 {
+        let byte = (OsRng.next_u32() & 0xff) as usize;
+        if byte < limit {
+            chars.push(ALPHABET[byte % ALPHABET.len()] as char);
+        }
+    }
+
+    let (first, second) = chars.split_at(GROUP_LENGTH);
+    format!(
+        "{}-{}",
+        first.iter().collect::<String>(),
+        second.iter().collect::<String>()
+    )
+}
+
+pub fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+pub fn hash(code: &str) -> String {
+    hex::encode(Sha256::digest(normalize(code).as_bytes()))
+}
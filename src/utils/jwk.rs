@@ -0,0 +1,120 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+        EllipticCurveKeyType, Jwk, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType,
+        OctetKeyParameters, OctetKeyType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use pkcs1::{RsaPublicKey, der::Decode};
+use sha2::{Digest, Sha256};
+use spki::{Document, SubjectPublicKeyInfoRef};
+
+pub fn public_jwk(algorithm: Algorithm, pem: &str) -> Result<Jwk, String> {
+    let (_, document) = Document::from_pem(pem).map_err(|e| e.to_string())?;
+    let spki = SubjectPublicKeyInfoRef::try_from(document.as_bytes()).map_err(|e| e.to_string())?;
+    let key = spki.subject_public_key.raw_bytes();
+
+    let parameters = match algorithm {
+        Algorithm::ES256 | Algorithm::ES384 => {
+            let (curve, size) = if algorithm == Algorithm::ES256 {
+                (EllipticCurve::P256, 32)
+            } else {
+                (EllipticCurve::P384, 48)
+            };
+            if key.len() != 1 + 2 * size || key[0] != 0x04 {
+                return Err("expected an uncompressed EC point".to_string());
+            }
+            AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                key_type: EllipticCurveKeyType::EC,
+                curve,
+                x: URL_SAFE_NO_PAD.encode(&key[1..=size]),
+                y: URL_SAFE_NO_PAD.encode(&key[size + 1..]),
+            })
+        }
+        Algorithm::EdDSA => {
+            if key.len() != 32 {
+                return Err("expected a 32 byte Ed25519 key".to_string());
+            }
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(key),
+            })
+        }
+        _ => {
+            let rsa = RsaPublicKey::from_der(key).map_err(|e| e.to_string())?;
+            AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(rsa.modulus.as_bytes()),
+                e: URL_SAFE_NO_PAD.encode(rsa.public_exponent.as_bytes()),
+            })
+        }
+    };
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm(algorithm)),
+            key_id: Some(thumbprint(&parameters)),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+pub fn secret_key_id(secret: &[u8]) -> String {
+    thumbprint(&AlgorithmParameters::OctetKey(OctetKeyParameters {
+        key_type: OctetKeyType::Octet,
+        value: URL_SAFE_NO_PAD.encode(secret),
+    }))
+}
+
+fn thumbprint(parameters: &AlgorithmParameters) -> String {
+    let canonical = match parameters {
+        AlgorithmParameters::RSA(rsa) => {
+            format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, rsa.e, rsa.n)
+        }
+        AlgorithmParameters::EllipticCurve(ec) => format!(
+            r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+            curve_name(&ec.curve),
+            ec.x,
+            ec.y
+        ),
+        AlgorithmParameters::OctetKeyPair(okp) => format!(
+            r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+            curve_name(&okp.curve),
+            okp.x
+        ),
+        AlgorithmParameters::OctetKey(oct) => format!(r#"{{"k":"{}","kty":"oct"}}"#, oct.value),
+    };
+
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+fn key_algorithm(algorithm: Algorithm) -> KeyAlgorithm {
+    match algorithm {
+        Algorithm::HS256 => KeyAlgorithm::HS256,
+        Algorithm::HS384 => KeyAlgorithm::HS384,
+        Algorithm::HS512 => KeyAlgorithm::HS512,
+        Algorithm::ES256 => KeyAlgorithm::ES256,
+        Algorithm::ES384 => KeyAlgorithm::ES384,
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        Algorithm::RS384 => KeyAlgorithm::RS384,
+        Algorithm::RS512 => KeyAlgorithm::RS512,
+        Algorithm::PS256 => KeyAlgorithm::PS256,
+        Algorithm::PS384 => KeyAlgorithm::PS384,
+        Algorithm::PS512 => KeyAlgorithm::PS512,
+        Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+    }
+}
+
+fn curve_name(curve: &EllipticCurve) -> &'static str {
+    match curve {
+        EllipticCurve::P256 => "P-256",
+        EllipticCurve::P384 => "P-384",
+        EllipticCurve::P521 => "P-521",
+        EllipticCurve::Ed25519 => "Ed25519",
+    }
+}
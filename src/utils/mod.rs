@@ -0,0 +1,12 @@
+pub mod api_key;
+pub mod backup_code;
+pub mod jwk;
+pub mod name_filter;
+pub mod password;
+pub mod public_id;
+pub mod request_signature;
+pub mod reset_code;
+pub mod secret_scanning;
+pub mod security_question;
+pub mod token;
+pub mod totp;
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::config::Config;
+
+const RESERVED_NAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "root",
+    "system",
+    "support",
+    "helpdesk",
+    "moderator",
+    "staff",
+    "security",
+    "official",
+    "superuser",
+];
+
+const PROFANITY: &[&str] = &[
+    "fuck", "fucker", "shit", "bitch", "cunt", "asshole", "bastard", "dick", "whore", "slut",
+];
+
+#[derive(Debug, Clone)]
+pub struct NameFilter {
+    denied: HashSet<String>,
+    reserved: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameViolation {
+    Profanity,
+    Reserved,
+}
+
+impl NameFilter {
+    pub fn new(config: &Config) -> Self {
+        let denied = PROFANITY
+            .iter()
+            .map(|word| word.to_string())
+            .chain(config.name_denylist.iter().map(|word| normalize(word)))
+            .collect();
+        let reserved = RESERVED_NAMES
+            .iter()
+            .map(|word| word.to_string())
+            .chain(config.name_reserved.iter().map(|word| normalize(word)))
+            .collect();
+
+        NameFilter { denied, reserved }
+    }
+
+    pub fn check(&self, name: &str) -> Result<(), NameViolation> {
+        let normalized = normalize(name);
+        let collapsed: String = normalized.split_whitespace().collect();
+
+        let matches = |list: &HashSet<String>| {
+            list.contains(&collapsed)
+                || normalized
+                    .split_whitespace()
+                    .any(|word| list.contains(word))
+        };
+
+        if matches(&self.denied) {
+            return Err(NameViolation::Profanity);
+        }
+        if matches(&self.reserved) {
+            return Err(NameViolation::Reserved);
+        }
+
+        Ok(())
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | '!' | '|' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect()
+}
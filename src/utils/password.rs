@@ -0,0 +1,465 @@
+use std::{collections::HashMap, sync::Arc};
+
+use argon2::{
+    Argon2,
+    password_hash::{
+        PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString, rand_core::OsRng,
+    },
+};
+use hmac::{Hmac, Mac};
+use scrypt::Scrypt;
+use sha2::Sha256;
+
+use crate::{
+    config::{Config, PasswordAlgorithm},
+    error::ErrorMessage,
+};
+
+const MAX_PASSWORD_LENGTH: usize = 64;
+const PEPPER_PREFIX: &str = "$pepper$";
+
+pub trait PasswordHasher: std::fmt::Debug + Send + Sync {
+    fn algorithm(&self) -> PasswordAlgorithm;
+
+    fn recognizes(&self, hashed_password: &str) -> bool;
+
+    fn hash(&self, password: &str) -> Result<String, ErrorMessage>;
+
+    fn verify(&self, password: &str, hashed_password: &str) -> Result<bool, ErrorMessage>;
+
+    fn needs_rehash(&self, hashed_password: &str) -> bool;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Argon2Hasher {
+    params: argon2::Params,
+}
+
+impl Argon2Hasher {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, ErrorMessage> {
+        let params = argon2::Params::new(memory_kib, iterations, parallelism, None)
+            .map_err(|_| ErrorMessage::HashingError)?;
+        Ok(Argon2Hasher { params })
+    }
+
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            self.params.clone(),
+        )
+    }
+}
+
+impl PasswordHasher for Argon2Hasher {
+    fn algorithm(&self) -> PasswordAlgorithm {
+        PasswordAlgorithm::Argon2
+    }
+
+    fn recognizes(&self, hashed_password: &str) -> bool {
+        hashed_password.starts_with("$argon2")
+    }
+
+    fn hash(&self, password: &str) -> Result<String, ErrorMessage> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hashed_password = self
+            .argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|_| ErrorMessage::HashingError)?
+            .to_string();
+
+        Ok(hashed_password)
+    }
+
+    fn verify(&self, password: &str, hashed_password: &str) -> Result<bool, ErrorMessage> {
+        let parsed_hash =
+            PasswordHash::new(hashed_password).map_err(|_| ErrorMessage::InvalidHashFormat)?;
+
+        Ok(self
+            .argon2()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hashed_password) else {
+            return true;
+        };
+        let Ok(params) = argon2::Params::try_from(&parsed_hash) else {
+            return true;
+        };
+
+        parsed_hash.algorithm != argon2::Algorithm::Argon2id.ident()
+            || params.m_cost() != self.params.m_cost()
+            || params.t_cost() != self.params.t_cost()
+            || params.p_cost() != self.params.p_cost()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BcryptHasher {
+    cost: u32,
+}
+
+impl BcryptHasher {
+    pub fn new(cost: u32) -> Result<Self, ErrorMessage> {
+        if !(4..=31).contains(&cost) {
+            return Err(ErrorMessage::HashingError);
+        }
+        Ok(BcryptHasher { cost })
+    }
+}
+
+impl PasswordHasher for BcryptHasher {
+    fn algorithm(&self) -> PasswordAlgorithm {
+        PasswordAlgorithm::Bcrypt
+    }
+
+    fn recognizes(&self, hashed_password: &str) -> bool {
+        ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hashed_password.starts_with(prefix))
+    }
+
+    fn hash(&self, password: &str) -> Result<String, ErrorMessage> {
+        bcrypt::hash(password, self.cost).map_err(|_| ErrorMessage::HashingError)
+    }
+
+    fn verify(&self, password: &str, hashed_password: &str) -> Result<bool, ErrorMessage> {
+        bcrypt::verify(password, hashed_password).map_err(|_| ErrorMessage::InvalidHashFormat)
+    }
+
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        hashed_password
+            .parse::<bcrypt::HashParts>()
+            .map(|parts| parts.get_cost() != self.cost)
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScryptHasher {
+    params: scrypt::Params,
+}
+
+impl ScryptHasher {
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, ErrorMessage> {
+        let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+            .map_err(|_| ErrorMessage::HashingError)?;
+        Ok(ScryptHasher { params })
+    }
+}
+
+impl PasswordHasher for ScryptHasher {
+    fn algorithm(&self) -> PasswordAlgorithm {
+        PasswordAlgorithm::Scrypt
+    }
+
+    fn recognizes(&self, hashed_password: &str) -> bool {
+        hashed_password.starts_with("$scrypt$")
+    }
+
+    fn hash(&self, password: &str) -> Result<String, ErrorMessage> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hashed_password = Scrypt
+            .hash_password_customized(password.as_bytes(), None, None, self.params, &salt)
+            .map_err(|_| ErrorMessage::HashingError)?
+            .to_string();
+
+        Ok(hashed_password)
+    }
+
+    fn verify(&self, password: &str, hashed_password: &str) -> Result<bool, ErrorMessage> {
+        let parsed_hash =
+            PasswordHash::new(hashed_password).map_err(|_| ErrorMessage::InvalidHashFormat)?;
+
+        Ok(Scrypt
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+
+    fn needs_rehash(&self, hashed_password: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hashed_password) else {
+            return true;
+        };
+
+        scrypt::Params::try_from(&parsed_hash)
+            .map(|params| {
+                params.log_n() != self.params.log_n()
+                    || params.r() != self.params.r()
+                    || params.p() != self.params.p()
+            })
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Verification {
+    pub matched: bool,
+    pub needs_rehash: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct Peppers {
+    current: Option<String>,
+    secrets: HashMap<String, Vec<u8>>,
+}
+
+impl std::fmt::Debug for Peppers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ids: Vec<&String> = self.secrets.keys().collect();
+        ids.sort();
+        f.debug_struct("Peppers")
+            .field("current", &self.current)
+            .field("ids", &ids)
+            .finish()
+    }
+}
+
+impl Peppers {
+    pub fn new(current: Option<String>, secrets: HashMap<String, Vec<u8>>) -> Self {
+        Peppers { current, secrets }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Peppers::new(
+            config.password_pepper_id.clone(),
+            config
+                .password_peppers
+                .iter()
+                .map(|(id, secret)| (id.clone(), secret.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+
+    fn apply(&self, id: &str, password: &str) -> Result<String, ErrorMessage> {
+        let secret = self
+            .secrets
+            .get(id)
+            .ok_or(ErrorMessage::UnknownPasswordPepper)?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret).map_err(|_| ErrorMessage::HashingError)?;
+        mac.update(password.as_bytes());
+
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+fn split_pepper(hashed_password: &str) -> (Option<&str>, &str) {
+    hashed_password
+        .strip_prefix(PEPPER_PREFIX)
+        .and_then(|rest| rest.split_once('$'))
+        .map(|(id, _)| {
+            let inner = &hashed_password[PEPPER_PREFIX.len() + id.len()..];
+            (Some(id), inner)
+        })
+        .unwrap_or((None, hashed_password))
+}
+
+#[derive(Debug, Clone)]
+pub struct Passwords {
+    current: Arc<dyn PasswordHasher>,
+    hashers: Vec<Arc<dyn PasswordHasher>>,
+    peppers: Peppers,
+}
+
+impl Passwords {
+    pub fn new(current: Arc<dyn PasswordHasher>, hashers: Vec<Arc<dyn PasswordHasher>>) -> Self {
+        Passwords {
+            current,
+            hashers,
+            peppers: Peppers::default(),
+        }
+    }
+
+    pub fn with_peppers(mut self, peppers: Peppers) -> Self {
+        self.peppers = peppers;
+        self
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let argon2: Arc<dyn PasswordHasher> = Arc::new(
+            Argon2Hasher::new(
+                config.argon2_memory_kib,
+                config.argon2_iterations,
+                config.argon2_parallelism,
+            )
+            .expect("ARGON2_* settings must be valid argon2 parameters"),
+        );
+        let bcrypt: Arc<dyn PasswordHasher> = Arc::new(
+            BcryptHasher::new(config.bcrypt_cost).expect("BCRYPT_COST must be between 4 and 31"),
+        );
+        let scrypt: Arc<dyn PasswordHasher> = Arc::new(
+            ScryptHasher::new(config.scrypt_log_n, config.scrypt_r, config.scrypt_p)
+                .expect("SCRYPT_* settings must be valid scrypt parameters"),
+        );
+
+        let current = match config.password_hasher {
+            PasswordAlgorithm::Argon2 => argon2.clone(),
+            PasswordAlgorithm::Bcrypt => bcrypt.clone(),
+            PasswordAlgorithm::Scrypt => scrypt.clone(),
+        };
+
+        Passwords::new(current, vec![argon2, bcrypt, scrypt])
+            .with_peppers(Peppers::from_config(config))
+    }
+
+    pub fn hash(&self, password: impl Into<String>) -> Result<String, ErrorMessage> {
+        let password = password.into();
+        check_length(&password)?;
+
+        match &self.peppers.current {
+            Some(id) => {
+                let peppered = self.peppers.apply(id, &password)?;
+                let hashed_password = self.current.hash(&peppered)?;
+                Ok(format!("{}{}{}", PEPPER_PREFIX, id, hashed_password))
+            }
+            None => self.current.hash(&password),
+        }
+    }
+
+    pub fn verify(
+        &self,
+        password: &str,
+        hashed_password: &str,
+    ) -> Result<Verification, ErrorMessage> {
+        check_length(password)?;
+
+        let (pepper_id, hashed_password) = split_pepper(hashed_password);
+        let hasher = self
+            .hashers
+            .iter()
+            .find(|hasher| hasher.recognizes(hashed_password))
+            .ok_or(ErrorMessage::InvalidHashFormat)?;
+
+        let matched = match pepper_id {
+            Some(id) => hasher.verify(&self.peppers.apply(id, password)?, hashed_password)?,
+            None => hasher.verify(password, hashed_password)?,
+        };
+        let needs_rehash = matched
+            && (hasher.algorithm() != self.current.algorithm()
+                || pepper_id != self.peppers.current.as_deref()
+                || self.current.needs_rehash(hashed_password));
+
+        Ok(Verification {
+            matched,
+            needs_rehash,
+        })
+    }
+}
+
+impl Default for Passwords {
+    fn default() -> Self {
+        let argon2: Arc<dyn PasswordHasher> = Arc::new(Argon2Hasher::default());
+        let bcrypt: Arc<dyn PasswordHasher> = Arc::new(BcryptHasher {
+            cost: bcrypt::DEFAULT_COST,
+        });
+        let scrypt: Arc<dyn PasswordHasher> = Arc::new(ScryptHasher {
+            params: scrypt::Params::recommended(),
+        });
+
+        Passwords::new(argon2.clone(), vec![argon2, bcrypt, scrypt])
+    }
+}
+
+fn check_length(password: &str) -> Result<(), ErrorMessage> {
+    if password.is_empty() {
+        return Err(ErrorMessage::EmptyPassword);
+    }
+
+    if password.len() > MAX_PASSWORD_LENGTH {
+        return Err(ErrorMessage::ExceededMaxPasswordLength(MAX_PASSWORD_LENGTH));
+    }
+
+    Ok(())
+}
+
+pub fn hash(password: impl Into<String>) -> Result<String, ErrorMessage> {
+    Passwords::default().hash(password)
+}
+
+pub fn compare(password: &str, hashed_password: &str) -> Result<bool, ErrorMessage> {
+    Passwords::default()
+        .verify(password, hashed_password)
+        .map(|verification| verification.matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passwords(current: Option<&str>) -> Passwords {
+        let argon2: Arc<dyn PasswordHasher> = Arc::new(Argon2Hasher::new(8, 1, 1).unwrap());
+        let secrets = HashMap::from([
+            ("v1".to_string(), b"first".to_vec()),
+            ("v2".to_string(), b"second".to_vec()),
+        ]);
+
+        Passwords::new(argon2.clone(), vec![argon2])
+            .with_peppers(Peppers::new(current.map(str::to_string), secrets))
+    }
+
+    #[test]
+    fn split_pepper_reads_the_pepper_id() {
+        assert_eq!(
+            split_pepper("$pepper$v1$argon2id$v=19$m=8,t=1,p=1$salt$hash"),
+            (Some("v1"), "$argon2id$v=19$m=8,t=1,p=1$salt$hash")
+        );
+    }
+
+    #[test]
+    fn split_pepper_leaves_unpeppered_hashes_alone() {
+        let hash = "$argon2id$v=19$m=8,t=1,p=1$salt$hash";
+        assert_eq!(split_pepper(hash), (None, hash));
+        assert_eq!(split_pepper("$pepper$"), (None, "$pepper$"));
+    }
+
+    #[test]
+    fn peppered_hashes_record_the_pepper_id() {
+        let hash = passwords(Some("v1")).hash("password123").unwrap();
+        assert!(hash.starts_with("$pepper$v1$argon2"));
+    }
+
+    #[test]
+    fn verifies_with_the_recorded_pepper_and_flags_rotation() {
+        let hash = passwords(Some("v1")).hash("password123").unwrap();
+
+        let current = passwords(Some("v1")).verify("password123", &hash).unwrap();
+        assert!(current.matched);
+        assert!(!current.needs_rehash);
+
+        let rotated = passwords(Some("v2")).verify("password123", &hash).unwrap();
+        assert!(rotated.matched);
+        assert!(rotated.needs_rehash);
+
+        assert!(
+            !passwords(Some("v1"))
+                .verify("wrong-password", &hash)
+                .unwrap()
+                .matched
+        );
+    }
+
+    #[test]
+    fn unpeppered_hashes_are_upgraded_once_a_pepper_is_set() {
+        let hash = passwords(None).hash("password123").unwrap();
+        let verification = passwords(Some("v1")).verify("password123", &hash).unwrap();
+
+        assert!(verification.matched);
+        assert!(verification.needs_rehash);
+    }
+
+    #[test]
+    fn unknown_pepper_ids_are_rejected() {
+        let hash = passwords(Some("v1")).hash("password123").unwrap().replacen(
+            "$pepper$v1$",
+            "$pepper$v9$",
+            1,
+        );
+
+        assert!(matches!(
+            passwords(Some("v1")).verify("password123", &hash),
+            Err(ErrorMessage::UnknownPasswordPepper)
+        ));
+    }
+}
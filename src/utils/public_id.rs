@@ -0,0 +1,124 @@
+use std::sync::OnceLock;
+
+use aes::{
+    Aes128,
+    cipher::{BlockDecrypt, BlockEncrypt, KeyInit, generic_array::GenericArray},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+static PUBLIC_IDS: OnceLock<PublicIds> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct PublicIds {
+    cipher: Aes128,
+}
+
+impl std::fmt::Debug for PublicIds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublicIds").finish_non_exhaustive()
+    }
+}
+
+impl PublicIds {
+    pub fn new(secret: &str) -> Self {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+            .expect("HMAC can take a key of any size");
+        mac.update(b"public-id");
+        let key = mac.finalize().into_bytes();
+
+        PublicIds {
+            cipher: Aes128::new(GenericArray::from_slice(&key[..16])),
+        }
+    }
+
+    pub fn encode(&self, id: Uuid) -> String {
+        let mut block = GenericArray::clone_from_slice(id.as_bytes());
+        self.cipher.encrypt_block(&mut block);
+        URL_SAFE_NO_PAD.encode(block)
+    }
+
+    pub fn decode(&self, value: &str) -> Option<Uuid> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+        if bytes.len() != 16 {
+            return None;
+        }
+
+        let mut block = GenericArray::clone_from_slice(&bytes);
+        self.cipher.decrypt_block(&mut block);
+        Uuid::from_slice(&block).ok()
+    }
+}
+
+pub fn init(config: &Config) {
+    if config.public_ids
+        && let Some(secret) = &config.public_id_secret
+    {
+        let _ = PUBLIC_IDS.set(PublicIds::new(secret));
+    }
+}
+
+pub fn encode(id: Uuid) -> String {
+    match PUBLIC_IDS.get() {
+        Some(public_ids) => public_ids.encode(id),
+        None => id.to_string(),
+    }
+}
+
+pub fn decode(value: &str) -> Option<Uuid> {
+    match PUBLIC_IDS.get() {
+        Some(public_ids) => public_ids.decode(value),
+        None => Uuid::parse_str(value).ok(),
+    }
+}
+
+pub fn serialize<S: Serializer>(id: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode(*id))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    decode(&value).ok_or_else(|| D::Error::custom("invalid id"))
+}
+
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(id: &Option<Uuid>, serializer: S) -> Result<S::Ok, S::Error> {
+        id.map(encode).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Uuid>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| decode(&value).ok_or_else(|| D::Error::custom("invalid id")))
+            .transpose()
+    }
+}
+
+pub mod vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(ids: &[Uuid], serializer: S) -> Result<S::Ok, S::Error> {
+        ids.iter()
+            .map(|id| encode(*id))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Uuid>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| decode(value).ok_or_else(|| D::Error::custom("invalid id")))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PublicId(#[serde(with = "crate::utils::public_id")] pub Uuid);
@@ -0,0 +1,209 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+pub const SIGNATURE_HEADER: &str = "x-signature";
+pub const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+pub const NONCE_HEADER: &str = "x-signature-nonce";
+
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn canonical_request(
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method.to_uppercase(),
+        path,
+        timestamp,
+        nonce,
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+pub fn sign(
+    secret: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(canonical_request(method, path, timestamp, nonce, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub fn verify(
+    secret: &str,
+    signature: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> bool {
+    let Ok(signature) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+
+    mac.update(canonical_request(method, path, timestamp, nonce, body).as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+pub fn signed_headers(
+    secret: &str,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> [(&'static str, String); 3] {
+    let timestamp = Utc::now().timestamp();
+    let nonce = generate_nonce();
+    let signature = sign(secret, method, path, timestamp, &nonce, body);
+
+    [
+        (SIGNATURE_HEADER, signature),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (NONCE_HEADER, nonce),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"name":"x"}"#;
+
+    #[test]
+    fn sign_matches_a_known_vector() {
+        assert_eq!(
+            sign(
+                "secret",
+                "post",
+                "/api/users/me",
+                1_700_000_000,
+                "abc123",
+                BODY
+            ),
+            "6631ea730805aa26fb98f4b6caef63f1572d457eaf47c1b9bbf229dfedd27b35"
+        );
+    }
+
+    #[test]
+    fn verify_accepts_its_own_signature() {
+        let signature = sign("secret", "POST", "/api/users/me", 1_700_000_000, "n", BODY);
+        assert!(verify(
+            "secret",
+            &signature,
+            "POST",
+            "/api/users/me",
+            1_700_000_000,
+            "n",
+            BODY
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_any_changed_component() {
+        let signature = sign("secret", "POST", "/api/users/me", 1_700_000_000, "n", BODY);
+        let check = |secret, method, path, timestamp, nonce, body| {
+            verify(secret, &signature, method, path, timestamp, nonce, body)
+        };
+
+        assert!(!check(
+            "other",
+            "POST",
+            "/api/users/me",
+            1_700_000_000,
+            "n",
+            BODY
+        ));
+        assert!(!check(
+            "secret",
+            "PUT",
+            "/api/users/me",
+            1_700_000_000,
+            "n",
+            BODY
+        ));
+        assert!(!check(
+            "secret",
+            "POST",
+            "/api/users",
+            1_700_000_000,
+            "n",
+            BODY
+        ));
+        assert!(!check(
+            "secret",
+            "POST",
+            "/api/users/me",
+            1_700_000_001,
+            "n",
+            BODY
+        ));
+        assert!(!check(
+            "secret",
+            "POST",
+            "/api/users/me",
+            1_700_000_000,
+            "m",
+            BODY
+        ));
+        assert!(!check(
+            "secret",
+            "POST",
+            "/api/users/me",
+            1_700_000_000,
+            "n",
+            b"{}"
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_malformed_signatures() {
+        assert!(!verify("secret", "not-hex", "GET", "/", 0, "n", b""));
+        assert!(!verify("secret", "", "GET", "/", 0, "n", b""));
+    }
+
+    #[test]
+    fn signed_headers_verify() {
+        let headers = signed_headers("secret", "DELETE", "/api/users/api-keys/1", b"");
+        let value = |name: &str| {
+            headers
+                .iter()
+                .find(|(header, _)| *header == name)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+
+        assert!(verify(
+            "secret",
+            &value(SIGNATURE_HEADER),
+            "DELETE",
+            "/api/users/api-keys/1",
+            value(TIMESTAMP_HEADER).parse().unwrap(),
+            &value(NONCE_HEADER),
+            b""
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const CODE_SPACE: u32 = 1_000_000;
+
+pub fn generate() -> String {
+    let limit = u32::MAX - u32::MAX % CODE_SPACE;
+    loop {
+        let value = OsRng.next_u32();
+        if value < limit {
+            return format!("{:06}", value % CODE_SPACE);
+        }
+    }
+}
+
+pub fn hash(code: &str) -> String {
+    hex::encode(Sha256::digest(code.trim().as_bytes()))
+}
+
+pub fn matches(code: &str, code_hash: &str) -> bool {
+    let candidate = hash(code);
+    candidate.len() == code_hash.len()
+        && candidate
+            .bytes()
+            .zip(code_hash.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,130 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+use serde::Deserialize;
+
+pub const KEY_IDENTIFIER_HEADER: &str = "github-public-key-identifier";
+pub const SIGNATURE_HEADER: &str = "github-public-key-signature";
+
+const P256_POINT_LENGTH: usize = 65;
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct PublicKeys {
+    public_keys: Vec<PublicKey>,
+}
+
+#[derive(Deserialize)]
+struct PublicKey {
+    key_identifier: String,
+    key: String,
+}
+
+#[derive(Debug, Default)]
+struct CachedKeys {
+    points: HashMap<String, Vec<u8>>,
+    fetched_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PublicKeyCache {
+    keys_url: String,
+    ttl: Duration,
+    keys: Arc<Mutex<CachedKeys>>,
+}
+
+impl PublicKeyCache {
+    pub fn new(keys_url: &str, ttl_seconds: u64) -> Self {
+        PublicKeyCache {
+            keys_url: keys_url.to_string(),
+            ttl: Duration::from_secs(ttl_seconds),
+            keys: Arc::new(Mutex::new(CachedKeys::default())),
+        }
+    }
+
+    async fn get(&self, key_identifier: &str) -> Option<Vec<u8>> {
+        {
+            let keys = self.keys.lock().unwrap();
+            let age = keys.fetched_at.map(|fetched_at| fetched_at.elapsed());
+            if let Some(age) = age
+                && age < self.ttl
+            {
+                if let Some(point) = keys.points.get(key_identifier) {
+                    return Some(point.clone());
+                }
+                if age < MIN_REFETCH_INTERVAL {
+                    return None;
+                }
+            }
+        }
+
+        let fetched = match fetch_keys(&self.keys_url).await {
+            Ok(fetched) => fetched,
+            Err(err) => {
+                tracing::warn!("failed to fetch secret scanning public keys: {}", err);
+                return None;
+            }
+        };
+
+        let points: HashMap<String, Vec<u8>> = fetched
+            .public_keys
+            .into_iter()
+            .filter_map(|key| Some((key.key_identifier, public_key_point(&key.key)?)))
+            .collect();
+        let point = points.get(key_identifier).cloned();
+
+        let mut keys = self.keys.lock().unwrap();
+        keys.points = points;
+        keys.fetched_at = Some(Instant::now());
+
+        point
+    }
+}
+
+pub async fn verify_signature(
+    keys: &PublicKeyCache,
+    key_identifier: &str,
+    signature: &str,
+    payload: &[u8],
+) -> bool {
+    let Ok(signature) = STANDARD.decode(signature.trim()) else {
+        return false;
+    };
+
+    let Some(point) = keys.get(key_identifier).await else {
+        return false;
+    };
+
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, point)
+        .verify(payload, &signature)
+        .is_ok()
+}
+
+async fn fetch_keys(keys_url: &str) -> Result<PublicKeys, reqwest::Error> {
+    reqwest::Client::new()
+        .get(keys_url)
+        .header(reqwest::header::USER_AGENT, "axum-auth")
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<PublicKeys>()
+        .await
+}
+
+fn public_key_point(pem: &str) -> Option<Vec<u8>> {
+    let encoded: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    let der = STANDARD.decode(encoded).ok()?;
+
+    der.len()
+        .checked_sub(P256_POINT_LENGTH)
+        .map(|start| der[start..].to_vec())
+}
@@ -0,0 +1,23 @@
+use crate::{error::ErrorMessage, utils::password::Passwords};
+
+pub fn normalize_answer(answer: &str) -> String {
+    answer
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub fn hash_answer(passwords: &Passwords, answer: &str) -> Result<String, ErrorMessage> {
+    passwords.hash(normalize_answer(answer))
+}
+
+pub fn compare_answer(
+    passwords: &Passwords,
+    answer: &str,
+    answer_hash: &str,
+) -> Result<bool, ErrorMessage> {
+    passwords
+        .verify(&normalize_answer(answer), answer_hash)
+        .map(|verification| verification.matched)
+}
@@ -0,0 +1,528 @@
+use std::sync::{
+    Arc, RwLock,
+    atomic::{AtomicU64, Ordering},
+};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    jwk::{Jwk, JwkSet},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{Config, is_hmac, read_pem, read_secret},
+    error::{ErrorMessage, HttpError},
+    metrics::Metrics,
+    utils::jwk::{public_jwk, secret_key_id},
+};
+
+pub const LEGACY_VALIDATIONS_METRIC: &str = "jwt_legacy_validations";
+pub const KEY_ROTATIONS_METRIC: &str = "jwt_key_rotations";
+
+#[derive(Clone)]
+struct VerifyingKey {
+    kid: String,
+    decoding: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+impl VerifyingKey {
+    fn from_secret(secret: &str) -> Self {
+        VerifyingKey {
+            kid: secret_key_id(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            jwk: None,
+        }
+    }
+
+    fn from_public_pem(algorithm: Algorithm, pem: &str) -> Result<Self, String> {
+        let decoding = decoding_key(algorithm, pem.as_bytes()).map_err(|e| e.to_string())?;
+        let jwk = public_jwk(algorithm, pem)?;
+
+        Ok(VerifyingKey {
+            kid: jwk.common.key_id.clone().unwrap_or_default(),
+            decoding,
+            jwk: Some(jwk),
+        })
+    }
+}
+
+struct KeyRing {
+    current: VerifyingKey,
+    encoding: Option<EncodingKey>,
+    previous: Vec<(VerifyingKey, DateTime<Utc>)>,
+}
+
+impl KeyRing {
+    fn active(&self) -> impl Iterator<Item = &VerifyingKey> {
+        let now = Utc::now();
+        std::iter::once(&self.current).chain(
+            self.previous
+                .iter()
+                .filter(move |(_, until)| *until > now)
+                .map(|(key, _)| key),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetiredKey {
+    pub kid: String,
+    pub verifies_until: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    grace: Duration,
+    ring: Arc<RwLock<KeyRing>>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    legacy: Option<(Algorithm, Vec<u8>)>,
+    legacy_validations: Arc<AtomicU64>,
+    rotations: Arc<AtomicU64>,
+}
+
+impl JwtKeys {
+    pub fn new(config: &Config, metrics: &Metrics) -> Self {
+        let algorithm = config.jwt_algorithm;
+        let grace = Duration::seconds(config.jwt_key_grace_secs as i64);
+        let (encoding, current) = load_keys(
+            algorithm,
+            Some(&config.jwt_secret),
+            config.jwt_private_key.as_deref(),
+            config.jwt_public_key.as_deref(),
+        )
+        .unwrap_or_else(|e| panic!("{}", e));
+
+        let previous = if is_hmac(algorithm) {
+            config
+                .jwt_previous_secret
+                .as_deref()
+                .map(|secret| Ok(VerifyingKey::from_secret(secret)))
+        } else {
+            config
+                .jwt_previous_public_key
+                .as_deref()
+                .map(|pem| VerifyingKey::from_public_pem(algorithm, pem))
+        }
+        .transpose()
+        .unwrap_or_else(|e| panic!("JWT_PREVIOUS_PUBLIC_KEY is not a valid key: {}", e))
+        .filter(|key| key.kid != current.kid)
+        .map(|key| (key, Utc::now() + grace));
+
+        JwtKeys {
+            algorithm,
+            grace,
+            ring: Arc::new(RwLock::new(KeyRing {
+                current,
+                encoding,
+                previous: previous.into_iter().collect(),
+            })),
+            issuer: config.jwt_issuer.clone(),
+            audience: config.jwt_audience.clone(),
+            legacy: config
+                .jwt_legacy_secret
+                .as_ref()
+                .map(|secret| (config.jwt_legacy_algorithm, secret.as_bytes().to_vec())),
+            legacy_validations: metrics.counter(LEGACY_VALIDATIONS_METRIC),
+            rotations: metrics.counter(KEY_ROTATIONS_METRIC),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn can_sign(&self) -> bool {
+        self.ring.read().unwrap().encoding.is_some()
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.audience.as_deref()
+    }
+
+    pub fn key_id(&self) -> String {
+        self.ring.read().unwrap().current.kid.clone()
+    }
+
+    pub fn retired_keys(&self) -> Vec<RetiredKey> {
+        let now = Utc::now();
+        self.ring
+            .read()
+            .unwrap()
+            .previous
+            .iter()
+            .filter(|(_, until)| *until > now)
+            .map(|(key, until)| RetiredKey {
+                kid: key.kid.clone(),
+                verifies_until: *until,
+            })
+            .collect()
+    }
+
+    pub fn jwks(&self) -> Option<JwkSet> {
+        if is_hmac(self.algorithm) {
+            return None;
+        }
+
+        Some(JwkSet {
+            keys: self
+                .ring
+                .read()
+                .unwrap()
+                .active()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        })
+    }
+
+    pub fn rotate(&self) -> Result<bool, String> {
+        let (encoding, current) = load_keys(
+            self.algorithm,
+            read_secret("JWT_SECRET").as_deref(),
+            read_pem("JWT_PRIVATE_KEY").as_deref(),
+            read_pem("JWT_PUBLIC_KEY").as_deref(),
+        )?;
+
+        let mut ring = self.ring.write().unwrap();
+        let now = Utc::now();
+        ring.previous
+            .retain(|(key, until)| *until > now && key.kid != current.kid);
+        ring.encoding = encoding;
+        if ring.current.kid == current.kid {
+            return Ok(false);
+        }
+
+        let kid = current.kid.clone();
+        let retired = std::mem::replace(&mut ring.current, current);
+        tracing::info!(
+            kid = %kid,
+            previous_kid = %retired.kid,
+            grace_secs = self.grace.num_seconds(),
+            "JWT signing key rotated"
+        );
+        ring.previous.push((retired, now + self.grace));
+        self.rotations.fetch_add(1, Ordering::Relaxed);
+
+        Ok(true)
+    }
+
+    fn verifying_keys(&self, kid: Option<&str>) -> Vec<DecodingKey> {
+        self.ring
+            .read()
+            .unwrap()
+            .active()
+            .filter(|key| kid.is_none_or(|kid| key.kid == kid))
+            .map(|key| key.decoding.clone())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for JwtKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKeys")
+            .field("algorithm", &self.algorithm)
+            .field("can_sign", &self.can_sign())
+            .field("kid", &self.key_id())
+            .field(
+                "legacy",
+                &self.legacy.as_ref().map(|(algorithm, _)| algorithm),
+            )
+            .finish()
+    }
+}
+
+fn load_keys(
+    algorithm: Algorithm,
+    secret: Option<&str>,
+    private_key: Option<&str>,
+    public_key: Option<&str>,
+) -> Result<(Option<EncodingKey>, VerifyingKey), String> {
+    if is_hmac(algorithm) {
+        let secret = secret.ok_or("JWT_SECRET must be set")?;
+        return Ok((
+            Some(EncodingKey::from_secret(secret.as_bytes())),
+            VerifyingKey::from_secret(secret),
+        ));
+    }
+
+    let encoding = private_key
+        .map(|pem| encoding_key(algorithm, pem.as_bytes()))
+        .transpose()
+        .map_err(|e| format!("JWT_PRIVATE_KEY is not a valid key: {}", e))?;
+    let public_key =
+        public_key.ok_or("JWT_PUBLIC_KEY must be set for asymmetric JWT algorithms")?;
+    let current = VerifyingKey::from_public_pem(algorithm, public_key)
+        .map_err(|e| format!("JWT_PUBLIC_KEY is not a valid key: {}", e))?;
+
+    if let Some(encoding) = &encoding {
+        let mut validation = Validation::new(algorithm);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        encode(&Header::new(algorithm), &serde_json::json!({}), encoding)
+            .and_then(|probe| decode::<serde_json::Value>(&probe, &current.decoding, &validation))
+            .map_err(|_| "JWT_PRIVATE_KEY does not match JWT_PUBLIC_KEY".to_string())?;
+    }
+
+    Ok((encoding, current))
+}
+
+fn encoding_key(
+    algorithm: Algorithm,
+    pem: &[u8],
+) -> Result<EncodingKey, jsonwebtoken::errors::Error> {
+    match algorithm {
+        Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(pem),
+        Algorithm::EdDSA => EncodingKey::from_ed_pem(pem),
+        _ => EncodingKey::from_rsa_pem(pem),
+    }
+}
+
+fn decoding_key(
+    algorithm: Algorithm,
+    pem: &[u8],
+) -> Result<DecodingKey, jsonwebtoken::errors::Error> {
+    match algorithm {
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+        _ => DecodingKey::from_rsa_pem(pem),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub sub: String,
+    pub iat: usize,
+    pub exp: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acting_for: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sudo: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "audience")]
+    pub aud: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mfa: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub magic: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+pub fn create_token(
+    user_id: &str,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    sign(&new_claims(user_id, expires_in_seconds)?, keys)
+}
+
+pub fn create_session_token(
+    user_id: &str,
+    session_id: &str,
+    audience: Option<&str>,
+    scope: Option<&str>,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = new_claims(user_id, expires_in_seconds)?;
+    claims.sid = Some(session_id.to_string());
+    claims.aud = audience.map(str::to_string).into_iter().collect();
+    claims.scope = scope.map(str::to_string);
+    sign(&claims, keys)
+}
+
+pub fn create_delegated_token(
+    user_id: &str,
+    acting_for: Option<&str>,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = new_claims(user_id, expires_in_seconds)?;
+    claims.acting_for = acting_for.map(str::to_string);
+    sign(&claims, keys)
+}
+
+pub fn create_sudo_token(
+    user_id: &str,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = new_claims(user_id, expires_in_seconds)?;
+    claims.sudo = true;
+    sign(&claims, keys)
+}
+
+pub fn create_mfa_token(
+    user_id: &str,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = new_claims(user_id, expires_in_seconds)?;
+    claims.mfa = true;
+    sign(&claims, keys)
+}
+
+pub fn create_magic_link_token(
+    user_id: &str,
+    keys: &JwtKeys,
+    expires_in_seconds: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = new_claims(user_id, expires_in_seconds)?;
+    claims.magic = true;
+    sign(&claims, keys)
+}
+
+fn new_claims(
+    user_id: &str,
+    expires_in_seconds: i64,
+) -> Result<TokenClaims, jsonwebtoken::errors::Error> {
+    if user_id.is_empty() {
+        return Err(jsonwebtoken::errors::ErrorKind::InvalidSubject.into());
+    }
+
+    let now = Utc::now();
+    let iat = now.timestamp() as usize;
+    let exp = (now + Duration::minutes(expires_in_seconds)).timestamp() as usize;
+
+    Ok(TokenClaims {
+        sub: user_id.to_string(),
+        iat,
+        exp,
+        acting_for: None,
+        sudo: false,
+        sid: None,
+        iss: None,
+        aud: Vec::new(),
+        jti: Some(uuid::Uuid::new_v4().to_string()),
+        mfa: false,
+        magic: false,
+        scope: None,
+    })
+}
+
+fn sign(claims: &TokenClaims, keys: &JwtKeys) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut claims = claims.clone();
+    claims.iss = keys.issuer.clone();
+    if let Some(audience) = &keys.audience
+        && !claims.aud.contains(audience)
+    {
+        claims.aud.insert(0, audience.clone());
+    }
+
+    let ring = keys.ring.read().unwrap();
+    let encoding = ring
+        .encoding
+        .as_ref()
+        .ok_or(jsonwebtoken::errors::ErrorKind::InvalidKeyFormat)?;
+
+    let mut header = Header::new(keys.algorithm);
+    header.kid = Some(ring.current.kid.clone());
+
+    encode(&header, &claims, encoding)
+}
+
+fn verify(token: &str, algorithm: Algorithm, key: &DecodingKey) -> Option<TokenClaims> {
+    let mut validation = Validation::new(algorithm);
+    validation.validate_aud = false;
+
+    decode::<TokenClaims>(token, key, &validation)
+        .ok()
+        .map(|token| token.claims)
+}
+
+pub fn decode_token<T: Into<String>>(token: T, keys: &JwtKeys) -> Result<String, HttpError> {
+    decode_claims(token, keys).map(|claims| claims.sub)
+}
+
+pub fn decode_claims<T: Into<String>>(token: T, keys: &JwtKeys) -> Result<TokenClaims, HttpError> {
+    let token = token.into();
+
+    let kid = decode_header(&token).ok().and_then(|header| header.kid);
+    let verified = keys
+        .verifying_keys(kid.as_deref())
+        .iter()
+        .find_map(|key| verify(&token, keys.algorithm, key));
+    if let Some(claims) = verified {
+        return check_claims(claims, keys);
+    }
+
+    let legacy = keys.legacy.as_ref().and_then(|(algorithm, secret)| {
+        verify(&token, *algorithm, &DecodingKey::from_secret(secret))
+    });
+
+    match legacy {
+        Some(claims) => {
+            keys.legacy_validations.fetch_add(1, Ordering::Relaxed);
+            check_claims(claims, keys)
+        }
+        None => Err(HttpError::new(
+            axum::http::StatusCode::UNAUTHORIZED,
+            ErrorMessage::InvalidToken.to_string(),
+        )),
+    }
+}
+
+fn check_claims(claims: TokenClaims, keys: &JwtKeys) -> Result<TokenClaims, HttpError> {
+    if keys.issuer.is_some() && claims.iss != keys.issuer {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidTokenIssuer.to_string(),
+        ));
+    }
+
+    if let Some(audience) = &keys.audience
+        && !claims.aud.contains(audience)
+    {
+        return Err(HttpError::unauthorized(
+            ErrorMessage::InvalidTokenAudience.to_string(),
+        ));
+    }
+
+    Ok(claims)
+}
+
+mod audience {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Audience {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn serialize<S: Serializer>(audience: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        match audience {
+            [single] => single.serialize(serializer),
+            many => many.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        Ok(match Audience::deserialize(deserializer)? {
+            Audience::One(audience) => vec![audience],
+            Audience::Many(audiences) => audiences,
+        })
+    }
+}
+
+pub fn generate_opaque() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_opaque(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
@@ -0,0 +1,117 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const SECRET_BYTES: usize = 20;
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const ALLOWED_DRIFT: i64 = 1;
+
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+pub fn otpauth_url(issuer: &str, account: &str, secret: &str) -> String {
+    let label = format!("{}:{}", issuer, account);
+    reqwest::Url::parse_with_params(
+        &format!("otpauth://totp/{}", urlencode(&label)),
+        &[
+            ("secret", secret),
+            ("issuer", issuer),
+            ("digits", "6"),
+            ("period", "30"),
+        ],
+    )
+    .map(String::from)
+    .unwrap_or_default()
+}
+
+pub fn verify(secret: &str, code: &str, timestamp: i64) -> bool {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let Some(key) = base32_decode(secret) else {
+        return false;
+    };
+
+    let counter = timestamp / STEP_SECONDS;
+    (-ALLOWED_DRIFT..=ALLOWED_DRIFT).any(|drift| {
+        let expected = hotp(&key, (counter + drift) as u64);
+        format!("{:0width$}", expected, width = DIGITS as usize) == code
+    })
+}
+
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(key) else {
+        return 0;
+    };
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            output.push(BASE32_ALPHABET[((buffer >> (bits - 5)) & 0x1f) as usize] as char);
+            bits -= 5;
+        }
+    }
+
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    output
+}
+
+fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in value.trim_end_matches('=').chars() {
+        let index = BASE32_ALPHABET
+            .iter()
+            .position(|b| *b as char == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | index as u32;
+        bits += 5;
+        if bits >= 8 {
+            output.push((buffer >> (bits - 8)) as u8);
+            bits -= 8;
+        }
+    }
+
+    Some(output)
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
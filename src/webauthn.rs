@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use webauthn_rs::prelude::{Passkey, Url, Webauthn, WebauthnBuilder};
+
+use crate::config::Config;
+
+pub fn from_config(env: &Config) -> Arc<Webauthn> {
+    let origin =
+        Url::parse(&env.webauthn_rp_origin).expect("WEBAUTHN_RP_ORIGIN must be a valid URL");
+
+    let webauthn = WebauthnBuilder::new(&env.webauthn_rp_id, &origin)
+        .map(|builder| builder.rp_name(&env.webauthn_rp_name))
+        .and_then(|builder| builder.build())
+        .expect("WEBAUTHN_RP_ID must be a valid domain for WEBAUTHN_RP_ORIGIN");
+
+    Arc::new(webauthn)
+}
+
+pub fn credential_id(passkey: &Passkey) -> String {
+    hex::encode(passkey.cred_id())
+}